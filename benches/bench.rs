@@ -1,10 +1,10 @@
 extern crate contour;
 
-use contour::{contour_rings, ContourBuilder};
+use contour::{contour_rings, contour_rings_fixed, ContourBuilder, Float};
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
 #[rustfmt::skip]
-static VALUES: [f64; 110] = [
+static VALUES: [Float; 110] = [
     0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
     0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
     0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
@@ -19,7 +19,7 @@ static VALUES: [f64; 110] = [
 ];
 
 #[rustfmt::skip]
-static VALUES2: [f64; 238] = [
+static VALUES2: [Float; 238] = [
     0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
     0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 3., 3., 0., 0.,
     0., 0., 0., 1., 1., 1., 1., 0., 0., 0., 3., 3., 0., 0.,
@@ -47,6 +47,13 @@ criterion_group!(
     bench_build_geojson_contour_no_smoothing,
     bench_build_isoring,
     bench_build_isoring_values2,
+    bench_build_isoring_noisy,
+    bench_build_isoring_small,
+    bench_build_isoring_small_fixed,
+    bench_build_contours_wide,
+    bench_build_contours_wide_blocked,
+    bench_build_contours_large_strips,
+    bench_build_lines_smoothed_noisy,
     bench_contourbuilder_isobands_volcano_without_xy_step_xy_origin,
     bench_contourbuilder_isobands_pot_pop_fr_without_xy_step_xy_origin
 );
@@ -97,14 +104,106 @@ fn bench_build_isoring_values2(c: &mut Criterion) {
     });
 }
 
+/// Builds a noisy grid (with many small rings) using a simple linear congruential generator.
+fn noisy_values(w: usize, h: usize) -> Vec<Float> {
+    let mut state: u64 = 42;
+    (0..w * h)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) as Float / (1u64 << 31) as Float
+        })
+        .collect()
+}
+
+fn bench_build_isoring_noisy(c: &mut Criterion) {
+    let values = noisy_values(200, 200);
+    c.bench_function("build_isoring_noisy", |b| {
+        b.iter(|| black_box(contour_rings(&values, 0.5, 200, 200)))
+    });
+}
+
+/// Builds a small (16 x 16) grid of smooth waves, stored row by row.
+fn small_values() -> [[Float; 16]; 16] {
+    let mut values = [[0.; 16]; 16];
+    for (y, row) in values.iter_mut().enumerate() {
+        for (x, value) in row.iter_mut().enumerate() {
+            *value = ((x as Float) / 3.).sin() * ((y as Float) / 2.).cos();
+        }
+    }
+    values
+}
+
+fn bench_build_isoring_small(c: &mut Criterion) {
+    let values = small_values().concat();
+    c.bench_function("build_isoring_small", |b| {
+        b.iter(|| black_box(contour_rings(&values, 0.25, 16, 16)))
+    });
+}
+
+fn bench_build_isoring_small_fixed(c: &mut Criterion) {
+    let values = small_values();
+    c.bench_function("build_isoring_small_fixed", |b| {
+        b.iter(|| black_box(contour_rings_fixed(&values, 0.25)))
+    });
+}
+
+/// Builds a grid of `w` by `h` smooth waves (e.g. a wide grid of 10k columns).
+fn wide_values(w: usize, h: usize) -> Vec<Float> {
+    (0..w * h)
+        .map(|i| {
+            let (x, y) = ((i % w) as Float, (i / w) as Float);
+            (x / 37.).sin() * (y / 23.).cos() + (x / 211.).cos()
+        })
+        .collect()
+}
+
+fn bench_build_contours_wide(c: &mut Criterion) {
+    let values = wide_values(10_000, 100);
+    let cb = ContourBuilder::new(10_000, 100, true);
+    c.bench_function("build_contours_wide", |b| {
+        b.iter(|| black_box(cb.contours(&values, &[0.5])))
+    });
+}
+
+fn bench_build_contours_wide_blocked(c: &mut Criterion) {
+    let values = wide_values(10_000, 100);
+    let cb = ContourBuilder::new(10_000, 100, true).block_width(Some(1024));
+    c.bench_function("build_contours_wide_blocked", |b| {
+        b.iter(|| black_box(cb.contours(&values, &[0.5])))
+    });
+}
+
+fn bench_build_contours_large_strips(c: &mut Criterion) {
+    let values = wide_values(2_000, 2_000);
+    let cb = ContourBuilder::new(2_000, 2_000, true).strips(4);
+    c.bench_function("build_contours_large_strips", |b| {
+        b.iter(|| black_box(cb.contours(&values, &[0.5])))
+    });
+}
+
+fn bench_build_lines_smoothed_noisy(c: &mut Criterion) {
+    // Noisy values producing many short rings, on which smoothing is a large part of the work
+    let values = (0..1_000_000)
+        .map(|i: u64| (i.wrapping_mul(2_654_435_761) % 1_000) as Float / 1_000.)
+        .collect::<Vec<_>>();
+    let cb = ContourBuilder::new(1_000, 1_000, true);
+    c.bench_function("build_lines_smoothed_noisy", |b| {
+        b.iter(|| black_box(cb.lines(&values, &[0.5])))
+    });
+}
+
+// The cast to Float is unnecessary when not using the "f32" feature.
+#[allow(clippy::unnecessary_cast)]
 fn bench_contourbuilder_isobands_volcano_without_xy_step_xy_origin(c: &mut Criterion) {
     let data_str = include_str!("../tests/fixtures/volcano.json");
     let raw_data: serde_json::Value = serde_json::from_str(data_str).unwrap();
-    let matrix: Vec<f64> = raw_data["data"]
+    let matrix: Vec<Float> = raw_data["data"]
         .as_array()
         .unwrap()
         .iter()
-        .map(|x| x.as_f64().unwrap())
+        .map(|x| x.as_f64().unwrap() as Float)
         .collect();
     let h = raw_data["height"].as_u64().unwrap() as usize;
     let w = raw_data["width"].as_u64().unwrap() as usize;
@@ -130,14 +229,16 @@ fn bench_contourbuilder_isobands_volcano_without_xy_step_xy_origin(c: &mut Crite
     );
 }
 
+// The cast to Float is unnecessary when not using the "f32" feature.
+#[allow(clippy::unnecessary_cast)]
 fn bench_contourbuilder_isobands_pot_pop_fr_without_xy_step_xy_origin(c: &mut Criterion) {
     let data_str = include_str!("../tests/fixtures/pot_pop_fr.json");
     let raw_data: serde_json::Value = serde_json::from_str(data_str).unwrap();
-    let matrix: Vec<f64> = raw_data["data"]
+    let matrix: Vec<Float> = raw_data["data"]
         .as_array()
         .unwrap()
         .iter()
-        .map(|x| x.as_f64().unwrap())
+        .map(|x| x.as_f64().unwrap() as Float)
         .collect();
     let h = raw_data["height"].as_u64().unwrap() as usize;
     let w = raw_data["width"].as_u64().unwrap() as usize;
@@ -1,3 +1,6 @@
+// The coordinates are given with the precision of f64 (rounded with the "f32" feature).
+#![allow(clippy::excessive_precision)]
+
 use contour::{ContourBuilder, Float};
 use geojson::{FeatureCollection, GeoJson};
 use std::fs::File;
@@ -56,7 +59,7 @@ fn main() {
     .to_string();
 
     let mut file_writer = BufWriter::new(File::create("/tmp/example-output.geojson").unwrap());
-    file_writer.write(&geojson_str.as_bytes()).unwrap();
+    file_writer.write_all(geojson_str.as_bytes()).unwrap();
 
     let volcano = include_str!("../tests/fixtures/volcano.json");
     let raw_data: serde_json::Value = serde_json::from_str(volcano).unwrap();
@@ -101,5 +104,5 @@ fn main() {
     .to_string();
 
     let mut file_writer = BufWriter::new(File::create("/tmp/example-output2.geojson").unwrap());
-    file_writer.write(&geojson_str.as_bytes()).unwrap();
+    file_writer.write_all(geojson_str.as_bytes()).unwrap();
 }
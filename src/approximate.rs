@@ -0,0 +1,252 @@
+use crate::axis::Axis;
+use crate::error::Result;
+use crate::order::GridOrder;
+use crate::{Band, Contour, ContourBuilder, Float, Line};
+use std::collections::VecDeque;
+
+/// A grid downsampled by averaging blocks of `factor` * `factor` samples,
+/// along with the range of the values of each block.
+struct Downsampled {
+    builder: ContourBuilder,
+    values: Vec<Float>,
+    /// The (min, max) of the values of each block, `NaN` being counted as `-inf`
+    /// (the cells without data being outside of every contour).
+    ranges: Vec<(Float, Float)>,
+}
+
+impl ContourBuilder {
+    /// Computes isolines (see [`ContourBuilder::lines`]) on a version of the grid
+    /// downsampled by `factor`, returning them along with a bound of their positional error
+    /// (see [`ContourBuilder::contours_approximate`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    /// * `factor` - The number of samples averaged along each axis.
+    pub fn lines_approximate(
+        &self,
+        values: &[Float],
+        thresholds: &[Float],
+        factor: usize,
+    ) -> Result<(Vec<Line>, Float)> {
+        let grid = self.downsample(values, factor)?;
+        let lines = grid.builder.lines(&grid.values, thresholds)?;
+        Ok((lines, grid.error_bound(self, thresholds)))
+    }
+
+    /// Computes contours (see [`ContourBuilder::contours`]) on a version of the grid
+    /// downsampled by `factor` (each block of `factor` * `factor` samples being averaged,
+    /// ignoring the samples without data), returning them along with a bound
+    /// of their positional error.
+    ///
+    /// The bound is the largest distance (in the units of the steps of the grid, i.e.
+    /// before any custom [`transform`](ContourBuilder::transform)) between a point of
+    /// the exact contours and the approximate ones, or the other way around. It is derived
+    /// from the range of the values around each cell of the downsampled grid: it is 1.5
+    /// times the diagonal of its cells where the approximate contours follow the exact ones,
+    /// and grows where features smaller than a cell vanish (being infinite
+    /// if every contour of a threshold vanishes). The [`padding`](ContourBuilder::padding)
+    /// of the grid isn't taken into account.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    /// * `factor` - The number of samples averaged along each axis.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use contour::{ContourBuilder, Float};
+    ///
+    /// let values = (0..64 * 64)
+    ///     .map(|i| ((i % 64) as Float - 32.).hypot((i / 64) as Float - 32.))
+    ///     .collect::<Vec<_>>();
+    /// let builder = ContourBuilder::new(64, 64, true);
+    /// let (contours, error) = builder.contours_approximate(&values, &[10., 20.], 4).unwrap();
+    /// assert_eq!(contours.len(), 2);
+    /// assert!(error < 3. * 4. * Float::sqrt(2.));
+    /// ```
+    pub fn contours_approximate(
+        &self,
+        values: &[Float],
+        thresholds: &[Float],
+        factor: usize,
+    ) -> Result<(Vec<Contour>, Float)> {
+        let grid = self.downsample(values, factor)?;
+        let contours = grid.builder.contours(&grid.values, thresholds)?;
+        Ok((contours, grid.error_bound(self, thresholds)))
+    }
+
+    /// Computes isobands (see [`ContourBuilder::isobands`]) on a version of the grid
+    /// downsampled by `factor`, returning them along with a bound of their positional error
+    /// (see [`ContourBuilder::contours_approximate`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used
+    ///   (have to be equal to or greater than 2).
+    /// * `factor` - The number of samples averaged along each axis.
+    pub fn isobands_approximate(
+        &self,
+        values: &[Float],
+        thresholds: &[Float],
+        factor: usize,
+    ) -> Result<(Vec<Band>, Float)> {
+        let grid = self.downsample(values, factor)?;
+        let bands = grid.builder.isobands(&grid.values, thresholds)?;
+        Ok((bands, grid.error_bound(self, thresholds)))
+    }
+
+    /// Averages the blocks of `factor` * `factor` samples of the given `values`.
+    fn downsample(&self, values: &[Float], factor: usize) -> Result<Downsampled> {
+        let grid = self.grid(values)?;
+        let factor = factor.max(1);
+        let (cdx, cdy) = (self.dx.div_ceil(factor), self.dy.div_ceil(factor));
+        let mut sums = vec![(0., 0usize); cdx * cdy];
+        let mut ranges = vec![(Float::INFINITY, Float::NEG_INFINITY); cdx * cdy];
+        for y in 0..self.dy {
+            for x in 0..self.dx {
+                let b = (y / factor) * cdx + x / factor;
+                let v = grid.get(y * self.dx + x);
+                let (lo, hi) = &mut ranges[b];
+                if v.is_nan() {
+                    *lo = Float::NEG_INFINITY;
+                } else {
+                    sums[b].0 += v;
+                    sums[b].1 += 1;
+                    *lo = lo.min(v);
+                    *hi = hi.max(v);
+                }
+            }
+        }
+        let values = sums
+            .into_iter()
+            .map(|(sum, n)| if n > 0 { sum / n as Float } else { Float::NAN })
+            .collect();
+        // The values are combined with the weights, and the cells without data are `NaN`
+        let mut builder = self.clone();
+        builder.dx = cdx;
+        builder.dy = cdy;
+        builder.x_step = self.x_step * factor as Float;
+        builder.y_step = self.y_step * factor as Float;
+        builder.x_skew = self.x_skew * factor as Float;
+        builder.y_skew = self.y_skew * factor as Float;
+        builder.x_coords = self.x_coords.as_ref().map(|a| a.downsample(factor));
+        builder.y_coords = self.y_coords.as_ref().map(|a| a.downsample(factor));
+        builder.curvilinear =
+            (self.curvilinear.as_ref()).map(|c| self.curvilinear_downsample(c, factor));
+        builder.weights = None;
+        builder.nodata = None;
+        builder.order = GridOrder::RowMajor;
+        Ok(Downsampled {
+            builder,
+            values,
+            ranges,
+        })
+    }
+}
+
+impl Downsampled {
+    /// Computes the bound of the positional error of the contours of the given `thresholds`
+    /// (see [`ContourBuilder::contours_approximate`]).
+    ///
+    /// The cells of the downsampled grid are considered along with the cells crossing its
+    /// edges (the samples outside of the grid being outside of every contour). The 4 blocks
+    /// of a cell span the cell extended by half a cell on each side, and every pair of
+    /// neighbouring samples lies in the blocks of a common cell: the exact contours of
+    /// a threshold only cross the blocks of the cells whose samples straddle it. Since
+    /// the averages of the blocks lie in the range of their samples, the cells crossed
+    /// by the approximate contours are among them, and the points of the exact contours
+    /// around a cell `n` cells away from the nearest cell crossed by the approximate
+    /// contours are at most `n + 1.5` cell diagonals away from them (and vice versa).
+    fn error_bound(&self, fine: &ContourBuilder, thresholds: &[Float]) -> Float {
+        let (cdx, cdy) = (self.builder.dx, self.builder.dy);
+        let (w, h) = (cdx + 1, cdy + 1);
+        let (x_step, y_step) = (
+            (self.builder.x_coords.as_ref()).map_or(self.builder.x_step, Axis::max_spacing),
+            (self.builder.y_coords.as_ref()).map_or(self.builder.y_step, Axis::max_spacing),
+        );
+        let diagonal = match &self.builder.curvilinear {
+            Some(grid) => self.builder.curvilinear_max_diagonal(grid),
+            None if self.builder.has_skew() => {
+                let (a, b) = (self.builder.x_skew, self.builder.y_skew);
+                Float::max(
+                    (x_step + a).hypot(b + y_step),
+                    (x_step - a).hypot(b - y_step),
+                )
+            }
+            None => x_step.hypot(y_step),
+        };
+        // The value and the range of the block (x - 1, y - 1)
+        let block = |x: usize, y: usize| {
+            if x == 0 || y == 0 || x > cdx || y > cdy {
+                (
+                    Float::NEG_INFINITY,
+                    (Float::NEG_INFINITY, Float::NEG_INFINITY),
+                )
+            } else {
+                let b = (y - 1) * cdx + x - 1;
+                (self.values[b], self.ranges[b])
+            }
+        };
+        let cells = (0..w * h)
+            .map(|c| {
+                let (x, y) = (c % w, c / w);
+                [
+                    block(x, y),
+                    block(x + 1, y),
+                    block(x, y + 1),
+                    block(x + 1, y + 1),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let mut bound: Float = 0.;
+        for &threshold in thresholds {
+            let t = fine.ties.apply(threshold);
+            let mut exact = vec![false; w * h];
+            let mut distances = vec![usize::MAX; w * h];
+            let mut queue = VecDeque::new();
+            for (c, blocks) in cells.iter().enumerate() {
+                let (lo, hi) = blocks.iter().fold(
+                    (Float::INFINITY, Float::NEG_INFINITY),
+                    |(lo, hi), (_, range)| (lo.min(range.0), hi.max(range.1)),
+                );
+                exact[c] = lo < t && hi >= t;
+                let first = blocks[0].0 >= t;
+                if blocks[1..].iter().any(|&(v, _)| (v >= t) != first) {
+                    distances[c] = 0;
+                    queue.push_back(c);
+                }
+            }
+            if !exact.contains(&true) {
+                continue;
+            }
+            if queue.is_empty() {
+                return Float::INFINITY;
+            }
+            // The chessboard distance to the nearest cell crossed by the approximate contours
+            while let Some(c) = queue.pop_front() {
+                let (x, y) = (c % w, c / w);
+                for ny in y.saturating_sub(1)..=(y + 1).min(h - 1) {
+                    for nx in x.saturating_sub(1)..=(x + 1).min(w - 1) {
+                        let n = ny * w + nx;
+                        if distances[n] == usize::MAX {
+                            distances[n] = distances[c] + 1;
+                            queue.push_back(n);
+                        }
+                    }
+                }
+            }
+            let farthest = (0..w * h)
+                .filter(|&c| exact[c])
+                .map(|c| distances[c])
+                .max()
+                .unwrap_or(0);
+            bound = bound.max((farthest as Float + 1.5) * diagonal);
+        }
+        bound
+    }
+}
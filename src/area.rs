@@ -28,10 +28,28 @@ pub fn contains(ring: &[Pt], hole: &[Pt]) -> i32 {
         }
         i += 1;
     }
+    // All the vertices lie on the ring (e.g. around a cell without data, whose boundary
+    // is shared by the rings of several thresholds): the middles of the edges which
+    // aren't shared with the ring decide
+    let shared = |a: &Pt, b: &Pt| {
+        ring.windows(2)
+            .any(|e| (e[0] == *a && e[1] == *b) || (e[0] == *b && e[1] == *a))
+    };
+    for w in hole.windows(2).filter(|w| !shared(&w[0], &w[1])) {
+        let middle = Pt {
+            x: (w[0].x + w[1].x) / 2.,
+            y: (w[0].y + w[1].y) / 2.,
+        };
+        c = ring_contains(ring, &middle);
+        if c != 0 {
+            return c;
+        }
+    }
     0
 }
 
-fn ring_contains(ring: &[Pt], point: &Pt) -> i32 {
+/// Returns 1 if the `point` is inside the `ring`, -1 if it is outside and 0 if it is on its boundary.
+pub(crate) fn ring_contains(ring: &[Pt], point: &Pt) -> i32 {
     let x = point.x;
     let y = point.y;
     let n = ring.len();
@@ -57,11 +75,9 @@ fn ring_contains(ring: &[Pt], point: &Pt) -> i32 {
 
 fn segment_contains(a: &Pt, b: &Pt, c: &Pt) -> bool {
     if collinear(a, b, c) {
-        if (a.x - b.x).abs() < Float::EPSILON {
-            within(a.y, c.y, b.y)
-        } else {
-            within(a.x, c.x, b.x)
-        }
+        // Both coordinates are checked so that a degenerate segment
+        // (e.g. the one closing a ring) only contains its own point
+        within(a.x, c.x, b.x) && within(a.y, c.y, b.y)
     } else {
         false
     }
@@ -74,3 +90,28 @@ fn collinear(a: &Pt, b: &Pt, c: &Pt) -> bool {
 fn within(p: Float, q: Float, r: Float) -> bool {
     p <= q && q <= r || r <= q && q <= p
 }
+
+/// Returns true if the `point` is inside the `polygon` (or on its exterior boundary)
+/// and not strictly inside one of its holes.
+pub(crate) fn polygon_contains(polygon: &geo_types::Polygon<Float>, point: &Pt) -> bool {
+    ring_contains(&polygon.exterior().0, point) != -1
+        && polygon
+            .interiors()
+            .iter()
+            .all(|hole| ring_contains(&hole.0, point) != 1)
+}
+
+/// Applies `f` to every coordinate of the given `multipolygon`.
+pub(crate) fn for_each_coord_mut(
+    multipolygon: &mut geo_types::MultiPolygon<Float>,
+    mut f: impl FnMut(&mut Pt),
+) {
+    multipolygon.0.iter_mut().for_each(|polygon| {
+        polygon.exterior_mut(|ring| ring.0.iter_mut().for_each(&mut f));
+        polygon.interiors_mut(|rings| {
+            rings
+                .iter_mut()
+                .for_each(|ring| ring.0.iter_mut().for_each(&mut f))
+        });
+    });
+}
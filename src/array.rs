@@ -0,0 +1,79 @@
+use crate::error::Result;
+use crate::{Band, Contour, ContourBuilder, Float, Line};
+use ndarray::ArrayView2;
+
+impl ContourBuilder {
+    /// Returns the builder for the dimensions of the given array (of shape
+    /// `(rows, columns)`, i.e. `(dy, dx)`), keeping all its other settings.
+    fn for_array(&self, values: &ArrayView2<'_, Float>) -> ContourBuilder {
+        let (dy, dx) = values.dim();
+        ContourBuilder {
+            dx,
+            dy,
+            ..self.clone()
+        }
+    }
+
+    /// Computes isolines (see [`ContourBuilder::lines`]) according to the given 2D array
+    /// of `values`, indexed by row (`y`) then column (`x`), the dimensions of the grid
+    /// being those of the array. Non-contiguous views (e.g. transposed or strided ones)
+    /// are read in place (see [`GridSource`](crate::GridSource)).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The view of the array of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn lines_from_array(
+        &self,
+        values: ArrayView2<'_, Float>,
+        thresholds: &[Float],
+    ) -> Result<Vec<Line>> {
+        let builder = self.for_array(&values);
+        match values.as_slice() {
+            Some(values) => builder.lines(values, thresholds),
+            None => builder.lines_from_source(&values, thresholds),
+        }
+    }
+
+    /// Computes contours (see [`ContourBuilder::contours`]) according to the given 2D array
+    /// of `values`, indexed by row (`y`) then column (`x`), the dimensions of the grid
+    /// being those of the array. Non-contiguous views (e.g. transposed or strided ones)
+    /// are read in place (see [`GridSource`](crate::GridSource)).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The view of the array of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn contours_from_array(
+        &self,
+        values: ArrayView2<'_, Float>,
+        thresholds: &[Float],
+    ) -> Result<Vec<Contour>> {
+        let builder = self.for_array(&values);
+        match values.as_slice() {
+            Some(values) => builder.contours(values, thresholds),
+            None => builder.contours_from_source(&values, thresholds),
+        }
+    }
+
+    /// Computes isobands (see [`ContourBuilder::isobands`]) according to the given 2D array
+    /// of `values`, indexed by row (`y`) then column (`x`), the dimensions of the grid
+    /// being those of the array. Non-contiguous views (e.g. transposed or strided ones)
+    /// are read in place (see [`GridSource`](crate::GridSource)).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The view of the array of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn isobands_from_array(
+        &self,
+        values: ArrayView2<'_, Float>,
+        thresholds: &[Float],
+    ) -> Result<Vec<Band>> {
+        let builder = self.for_array(&values);
+        match values.as_slice() {
+            Some(values) => builder.isobands(values, thresholds),
+            None => builder.isobands_from_source(&values, thresholds),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::error::Result;
+use crate::gradient::{Gradient, GradientOperator};
+use crate::{ContourBuilder, Float, Line, Pt};
+use geo_types::{LineString, Polygon};
+
+/// The side of the isolines the higher values lie on when following the direction
+/// of the arrows (see [`ContourBuilder::arrows`]), the y axis pointing up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlowDirection {
+    /// The higher values are on the right (e.g. the geostrophic wind along the isobars
+    /// in the northern hemisphere).
+    #[default]
+    HighOnRight,
+    /// The higher values are on the left (e.g. the geostrophic wind along the isobars
+    /// in the southern hemisphere).
+    HighOnLeft,
+}
+
+/// A directional marker placed along an isoline (see [`ContourBuilder::arrows`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arrow {
+    point: Pt,
+    angle: Float,
+}
+
+impl Arrow {
+    /// Get the point of the isoline the arrow is placed at.
+    pub fn point(&self) -> Pt {
+        self.point
+    }
+
+    /// Get the direction of the arrow, as the angle (in radians, counter-clockwise
+    /// if the y axis points up) from the x axis.
+    pub fn angle(&self) -> Float {
+        self.angle
+    }
+
+    /// Returns the arrowhead as a triangle of the given `size` (its length along
+    /// the isoline) centered on the point of the arrow.
+    pub fn polygon(&self, size: Float) -> Polygon<Float> {
+        let (dy, dx) = self.angle.sin_cos();
+        let (half, width) = (size / 2., size / 3.);
+        let at = |along: Float, across: Float| Pt {
+            x: self.point.x + dx * along - dy * across,
+            y: self.point.y + dy * along + dx * across,
+        };
+        let tip = at(half, 0.);
+        Polygon::new(
+            LineString(vec![tip, at(-half, -width), at(-half, width), tip]),
+            vec![],
+        )
+    }
+}
+
+impl ContourBuilder {
+    /// Places directional markers along the given isolines (computed by this builder from
+    /// the given `values`), oriented along the lines with the higher values on the given
+    /// side, e.g. to show the direction of the flow along streamlines or isobars.
+    ///
+    /// Returns the arrows of each given isoline. As for the [`hachures`](Self::hachures),
+    /// the arrows are oriented using the gradient of the values, and no arrow is placed
+    /// where it is missing or flat, nor along the edges of the grid (where the isolines
+    /// are closed). No arrow is placed if `spacing` isn't strictly positive.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values used to compute the isolines.
+    /// * `lines` - The isolines along which the arrows are placed.
+    /// * `spacing` - The distance between the arrows along the lines
+    ///   (in the output coordinate system), the first arrow being at half of it.
+    /// * `direction` - The side of the lines the higher values lie on.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use contour::{ContourBuilder, Float, FlowDirection};
+    ///
+    /// // A high pressure area centered on (5, 5)
+    /// let values = (0..100)
+    ///     .map(|i| 1020. - ((i % 10) as Float - 4.5).hypot((i / 10) as Float - 4.5))
+    ///     .collect::<Vec<_>>();
+    /// let builder = ContourBuilder::new(10, 10, true);
+    /// let lines = builder.lines(&values, &[1017.]).unwrap();
+    /// let arrows = builder.arrows(&values, &lines, 4., FlowDirection::HighOnRight).unwrap();
+    /// let triangles = arrows[0].iter().map(|a| a.polygon(0.5)).collect::<Vec<_>>();
+    /// assert_eq!(triangles.len(), 5);
+    /// ```
+    pub fn arrows(
+        &self,
+        values: &[Float],
+        lines: &[Line],
+        spacing: Float,
+        direction: FlowDirection,
+    ) -> Result<Vec<Vec<Arrow>>> {
+        let gradient = self.gradient(values, GradientOperator::CentralDifferences, None)?;
+        Ok(lines
+            .iter()
+            .map(|line| {
+                if spacing > 0. && spacing.is_finite() {
+                    line.geometry
+                        .0
+                        .iter()
+                        .flat_map(|ls| self.line_arrows(&gradient, &ls.0, spacing, direction))
+                        .collect()
+                } else {
+                    Vec::new()
+                }
+            })
+            .collect())
+    }
+
+    /// Places the arrows along the given line, every `spacing` from `spacing / 2`.
+    fn line_arrows(
+        &self,
+        gradient: &Gradient,
+        line: &[Pt],
+        spacing: Float,
+        direction: FlowDirection,
+    ) -> Vec<Arrow> {
+        let mut arrows = Vec::new();
+        // The distance along the line to the next arrow
+        let mut next = spacing / 2.;
+        for w in line.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            let (sx, sy) = (b.x - a.x, b.y - a.y);
+            let len = sx.hypot(sy);
+            if self.on_edge(&a) && self.on_edge(&b) {
+                // The isoline follows the edges of the grid: start over after them
+                next = spacing / 2.;
+                continue;
+            }
+            while next <= len && len > 0. {
+                let t = next / len;
+                let p = Pt {
+                    x: a.x + sx * t,
+                    y: a.y + sy * t,
+                };
+                let (gx, gy) = self.gradient_at(gradient, &p);
+                // The component of the gradient on the right of the segment
+                let right = sy * gx - sx * gy;
+                if right.is_finite() && right != 0. {
+                    let forward = (right > 0.) == (direction == FlowDirection::HighOnRight);
+                    let (dx, dy) = if forward { (sx, sy) } else { (-sx, -sy) };
+                    arrows.push(Arrow {
+                        point: p,
+                        angle: dy.atan2(dx),
+                    });
+                }
+                next += spacing;
+            }
+            next -= len;
+        }
+        arrows
+    }
+}
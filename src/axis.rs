@@ -0,0 +1,93 @@
+use crate::Float;
+use std::sync::Arc;
+
+/// The coordinates of the samples along an axis of a grid with a non-uniform spacing
+/// (see [`ContourBuilder::x_coords`](crate::ContourBuilder::x_coords)).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Axis(Arc<[Float]>);
+
+impl Axis {
+    pub(crate) fn new(coords: &[Float]) -> Self {
+        Axis(coords.into())
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub(crate) fn coords(&self) -> &[Float] {
+        &self.0
+    }
+
+    /// Returns the index of the first sample of the segment used to map the given
+    /// position, the positions before the first sample and after the last one
+    /// being extrapolated from the first and last segments.
+    fn segment(&self, u: Float) -> usize {
+        (u.floor().max(0.) as usize).min(self.0.len().saturating_sub(2))
+    }
+
+    /// Returns the spacing between the samples `i` and `i + 1` (1 if there is a single sample).
+    fn spacing(&self, i: usize) -> Float {
+        match self.0.get(i + 1) {
+            Some(next) => next - self.0[i],
+            None => 1.,
+        }
+    }
+
+    /// Maps the given grid coordinate (the sample `i` lying at `i + 0.5`)
+    /// to the output coordinate, interpolating linearly between the samples.
+    pub(crate) fn map(&self, g: Float) -> Float {
+        if self.0.is_empty() {
+            return g;
+        }
+        let u = g - 0.5;
+        let i = self.segment(u);
+        self.0[i] + (u - i as Float) * self.spacing(i)
+    }
+
+    /// Maps the given output coordinate back to a (fractional) grid coordinate.
+    pub(crate) fn unmap(&self, v: Float) -> Float {
+        if self.0.is_empty() {
+            return v;
+        }
+        let direction = self.direction();
+        let before = self.0.partition_point(|&c| (c - v) * direction < 0.);
+        let i = before.saturating_sub(1).min(self.0.len().saturating_sub(2));
+        i as Float + (v - self.0[i]) / self.spacing(i) + 0.5
+    }
+
+    /// Returns `-1` if the coordinates decrease along the axis, `1` otherwise.
+    pub(crate) fn direction(&self) -> Float {
+        match (self.0.first(), self.0.last()) {
+            (Some(first), Some(last)) if last < first => -1.,
+            _ => 1.,
+        }
+    }
+
+    /// Returns the largest spacing between two consecutive samples.
+    pub(crate) fn max_spacing(&self) -> Float {
+        (0..self.0.len().saturating_sub(1))
+            .map(|i| self.spacing(i).abs())
+            .fold(0., Float::max)
+    }
+
+    /// Extends the axis by `n` samples on each side, extrapolated from the first
+    /// and last segments (see [`ContourBuilder::padding`](crate::ContourBuilder::padding)).
+    pub(crate) fn pad(&self, n: usize) -> Axis {
+        Axis(
+            (0..self.0.len() + 2 * n)
+                .map(|i| self.map(i as Float - n as Float + 0.5))
+                .collect(),
+        )
+    }
+
+    /// Averages the coordinates of the blocks of `factor` samples.
+    pub(crate) fn downsample(&self, factor: usize) -> Axis {
+        Axis(
+            self.0
+                .chunks(factor.max(1))
+                .map(|block| block.iter().sum::<Float>() / block.len() as Float)
+                .collect(),
+        )
+    }
+}
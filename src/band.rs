@@ -1,12 +1,66 @@
-use crate::Float;
-use geo_types::MultiPolygon;
+use crate::area::{contains, for_each_coord_mut, polygon_contains};
+use crate::bbox::polygons_bbox;
+use crate::resample::resample_polygons;
+use crate::validity::{validate, ValidityReport};
+use crate::{Float, Metadata, Pt};
+use geo_types::{MultiPolygon, Rect};
+use std::sync::Arc;
+
+/// A function formatting the label of a band from its min / max values.
+#[derive(Clone)]
+pub(crate) struct LabelFormatter(pub(crate) Arc<dyn Fn(Float, Float) -> String + Send + Sync>);
+
+impl std::fmt::Debug for LabelFormatter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("LabelFormatter")
+    }
+}
+
+impl PartialEq for LabelFormatter {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
 
 /// An isoband has the geometry and min / max values of a contour ring, built by [ContourBuilder](`crate::contourbuilder::ContourBuilder`).
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Band {
     pub(crate) geometry: MultiPolygon<Float>,
     pub(crate) min_v: Float,
     pub(crate) max_v: Float,
+    pub(crate) threshold_index: usize,
+    pub(crate) metadata: Metadata,
+    pub(crate) bbox: Option<Rect<Float>>,
+    pub(crate) label: Option<String>,
+    pub(crate) depths: Vec<usize>,
+    pub(crate) part: Option<usize>,
+}
+
+/// Computes the nesting depth of each polygon of the given `geometry`
+/// (see [`Band::depths`]).
+pub(crate) fn nesting_depths(geometry: &MultiPolygon<Float>) -> Vec<usize> {
+    let rings = geometry
+        .0
+        .iter()
+        .enumerate()
+        .flat_map(|(i, p)| {
+            std::iter::once(p.exterior())
+                .chain(p.interiors())
+                .map(move |r| (i, r))
+        })
+        .collect::<Vec<_>>();
+    geometry
+        .0
+        .iter()
+        .enumerate()
+        .map(|(i, polygon)| {
+            rings
+                .iter()
+                .filter(|(j, ring)| *j != i && contains(&ring.0, &polygon.exterior().0) != -1)
+                .count()
+        })
+        .collect()
 }
 
 impl Band {
@@ -15,6 +69,56 @@ impl Band {
         &self.geometry
     }
 
+    /// Get the bounding box of the geometry of this band (computed when it was built),
+    /// or `None` if its geometry is empty.
+    pub fn bbox(&self) -> Option<Rect<Float>> {
+        self.bbox
+    }
+
+    /// Recomputes the bounding box of this band after its geometry was modified.
+    pub(crate) fn update_bbox(&mut self) {
+        self.bbox = polygons_bbox(&self.geometry);
+    }
+
+    /// Get the nesting depth of each polygon of this band (in the order of its geometry):
+    /// the number of rings of the band (exterior rings and holes) enclosing its exterior
+    /// ring. It is always even, the rings enclosed by an odd number of rings being holes,
+    /// so that drawing the polygons by increasing depth (each hole being painted over by
+    /// the polygons it encloses) renders the band without having to cut its holes.
+    pub fn depths(&self) -> &[usize] {
+        &self.depths
+    }
+
+    /// Get the position of the polygon of this band in the geometry of the band
+    /// it was split from, if it was built by [`into_polygons`](Self::into_polygons).
+    pub fn part(&self) -> Option<usize> {
+        self.part
+    }
+
+    /// Splits this band into one band per polygon of its geometry (e.g. to write
+    /// one polygon per row), each carrying the min / max values, label and metadata
+    /// of this band, the nesting depth of its polygon and its position as [`part`](Self::part).
+    pub fn into_polygons(mut self) -> Vec<Band> {
+        let polygons = std::mem::replace(&mut self.geometry, MultiPolygon(vec![]));
+        let depths = std::mem::take(&mut self.depths);
+        polygons
+            .0
+            .into_iter()
+            .zip(depths)
+            .enumerate()
+            .map(|(i, (polygon, depth))| {
+                let mut band = Band {
+                    geometry: MultiPolygon(vec![polygon]),
+                    depths: vec![depth],
+                    part: Some(i),
+                    ..self.clone()
+                };
+                band.update_bbox();
+                band
+            })
+            .collect()
+    }
+
     /// Get the owned polygons and thresholds (min and max) of this band.
     pub fn into_inner(self) -> (MultiPolygon<Float>, Float, Float) {
         (self.geometry, self.min_v, self.max_v)
@@ -30,6 +134,66 @@ impl Band {
         self.max_v
     }
 
+    /// Get the position of its lower threshold in the slice of thresholds
+    /// used to construct this band (e.g. to map it back to a legend or a style
+    /// after filtering, sorting or parallel computation).
+    pub fn threshold_index(&self) -> usize {
+        self.threshold_index
+    }
+
+    /// Get the position of its lower threshold in the slice of thresholds
+    /// used to construct this band.
+    #[deprecated(note = "use `threshold_index` instead")]
+    pub fn index(&self) -> usize {
+        self.threshold_index()
+    }
+
+    /// Borrow the label of this band, if a label formatter was set
+    /// (see [`ContourBuilder::band_labels`](crate::ContourBuilder::band_labels)).
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Applies the transform `f` to every vertex of the geometry of this band, in place
+    /// (e.g. to project its coordinates or convert their units), preserving its min / max values.
+    pub fn map_coords(&mut self, mut f: impl FnMut(Pt) -> Pt) {
+        for_each_coord_mut(&mut self.geometry, |pt| *pt = f(*pt));
+        self.update_bbox();
+    }
+
+    /// Resamples each ring (see [`Line::resample_uniform`](crate::Line::resample_uniform)).
+    pub fn resample_uniform(&mut self, spacing: Float) {
+        resample_polygons(&mut self.geometry, spacing);
+        self.update_bbox();
+    }
+
+    /// Returns true if the point (`x`, `y`) is inside one of the polygons of this band
+    /// (or on its exterior boundary), and not strictly inside one of their holes.
+    pub fn contains(&self, x: Float, y: Float) -> bool {
+        let point = Pt { x, y };
+        self.geometry
+            .0
+            .iter()
+            .any(|polygon| polygon_contains(polygon, &point))
+    }
+
+    /// Checks the validity of the geometry of this band (closure, winding,
+    /// self-intersections of the rings and containment of the holes) without
+    /// modifying it, e.g. before handing it over to strict consumers.
+    pub fn validate(&self) -> ValidityReport {
+        validate(&self.geometry)
+    }
+
+    /// Borrow the metadata attached to the threshold used to construct this band (the metadata of its lower threshold).
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Formats the geometry of this band as WKT (a `MULTIPOLYGON`).
+    pub fn to_wkt(&self) -> String {
+        crate::tabular::multipolygon_wkt(&self.geometry)
+    }
+
     #[cfg(feature = "geojson")]
     /// Convert the band to a struct from the `geojson` crate.
     ///
@@ -58,16 +222,35 @@ impl Band {
     /// assert_eq!(&geojson_string[0..27], r#"{"geometry":{"coordinates":"#);
     /// ```
     pub fn to_geojson(&self) -> geojson::Feature {
-        let mut properties = geojson::JsonObject::with_capacity(2);
-        properties.insert("min_v".to_string(), self.min_v.into());
-        properties.insert("max_v".to_string(), self.max_v.into());
+        self.to_geojson_with_keys(&crate::GeoJsonKeys::default())
+    }
 
+    #[cfg(feature = "geojson")]
+    /// Convert the band to a struct from the `geojson` crate,
+    /// using the given names for its properties.
+    pub fn to_geojson_with_keys(&self, keys: &crate::GeoJsonKeys) -> geojson::Feature {
         geojson::Feature {
             bbox: None,
             geometry: Some(geojson::Geometry::from(self.geometry())),
             id: None,
-            properties: Some(properties),
+            properties: Some(self.properties(keys)),
             foreign_members: None,
         }
     }
+
+    #[cfg(feature = "geojson")]
+    /// Builds the GeoJSON properties of the band, using the given names.
+    pub(crate) fn properties(&self, keys: &crate::GeoJsonKeys) -> geojson::JsonObject {
+        let mut properties = geojson::JsonObject::with_capacity(2);
+        properties.insert(keys.min_v.clone(), self.min_v.into());
+        properties.insert(keys.max_v.clone(), self.max_v.into());
+        if let Some(label) = &self.label {
+            properties.insert(keys.label.clone(), label.clone().into());
+        }
+        if let Some(part) = self.part {
+            properties.insert(keys.part.clone(), part.into());
+        }
+        crate::metadata::insert_metadata(&mut properties, &self.metadata);
+        properties
+    }
 }
@@ -0,0 +1,32 @@
+use crate::{Float, Pt};
+use geo_types::{MultiLineString, MultiPolygon, Rect};
+
+/// Extends the (optional) bounding box `rect` to the given point.
+pub(crate) fn extend(rect: Option<Rect<Float>>, pt: &Pt) -> Option<Rect<Float>> {
+    Some(match rect {
+        None => Rect::new(*pt, *pt),
+        Some(rect) => Rect::new(
+            (rect.min().x.min(pt.x), rect.min().y.min(pt.y)),
+            (rect.max().x.max(pt.x), rect.max().y.max(pt.y)),
+        ),
+    })
+}
+
+/// Computes the bounding box of the given polygons (i.e. of their exterior rings),
+/// if they have any vertex.
+pub(crate) fn polygons_bbox(geometry: &MultiPolygon<Float>) -> Option<Rect<Float>> {
+    geometry
+        .0
+        .iter()
+        .flat_map(|polygon| polygon.exterior().0.iter())
+        .fold(None, extend)
+}
+
+/// Computes the bounding box of the given lines, if they have any vertex.
+pub(crate) fn lines_bbox(geometry: &MultiLineString<Float>) -> Option<Rect<Float>> {
+    geometry
+        .0
+        .iter()
+        .flat_map(|line| line.0.iter())
+        .fold(None, extend)
+}
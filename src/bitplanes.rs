@@ -0,0 +1,122 @@
+use crate::diagnostics::ThresholdDiagnostics;
+use crate::error::Result;
+use crate::isoringbuilder::IsoRingBuilder;
+use crate::nodata::cut_rings;
+use crate::{Contour, ContourBuilder, Float, Line, Ring};
+
+/// The classification of the cells of a fixed grid for a set of candidate levels,
+/// packed into one bitset per level, built by [`ContourBuilder::bit_planes`].
+///
+/// The isolines and contours of any of these levels are then computed without
+/// comparing the values of the grid to the threshold again (e.g. for interactive
+/// threshold scrubbing), trading memory (one bit per cell and level) for speed.
+#[derive(Debug, Clone)]
+pub struct BitPlanes {
+    builder: ContourBuilder,
+    values: Vec<Float>,
+    levels: Vec<Float>,
+    planes: Vec<Vec<u64>>,
+}
+
+impl BitPlanes {
+    /// Get the candidate levels whose classification was precomputed.
+    pub fn levels(&self) -> &[Float] {
+        &self.levels
+    }
+
+    /// Computes the rings (in grid coordinates) of the `threshold`, from its bitset
+    /// if it is one of the precomputed levels, cut along the cells without data if
+    /// they are used as `lines`.
+    fn rings(
+        &self,
+        isoring: &mut IsoRingBuilder,
+        threshold: Float,
+        lines: bool,
+    ) -> Result<Vec<Ring>> {
+        let grid = self.builder.grid(&self.values)?;
+        match self.levels.iter().position(|l| *l == threshold) {
+            Some(ix) => {
+                let plane = &self.planes[ix];
+                let mut rings = isoring.compute_with(|i| plane[i >> 6] >> (i & 63) & 1 == 1)?;
+                if lines {
+                    let (dx, dy) = (self.builder.dx, self.builder.dy);
+                    rings = cut_rings(&rings, grid, dx, dy).unwrap_or(rings);
+                }
+                self.builder
+                    .finalize_rings(&mut rings, grid, self.builder.ties.apply(threshold));
+                Ok(rings)
+            }
+            None if lines => self.builder.final_lines(isoring, grid, threshold, None),
+            None => self.builder.final_rings(isoring, grid, threshold, None),
+        }
+    }
+
+    /// Computes isolines for the given `thresholds` (see [`ContourBuilder::lines`]),
+    /// using the precomputed classification of those which are candidate levels.
+    ///
+    /// # Arguments
+    ///
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn lines(&self, thresholds: &[Float]) -> Result<Vec<Line>> {
+        let lines = self
+            .builder
+            .map_thresholds(thresholds.len(), |isoring, i| {
+                let rings = self.rings(isoring, thresholds[i], true)?;
+                Ok(self.builder.line(rings, thresholds, i))
+            })?;
+        Ok(self
+            .builder
+            .without_empty(lines, |l| l.geometry.0.is_empty()))
+    }
+
+    /// Computes contours for the given `thresholds` (see [`ContourBuilder::contours`]),
+    /// using the precomputed classification of those which are candidate levels.
+    ///
+    /// # Arguments
+    ///
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn contours(&self, thresholds: &[Float]) -> Result<Vec<Contour>> {
+        let contours = self
+            .builder
+            .map_thresholds(thresholds.len(), |isoring, i| {
+                let rings = self.rings(isoring, thresholds[i], false)?;
+                let mut diagnostics = ThresholdDiagnostics::new(thresholds[i], i);
+                Ok(self.builder.contour(rings, thresholds, i, &mut diagnostics))
+            })?;
+        Ok(self
+            .builder
+            .without_empty(contours, |c| c.geometry.0.is_empty()))
+    }
+}
+
+impl ContourBuilder {
+    /// Precomputes the classification of the cells of the grid of `values` for each
+    /// of the candidate `levels`, packed into bitsets, to answer subsequent requests
+    /// of isolines and contours for any of these levels faster (see [`BitPlanes`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `levels` - The slice of candidate levels.
+    pub fn bit_planes(&self, values: &[Float], levels: &[Float]) -> Result<BitPlanes> {
+        self.grid(values)?;
+        let (builder, values) = self
+            .padded(values)
+            .unwrap_or_else(|| (self.clone(), values.to_vec()));
+        let grid = builder.grid(&values)?;
+        let planes = builder.map_thresholds(levels.len(), |_, i| {
+            let threshold = builder.ties.apply(levels[i]);
+            let mut plane = vec![0u64; grid.len().div_ceil(64)];
+            (0..grid.len())
+                .filter(|&j| grid.get(j) >= threshold)
+                .for_each(|j| plane[j >> 6] |= 1 << (j & 63));
+            Ok(plane)
+        })?;
+        Ok(BitPlanes {
+            builder,
+            values,
+            levels: levels.to_vec(),
+            planes,
+        })
+    }
+}
@@ -0,0 +1,223 @@
+use crate::area::area;
+use crate::merge::clip_ring;
+use crate::nodata::cut_rings_at;
+use crate::padding::Padding;
+use crate::weights::Grid;
+use crate::{ContourBuilder, Float, Pt, Ring};
+
+/// How the (virtual) values around the grid are considered
+/// (see [`ContourBuilder::border_policy`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BorderPolicy {
+    /// The values around the grid are below every threshold: the contours touching
+    /// the edges of the grid are closed along them, as are the isolines.
+    #[default]
+    Below,
+    /// The values around the grid are above every threshold: the contours cover the
+    /// edges of the grid (but for the regions below the threshold touching them,
+    /// which are closed along them).
+    Above,
+    /// The values around the grid mirror the values on its edges: the contours and
+    /// isolines cross the edges of the grid (the contours being closed along them),
+    /// the isolines ending on them.
+    Mirror,
+    /// As [`BorderPolicy::Below`], but the isolines are open, ending on the outermost
+    /// samples of the grid rather than following its edges (as GDAL or matplotlib).
+    Open,
+}
+
+impl ContourBuilder {
+    /// Returns the builder and the values of the grid extended by one cell on each side
+    /// (whose rings are clipped to the original grid), if the border policy requires
+    /// the values around the grid.
+    pub(crate) fn bordered(&self, values: &[Float]) -> Option<(ContourBuilder, Vec<Float>)> {
+        if !matches!(self.border, BorderPolicy::Above | BorderPolicy::Mirror) {
+            return None;
+        }
+        let (mut builder, mut values) = self.pad_grid(values, 1, Padding::Replicate);
+        if self.border == BorderPolicy::Above {
+            let (dx, dy) = (builder.dx, builder.dy);
+            for (k, v) in values.iter_mut().enumerate() {
+                let (x, y) = (k % dx, k / dx);
+                if x == 0 || y == 0 || x == dx - 1 || y == dy - 1 {
+                    *v = Float::MAX;
+                }
+            }
+        }
+        builder.clip_border = true;
+        Some((builder, values))
+    }
+
+    /// Returns the extent of the original grid in the coordinates of the extended one.
+    fn inner_extent(&self) -> [Float; 4] {
+        [1., 1., (self.dx - 1) as Float, (self.dy - 1) as Float]
+    }
+
+    /// Smooths the given `rings` of the extended grid (see [`ContourBuilder::bordered`]),
+    /// leaving them in grid coordinates: the vertices between the grid and the virtual
+    /// cells above every threshold are kept on the edges of the grid.
+    fn smooth_extended(&self, rings: &mut Vec<Ring>, grid: Grid<'_>, threshold: Float) {
+        let inner = ContourBuilder {
+            clip_border: false,
+            ..self.grid_space()
+        };
+        let [x0, y0, x1, y1] = self.inner_extent();
+        let on_edge = |v: Float, min: Float, max: Float| (v == min || v == max).then_some(v);
+        let fixed = (self.border == BorderPolicy::Above).then(|| {
+            rings
+                .iter()
+                .map(|ring| {
+                    ring.iter()
+                        .map(|p| (on_edge(p.x, x0, x1), on_edge(p.y, y0, y1)))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        });
+        inner.finalize_rings(rings, grid, threshold);
+        for (ring, fixed) in rings.iter_mut().zip(fixed.iter().flatten()) {
+            if ring.len() != fixed.len() {
+                continue;
+            }
+            for (p, (x, y)) in ring.iter_mut().zip(fixed) {
+                p.x = x.unwrap_or(p.x);
+                p.y = y.unwrap_or(p.y);
+            }
+        }
+    }
+
+    /// Computes the final coordinates of the given `rings` of the extended grid
+    /// (see [`ContourBuilder::bordered`]), clipped to the original grid.
+    pub(crate) fn finalize_extended(
+        &self,
+        rings: &mut Vec<Ring>,
+        grid: Grid<'_>,
+        threshold: Float,
+    ) {
+        self.smooth_extended(rings, grid, threshold);
+        let extent = self.inner_extent();
+        for ring in rings.iter_mut() {
+            *ring = clip_ring(ring, &extent);
+            // The rings collapsed once clipped (along the edges of the grid, or around
+            // the samples equal to the threshold) don't bound anything: they are dropped
+            if ring.len() < 4 || area(ring) == 0. {
+                ring.clear();
+            }
+        }
+        self.transform_rings(rings);
+        self.densify_rings(rings);
+    }
+
+    /// Returns the final isolines of the given (unsmoothed) `rings`, if they differ from
+    /// the final rings: cut where they follow the boundary of the cells without data,
+    /// the edges of the grid according to the border policy, or the seam of a grid
+    /// periodic in x.
+    pub(crate) fn final_isolines(
+        &self,
+        rings: &[Ring],
+        grid: Grid<'_>,
+        threshold: Float,
+    ) -> Option<Vec<Ring>> {
+        let cut = self.cut_nodata_rings(rings, grid, threshold);
+        if self.clip_border {
+            let mut lines = cut.unwrap_or_else(|| rings.to_vec());
+            self.smooth_extended(&mut lines, grid, threshold);
+            let extent = self.inner_extent();
+            // The rings collapsed once clipped are dropped, as in the contours
+            lines.retain(|line| {
+                let closed = line.len() > 1 && line[0] == line[line.len() - 1];
+                let clipped = closed.then(|| clip_ring(line, &extent));
+                clipped.is_none_or(|ring| ring.len() > 3 && area(&ring) != 0.)
+            });
+            let mut lines = clip_lines(&lines, &extent);
+            self.transform_rings(&mut lines);
+            self.densify_rings(&mut lines);
+            return Some(lines);
+        }
+        if self.seam.is_some() {
+            // The isolines cut at the seam (as the contours) aren't closed along it
+            let builder = ContourBuilder {
+                open_seam: true,
+                ..self.clone()
+            };
+            let mut lines = cut.unwrap_or_else(|| rings.to_vec());
+            builder.finalize_rings(&mut lines, grid, threshold);
+            return Some(lines);
+        }
+        let cut = match self.border {
+            BorderPolicy::Open => {
+                let (dx, dy) = (self.dx as Float, self.dy as Float);
+                let on_edge = |p: &Pt| p.x == 0. || p.y == 0. || p.x == dx || p.y == dy;
+                cut_rings_at(cut.as_deref().unwrap_or(rings), on_edge).or(cut)
+            }
+            _ => cut,
+        };
+        cut.map(|mut lines| {
+            self.finalize_rings(&mut lines, grid, threshold);
+            lines
+        })
+    }
+}
+
+/// Clips the given lines with the given (x_min, y_min, x_max, y_max) rectangle,
+/// returning the parts inside it (Liang–Barsky).
+fn clip_lines(lines: &[Ring], clip: &[Float; 4]) -> Vec<Ring> {
+    let mut result = Vec::new();
+    for line in lines {
+        let mut current: Ring = Vec::new();
+        for w in line.windows(2) {
+            let Some((a, b)) = clip_segment(w[0], w[1], clip) else {
+                if current.len() > 1 {
+                    result.push(std::mem::take(&mut current));
+                }
+                current.clear();
+                continue;
+            };
+            if current.last() != Some(&a) {
+                if current.len() > 1 {
+                    result.push(std::mem::take(&mut current));
+                }
+                current = vec![a];
+            }
+            current.push(b);
+        }
+        if current.len() > 1 {
+            result.push(current);
+        }
+    }
+    result
+}
+
+/// Clips the segment [a, b] with the given rectangle.
+fn clip_segment(a: Pt, b: Pt, clip: &[Float; 4]) -> Option<(Pt, Pt)> {
+    let (sx, sy) = (b.x - a.x, b.y - a.y);
+    let (mut t0, mut t1): (Float, Float) = (0., 1.);
+    for (p, q) in [
+        (-sx, a.x - clip[0]),
+        (sx, clip[2] - a.x),
+        (-sy, a.y - clip[1]),
+        (sy, clip[3] - a.y),
+    ] {
+        if p == 0. {
+            if q < 0. {
+                return None;
+            }
+        } else if p < 0. {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+    let at = |t: Float| {
+        if t == 0. {
+            a
+        } else if t == 1. {
+            b
+        } else {
+            Pt {
+                x: a.x + t * sx,
+                y: a.y + t * sy,
+            }
+        }
+    };
+    (t0 <= t1).then(|| (at(t0), at(t1)))
+}
@@ -0,0 +1,74 @@
+use crate::{Float, Ring};
+use rustc_hash::{FxHashMap, FxHasher};
+use std::hash::Hasher;
+use std::sync::{Arc, Mutex};
+
+/// The cached rings, by grid fingerprint and threshold.
+type Entries = FxHashMap<(u64, u64), Vec<Ring>>;
+
+/// Cache of the (raw) rings computed for a grid and a threshold,
+/// shared between the clones of a [`ContourBuilder`](crate::ContourBuilder).
+#[derive(Clone, Default)]
+pub(crate) struct RingCache {
+    entries: Arc<Mutex<Entries>>,
+}
+
+impl RingCache {
+    /// Returns the rings cached for the grid with the given `fingerprint`
+    /// and the given `threshold`, computing (and caching) them if needed.
+    pub(crate) fn get_or_compute(
+        &self,
+        fingerprint: u64,
+        threshold: Float,
+        compute: impl FnOnce() -> crate::Result<Vec<Ring>>,
+    ) -> crate::Result<Vec<Ring>> {
+        #[allow(clippy::unnecessary_cast)]
+        let key = (fingerprint, (threshold as f64).to_bits());
+        if let Some(rings) = self.lock().get(&key) {
+            return Ok(rings.clone());
+        }
+        let rings = compute()?;
+        self.lock().insert(key, rings.clone());
+        Ok(rings)
+    }
+
+    /// Removes all the cached rings.
+    pub(crate) fn clear(&self) {
+        self.lock().clear();
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        // The map can't be left in an inconsistent state, so a poisoned lock is recovered
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl std::fmt::Debug for RingCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RingCache")
+            .field("entries", &self.len())
+            .finish()
+    }
+}
+
+// The content of the cache doesn't change the results of the builder.
+impl PartialEq for RingCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+/// Computes the fingerprint of a grid of values.
+// The cast to u64 is unnecessary when not using the "f32" feature.
+#[allow(clippy::unnecessary_cast)]
+pub(crate) fn fingerprint(values: impl Iterator<Item = Float>, dx: usize, dy: usize) -> u64 {
+    let mut hasher = FxHasher::default();
+    hasher.write_usize(dx);
+    hasher.write_usize(dy);
+    values.for_each(|v| hasher.write_u64(v.to_bits() as u64));
+    hasher.finish()
+}
@@ -0,0 +1,110 @@
+use crate::bbox::polygons_bbox;
+use crate::diagnostics::ThresholdDiagnostics;
+use crate::error::{new_error, ErrorKind, Result};
+use crate::order::GridOrder;
+use crate::validity::{validate, ValidityReport};
+use crate::{ContourBuilder, Float};
+use geo_types::{MultiPolygon, Rect};
+
+/// The polygons covering the cells of a class of a categorical raster,
+/// built by [`ContourBuilder::vectorize_classes`].
+#[derive(Debug, Clone)]
+pub struct ClassRegion<V> {
+    pub(crate) geometry: MultiPolygon<Float>,
+    pub(crate) class: V,
+    pub(crate) bbox: Option<Rect<Float>>,
+}
+
+impl<V: Copy> ClassRegion<V> {
+    /// Borrow the [`MultiPolygon`](geo_types::MultiPolygon) geometry of this region.
+    pub fn geometry(&self) -> &MultiPolygon<Float> {
+        &self.geometry
+    }
+
+    /// Get the class of the cells covered by this region.
+    pub fn class(&self) -> V {
+        self.class
+    }
+
+    /// Get the bounding box of the geometry of this region,
+    /// or `None` if its geometry is empty.
+    pub fn bbox(&self) -> Option<Rect<Float>> {
+        self.bbox
+    }
+
+    /// Get the owned polygons and class of this region.
+    pub fn into_inner(self) -> (MultiPolygon<Float>, V) {
+        (self.geometry, self.class)
+    }
+
+    /// Checks the validity of the geometry of this region (see [`Contour::validate`](crate::Contour::validate)).
+    pub fn validate(&self) -> ValidityReport {
+        validate(&self.geometry)
+    }
+
+    /// Formats the geometry of this region as WKT (a `MULTIPOLYGON`).
+    pub fn to_wkt(&self) -> String {
+        crate::tabular::multipolygon_wkt(&self.geometry)
+    }
+}
+
+impl ContourBuilder {
+    /// Extracts the polygons covering the cells of each distinct class of the given
+    /// categorical raster (e.g. a land cover map or a segmentation mask), the cells
+    /// being classified by equality rather than against thresholds.
+    ///
+    /// The regions are returned in ascending order of their class. As with
+    /// [`contour_mask`](ContourBuilder::contour_mask), the rings pass through the middle
+    /// of the cell edges (smoothing doesn't apply), so that the boundaries shared by
+    /// two regions have the same vertices. The regions don't overlap, but their corners
+    /// are cut where three classes meet (and on the corners of the grid), leaving small
+    /// gaps between them. The weights and the nodata value of the builder are ignored
+    /// (a class can be used to mark the cells without data).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of classes to be used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use contour::ContourBuilder;
+    ///
+    /// # #[rustfmt::skip]
+    /// let classes = [
+    ///     1, 1, 2, 2,
+    ///     1, 1, 2, 2,
+    ///     3, 3, 3, 3,
+    /// ];
+    /// let regions = ContourBuilder::new(4, 3, false).vectorize_classes(&classes).unwrap();
+    /// assert_eq!(regions.iter().map(|r| r.class()).collect::<Vec<_>>(), vec![1, 2, 3]);
+    /// ```
+    pub fn vectorize_classes<V>(&self, values: &[V]) -> Result<Vec<ClassRegion<V>>>
+    where
+        V: Copy + Ord + Send + Sync,
+    {
+        if values.len() != self.dx * self.dy {
+            return Err(new_error(ErrorKind::BadDimension));
+        }
+        let (dx, dy) = (self.dx, self.dy);
+        let class = |i: usize| match self.order {
+            GridOrder::RowMajor => values[i],
+            GridOrder::ColumnMajor => values[(i % dx) * dy + i / dx],
+        };
+        let mut classes = values.to_vec();
+        classes.sort_unstable();
+        classes.dedup();
+        self.map_thresholds(classes.len(), |isoring, i| {
+            let mut rings = isoring.compute_with(|j| class(j) == classes[i])?;
+            self.transform_rings(&mut rings);
+            self.densify_rings(&mut rings);
+            let mut diagnostics = ThresholdDiagnostics::new(Float::NAN, i);
+            let geometry = self.assemble_polygons(rings, &mut diagnostics);
+            Ok(ClassRegion {
+                bbox: polygons_bbox(&geometry),
+                geometry,
+                class: classes[i],
+            })
+        })
+    }
+}
@@ -0,0 +1,34 @@
+use crate::Float;
+
+/// The class of an isoline or of a contour in topographic map styling
+/// (see [`ContourBuilder::index_interval`](crate::ContourBuilder::index_interval)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContourClass {
+    /// An index contour (e.g. every 5th contour, drawn thicker and labeled)
+    Index,
+    /// An intermediate contour, between the index contours
+    Intermediate,
+}
+
+impl ContourClass {
+    /// Classifies the given `threshold`: it is an index contour if it is
+    /// a multiple of `interval`.
+    pub(crate) fn classify(threshold: Float, interval: Float) -> Self {
+        let ratio = threshold / interval;
+        if (ratio - ratio.round()).abs() <= 1e-6 * ratio.abs().max(1.) {
+            ContourClass::Index
+        } else {
+            ContourClass::Intermediate
+        }
+    }
+
+    /// Returns the name of the class, as used in the GeoJSON properties
+    /// (`"index"` or `"intermediate"`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContourClass::Index => "index",
+            ContourClass::Intermediate => "intermediate",
+        }
+    }
+}
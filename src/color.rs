@@ -0,0 +1,163 @@
+use crate::{Band, Float, MetadataValue};
+
+/// A RGBA color, as produced by a [`ColorRamp`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Rgba {
+    /// Constructs a new opaque color.
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Rgba { r, g, b, a: 255 }
+    }
+
+    /// Parses a color from its `#rrggbb` or `#rrggbbaa` hexadecimal representation.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        let component = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        match hex.len() {
+            6 => Some(Rgba::rgb(component(0)?, component(2)?, component(4)?)),
+            8 => Some(Rgba {
+                r: component(0)?,
+                g: component(2)?,
+                b: component(4)?,
+                a: component(6)?,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the `#rrggbb` hexadecimal representation of this color
+    /// (or `#rrggbbaa` if it isn't fully opaque).
+    pub fn to_hex(&self) -> String {
+        if self.a == 255 {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        }
+    }
+
+    fn lerp(&self, other: &Rgba, t: Float) -> Rgba {
+        let mix = |a: u8, b: u8| (a as Float + (b as Float - a as Float) * t).round() as u8;
+        Rgba {
+            r: mix(self.r, other.r),
+            g: mix(self.g, other.g),
+            b: mix(self.b, other.b),
+            a: mix(self.a, other.a),
+        }
+    }
+}
+
+impl From<Rgba> for MetadataValue {
+    fn from(color: Rgba) -> Self {
+        MetadataValue::String(color.to_hex())
+    }
+}
+
+const VIRIDIS: [Rgba; 10] = [
+    Rgba::rgb(0x44, 0x01, 0x54),
+    Rgba::rgb(0x48, 0x28, 0x78),
+    Rgba::rgb(0x3e, 0x4a, 0x89),
+    Rgba::rgb(0x31, 0x68, 0x8e),
+    Rgba::rgb(0x26, 0x82, 0x8e),
+    Rgba::rgb(0x1f, 0x9e, 0x89),
+    Rgba::rgb(0x35, 0xb7, 0x79),
+    Rgba::rgb(0x6d, 0xcd, 0x59),
+    Rgba::rgb(0xb4, 0xde, 0x2c),
+    Rgba::rgb(0xfd, 0xe7, 0x25),
+];
+
+const TURBO: [Rgba; 10] = [
+    Rgba::rgb(0x30, 0x12, 0x3b),
+    Rgba::rgb(0x46, 0x62, 0xd7),
+    Rgba::rgb(0x36, 0xaa, 0xf9),
+    Rgba::rgb(0x1a, 0xe4, 0xb6),
+    Rgba::rgb(0x72, 0xfe, 0x5e),
+    Rgba::rgb(0xc7, 0xef, 0x34),
+    Rgba::rgb(0xfa, 0xba, 0x39),
+    Rgba::rgb(0xf6, 0x6b, 0x19),
+    Rgba::rgb(0xcb, 0x2a, 0x04),
+    Rgba::rgb(0x7a, 0x04, 0x03),
+];
+
+/// A color ramp, mapping values in the `[0, 1]` range to colors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColorRamp {
+    /// The perceptually uniform "viridis" color map.
+    Viridis,
+    /// The "turbo" rainbow color map.
+    Turbo,
+    /// User defined stops, as (position, color) pairs sorted by position
+    /// (positions being in the `[0, 1]` range).
+    Stops(Vec<(Float, Rgba)>),
+}
+
+impl ColorRamp {
+    /// Returns the color at position `t` (clamped to the `[0, 1]` range) of the ramp.
+    pub fn color_at(&self, t: Float) -> Rgba {
+        let t = if t.is_nan() { 0. } else { t.clamp(0., 1.) };
+        match self {
+            ColorRamp::Viridis => interpolate_uniform(&VIRIDIS, t),
+            ColorRamp::Turbo => interpolate_uniform(&TURBO, t),
+            ColorRamp::Stops(stops) => {
+                let (first, last) = match (stops.first(), stops.last()) {
+                    (Some(first), Some(last)) => (first, last),
+                    _ => return Rgba::rgb(0, 0, 0),
+                };
+                if t <= first.0 {
+                    return first.1;
+                }
+                stops
+                    .windows(2)
+                    .find(|w| t <= w[1].0)
+                    .map(|w| {
+                        let span = w[1].0 - w[0].0;
+                        if span > 0. {
+                            w[0].1.lerp(&w[1].1, (t - w[0].0) / span)
+                        } else {
+                            w[1].1
+                        }
+                    })
+                    .unwrap_or(last.1)
+            }
+        }
+    }
+
+    /// Returns the color of each of the given `bands`, the middle of each band
+    /// being mapped on the ramp relatively to the whole range of values covered by the bands.
+    pub fn band_colors(&self, bands: &[Band]) -> Vec<Rgba> {
+        let lo = bands
+            .iter()
+            .map(|b| b.min_v())
+            .fold(Float::INFINITY, Float::min);
+        let hi = bands
+            .iter()
+            .map(|b| b.max_v())
+            .fold(Float::NEG_INFINITY, Float::max);
+        bands
+            .iter()
+            .map(|b| self.color_for_range(b.min_v(), b.max_v(), lo, hi))
+            .collect()
+    }
+
+    /// Returns the color of the `[min_v, max_v]` range,
+    /// its middle being mapped on the ramp relatively to the `[lo, hi]` domain.
+    pub fn color_for_range(&self, min_v: Float, max_v: Float, lo: Float, hi: Float) -> Rgba {
+        let mid = (min_v + max_v) / 2.;
+        if hi > lo {
+            self.color_at((mid - lo) / (hi - lo))
+        } else {
+            self.color_at(0.5)
+        }
+    }
+}
+
+fn interpolate_uniform(colors: &[Rgba], t: Float) -> Rgba {
+    let pos = t * (colors.len() - 1) as Float;
+    let i = (pos.floor() as usize).min(colors.len() - 2);
+    colors[i].lerp(&colors[i + 1], pos - i as Float)
+}
@@ -1,11 +1,22 @@
-use crate::Float;
-use geo_types::MultiPolygon;
+use crate::area::{for_each_coord_mut, polygon_contains};
+use crate::bbox::polygons_bbox;
+use crate::resample::resample_polygons;
+use crate::validity::{validate, ValidityReport};
+use crate::{ContourClass, Float, Metadata, Pt};
+use geo_types::{MultiPolygon, Rect};
 
 /// A contour has the geometry and threshold of a contour ring, built by [ContourBuilder](`crate::contourbuilder::ContourBuilder`).
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Contour {
     pub(crate) geometry: MultiPolygon<Float>,
     pub(crate) threshold: Float,
+    pub(crate) max_threshold: Option<Float>,
+    pub(crate) threshold_index: usize,
+    pub(crate) metadata: Metadata,
+    pub(crate) bbox: Option<Rect<Float>>,
+    pub(crate) class: Option<ContourClass>,
+    pub(crate) part: Option<usize>,
 }
 
 impl Contour {
@@ -14,6 +25,17 @@ impl Contour {
         &self.geometry
     }
 
+    /// Get the bounding box of the geometry of this contour (computed when it was built),
+    /// or `None` if its geometry is empty.
+    pub fn bbox(&self) -> Option<Rect<Float>> {
+        self.bbox
+    }
+
+    /// Recomputes the bounding box of this contour after its geometry was modified.
+    pub(crate) fn update_bbox(&mut self) {
+        self.bbox = polygons_bbox(&self.geometry);
+    }
+
     /// Get the owned polygons and threshold of this contour.
     pub fn into_inner(self) -> (MultiPolygon<Float>, Float) {
         (self.geometry, self.threshold)
@@ -24,6 +46,100 @@ impl Contour {
         self.threshold
     }
 
+    /// Get the next threshold above the one used to construct this contour, if any
+    /// (i.e. the smallest of the thresholds requested alongside it that is greater
+    /// than its threshold), so that contours can be styled by range.
+    pub fn max_threshold(&self) -> Option<Float> {
+        self.max_threshold
+    }
+
+    /// Get the position of its threshold in the slice of thresholds
+    /// used to construct this contour (e.g. to map it back to a legend or a style
+    /// after filtering, sorting or parallel computation).
+    pub fn threshold_index(&self) -> usize {
+        self.threshold_index
+    }
+
+    /// Get the position of its threshold in the slice of thresholds
+    /// used to construct this contour.
+    #[deprecated(note = "use `threshold_index` instead")]
+    pub fn index(&self) -> usize {
+        self.threshold_index()
+    }
+
+    /// Get the position of the polygon of this contour in the geometry of the contour
+    /// it was split from, if it was built by [`into_polygons`](Self::into_polygons).
+    pub fn part(&self) -> Option<usize> {
+        self.part
+    }
+
+    /// Splits this contour into one contour per polygon of its geometry (e.g. to write
+    /// one polygon per row), each carrying the threshold and metadata of this contour
+    /// and the position of its polygon as [`part`](Self::part).
+    pub fn into_polygons(mut self) -> Vec<Contour> {
+        let polygons = std::mem::replace(&mut self.geometry, MultiPolygon(vec![]));
+        polygons
+            .0
+            .into_iter()
+            .enumerate()
+            .map(|(i, polygon)| {
+                let mut contour = Contour {
+                    geometry: MultiPolygon(vec![polygon]),
+                    part: Some(i),
+                    ..self.clone()
+                };
+                contour.update_bbox();
+                contour
+            })
+            .collect()
+    }
+
+    /// Applies the transform `f` to every vertex of the geometry of this contour, in place
+    /// (e.g. to project its coordinates or convert their units), preserving its threshold.
+    pub fn map_coords(&mut self, mut f: impl FnMut(Pt) -> Pt) {
+        for_each_coord_mut(&mut self.geometry, |pt| *pt = f(*pt));
+        self.update_bbox();
+    }
+
+    /// Resamples each ring (see [`Line::resample_uniform`](crate::Line::resample_uniform)).
+    pub fn resample_uniform(&mut self, spacing: Float) {
+        resample_polygons(&mut self.geometry, spacing);
+        self.update_bbox();
+    }
+
+    /// Returns true if the point (`x`, `y`) is inside one of the polygons of this contour
+    /// (or on its exterior boundary), and not strictly inside one of their holes.
+    pub fn contains(&self, x: Float, y: Float) -> bool {
+        let point = Pt { x, y };
+        self.geometry
+            .0
+            .iter()
+            .any(|polygon| polygon_contains(polygon, &point))
+    }
+
+    /// Checks the validity of the geometry of this contour (closure, winding,
+    /// self-intersections of the rings and containment of the holes) without
+    /// modifying it, e.g. before handing it over to strict consumers.
+    pub fn validate(&self) -> ValidityReport {
+        validate(&self.geometry)
+    }
+
+    /// Get the class (index or intermediate) of this contour, if requested
+    /// (see [`ContourBuilder::index_interval`](crate::ContourBuilder::index_interval)).
+    pub fn class(&self) -> Option<ContourClass> {
+        self.class
+    }
+
+    /// Borrow the metadata attached to the threshold used to construct this contour.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Formats the geometry of this contour as WKT (a `MULTIPOLYGON`).
+    pub fn to_wkt(&self) -> String {
+        crate::tabular::multipolygon_wkt(&self.geometry)
+    }
+
     #[cfg(feature = "geojson")]
     /// Convert the contour to a struct from the `geojson` crate.
     ///
@@ -52,15 +168,37 @@ impl Contour {
     /// assert_eq!(&geojson_string[0..27], r#"{"geometry":{"coordinates":"#);
     /// ```
     pub fn to_geojson(&self) -> geojson::Feature {
-        let mut properties = geojson::JsonObject::with_capacity(1);
-        properties.insert("threshold".to_string(), self.threshold.into());
+        self.to_geojson_with_keys(&crate::GeoJsonKeys::default())
+    }
 
+    #[cfg(feature = "geojson")]
+    /// Convert the contour to a struct from the `geojson` crate,
+    /// using the given names for its properties.
+    pub fn to_geojson_with_keys(&self, keys: &crate::GeoJsonKeys) -> geojson::Feature {
         geojson::Feature {
             bbox: None,
             geometry: Some(geojson::Geometry::from(self.geometry())),
             id: None,
-            properties: Some(properties),
+            properties: Some(self.properties(keys)),
             foreign_members: None,
         }
     }
+
+    #[cfg(feature = "geojson")]
+    /// Builds the GeoJSON properties of the contour, using the given names.
+    pub(crate) fn properties(&self, keys: &crate::GeoJsonKeys) -> geojson::JsonObject {
+        let mut properties = geojson::JsonObject::with_capacity(1);
+        properties.insert(keys.threshold.clone(), self.threshold.into());
+        if let Some(max_threshold) = self.max_threshold {
+            properties.insert(keys.max_threshold.clone(), max_threshold.into());
+        }
+        if let Some(class) = self.class {
+            properties.insert(keys.class.clone(), class.as_str().into());
+        }
+        if let Some(part) = self.part {
+            properties.insert(keys.part.clone(), part.into());
+        }
+        crate::metadata::insert_metadata(&mut properties, &self.metadata);
+        properties
+    }
 }
@@ -1,30 +1,165 @@
-use crate::area::{area, contains};
+use crate::area::{area, contains, for_each_coord_mut};
+use crate::axis::Axis;
+use crate::band::LabelFormatter;
+use crate::bbox::{lines_bbox, polygons_bbox};
+use crate::border::BorderPolicy;
+use crate::cache::RingCache;
+use crate::convert::{to_floats, Converted};
+use crate::curvilinear::Curvilinear;
+use crate::diagnostics::{Diagnostics, ThresholdDiagnostics, Warning};
+use crate::dual::{dual_ring, VertexPlacement};
+use crate::duplicates::DuplicatePolicy;
 use crate::error::{new_error, ErrorKind, Result};
-use crate::isoringbuilder::IsoRingBuilder;
-use crate::{Band, Contour, Float, Line, Ring};
+use crate::fillrule::{self, FillRule};
+use crate::geographic::{split_line, split_polygons};
+use crate::infinity::InfinityPolicy;
+use crate::interpolation::{self, Interpolation};
+use crate::isoringbuilder::{compute_strips, IsoRingBuilder};
+use crate::nodata::NodataSmoothing;
+use crate::order::{to_row_major, GridOrder};
+use crate::padding::{pad, Padding};
+use crate::periodic::{cut_at_seam, join_paths, unwrap_rings};
+use crate::ties::TiePolicy;
+use crate::topology::preserve_topology;
+use crate::weights::{Grid, Weights};
+use crate::{
+    Band, Contour, ContourClass, Densify, Float, GridSource, GridValue, Line, Metadata, Pt, Ring,
+};
 use geo_types::{LineString, MultiLineString, MultiPolygon, Polygon};
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet, FxHasher};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// The values at both ends of the edges the vertices of a ring lie on, gathered
+/// to smooth them by linear interpolation in a single (vectorizable) loop,
+/// reused across the rings to avoid allocating.
+#[derive(Default)]
+struct LinearSmoothing {
+    /// Whether each vertex lies on a vertical edge (1), a horizontal edge (2),
+    /// both (3, smoothed separately) or none (0).
+    edges: Vec<u8>,
+    v0: Vec<Float>,
+    v1: Vec<Float>,
+}
+
+/// A user-supplied transform applied to the (world) coordinates of every vertex.
+#[derive(Clone)]
+pub(crate) struct VertexTransform(Arc<dyn Fn(Float, Float) -> (Float, Float) + Send + Sync>);
+
+impl std::fmt::Debug for VertexTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("VertexTransform")
+    }
+}
+
+impl PartialEq for VertexTransform {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
 
 /// Contours generator, using builder pattern, to
 /// be used on a rectangular `Slice` of values to
 /// get a `Vec` of [`Contour`] (uses [`contour_rings`] internally).
 ///
 /// [`contour_rings`]: fn.contour_rings.html
+#[derive(Debug, Clone, PartialEq)]
 pub struct ContourBuilder {
     /// The number of columns in the grid
-    dx: usize,
+    pub(crate) dx: usize,
     /// The number of rows in the grid
-    dy: usize,
+    pub(crate) dy: usize,
     /// Whether to smooth the contours
-    smooth: bool,
+    pub(crate) smooth: bool,
     /// The horizontal coordinate for the origin of the grid.
-    x_origin: Float,
+    pub(crate) x_origin: Float,
     /// The vertical coordinate for the origin of the grid.
-    y_origin: Float,
+    pub(crate) y_origin: Float,
     /// The horizontal step for the grid
-    x_step: Float,
+    pub(crate) x_step: Float,
     /// The vertical step for the grid
-    y_step: Float,
+    pub(crate) y_step: Float,
+    /// The shift of the horizontal coordinate per row (the rotation / shear term of the geotransform)
+    pub(crate) x_skew: Float,
+    /// The shift of the vertical coordinate per column (the rotation / shear term of the geotransform)
+    pub(crate) y_skew: Float,
+    /// The horizontal coordinates of the samples, replacing the x origin and step if set
+    pub(crate) x_coords: Option<Axis>,
+    /// The vertical coordinates of the samples, replacing the y origin and step if set
+    pub(crate) y_coords: Option<Axis>,
+    /// The coordinates of each sample of a curvilinear grid, replacing the other ones if set
+    pub(crate) curvilinear: Option<Curvilinear>,
+    /// Whether to compute unsmoothed coordinates from exact lattice coordinates
+    pub(crate) lattice: bool,
+    /// Whether to revert the smoothing of the vertices that would make rings intersect
+    pub(crate) preserve_topology: bool,
+    /// Whether the rings closed by marching squares are kept explicitly closed
+    pub(crate) close_rings: bool,
+    /// The precision to which the coordinates of the vertices on the edges of the grid are rounded
+    pub(crate) edge_precision: Option<Float>,
+    /// The cache of the rings computed for each grid and threshold, if enabled
+    pub(crate) cache: Option<RingCache>,
+    /// The number of threads used to process the thresholds
+    pub(crate) threads: usize,
+    /// The densification of the output segments, if any
+    pub(crate) densify: Option<Densify>,
+    /// Whether to split the output geometries crossing the antimeridian
+    pub(crate) split_antimeridian: bool,
+    /// The interpolation used to place the vertices when smoothing
+    pub(crate) interpolation: Interpolation,
+    /// Whether to omit the features whose geometry is empty
+    pub(crate) skip_empty: bool,
+    /// The function formatting the labels of the bands, if any
+    pub(crate) band_labels: Option<LabelFormatter>,
+    /// The number of virtual cells added around the grid and how their values are computed, if any
+    pub(crate) padding: Option<(usize, Padding)>,
+    /// The grid of weights combined with the values, if any
+    pub(crate) weights: Option<Weights>,
+    /// How the values exactly equal to a threshold are classified
+    pub(crate) ties: TiePolicy,
+    /// How the infinite values of the grid are handled
+    pub(crate) infinity: InfinityPolicy,
+    /// How the thresholds requested several times are handled
+    pub(crate) duplicates: DuplicatePolicy,
+    /// The value marking the cells without data (in addition to `NaN`), if any
+    pub(crate) nodata: Option<Float>,
+    /// How the vertices next to the cells without data are placed when smoothing
+    pub(crate) nodata_smoothing: NodataSmoothing,
+    /// The order in which the values (and weights) are stored
+    pub(crate) order: GridOrder,
+    /// The user-supplied transform applied to every vertex, if any
+    pub(crate) transform: Option<VertexTransform>,
+    /// The width of the vertical blocks in which the grid is traversed, if any
+    pub(crate) block_width: Option<usize>,
+    /// Whether the grid wraps around horizontally (its last column being next to its first one)
+    pub(crate) periodic_x: bool,
+    /// The polygon outside of which the grid isn't contoured
+    pub(crate) mask: Option<Polygon<Float>>,
+    /// The number of columns of the grid periodic in x whose copy of the first column
+    /// is appended after the last one (see [`ContourBuilder::wrapped`])
+    pub(crate) seam: Option<usize>,
+    /// Whether the rings cut where they cross the seam are left open rather than closed
+    /// along it, for the isolines (see [`cut_at_seam`])
+    pub(crate) open_seam: bool,
+    /// How the (virtual) values around the grid are considered
+    pub(crate) border: BorderPolicy,
+    /// Whether the grid is extended by one cell on each side whose rings are clipped
+    /// to the original grid (see [`ContourBuilder::bordered`])
+    pub(crate) clip_border: bool,
+    /// The unsmoothed vertices (by tied threshold) whose smoothing is reverted to preserve
+    /// the topology of the rings of all the thresholds at once (see [`ContourBuilder::jointly`])
+    pub(crate) reverted: Option<Arc<Reverted>>,
+    /// The number of horizontal strips processed in parallel for each threshold
+    pub(crate) strips: usize,
+    /// How the rings are assembled into contour polygons
+    pub(crate) fill_rule: FillRule,
+    /// Where the vertices of the rings are placed
+    pub(crate) vertex_placement: VertexPlacement,
+    /// Whether to flag the closed depression contours of the isolines
+    pub(crate) depressions: bool,
+    /// The interval between the index contours, if they have to be classified
+    pub(crate) index_interval: Option<Float>,
 }
 
 impl ContourBuilder {
@@ -47,9 +182,53 @@ impl ContourBuilder {
             y_origin: 0.,
             x_step: 1.,
             y_step: 1.,
+            x_skew: 0.,
+            y_skew: 0.,
+            x_coords: None,
+            y_coords: None,
+            curvilinear: None,
+            lattice: false,
+            preserve_topology: false,
+            close_rings: true,
+            edge_precision: None,
+            cache: None,
+            threads: 1,
+            densify: None,
+            split_antimeridian: false,
+            interpolation: Interpolation::Linear,
+            skip_empty: false,
+            band_labels: None,
+            padding: None,
+            weights: None,
+            ties: TiePolicy::Inside,
+            infinity: InfinityPolicy::Clamp,
+            duplicates: DuplicatePolicy::Keep,
+            nodata: None,
+            nodata_smoothing: NodataSmoothing::Boundary,
+            order: GridOrder::RowMajor,
+            transform: None,
+            block_width: None,
+            periodic_x: false,
+            mask: None,
+            seam: None,
+            open_seam: false,
+            border: BorderPolicy::Below,
+            clip_border: false,
+            reverted: None,
+            strips: 1,
+            fill_rule: FillRule::Containment,
+            vertex_placement: VertexPlacement::Edges,
+            depressions: false,
+            index_interval: None,
         }
     }
 
+    /// Sets whether the generated rings are smoothed using linear interpolation.
+    pub fn smooth(mut self, smooth: bool) -> Self {
+        self.smooth = smooth;
+        self
+    }
+
     /// Sets the x origin of the grid.
     pub fn x_origin(mut self, x_origin: impl Into<Float>) -> Self {
         self.x_origin = x_origin.into();
@@ -74,269 +253,2350 @@ impl ContourBuilder {
         self
     }
 
-    fn smoooth_linear(&self, ring: &mut Ring, values: &[Float], value: Float) {
-        let dx = self.dx;
-        let dy = self.dy;
-        let len_values = values.len();
-
-        ring.iter_mut()
-            .map(|point| {
-                let x = point.x;
-                let y = point.y;
-                let xt = x.trunc() as usize;
-                let yt = y.trunc() as usize;
-                let mut v0;
-                let ix = yt * dx + xt;
-                if ix < len_values {
-                    let v1 = values[ix];
-                    if x > 0.0 && x < (dx as Float) && (xt as Float - x).abs() < Float::EPSILON {
-                        v0 = values[yt * dx + xt - 1];
-                        point.x = x + (value - v0) / (v1 - v0) - 0.5;
-                    }
-                    if y > 0.0 && y < (dy as Float) && (yt as Float - y).abs() < Float::EPSILON {
-                        v0 = values[(yt - 1) * dx + xt];
-                        point.y = y + (value - v0) / (v1 - v0) - 0.5;
-                    }
-                }
-            })
-            .for_each(drop);
+    /// Sets the affine transform of the grid from a GDAL-style geotransform
+    /// `[x_origin, x_step, x_skew, y_origin, y_skew, y_step]`, mapping the corner
+    /// `(column, row)` of a cell to `(x_origin + column * x_step + row * x_skew,
+    /// y_origin + column * y_skew + row * y_step)`. Unlike the origin and steps alone,
+    /// it can describe rotated or sheared grids.
+    ///
+    /// The rotation / shear terms are ignored along the axes whose coordinates are set
+    /// (see [`x_coords`](Self::x_coords)).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use contour::ContourBuilder;
+    ///
+    /// // A grid of 10 m cells rotated by 90°
+    /// let builder = ContourBuilder::new(3, 3, false).geotransform([500., 0., 10., 1000., 10., 0.]);
+    /// let contours = builder.contours(&[0., 0., 0., 0., 1., 0., 0., 0., 0.], &[0.5]).unwrap();
+    /// assert!(contours[0].validate().is_valid());
+    /// ```
+    pub fn geotransform(mut self, geotransform: [Float; 6]) -> Self {
+        let [x_origin, x_step, x_skew, y_origin, y_skew, y_step] = geotransform;
+        self.x_origin = x_origin;
+        self.x_step = x_step;
+        self.x_skew = x_skew;
+        self.y_origin = y_origin;
+        self.y_skew = y_skew;
+        self.y_step = y_step;
+        self
     }
 
-    /// Computes isolines according the given input `values` and the given `thresholds`.
-    /// Returns a `Vec` of [`Line`] (that can easily be transformed
-    /// to GeoJSON Features of MultiLineString).
-    /// The threshold value of each Feature is stored in its `value` property.
+    /// Sets the horizontal coordinates of the samples of each column of the grid
+    /// (one per column, increasing or decreasing), for grids with a non-uniform spacing.
+    /// They replace the x origin and step when computing the coordinates of the output
+    /// geometries, which are interpolated linearly between the samples (and extrapolated
+    /// from the first and last intervals on the edges of the grid).
+    ///
+    /// The helpers mapping arbitrary points to the grid (e.g. [`sampler`](Self::sampler))
+    /// keep using the origin and step.
+    pub fn x_coords(mut self, coords: &[Float]) -> Self {
+        self.x_coords = Some(Axis::new(coords));
+        self
+    }
+
+    /// Sets the vertical coordinates of the samples of each row of the grid
+    /// (see [`x_coords`](Self::x_coords)).
+    pub fn y_coords(mut self, coords: &[Float]) -> Self {
+        self.y_coords = Some(Axis::new(coords));
+        self
+    }
+
+    /// Sets the coordinates of each sample of a curvilinear grid (e.g. the longitudes
+    /// and latitudes of the cells of an ocean or atmospheric model), as two arrays
+    /// of the same shape (and order) as the values.
+    ///
+    /// They replace the origin, steps and [`x_coords`](Self::x_coords) /
+    /// [`y_coords`](Self::y_coords) when computing the coordinates of the output
+    /// geometries, which are interpolated bilinearly between the four samples of their
+    /// cell (and extrapolated from the cells on the edges of the grid). The grid
+    /// is expected not to fold over itself.
     ///
     /// # Arguments
     ///
-    /// * `values` - The slice of values to be used.
-    /// * `thresholds` - The slice of thresholds values to be used.
-    pub fn lines(&self, values: &[Float], thresholds: &[Float]) -> Result<Vec<Line>> {
-        if values.len() != self.dx * self.dy {
-            return Err(new_error(ErrorKind::BadDimension));
-        }
-        let mut isoring = IsoRingBuilder::new(self.dx, self.dy);
-        thresholds
-            .iter()
-            .map(|threshold| self.line(values, *threshold, &mut isoring))
-            .collect()
+    /// * `x` - The horizontal coordinate (e.g. the longitude) of each sample.
+    /// * `y` - The vertical coordinate (e.g. the latitude) of each sample.
+    pub fn curvilinear_coords(mut self, x: &[Float], y: &[Float]) -> Self {
+        self.curvilinear = Some(Curvilinear::new(x, y));
+        self
     }
 
-    fn line(
-        &self,
-        values: &[Float],
-        threshold: Float,
-        isoring: &mut IsoRingBuilder,
-    ) -> Result<Line> {
-        let mut result = isoring.compute(values, threshold)?;
-        let mut linestrings = Vec::new();
-
-        result.drain(..).for_each(|mut ring| {
-            // Smooth the ring if needed
-            if self.smooth {
-                self.smoooth_linear(&mut ring, values, threshold);
-            }
-            // Compute the polygon coordinates according to the grid properties if needed
-            if (self.x_origin, self.y_origin) != (0.0, 0.0)
-                || (self.x_step, self.y_step) != (1.0, 1.0)
-            {
-                ring.iter_mut().for_each(|point| {
-                    point.x = point.x * self.x_step + self.x_origin;
-                    point.y = point.y * self.y_step + self.y_origin;
-                });
-            }
-            linestrings.push(LineString(ring));
-        });
-        Ok(Line {
-            geometry: MultiLineString::<Float>(linestrings),
-            threshold,
-        })
+    /// Sets whether the smoothing must preserve the topology of the rings
+    /// (default: `false`).
+    ///
+    /// When enabled, the smoothing of the vertices that would make a ring
+    /// self-intersect, or intersect another ring of the same threshold (or of another
+    /// threshold of the same computation), is locally reverted, so that output rings
+    /// stay simple, holes stay strictly inside their exterior ring and the isobands
+    /// lie between the contours of their thresholds. This has no effect if smoothing
+    /// is disabled.
+    pub fn preserve_topology(mut self, preserve_topology: bool) -> Self {
+        self.preserve_topology = preserve_topology;
+        self
     }
 
-    /// Computes contours according the given input `values` and the given `thresholds`.
-    /// Returns a `Vec` of [`Contour`] (that can easily be transformed
-    /// to GeoJSON Features of MultiPolygon).
-    /// The threshold value of each Feature is stored in its `value` property.
+    /// Sets the precision to which the coordinates of the vertices lying on the edges
+    /// of the grid are rounded (disabled by default).
     ///
-    /// # Arguments
+    /// When contouring the tiles of a larger grid independently, the coordinates
+    /// of the vertices along the edge shared by two neighbouring tiles are computed
+    /// from different origins and may differ slightly because of floating point errors.
+    /// Rounding them to a multiple of `precision` (in the output coordinate system)
+    /// makes them coincide exactly, so that the outputs of the tiles can be rendered
+    /// side by side without seams.
+    /// The precision should be much smaller than the steps of the grid.
+    pub fn tile_edge_precision(mut self, precision: Option<Float>) -> Self {
+        self.edge_precision = precision;
+        self
+    }
+
+    /// Sets whether the rings computed for each grid and threshold are cached
+    /// (disabled by default).
     ///
-    /// * `values` - The slice of values to be used.
-    /// * `thresholds` - The slice of thresholds values to be used.
-    pub fn contours(&self, values: &[Float], thresholds: &[Float]) -> Result<Vec<Contour>> {
-        if values.len() != self.dx * self.dy {
-            return Err(new_error(ErrorKind::BadDimension));
+    /// When enabled, repeated calls on the same grid (identified by a fingerprint
+    /// of its values) reuse the rings already computed for the thresholds they share
+    /// (e.g. when a single level is tweaked in a user interface), instead of
+    /// recomputing every level. The cache is shared between the clones of the builder
+    /// and grows with each new grid and threshold: see [`ContourBuilder::clear_cache`].
+    pub fn ring_cache(mut self, enabled: bool) -> Self {
+        self.cache = if enabled {
+            Some(RingCache::default())
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Removes all the rings from the cache (if enabled).
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
         }
-        let mut isoring = IsoRingBuilder::new(self.dx, self.dy);
-        thresholds
-            .iter()
-            .map(|threshold| self.contour(values, *threshold, &mut isoring))
-            .collect()
     }
 
-    fn contour(
-        &self,
-        values: &[Float],
-        threshold: Float,
-        isoring: &mut IsoRingBuilder,
-    ) -> Result<Contour> {
-        let (mut polygons, mut holes) = (Vec::new(), Vec::new());
-        let mut result = isoring.compute(values, threshold)?;
+    /// Sets the number of threads used to process the thresholds (1 by default).
+    ///
+    /// The thresholds are split in contiguous chunks, each processed on its own
+    /// (scoped) thread, without requiring any additional dependency.
+    /// The results are the same, in the same order, as when using a single thread.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
 
-        result.drain(..).for_each(|mut ring| {
-            // Smooth the ring if needed
-            if self.smooth {
-                self.smoooth_linear(&mut ring, values, threshold);
-            }
-            // Compute the polygon coordinates according to the grid properties if needed
-            if (self.x_origin, self.y_origin) != (0.0, 0.0)
-                || (self.x_step, self.y_step) != (1.0, 1.0)
-            {
-                ring.iter_mut().for_each(|point| {
-                    point.x = point.x * self.x_step + self.x_origin;
-                    point.y = point.y * self.y_step + self.y_origin;
-                });
-            }
-            if area(&ring) > 0.0 {
-                polygons.push(Polygon::<Float>::new(LineString::new(ring), vec![]))
-            } else {
-                holes.push(LineString::new(ring));
-            }
-        });
+    /// Sets the densification of the output segments (disabled by default).
+    ///
+    /// When the output coordinates are geographic, the long straight segments
+    /// between grid columns distort when reprojected: intermediate vertices can be
+    /// inserted (in the output coordinate system, or along great circles) so that
+    /// global contour layers reproject cleanly.
+    pub fn densify(mut self, densify: Option<Densify>) -> Self {
+        self.densify = densify;
+        self
+    }
 
-        holes.drain(..).for_each(|hole| {
-            for polygon in &mut polygons {
-                if contains(&polygon.exterior().0, &hole.0) != -1 {
-                    polygon.interiors_push(hole);
-                    return;
-                }
-            }
-        });
+    /// Sets whether to split the output geometries crossing the antimeridian
+    /// (disabled by default).
+    ///
+    /// When the output coordinates are geographic (longitudes and latitudes in degrees),
+    /// the lines and polygons extending beyond ±180° of longitude are split into parts
+    /// brought back in the [-180, 180] range, so that web maps render them correctly
+    /// instead of smearing them across the world.
+    pub fn split_antimeridian(mut self, split: bool) -> Self {
+        self.split_antimeridian = split;
+        self
+    }
 
-        Ok(Contour {
-            geometry: MultiPolygon::<Float>(polygons),
-            threshold,
-        })
+    /// Sets the interpolation used to place the vertices along the cell edges
+    /// when smoothing is enabled ([`Interpolation::Linear`] by default).
+    pub fn interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
     }
 
-    /// Computes isobands according the given input `values` and the given `thresholds`.
-    /// Returns a `Vec` of [`Band`] (that can easily be transformed
-    /// to GeoJSON Features of MultiPolygon).
-    /// The threshold value of each Feature is stored in its `value` property.
+    /// Sets whether to omit the features whose geometry is empty (e.g. for thresholds
+    /// outside of the range of the values) from the returned `Vec` (disabled by default).
+    ///
+    /// The threshold of the returned features (and its position in the requested
+    /// thresholds, see `threshold_index`) can be used to map them back to the request.
+    pub fn skip_empty(mut self, skip_empty: bool) -> Self {
+        self.skip_empty = skip_empty;
+        self
+    }
+
+    /// Sets the function formatting the label of each band from its min / max values
+    /// (e.g. `|min, max| format!("{min}–{max} m")`), stored on the [`Band`] and
+    /// in the `label` property of its GeoJSON representation, so that legends and labels
+    /// are generated consistently.
+    pub fn band_labels(
+        mut self,
+        formatter: impl Fn(Float, Float) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.band_labels = Some(LabelFormatter(Arc::new(formatter)));
+        self
+    }
+
+    /// Sets the number of `cells` by which the grid is virtually extended on each side
+    /// before contouring, the values of these cells being computed from the edge values
+    /// of the grid according to `mode` (disabled by default, or if `cells` is 0).
+    ///
+    /// The features that would be truncated exactly at the edge of the grid thus close
+    /// naturally outside of it (e.g. when the analysis window is slightly smaller than
+    /// the phenomenon), the coordinates of the output being computed with the origin
+    /// and step of the original grid.
+    pub fn padding(mut self, cells: usize, mode: Padding) -> Self {
+        self.padding = (cells > 0).then_some((cells, mode));
+        self
+    }
+
+    /// Sets the grid of `weights` (with the same dimensions as the grid of values)
+    /// multiplied cell-by-cell with the values when classifying and smoothing them
+    /// (e.g. for a per-cell area correction of a grid in geographic coordinates),
+    /// without materializing a weighted copy of the values.
+    ///
+    /// The methods computing the contours return a [`BadDimension`](ErrorKind::BadDimension)
+    /// error if the dimensions of the weights don't match the grid.
+    pub fn weights(mut self, weights: Option<Vec<Float>>) -> Self {
+        self.weights = weights.map(|w| Weights::new(w, Arc::new(|v, w| v * w)));
+        self
+    }
+
+    /// Sets the grid of `weights` combined cell-by-cell with the values using `op`
+    /// (called with the value and the weight of each cell) when classifying and
+    /// smoothing them (see [`ContourBuilder::weights`]).
+    pub fn weights_with(
+        mut self,
+        weights: Vec<Float>,
+        op: impl Fn(Float, Float) -> Float + Send + Sync + 'static,
+    ) -> Self {
+        self.weights = Some(Weights::new(weights, Arc::new(op)));
+        self
+    }
+
+    /// Sets how the values exactly equal to a threshold (e.g. plateaus of integer
+    /// values) are classified ([`TiePolicy::Inside`] by default, following the `>=` rule).
+    pub fn tie_policy(mut self, ties: TiePolicy) -> Self {
+        self.ties = ties;
+        self
+    }
+
+    /// Sets how the infinite values of the grid are handled ([`InfinityPolicy::Clamp`]
+    /// by default): they never produce `NaN` coordinates when smoothing, but can be
+    /// considered outside of every contour or rejected with the location of the first one.
+    pub fn infinity_policy(mut self, infinity: InfinityPolicy) -> Self {
+        self.infinity = infinity;
+        self
+    }
+
+    /// Sets how the thresholds requested several times are handled
+    /// ([`DuplicatePolicy::Keep`] by default): their features can be repeated,
+    /// returned once or rejected. Their rings are only computed once in any case.
+    pub fn duplicate_policy(mut self, duplicates: DuplicatePolicy) -> Self {
+        self.duplicates = duplicates;
+        self
+    }
+
+    /// Sets how the values around the grid are considered ([`BorderPolicy::Below`]
+    /// by default), i.e. how the contours and isolines reaching its edges are closed.
+    ///
+    /// The virtual values are only used by the methods taking slices of values
+    /// (e.g. [`contours`](Self::contours)), and ignored if [`padding`](Self::padding)
+    /// or [`periodic_x`](Self::periodic_x) is set.
+    pub fn border_policy(mut self, border: BorderPolicy) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Sets the value marking the cells without data (none by default), handled
+    /// like `NaN` values: these cells are outside of every contour and band, the
+    /// vertices on their edges are placed on the boundary of the data by smoothing
+    /// (see [`nodata_smoothing`](Self::nodata_smoothing)) and the isolines are
+    /// cut where they would follow the boundary of the data instead of a threshold.
+    pub fn nodata(mut self, nodata: Option<Float>) -> Self {
+        self.nodata = nodata.filter(|v| !v.is_nan());
+        self
+    }
+
+    /// Sets where smoothing places the vertices lying on an edge between a sample
+    /// and a cell without data ([`NodataSmoothing::Boundary`] by default).
+    /// The invalid values are never used to place the vertices.
+    pub fn nodata_smoothing(mut self, nodata_smoothing: NodataSmoothing) -> Self {
+        self.nodata_smoothing = nodata_smoothing;
+        self
+    }
+
+    /// Sets the order in which the values (and the weights, if any) are stored in the
+    /// slices given to the builder ([`GridOrder::RowMajor`] by default), so that
+    /// column-major arrays are read in place rather than transposed beforehand
+    /// (the values of a [`GridSource`] being read by column and row whatever the order).
+    pub fn order(mut self, order: GridOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Sets a transform `f` (called with the `x` and `y` coordinates of each vertex and
+    /// returning its new coordinates) applied to every vertex after smoothing and after the
+    /// origin / step transform, in the same pass, so that arbitrary projections or lens
+    /// distortions are applied without traversing the vertices a second time.
+    ///
+    /// The vertices on the edges of the grid are rounded (see
+    /// [`ContourBuilder::tile_edge_precision`]) after this transform. As polygons are
+    /// assembled from the transformed rings, the transform should preserve their
+    /// orientation (i.e. not mirror them).
+    pub fn transform(
+        mut self,
+        f: impl Fn(Float, Float) -> (Float, Float) + Send + Sync + 'static,
+    ) -> Self {
+        self.transform = Some(VertexTransform(Arc::new(f)));
+        self
+    }
+
+    /// Sets the width (in cells) of the vertical blocks in which the grid is traversed
+    /// (by default, or if `None`, the grid is traversed row by row).
+    ///
+    /// Traversing very wide grids in blocks (e.g. of 1024 cells) keeps the parts of the
+    /// rows being processed in cache, which helps when two rows don't fit in it (compare
+    /// the `build_contours_wide` benchmarks on the target machine: for 10k columns of
+    /// `f64`, both rows still fit in the L2 cache of most CPUs and the performance is on par).
+    /// The same geometries are computed, but the starting vertex of their rings
+    /// and the order of their lines or polygons may differ.
+    pub fn block_width(mut self, width: Option<usize>) -> Self {
+        self.block_width = width;
+        self
+    }
+
+    /// Sets whether the grid wraps around in x (default: `false`), e.g. for global datasets
+    /// whose last column is followed by the first one (without repeating it): the cells
+    /// between the last column and the first one are contoured, and the rings crossing the
+    /// seam are stitched into continuous rings, extending past the last column.
+    /// The rings going around the whole grid (e.g. around a pole) are cut along the left
+    /// edge of the first column, the contours covering a strip of the grid.
+    ///
+    /// This is ignored with a single column, and by the methods reading the values
+    /// in place (e.g. [`contours_from_source`](Self::contours_from_source)).
+    /// The [`padding`](Self::padding) is ignored if set.
+    pub fn periodic_x(mut self, periodic: bool) -> Self {
+        self.periodic_x = periodic;
+        self
+    }
+
+    /// Sets the polygon (in the coordinates of the output geometries) restricting the
+    /// region which is contoured (none by default): the cells lying outside of it are
+    /// ignored (as cells without data, see [`nodata`](Self::nodata)), and the contours,
+    /// bands and isolines are clipped to it.
+    ///
+    /// The methods reading the values in place (e.g.
+    /// [`contours_from_source`](Self::contours_from_source)) only clip their output.
+    pub fn mask(mut self, mask: Option<Polygon<Float>>) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    /// Sets the number of horizontal strips in which the grid is split to compute
+    /// the rings of each threshold, each strip being processed in its own thread
+    /// before the fragments of the rings crossing the strip boundaries are linked
+    /// (1 by default, i.e. no split).
+    ///
+    /// Unlike [`ContourBuilder::threads`], which processes the thresholds in parallel,
+    /// this speeds up the computation of a single threshold on a large grid.
+    /// The same geometries are computed, but the starting vertex of their rings
+    /// and the order of their lines or polygons may differ.
+    pub fn strips(mut self, strips: usize) -> Self {
+        self.strips = strips.max(1);
+        self
+    }
+
+    /// Sets how the rings of each threshold are assembled into contour polygons
+    /// ([`FillRule::Containment`] by default).
+    ///
+    /// The global [`FillRule::EvenOdd`] and [`FillRule::NonZero`] rules classify every
+    /// ring according to the rings enclosing it rather than its own winding order,
+    /// which is more robust when smoothing makes rings touch, and matches how
+    /// most renderers fill the rings. They don't apply to the isobands, whose holes
+    /// are always identified by their nesting depth.
+    pub fn fill_rule(mut self, fill_rule: FillRule) -> Self {
+        self.fill_rule = fill_rule;
+        self
+    }
+
+    /// Sets where the vertices of the rings are placed ([`VertexPlacement::Edges`] by default).
+    ///
+    /// With [`VertexPlacement::Dual`], one vertex is placed inside each cell crossed
+    /// by a ring (using the gradient of the values) instead of one vertex on each
+    /// crossed cell edge: the smoothing settings have then no effect.
+    /// The isolines still follow the boundaries of the contours, but the vertices of
+    /// each threshold are placed on their own, so that the rings of close thresholds
+    /// may cross each other in a cell: the isobands aren't guaranteed to lie exactly
+    /// between the contours of their thresholds (nor their areas to add up).
+    pub fn vertex_placement(mut self, vertex_placement: VertexPlacement) -> Self {
+        self.vertex_placement = vertex_placement;
+        self
+    }
+
+    /// Sets whether the closed depression contours of the isolines are flagged
+    /// (disabled by default), see [`Line::depressions`]. The flags are also exported
+    /// as a property of the GeoJSON features of the isolines.
+    pub fn depressions(mut self, depressions: bool) -> Self {
+        self.depressions = depressions;
+        self
+    }
+
+    /// Sets the interval between the index contours (disabled by default), so that
+    /// each isoline and contour is classified as an index contour (if its threshold
+    /// is a multiple of the interval) or an intermediate one, as used to style
+    /// topographic maps (see [`Line::class`] and [`Contour::class`], also exported as
+    /// a property of their GeoJSON features).
+    ///
+    /// E.g. with thresholds every 20 meters, an interval of 100 makes every 5th contour
+    /// an index contour.
+    pub fn index_interval(mut self, interval: Option<Float>) -> Self {
+        self.index_interval = interval.filter(|i| *i != 0. && i.is_finite());
+        self
+    }
+
+    /// Sets whether the rings are kept explicitly closed (enabled by default): the closing
+    /// vertex of each closed ring (and thus of each polygon ring and of each closed isoline)
+    /// is set back to its first vertex if they no longer match after the coordinates were
+    /// computed (e.g. if a [`transform`](ContourBuilder::transform) returned different
+    /// coordinates for two calls with the same vertex). The isolines cut along
+    /// the cells without data (see [`ContourBuilder::nodata`]) are left open.
+    pub fn close_rings(mut self, close_rings: bool) -> Self {
+        self.close_rings = close_rings;
+        self
+    }
+
+    /// Sets whether unsmoothed coordinates are computed from exact lattice coordinates
+    /// (default: `false`).
+    ///
+    /// Unsmoothed vertices always lie on a lattice of multiples of 0.5 in grid space.
+    /// When enabled, they are converted to exact integer coordinates (scaled by 2)
+    /// before the origin / step transform is applied in double precision,
+    /// so that repeated runs (including with the `f32` feature, or with a different
+    /// set of thresholds) produce bit-identical geometries, for deduplication or hashing.
+    /// This has no effect if smoothing is enabled.
+    ///
+    /// See also [`ContourBuilder::lattice_rings`] to get the integer lattice coordinates.
+    pub fn lattice_coordinates(mut self, lattice: bool) -> Self {
+        self.lattice = lattice;
+        self
+    }
+
+    /// Get the number of columns in the grid.
+    pub fn dx(&self) -> usize {
+        self.dx
+    }
+
+    /// Get the number of rows in the grid.
+    pub fn dy(&self) -> usize {
+        self.dy
+    }
+
+    /// Get whether the contours are smoothed.
+    pub fn is_smooth(&self) -> bool {
+        self.smooth
+    }
+
+    /// Get the (x, y) origin of the grid.
+    pub fn origin(&self) -> (Float, Float) {
+        (self.x_origin, self.y_origin)
+    }
+
+    /// Get the (x, y) step of the grid.
+    pub fn step(&self) -> (Float, Float) {
+        (self.x_step, self.y_step)
+    }
+
+    /// Computes a fast (non-cryptographic) hash of the given `values`, of the dimensions
+    /// of the grid and of the options of the builder changing the results (the weights,
+    /// the origin and steps, the smoothing, the policies, etc.), to be used as a key
+    /// by caching layers: two calls returning the same fingerprint compute the same
+    /// results (barring hash collisions).
+    ///
+    /// The closures (see [`transform`](Self::transform) and
+    /// [`band_labels`](Self::band_labels)) are identified by their address,
+    /// clones of the builder sharing the same fingerprints.
+    ///
+    /// The fingerprints are only stable within a given version of this crate.
     ///
     /// # Arguments
     ///
     /// * `values` - The slice of values to be used.
-    /// * `thresholds` - The slice of thresholds values to be used
-    ///                  (have to be equal to or greater than 2).
-    pub fn isobands(&self, values: &[Float], thresholds: &[Float]) -> Result<Vec<Band>> {
-        // We will compute rings as previously, but we will
-        // iterate over the contours in pairs and use the paths from the lower threshold
-        // and the path from the upper threshold to create the isoband.
-        if values.len() != self.dx * self.dy {
-            return Err(new_error(ErrorKind::BadDimension));
+    // The casts to f64 are unnecessary when not using the "f32" feature.
+    #[allow(clippy::unnecessary_cast)]
+    pub fn fingerprint(&self, values: &[Float]) -> Result<u64> {
+        let grid = self.grid(values)?;
+        let bits = |v: Float| (v as f64).to_bits();
+        let mut hasher = FxHasher::default();
+        hasher.write_u64(grid.fingerprint(self.dx, self.dy));
+        [self.x_origin, self.y_origin, self.x_step, self.y_step]
+            .into_iter()
+            .for_each(|v| hasher.write_u64(bits(v)));
+        hasher.write_u8(self.smooth as u8);
+        hasher.write_u64(self.options_fingerprint());
+        Ok(hasher.finish())
+    }
+
+    /// Computes a hash of the options of the builder changing the results
+    /// (see [`ContourBuilder::fingerprint`]), but for the dimensions, the smoothing,
+    /// and the origin and steps of the grid.
+    // The casts to f64 are unnecessary when not using the "f32" feature.
+    #[allow(clippy::unnecessary_cast)]
+    pub(crate) fn options_fingerprint(&self) -> u64 {
+        let bits = |v: Float| (v as f64).to_bits();
+        let mut hasher = FxHasher::default();
+        [self.x_skew, self.y_skew]
+            .into_iter()
+            .for_each(|v| hasher.write_u64(bits(v)));
+        self.nodata.map(bits).hash(&mut hasher);
+        self.weights
+            .as_ref()
+            .map(|w| {
+                let values = w.weights.iter().map(|&v| bits(v)).collect::<Vec<_>>();
+                (values, Arc::as_ptr(&w.op) as *const () as usize)
+            })
+            .hash(&mut hasher);
+        for axis in [&self.x_coords, &self.y_coords] {
+            axis.as_ref()
+                .map(|a| a.coords().iter().map(|&c| bits(c)).collect::<Vec<_>>())
+                .hash(&mut hasher);
         }
-        if thresholds.len() < 2 {
-            return Err(new_error(ErrorKind::Unexpected));
+        self.curvilinear
+            .as_ref()
+            .map(|c| {
+                (c.x.iter().chain(c.y.iter()))
+                    .map(|&c| bits(c))
+                    .collect::<Vec<_>>()
+            })
+            .hash(&mut hasher);
+        [
+            self.lattice,
+            self.preserve_topology,
+            self.close_rings,
+            self.split_antimeridian,
+            self.skip_empty,
+            self.depressions,
+            self.periodic_x,
+            self.clip_border,
+        ]
+        .into_iter()
+        .for_each(|flag| hasher.write_u8(flag as u8));
+        for v in [self.edge_precision, self.index_interval] {
+            v.map(bits).hash(&mut hasher);
         }
-        let mut isoring = IsoRingBuilder::new(self.dx, self.dy);
-
-        let rings = thresholds
-            .iter()
-            .map(|threshold| {
-                // Compute the rings for the current threshold
-                let rings = isoring.compute(values, *threshold)?;
-                let rings = rings
-                    .into_iter()
-                    .map(|mut ring| {
-                        // Smooth the ring if needed
-                        if self.smooth {
-                            self.smoooth_linear(&mut ring, values, *threshold);
-                        }
-                        ring.dedup();
-                        // Compute the polygon coordinates according to the grid properties if needed
-                        if (self.x_origin, self.y_origin) != (0.0, 0.0)
-                            || (self.x_step, self.y_step) != (1.0, 1.0)
-                        {
-                            ring.iter_mut().for_each(|point| {
-                                point.x = point.x * self.x_step + self.x_origin;
-                                point.y = point.y * self.y_step + self.y_origin;
-                            });
-                        }
-                        ring
-                    })
-                    .filter(|ring| ring.len() > 3)
-                    .collect::<Vec<Ring>>();
-                Ok((rings, *threshold))
+        self.mask
+            .as_ref()
+            .map(|mask| {
+                std::iter::once(mask.exterior())
+                    .chain(mask.interiors())
+                    .map(|r| r.0.iter().flat_map(|p| [bits(p.x), bits(p.y)]).collect())
+                    .collect::<Vec<Vec<_>>>()
             })
-            .collect::<Result<Vec<(Vec<Ring>, Float)>>>()?;
-
-        // We now have the rings for each isolines for all the given thresholds,
-        // we can iterate over them in pairs to compute the isobands.
-        let b = rings
-            .windows(2)
-            .map(|rings| {
-                let ((lower_path, min_v), (upper_path, max_v)) = (&rings[0], &rings[1]);
-                let concatenated = [&lower_path[..], &upper_path[..]].concat();
-                (concatenated, min_v, max_v)
+            .hash(&mut hasher);
+        self.densify
+            .map(|densify| match densify {
+                Densify::Linear(max) => (0u8, bits(max)),
+                Densify::GreatCircle(max) => (1u8, bits(max)),
             })
-            .collect::<Vec<_>>();
-
-        let mut bands: Vec<Band> = Vec::new();
-        // Reconstruction of the polygons
-        b.into_iter().for_each(|(rings, min_v, max_v)| {
-            let mut rings_and_area = rings
-                .into_iter()
-                .map(|ring| {
-                    let area = area(&ring);
-                    (ring, area)
-                })
-                .collect::<Vec<_>>();
-
-            rings_and_area.sort_by_key(|(_, area)| area.abs() as u64);
-
-            let mut enclosed_by_n = FxHashMap::default();
+            .hash(&mut hasher);
+        self.padding
+            .map(|(n, mode)| (n, std::mem::discriminant(&mode)))
+            .hash(&mut hasher);
+        std::mem::discriminant(&self.interpolation).hash(&mut hasher);
+        std::mem::discriminant(&self.ties).hash(&mut hasher);
+        std::mem::discriminant(&self.infinity).hash(&mut hasher);
+        std::mem::discriminant(&self.duplicates).hash(&mut hasher);
+        std::mem::discriminant(&self.nodata_smoothing).hash(&mut hasher);
+        std::mem::discriminant(&self.border).hash(&mut hasher);
+        std::mem::discriminant(&self.order).hash(&mut hasher);
+        std::mem::discriminant(&self.fill_rule).hash(&mut hasher);
+        std::mem::discriminant(&self.vertex_placement).hash(&mut hasher);
+        let transform = self
+            .transform
+            .as_ref()
+            .map(|t| Arc::as_ptr(&t.0) as *const ());
+        let labels = self
+            .band_labels
+            .as_ref()
+            .map(|l| Arc::as_ptr(&l.0) as *const ());
+        (transform.map(|p| p as usize), labels.map(|p| p as usize)).hash(&mut hasher);
+        hasher.finish()
+    }
 
-            for (i, (ring, _)) in rings_and_area.iter().enumerate() {
-                let mut enclosed_by_j = 0;
-                for (j, (ring_test, _)) in rings_and_area.iter().enumerate() {
-                    if i == j {
-                        continue;
-                    }
-                    if contains(ring_test, ring) != -1 {
-                        enclosed_by_j += 1;
-                    }
-                }
-                enclosed_by_n.insert(i, enclosed_by_j);
+    /// Converts a point expressed in the coordinates of the output geometries
+    /// back to (fractional) grid coordinates.
+    pub(crate) fn to_grid(&self, x: Float, y: Float) -> (Float, Float) {
+        if let Some(grid) = &self.curvilinear {
+            return self.curvilinear_unmap(grid, x, y);
+        }
+        let gx = self.x_coords.as_ref().map(|axis| axis.unmap(x));
+        let gy = self.y_coords.as_ref().map(|axis| axis.unmap(y));
+        let (x, y) = (x - self.x_origin, y - self.y_origin);
+        match (gx, gy) {
+            (Some(gx), Some(gy)) => (gx, gy),
+            (Some(gx), None) => (gx, (y - gx * self.y_skew) / self.y_step),
+            (None, Some(gy)) => ((x - gy * self.x_skew) / self.x_step, gy),
+            (None, None) if self.has_skew() => {
+                // Inverts the affine transform of the grid
+                let det = self.x_step * self.y_step - self.x_skew * self.y_skew;
+                (
+                    (x * self.y_step - y * self.x_skew) / det,
+                    (y * self.x_step - x * self.y_skew) / det,
+                )
             }
+            (None, None) => (x / self.x_step, y / self.y_step),
+        }
+    }
 
-            let mut polygons: Vec<Polygon<Float>> = Vec::new();
-            let mut interior_rings: Vec<LineString<Float>> = Vec::new();
+    /// Whether the mapping from the grid to the output coordinates reverses
+    /// the winding order of the rings (i.e. the coordinates decrease along one axis).
+    pub(crate) fn reverses_winding(&self) -> bool {
+        if let Some(grid) = &self.curvilinear {
+            return self.curvilinear_reverses(grid);
+        }
+        let (x, y) = (self.x_coords.as_ref(), self.y_coords.as_ref());
+        // The determinant of the jacobian of the mapping
+        let x_skew = if x.is_some() { 0. } else { self.x_skew };
+        let y_skew = if y.is_some() { 0. } else { self.y_skew };
+        let x = x.map_or(self.x_step, Axis::direction);
+        let y = y.map_or(self.y_step, Axis::direction);
+        x * y - x_skew * y_skew < 0.
+    }
 
-            for (i, (ring, _)) in rings_and_area.into_iter().enumerate() {
-                if *enclosed_by_n.get(&i).unwrap() % 2 == 0 {
-                    polygons.push(Polygon::<Float>::new(ring.into(), vec![]));
-                } else {
-                    interior_rings.push(ring.into());
+    /// Smooths the vertices of the given `ring` (expressed in grid coordinates),
+    /// using linear interpolation if possible: the values at both ends of their edges
+    /// are gathered first, then interpolated all at once (a loop the compiler can
+    /// vectorize, unlike the branches and divisions of [`ContourBuilder::smooth_point`]).
+    /// Each vertex is also transformed as soon as it is smoothed if `transform` is set.
+    fn smooth_ring(
+        &self,
+        ring: &mut [Pt],
+        grid: Grid<'_>,
+        value: Float,
+        transform: bool,
+        scratch: &mut LinearSmoothing,
+    ) {
+        if self.interpolation != Interpolation::Linear {
+            ring.iter_mut().for_each(|point| {
+                self.smooth_point(point, grid, value);
+                if transform {
+                    self.transform_point(point);
                 }
-            }
-            for interior_ring in interior_rings.into_iter() {
-                for polygon in polygons.iter_mut() {
-                    if contains(&polygon.exterior().0, &interior_ring.0) != -1 {
-                        polygon.interiors_push(interior_ring);
-                        break;
-                    }
+            });
+            return;
+        }
+        let dx = self.dx;
+        let extrapolate = self.nodata_smoothing == NodataSmoothing::Extrapolate;
+        let LinearSmoothing { edges, v0, v1 } = scratch;
+        edges.clear();
+        v0.clear();
+        v1.clear();
+        for point in ring.iter() {
+            let (x, y) = (point.x, point.y);
+            let (xt, yt) = (x.trunc() as usize, y.trunc() as usize);
+            let ix = yt * dx + xt;
+            let mut edge = 0;
+            if ix < grid.len() {
+                if x > 0.0 && x < (dx as Float) && (xt as Float - x).abs() < Float::EPSILON {
+                    edge |= 1;
+                }
+                if y > 0.0 && y < (self.dy as Float) && (yt as Float - y).abs() < Float::EPSILON {
+                    edge |= 2;
                 }
             }
-
-            polygons.reverse();
-
-            bands.push(Band {
-                geometry: MultiPolygon::<Float>(polygons),
-                min_v: *min_v,
-                max_v: *max_v,
-            });
+            let (a, b) = match edge {
+                1 => (grid.get(ix - 1), grid.get(ix)),
+                2 => (grid.get(ix - dx), grid.get(ix)),
+                _ => (0., 1.),
+            };
+            if extrapolate && (a.is_nan() || b.is_nan()) {
+                edge = 3;
+            }
+            edges.push(edge);
+            v0.push(a);
+            v1.push(b);
+        }
+        // The positions along the edges replace the values at their start
+        v0.iter_mut()
+            .zip(v1.iter())
+            .for_each(|(t, v1)| *t = interpolation::linear(*t, *v1, value));
+        for ((point, edge), t) in ring.iter_mut().zip(edges.iter()).zip(v0.iter()) {
+            match edge {
+                1 => point.x = point.x + t - 0.5,
+                2 => point.y = point.y + t - 0.5,
+                3 => self.smooth_point(point, grid, value),
+                _ => (),
+            }
+            if transform {
+                self.transform_point(point);
+            }
+        }
+    }
+
+    /// Moves the given `point` (expressed in grid coordinates) along the cell edge
+    /// it lies on, interpolating between the values at both ends.
+    fn smooth_point(&self, point: &mut Pt, grid: Grid<'_>, value: Float) {
+        let dx = self.dx;
+        let dy = self.dy;
+        let x = point.x;
+        let y = point.y;
+        let xt = x.trunc() as usize;
+        let yt = y.trunc() as usize;
+        let mut v0;
+        let ix = yt * dx + xt;
+        if ix < grid.len() {
+            let v1 = grid.get(ix);
+            let quadratic = self.interpolation == Interpolation::Quadratic;
+            let extrapolate = self.nodata_smoothing == NodataSmoothing::Extrapolate;
+            let position = |before: Option<Float>, v0: Float, after: Option<Float>| {
+                if extrapolate && v0.is_nan() != v1.is_nan() {
+                    interpolation::valid_side(before, v0, v1, after, value)
+                } else if quadratic {
+                    interpolation::quadratic(before, v0, v1, after, value)
+                } else {
+                    interpolation::linear(v0, v1, value)
+                }
+            };
+            if x > 0.0 && x < (dx as Float) && (xt as Float - x).abs() < Float::EPSILON {
+                v0 = grid.get(yt * dx + xt - 1);
+                let t = position(
+                    (xt >= 2).then(|| grid.get(yt * dx + xt - 2)),
+                    v0,
+                    (xt + 1 < dx).then(|| grid.get(yt * dx + xt + 1)),
+                );
+                point.x = x + t - 0.5;
+            }
+            if y > 0.0 && y < (dy as Float) && (yt as Float - y).abs() < Float::EPSILON {
+                v0 = grid.get((yt - 1) * dx + xt);
+                let t = position(
+                    (yt >= 2).then(|| grid.get((yt - 2) * dx + xt)),
+                    v0,
+                    (yt + 1 < dy).then(|| grid.get((yt + 1) * dx + xt)),
+                );
+                point.y = y + t - 0.5;
+            }
+        }
+    }
+
+    /// Whether the grid coordinates of the vertices have to be transformed
+    /// to get the output coordinates.
+    fn has_transform(&self) -> bool {
+        (self.x_origin, self.y_origin) != (0.0, 0.0)
+            || (self.x_step, self.y_step) != (1.0, 1.0)
+            || self.has_skew()
+            || self.x_coords.is_some()
+            || self.y_coords.is_some()
+            || self.curvilinear.is_some()
+            || self.edge_precision.is_some()
+            || self.transform.is_some()
+    }
+
+    /// Computes the coordinates of the given `point` (expressed in grid coordinates)
+    /// according to the grid properties.
+    // The casts to f64 are unnecessary when not using the "f32" feature.
+    #[allow(clippy::unnecessary_cast)]
+    pub(crate) fn transform_point(&self, point: &mut Pt) {
+        let on_edge = self.edge_precision.is_some()
+            && (point.x <= 0.
+                || point.x >= self.dx as Float
+                || point.y <= 0.
+                || point.y >= self.dy as Float);
+        let uniform =
+            self.x_coords.is_none() && self.y_coords.is_none() && self.curvilinear.is_none();
+        if let Some(grid) = &self.curvilinear {
+            (point.x, point.y) = self.curvilinear_map(grid, point.x, point.y);
+        } else if self.lattice
+            && uniform
+            && !self.smooth
+            && self.vertex_placement == VertexPlacement::Edges
+        {
+            // Vertices lie on a lattice of multiples of 0.5: compute them from their
+            // exact (doubled) integer lattice coordinates, in double precision.
+            let (lx, ly) = lattice_coords(point);
+            let (lx, ly) = (lx as f64 / 2., ly as f64 / 2.);
+            point.x =
+                (self.x_origin as f64 + lx * self.x_step as f64 + ly * self.x_skew as f64) as Float;
+            point.y =
+                (self.y_origin as f64 + lx * self.y_skew as f64 + ly * self.y_step as f64) as Float;
+        } else {
+            let (gx, gy) = (point.x, point.y);
+            point.x = match &self.x_coords {
+                Some(axis) => axis.map(gx),
+                None => gx * self.x_step + gy * self.x_skew + self.x_origin,
+            };
+            point.y = match &self.y_coords {
+                Some(axis) => axis.map(gy),
+                None => gx * self.y_skew + gy * self.y_step + self.y_origin,
+            };
+        }
+        if let Some(transform) = &self.transform {
+            (point.x, point.y) = (transform.0)(point.x, point.y);
+        }
+        if let (Some(precision), true) = (self.edge_precision, on_edge) {
+            let precision = precision as f64;
+            let round = |v: Float| ((v as f64 / precision).round() * precision) as Float;
+            point.x = round(point.x);
+            point.y = round(point.y);
+        }
+    }
+
+    /// Computes the coordinates of the given `rings` (expressed in grid coordinates)
+    /// according to the grid properties, skipping the work when they are the identity.
+    pub(crate) fn transform_rings(&self, rings: &mut [Ring]) {
+        if self.has_transform() {
+            rings
+                .iter_mut()
+                .flat_map(|ring| ring.iter_mut())
+                .for_each(|point| self.transform_point(point));
+        }
+    }
+
+    /// Returns a copy of this builder whose smoothing preserves the topology of the rings
+    /// of all the given `thresholds` at once, so that the rings of different thresholds
+    /// don't touch or cross each other either (and the isobands match the contours),
+    /// or `None` if the topology isn't preserved (or already is for all of them).
+    pub(crate) fn jointly(
+        &self,
+        grid: Grid<'_>,
+        thresholds: &[Float],
+    ) -> Result<Option<ContourBuilder>> {
+        let dual = self.vertex_placement == VertexPlacement::Dual;
+        if !self.smooth || !self.preserve_topology || dual || self.reverted.is_some() {
+            return Ok(None);
+        }
+        let fingerprint = self.cache_fingerprint(grid);
+        let mut isoring = self.isoring();
+        let mut scratch = LinearSmoothing::default();
+        let (mut rings, mut original, mut tied) = (Vec::new(), Vec::new(), Vec::new());
+        for threshold in thresholds {
+            let threshold = self.ties.apply(*threshold);
+            for ring in self.compute_rings(&mut isoring, grid, threshold, fingerprint)? {
+                let mut smoothed = ring.clone();
+                self.smooth_ring(&mut smoothed, grid, threshold, false, &mut scratch);
+                original.push(ring);
+                rings.push(smoothed);
+                tied.push(threshold);
+            }
+        }
+        let smoothed = rings.clone();
+        preserve_topology(&mut rings, &original);
+        let mut reverted = Reverted::default();
+        for (((ring, smoothed), original), threshold) in
+            rings.iter().zip(&smoothed).zip(&original).zip(tied)
+        {
+            let points = reverted.entry(threshold_key(threshold)).or_default();
+            for ((point, smoothed), original) in ring.iter().zip(smoothed).zip(original) {
+                if point != smoothed {
+                    points.insert(lattice_coords(original));
+                }
+            }
+        }
+        Ok(Some(ContourBuilder {
+            reverted: Some(Arc::new(reverted)),
+            ..self.clone()
+        }))
+    }
+
+    /// Computes the final coordinates of the given `rings` (expressed in grid coordinates):
+    /// smooths them (if smoothing is enabled, reverting the moves that would break their
+    /// topology if requested) and transforms them according to the grid properties,
+    /// in a single pass over the vertices whenever possible.
+    pub(crate) fn finalize_rings(&self, rings: &mut Vec<Ring>, grid: Grid<'_>, threshold: Float) {
+        if self.ties != TiePolicy::Perturb {
+            self.place_rings(rings, grid, threshold);
+            return;
+        }
+        // The perturbed threshold is within rounding errors of the values equal to the
+        // requested one: the rings around them, which may be flipped once smoothed, are
+        // collapsed (like the rings around the values equal to an unperturbed threshold)
+        let areas = (rings.iter())
+            .map(|ring| if ring.is_empty() { 0. } else { area(ring) })
+            .collect::<Vec<_>>();
+        self.place_rings(rings, grid, threshold);
+        let winding = if self.reverses_winding() { -1. } else { 1. };
+        for (ring, before) in rings.iter_mut().zip(areas) {
+            let closed = ring.len() > 1 && ring[0] == ring[ring.len() - 1];
+            if closed && area(ring) * before * winding < 0. {
+                let first = ring[0];
+                ring.fill(first);
+            }
+        }
+    }
+
+    /// Smooths and transforms the given `rings` (see [`ContourBuilder::finalize_rings`]).
+    fn place_rings(&self, rings: &mut Vec<Ring>, grid: Grid<'_>, threshold: Float) {
+        if self.clip_border {
+            self.finalize_extended(rings, grid, threshold);
+            return;
+        }
+        if let Some(period) = self.seam {
+            // The rings are unwrapped once smoothed, before computing their coordinates
+            let grid_space = ContourBuilder {
+                seam: None,
+                ..self.grid_space()
+            };
+            grid_space.finalize_rings(rings, grid, threshold);
+            unwrap_rings(rings, period as Float);
+            cut_at_seam(rings, period as Float, self.open_seam);
+            self.transform_rings(rings);
+            self.densify_rings(rings);
+            return;
+        }
+        let closed = self.close_rings.then(|| {
+            rings
+                .iter()
+                .map(|r| r.len() > 1 && r[0] == r[r.len() - 1])
+                .collect::<Vec<_>>()
+        });
+        if self.vertex_placement == VertexPlacement::Dual {
+            rings
+                .iter_mut()
+                .for_each(|ring| *ring = dual_ring(ring, grid, self.dx, self.dy, threshold));
+            self.transform_rings(rings);
+        } else if self.smooth && self.preserve_topology {
+            // All the rings have to be smoothed before checking their topology
+            let original = rings.to_vec();
+            let mut scratch = LinearSmoothing::default();
+            rings
+                .iter_mut()
+                .for_each(|ring| self.smooth_ring(ring, grid, threshold, false, &mut scratch));
+            match &self.reverted {
+                Some(reverted) => {
+                    let reverted = reverted.get(&threshold_key(threshold));
+                    for (ring, original) in rings.iter_mut().zip(&original) {
+                        for (point, original) in ring.iter_mut().zip(original) {
+                            if reverted.is_some_and(|r| r.contains(&lattice_coords(original))) {
+                                *point = *original;
+                            }
+                        }
+                    }
+                }
+                None => preserve_topology(rings, &original),
+            }
+            self.transform_rings(rings);
+        } else if self.smooth {
+            // Each ring is smoothed and transformed at once
+            let transform = self.has_transform();
+            let mut scratch = LinearSmoothing::default();
+            rings
+                .iter_mut()
+                .for_each(|ring| self.smooth_ring(ring, grid, threshold, transform, &mut scratch));
+        } else {
+            self.transform_rings(rings);
+        }
+        self.densify_rings(rings);
+        if let Some(closed) = closed {
+            rings
+                .iter_mut()
+                .zip(closed)
+                .filter(|(ring, closed)| *closed && ring.len() > 1)
+                .for_each(|(ring, _)| {
+                    let n = ring.len();
+                    ring[n - 1] = ring[0];
+                });
+        }
+    }
+
+    /// Inserts intermediate vertices in the given (transformed) `rings` if requested.
+    pub(crate) fn densify_rings(&self, rings: &mut [Ring]) {
+        if let Some(densify) = &self.densify {
+            rings.iter_mut().for_each(|ring| densify.apply(ring));
+        }
+    }
+
+    /// Computes the fingerprint of the given `grid` if the cache is enabled.
+    fn cache_fingerprint(&self, grid: Grid<'_>) -> Option<u64> {
+        self.cache
+            .as_ref()
+            .map(|_| grid.fingerprint(self.dx, self.dy))
+    }
+
+    /// Computes the rings (in grid coordinates) for the given `threshold`,
+    /// reusing the cached ones if available.
+    fn compute_rings(
+        &self,
+        isoring: &mut IsoRingBuilder,
+        grid: Grid<'_>,
+        threshold: Float,
+        fingerprint: Option<u64>,
+    ) -> Result<Vec<Ring>> {
+        let mut compute = || {
+            if self.strips > 1 {
+                compute_strips(self.dx, self.dy, self.strips, |i| grid.get(i) >= threshold)
+            } else {
+                isoring.compute_with(|i| grid.get(i) >= threshold)
+            }
+        };
+        match (&self.cache, fingerprint) {
+            (Some(cache), Some(fingerprint)) => {
+                cache.get_or_compute(fingerprint, threshold, compute)
+            }
+            _ => compute(),
+        }
+    }
+
+    /// Returns a new [`IsoRingBuilder`] for the grid of this builder.
+    pub(crate) fn isoring(&self) -> IsoRingBuilder {
+        IsoRingBuilder::new(self.dx, self.dy)
+            .block_width(self.block_width)
+            .period(self.seam)
+    }
+
+    /// Applies `f` to the indexes `0..n` (of thresholds), splitting them between
+    /// the configured number of threads (each one using its own [`IsoRingBuilder`]),
+    /// and collects the results in order.
+    pub(crate) fn map_thresholds<T, F>(&self, n: usize, f: F) -> Result<Vec<T>>
+    where
+        T: Send,
+        F: Fn(&mut IsoRingBuilder, usize) -> Result<T> + Sync,
+    {
+        self.map_thresholds_in(&mut Vec::new(), n, f)
+    }
+
+    /// Applies `f` to the indexes `0..n` (of thresholds) as [`ContourBuilder::map_thresholds`],
+    /// taking the [`IsoRingBuilder`] of each thread from the given `pool` (and putting
+    /// them back afterwards), so that their allocations are reused across calls.
+    pub(crate) fn map_thresholds_in<T, F>(
+        &self,
+        pool: &mut Vec<IsoRingBuilder>,
+        n: usize,
+        f: F,
+    ) -> Result<Vec<T>>
+    where
+        T: Send,
+        F: Fn(&mut IsoRingBuilder, usize) -> Result<T> + Sync,
+    {
+        let run = |isoring: &mut IsoRingBuilder, range: std::ops::Range<usize>| {
+            range.map(|i| f(isoring, i)).collect::<Result<Vec<T>>>()
+        };
+        if self.threads <= 1 || n <= 1 {
+            let mut isoring = pool.pop().unwrap_or_else(|| self.isoring());
+            let results = run(&mut isoring, 0..n);
+            pool.push(isoring);
+            return results;
+        }
+        let chunk_size = n.div_ceil(self.threads);
+        std::thread::scope(|scope| {
+            let handles = (0..n)
+                .step_by(chunk_size)
+                .map(|start| {
+                    let mut isoring = pool.pop().unwrap_or_else(|| self.isoring());
+                    let run = &run;
+                    scope.spawn(move || {
+                        let chunk = run(&mut isoring, start..(start + chunk_size).min(n));
+                        (isoring, chunk)
+                    })
+                })
+                .collect::<Vec<_>>();
+            let mut results = Vec::with_capacity(n);
+            for handle in handles {
+                match handle.join() {
+                    Ok((isoring, chunk)) => {
+                        pool.push(isoring);
+                        results.extend(chunk?)
+                    }
+                    Err(e) => std::panic::resume_unwind(e),
+                }
+            }
+            Ok(results)
+        })
+    }
+
+    /// Computes isolines according the given input `values` and the given `thresholds`.
+    /// Returns a `Vec` of [`Line`] (that can easily be transformed
+    /// to GeoJSON Features of MultiLineString).
+    /// The threshold value of each Feature is stored in its `value` property.
+    ///
+    /// The values (and thresholds) can be of any [`GridValue`] type (e.g. integers),
+    /// being converted to [`Float`] as they are read.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn lines<V: GridValue>(&self, values: &[V], thresholds: &[V]) -> Result<Vec<Line>> {
+        self.lines_with_diagnostics(values, thresholds)
+            .map(|(lines, _)| lines)
+    }
+
+    /// Computes isolines according the given input `values` and the given `thresholds`
+    /// (see [`ContourBuilder::lines`]), returning the [`Diagnostics`] of the computation
+    /// of each threshold alongside them.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn lines_with_diagnostics<V: GridValue>(
+        &self,
+        values: &[V],
+        thresholds: &[V],
+    ) -> Result<(Vec<Line>, Diagnostics)> {
+        let thresholds = to_floats(thresholds);
+        self.with_grid(values, |builder, grid| builder.lines_in(grid, &thresholds))
+    }
+
+    /// Computes the isolines of the (unpadded) `grid` for the given `thresholds`.
+    pub(crate) fn lines_in(
+        &self,
+        grid: Grid<'_>,
+        thresholds: &[Float],
+    ) -> Result<(Vec<Line>, Diagnostics)> {
+        if let Some(builder) = self.jointly(grid, thresholds)? {
+            return builder.lines_in(grid, thresholds);
+        }
+        let fingerprint = self.cache_fingerprint(grid);
+        let distinct = self.duplicates.distinct(thresholds)?;
+        let thresholds = &distinct.values;
+        let (lines, diagnostics) = self
+            .map_thresholds(thresholds.len(), |isoring, i| {
+                let start = Instant::now();
+                let mut diagnostics = ThresholdDiagnostics::new(thresholds[i], i);
+                let rings = self.final_lines(isoring, grid, thresholds[i], fingerprint)?;
+                diagnostics.rings = rings.len();
+                let line = self.line(rings, thresholds, i);
+                diagnostics.duration = start.elapsed();
+                Ok((line, diagnostics))
+            })?
+            .into_iter()
+            .unzip();
+        let lines = distinct.expand(lines, |l, i| l.threshold_index = i);
+        Ok((
+            self.without_empty(lines, |l| l.geometry.0.is_empty()),
+            Diagnostics {
+                thresholds: distinct.expand(diagnostics, |d, i| d.threshold_index = i),
+            },
+        ))
+    }
+
+    /// Computes isolines according the given input `values` and the given `thresholds`
+    /// (see [`ContourBuilder::lines`]), passing each of them to `f` as soon as it is
+    /// computed (in the order of the thresholds, on the calling thread) instead of
+    /// collecting them, e.g. to write them out while the next ones are computed.
+    ///
+    /// Stops at the first error returned by `f`.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    /// * `f` - The function called with each isoline.
+    pub fn for_each_line<V: GridValue>(
+        &self,
+        values: &[V],
+        thresholds: &[V],
+        f: impl FnMut(Line) -> Result<()>,
+    ) -> Result<()> {
+        let thresholds = to_floats(thresholds);
+        self.with_grid(values, |builder, grid| {
+            builder.for_each_line_in(grid, &thresholds, f)
+        })
+    }
+
+    /// Computes the isolines of the (unpadded) `grid` for the given `thresholds`,
+    /// passing each of them to `f`.
+    fn for_each_line_in(
+        &self,
+        grid: Grid<'_>,
+        thresholds: &[Float],
+        mut f: impl FnMut(Line) -> Result<()>,
+    ) -> Result<()> {
+        if let Some(builder) = self.jointly(grid, thresholds)? {
+            return builder.for_each_line_in(grid, thresholds, f);
+        }
+        let fingerprint = self.cache_fingerprint(grid);
+        let mut isoring = self.isoring();
+        let distinct = self.duplicates.distinct(thresholds)?;
+        let thresholds = &distinct.values;
+        distinct.for_each(
+            |i| {
+                let rings = self.final_lines(&mut isoring, grid, thresholds[i], fingerprint)?;
+                Ok(self.line(rings, thresholds, i))
+            },
+            |l, i| l.threshold_index = i,
+            |line| {
+                if !(self.skip_empty && line.geometry.0.is_empty()) {
+                    f(line)?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Calls `f` with the grid of the given `values` (read through a conversion if they
+    /// aren't [`Float`] values) and the builder computing it, padding it first if requested.
+    fn with_grid<V: GridValue, T>(
+        &self,
+        values: &[V],
+        f: impl FnOnce(&ContourBuilder, Grid<'_>) -> Result<T>,
+    ) -> Result<T> {
+        let Some(values) = V::as_floats(values) else {
+            if values.len() != self.dx * self.dy {
+                return Err(new_error(ErrorKind::BadDimension));
+            }
+            let source = Converted::new(values, self.dx, self.dy, self.order);
+            let grid = self.source_grid(&source)?;
+            if self.padding.is_some()
+                || self.periodic_x
+                || self.mask.is_some()
+                || matches!(self.border, BorderPolicy::Above | BorderPolicy::Mirror)
+            {
+                return self.row_major().with_grid(&grid.to_vec(), f);
+            }
+            return f(self, grid);
+        };
+        let grid = self.grid(values)?;
+        // The values derived from the checked ones don't have to be checked again
+        let masked = self.masked(values);
+        let (values, grid) = match &masked {
+            Some(masked) => (masked.as_slice(), self.grid_view(masked)),
+            None => (values, grid),
+        };
+        match (self.wrapped(values))
+            .or_else(|| self.padded(values))
+            .or_else(|| self.bordered(values))
+        {
+            Some((builder, values)) => f(&builder, builder.grid_view(&values)),
+            None => f(self, grid),
+        }
+    }
+
+    /// Checks the dimensions of the given `values` (and of the weights, if any),
+    /// and their infinite values if requested, and returns the view of the grid they form.
+    pub(crate) fn grid<'a>(&'a self, values: &'a [Float]) -> Result<Grid<'a>> {
+        let len = self.dx * self.dy;
+        if values.len() != len
+            || self
+                .weights
+                .as_ref()
+                .is_some_and(|w| w.weights.len() != len)
+            || !self.coords_match()
+        {
+            return Err(new_error(ErrorKind::BadDimension));
+        }
+        let grid = self.grid_view(values);
+        self.check_infinity(grid)?;
+        Ok(grid)
+    }
+
+    /// Returns the view of the grid formed by the given (already checked) `values`.
+    fn grid_view<'a>(&'a self, values: &'a [Float]) -> Grid<'a> {
+        let grid = Grid::new(
+            values,
+            self.weights.as_ref(),
+            self.infinity == InfinityPolicy::Outside,
+            self.nodata,
+        );
+        match self.order {
+            GridOrder::RowMajor => grid,
+            GridOrder::ColumnMajor => grid.column_major(self.dx, self.dy),
+        }
+    }
+
+    /// Checks the dimensions of the given `source` (and of the weights, if any),
+    /// and their infinite values if requested, and returns the view of the grid it forms.
+    pub(crate) fn source_grid<'a>(&'a self, source: &'a dyn GridSource) -> Result<Grid<'a>> {
+        if source.width() != self.dx
+            || source.height() != self.dy
+            || self
+                .weights
+                .as_ref()
+                .is_some_and(|w| w.weights.len() != self.dx * self.dy)
+            || !self.coords_match()
+        {
+            return Err(new_error(ErrorKind::BadDimension));
+        }
+        let grid = Grid::from_source(
+            source,
+            self.weights.as_ref(),
+            self.infinity == InfinityPolicy::Outside,
+            self.nodata,
+        );
+        let grid = match self.order {
+            GridOrder::RowMajor => grid,
+            GridOrder::ColumnMajor => grid.column_major(self.dx, self.dy),
+        };
+        self.check_infinity(grid)?;
+        Ok(grid)
+    }
+
+    /// Whether the grid is rotated or sheared (see [`ContourBuilder::geotransform`]).
+    pub(crate) fn has_skew(&self) -> bool {
+        (self.x_skew, self.y_skew) != (0., 0.)
+    }
+
+    /// Whether the coordinates of the samples (if any) match the dimensions of the grid.
+    fn coords_match(&self) -> bool {
+        self.x_coords.as_ref().is_none_or(|a| a.len() == self.dx)
+            && self.y_coords.as_ref().is_none_or(|a| a.len() == self.dy)
+            && (self.curvilinear.as_ref()).is_none_or(|c| c.has_len(self.dx * self.dy))
+    }
+
+    /// Returns an error locating the first infinite value of the `grid`, if they are rejected.
+    fn check_infinity(&self, grid: Grid<'_>) -> Result<()> {
+        let len = grid.len();
+        if self.infinity == InfinityPolicy::Error {
+            if let Some(i) = (0..len).find(|&i| grid.get(i).is_infinite()) {
+                return Err(new_error(ErrorKind::InfiniteValue {
+                    x: i % self.dx,
+                    y: i / self.dx,
+                }));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the builder and the values of the padded grid if padding is enabled.
+    pub(crate) fn padded(&self, values: &[Float]) -> Option<(ContourBuilder, Vec<Float>)> {
+        let (n, mode) = self.padding?;
+        Some(self.pad_grid(values, n, mode))
+    }
+
+    /// Returns the builder and the values of the grid extended by `n` cells on each side,
+    /// whose values are computed according to `mode`.
+    pub(crate) fn pad_grid(
+        &self,
+        values: &[Float],
+        n: usize,
+        mode: Padding,
+    ) -> (ContourBuilder, Vec<Float>) {
+        // The padded grid is stored row by row
+        let (dx, dy) = (self.dx, self.dy);
+        let pad = |values: &[Float]| match self.order {
+            GridOrder::RowMajor => pad(values, dx, dy, n, mode),
+            GridOrder::ColumnMajor => pad(&to_row_major(values, dx, dy), dx, dy, n, mode),
+        };
+        let values = pad(values);
+        let builder = ContourBuilder {
+            dx: self.dx + 2 * n,
+            dy: self.dy + 2 * n,
+            x_origin: self.x_origin - n as Float * (self.x_step + self.x_skew),
+            y_origin: self.y_origin - n as Float * (self.y_step + self.y_skew),
+            x_coords: self.x_coords.as_ref().map(|axis| axis.pad(n)),
+            y_coords: self.y_coords.as_ref().map(|axis| axis.pad(n)),
+            curvilinear: (self.curvilinear.as_ref()).map(|c| self.curvilinear_pad(c, n)),
+            weights: self
+                .weights
+                .as_ref()
+                .map(|w| Weights::new(pad(&w.weights), w.op.clone())),
+            order: GridOrder::RowMajor,
+            padding: None,
+            ..self.clone()
+        };
+        (builder, values)
+    }
+
+    /// Returns the builder reading the values (and weights) row by row.
+    pub(crate) fn row_major(&self) -> ContourBuilder {
+        let (dx, dy) = (self.dx, self.dy);
+        match self.order {
+            GridOrder::RowMajor => self.clone(),
+            GridOrder::ColumnMajor => ContourBuilder {
+                weights: self
+                    .weights
+                    .as_ref()
+                    .map(|w| Weights::new(to_row_major(&w.weights, dx, dy), w.op.clone())),
+                curvilinear: (self.curvilinear.as_ref()).map(|c| self.curvilinear_row_major(c)),
+                order: GridOrder::RowMajor,
+                ..self.clone()
+            },
+        }
+    }
+
+    /// Removes the features whose geometry is empty if requested.
+    pub(crate) fn without_empty<T>(
+        &self,
+        mut features: Vec<T>,
+        is_empty: impl Fn(&T) -> bool,
+    ) -> Vec<T> {
+        if self.skip_empty {
+            features.retain(|f| !is_empty(f));
+        }
+        features
+    }
+
+    /// Computes the rings for the given `threshold`, smoothing them and computing
+    /// their coordinates if needed.
+    pub(crate) fn final_rings(
+        &self,
+        isoring: &mut IsoRingBuilder,
+        grid: Grid<'_>,
+        threshold: Float,
+        fingerprint: Option<u64>,
+    ) -> Result<Vec<Ring>> {
+        let threshold = self.ties.apply(threshold);
+        let mut result = self.compute_rings(isoring, grid, threshold, fingerprint)?;
+        // Smooth the rings and compute their coordinates if needed
+        self.finalize_rings(&mut result, grid, threshold);
+        Ok(result)
+    }
+
+    /// Computes the lines for the given `threshold` (the rings, cut where they follow
+    /// the boundary of the cells without data or the edges of the grid according to
+    /// the border policy), smoothing them and computing
+    /// their coordinates if needed.
+    pub(crate) fn final_lines(
+        &self,
+        isoring: &mut IsoRingBuilder,
+        grid: Grid<'_>,
+        threshold: Float,
+        fingerprint: Option<u64>,
+    ) -> Result<Vec<Ring>> {
+        let threshold = self.ties.apply(threshold);
+        let mut rings = self.compute_rings(isoring, grid, threshold, fingerprint)?;
+        match self.final_isolines(&rings, grid, threshold) {
+            Some(lines) => Ok(lines),
+            None => {
+                self.finalize_rings(&mut rings, grid, threshold);
+                Ok(rings)
+            }
+        }
+    }
+
+    /// Classifies the isolines and contours of the given `threshold`, if requested.
+    fn class(&self, threshold: Float) -> Option<ContourClass> {
+        self.index_interval
+            .map(|interval| ContourClass::classify(threshold, interval))
+    }
+
+    /// Builds the [`Line`] of the `i`-th threshold from its (final) rings.
+    pub(crate) fn line(&self, rings: Vec<Ring>, thresholds: &[Float], i: usize) -> Line {
+        let geometry = self.line_geometry(rings);
+        Line {
+            bbox: lines_bbox(&geometry),
+            class: self.class(thresholds[i]),
+            depressions: self.depressions.then(|| {
+                geometry
+                    .0
+                    .iter()
+                    .map(|l| self.is_depression(&l.0))
+                    .collect()
+            }),
+            geometry,
+            threshold: thresholds[i],
+            threshold_index: i,
+            metadata: Metadata::new(),
+        }
+    }
+
+    /// Builds the geometry of an isoline from its (final) rings, clipping them
+    /// with the mask and splitting them where they cross the antimeridian if requested.
+    fn line_geometry(&self, rings: Vec<Ring>) -> MultiLineString<Float> {
+        let rings = self.mask_lines(rings);
+        MultiLineString::<Float>(if self.split_antimeridian {
+            rings
+                .iter()
+                .flat_map(|ring| split_line(ring))
+                .map(LineString)
+                .collect()
+        } else {
+            rings.into_iter().map(LineString).collect()
+        })
+    }
+
+    /// Computes contours according the given input `values` and the given `thresholds`.
+    /// Returns a `Vec` of [`Contour`] (that can easily be transformed
+    /// to GeoJSON Features of MultiPolygon).
+    /// The threshold value of each Feature is stored in its `value` property
+    /// (and the next threshold above it, if any, in its `max_threshold` property).
+    ///
+    /// The values (and thresholds) can be of any [`GridValue`] type (e.g. integers),
+    /// being converted to [`Float`] as they are read.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn contours<V: GridValue>(&self, values: &[V], thresholds: &[V]) -> Result<Vec<Contour>> {
+        self.contours_with_diagnostics(values, thresholds)
+            .map(|(contours, _)| contours)
+    }
+
+    /// Computes contours according the given input `values` and the given `thresholds`
+    /// (see [`ContourBuilder::contours`]), returning the [`Diagnostics`] of the computation
+    /// of each threshold alongside them.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn contours_with_diagnostics<V: GridValue>(
+        &self,
+        values: &[V],
+        thresholds: &[V],
+    ) -> Result<(Vec<Contour>, Diagnostics)> {
+        let thresholds = to_floats(thresholds);
+        self.with_grid(values, |builder, grid| {
+            builder.contours_in(&mut Vec::new(), grid, &thresholds)
+        })
+    }
+
+    /// Computes the contours of the (unpadded) `grid` for the given `thresholds`,
+    /// reusing the [`IsoRingBuilder`]s of the given `pool`.
+    pub(crate) fn contours_in(
+        &self,
+        pool: &mut Vec<IsoRingBuilder>,
+        grid: Grid<'_>,
+        thresholds: &[Float],
+    ) -> Result<(Vec<Contour>, Diagnostics)> {
+        if let Some(builder) = self.jointly(grid, thresholds)? {
+            return builder.contours_in(pool, grid, thresholds);
+        }
+        let fingerprint = self.cache_fingerprint(grid);
+        let distinct = self.duplicates.distinct(thresholds)?;
+        let thresholds = &distinct.values;
+        let (contours, diagnostics) = self
+            .map_thresholds_in(pool, thresholds.len(), |isoring, i| {
+                let start = Instant::now();
+                let mut diagnostics = ThresholdDiagnostics::new(thresholds[i], i);
+                let rings = self.final_rings(isoring, grid, thresholds[i], fingerprint)?;
+                diagnostics.rings = rings.len();
+                let contour = self.contour(rings, thresholds, i, &mut diagnostics);
+                diagnostics.duration = start.elapsed();
+                Ok((contour, diagnostics))
+            })?
+            .into_iter()
+            .unzip();
+        let contours = distinct.expand(contours, |c, i| c.threshold_index = i);
+        Ok((
+            self.without_empty(contours, |c| c.geometry.0.is_empty()),
+            Diagnostics {
+                thresholds: distinct.expand(diagnostics, |d, i| d.threshold_index = i),
+            },
+        ))
+    }
+
+    /// Computes the contours of each of the given `grids` of values (e.g. the variables
+    /// or time steps sharing the dimensions of this builder) for the given `thresholds`
+    /// (see [`ContourBuilder::contours`]), reusing the same internal ring builders
+    /// (and their allocations) for all of them.
+    ///
+    /// Returns the contours of each grid, in the order of the grids.
+    ///
+    /// # Arguments
+    ///
+    /// * `grids` - The slices of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn contours_multi<V: GridValue>(
+        &self,
+        grids: &[&[V]],
+        thresholds: &[V],
+    ) -> Result<Vec<Vec<Contour>>> {
+        let thresholds = to_floats(thresholds);
+        let mut pool = Vec::new();
+        grids
+            .iter()
+            .map(|values| {
+                self.with_grid(values, |builder, grid| {
+                    builder
+                        .contours_in(&mut pool, grid, &thresholds)
+                        .map(|(contours, _)| contours)
+                })
+            })
+            .collect()
+    }
+
+    /// Computes contours according the given input `values` and the given `thresholds`
+    /// (see [`ContourBuilder::contours`]), passing each of them to `f` as soon as it is
+    /// computed (in the order of the thresholds, on the calling thread) instead of
+    /// collecting them, e.g. to write them out while the next ones are computed.
+    ///
+    /// Stops at the first error returned by `f`.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    /// * `f` - The function called with each contour.
+    pub fn for_each_contour<V: GridValue>(
+        &self,
+        values: &[V],
+        thresholds: &[V],
+        f: impl FnMut(Contour) -> Result<()>,
+    ) -> Result<()> {
+        let thresholds = to_floats(thresholds);
+        self.with_grid(values, |builder, grid| {
+            builder.for_each_contour_in(grid, &thresholds, f)
+        })
+    }
+
+    /// Computes the contours of the (unpadded) `grid` for the given `thresholds`,
+    /// passing each of them to `f`.
+    fn for_each_contour_in(
+        &self,
+        grid: Grid<'_>,
+        thresholds: &[Float],
+        mut f: impl FnMut(Contour) -> Result<()>,
+    ) -> Result<()> {
+        if let Some(builder) = self.jointly(grid, thresholds)? {
+            return builder.for_each_contour_in(grid, thresholds, f);
+        }
+        let fingerprint = self.cache_fingerprint(grid);
+        let mut isoring = self.isoring();
+        let distinct = self.duplicates.distinct(thresholds)?;
+        let thresholds = &distinct.values;
+        distinct.for_each(
+            |i| {
+                let rings = self.final_rings(&mut isoring, grid, thresholds[i], fingerprint)?;
+                let mut diagnostics = ThresholdDiagnostics::new(thresholds[i], i);
+                Ok(self.contour(rings, thresholds, i, &mut diagnostics))
+            },
+            |c, i| c.threshold_index = i,
+            |contour| {
+                if !(self.skip_empty && contour.geometry.0.is_empty()) {
+                    f(contour)?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Builds the [`Contour`] of the `i`-th threshold from its (final) rings.
+    pub(crate) fn contour(
+        &self,
+        rings: Vec<Ring>,
+        thresholds: &[Float],
+        i: usize,
+        diagnostics: &mut ThresholdDiagnostics,
+    ) -> Contour {
+        let threshold = thresholds[i];
+        let mut rings = rings;
+        if self.seam.is_some() {
+            join_paths(&mut rings);
+        }
+        let geometry = self.assemble_polygons(rings, diagnostics);
+        Contour {
+            bbox: polygons_bbox(&geometry),
+            class: self.class(threshold),
+            geometry,
+            threshold,
+            max_threshold: thresholds
+                .iter()
+                .filter(|t| **t > threshold)
+                .copied()
+                .reduce(Float::min),
+            threshold_index: i,
+            metadata: Metadata::new(),
+            part: None,
+        }
+    }
+
+    /// Computes both isolines and contours according the given input `values`
+    /// and the given `thresholds` (see [`ContourBuilder::lines`] and
+    /// [`ContourBuilder::contours`]), deriving both from a single computation
+    /// of the rings of each threshold.
+    ///
+    /// The isolines are thus guaranteed to lie exactly on the boundaries of the
+    /// corresponding contours (with identical vertex sequences), so that the contours
+    /// can be filled and outlined without hairline offsets (except where the isolines
+    /// are cut along the cells without data, see [`ContourBuilder::nodata`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn lines_and_contours<V: GridValue>(
+        &self,
+        values: &[V],
+        thresholds: &[V],
+    ) -> Result<(Vec<Line>, Vec<Contour>)> {
+        let thresholds = to_floats(thresholds);
+        self.with_grid(values, |builder, grid| {
+            builder.lines_and_contours_in(grid, &thresholds)
+        })
+    }
+
+    /// Computes both isolines and contours of the (unpadded) `grid` for the given `thresholds`.
+    fn lines_and_contours_in(
+        &self,
+        grid: Grid<'_>,
+        thresholds: &[Float],
+    ) -> Result<(Vec<Line>, Vec<Contour>)> {
+        if let Some(builder) = self.jointly(grid, thresholds)? {
+            return builder.lines_and_contours_in(grid, thresholds);
+        }
+        let fingerprint = self.cache_fingerprint(grid);
+        let distinct = self.duplicates.distinct(thresholds)?;
+        let thresholds = &distinct.values;
+        let (lines, contours) = self
+            .map_thresholds(thresholds.len(), |isoring, i| {
+                let threshold = self.ties.apply(thresholds[i]);
+                let mut rings = self.compute_rings(isoring, grid, threshold, fingerprint)?;
+                // The lines reaching the cells without data are finalized on their own
+                let cut = self.final_isolines(&rings, grid, threshold);
+                self.finalize_rings(&mut rings, grid, threshold);
+                Ok((
+                    self.line(cut.unwrap_or_else(|| rings.clone()), thresholds, i),
+                    self.contour(
+                        rings,
+                        thresholds,
+                        i,
+                        &mut ThresholdDiagnostics::new(thresholds[i], i),
+                    ),
+                ))
+            })?
+            .into_iter()
+            .unzip();
+        let lines = distinct.expand(lines, |l, i| l.threshold_index = i);
+        let contours = distinct.expand(contours, |c, i| c.threshold_index = i);
+        Ok((
+            self.without_empty(lines, |l| l.geometry.0.is_empty()),
+            self.without_empty(contours, |c| c.geometry.0.is_empty()),
+        ))
+    }
+
+    /// Assembles the given (transformed) `rings` into polygons according to the fill rule
+    /// (by default, exterior rings being identified by their winding order, holes being
+    /// assigned to the polygon containing them).
+    pub(crate) fn assemble_polygons(
+        &self,
+        mut rings: Vec<Ring>,
+        diagnostics: &mut ThresholdDiagnostics,
+    ) -> MultiPolygon<Float> {
+        // The empty rings are the ones dropped once finalized
+        rings.retain(|ring| !ring.is_empty());
+        if self.fill_rule != FillRule::Containment {
+            let polygons = fillrule::assemble(rings, self.fill_rule, diagnostics);
+            return self.split_polygons(self.mask_polygons(MultiPolygon::<Float>(polygons)));
+        }
+        let (mut polygons, mut holes) = (Vec::new(), Vec::new());
+        // Decreasing coordinates along one axis reverse the winding order
+        let exterior = if self.reverses_winding() { -1. } else { 1. };
+
+        rings.drain(..).for_each(|ring| {
+            if area(&ring) * exterior > 0.0 {
+                polygons.push(Polygon::<Float>::new(LineString::new(ring), vec![]))
+            } else {
+                holes.push(LineString::new(ring));
+            }
         });
 
+        holes.drain(..).for_each(|hole| {
+            for polygon in &mut polygons {
+                if contains(&polygon.exterior().0, &hole.0) != -1 {
+                    polygon.interiors_push(hole);
+                    diagnostics.holes += 1;
+                    return;
+                }
+            }
+            diagnostics.drop_ring(Warning::OrphanHole { ring: hole });
+        });
+
+        self.split_polygons(self.mask_polygons(MultiPolygon::<Float>(polygons)))
+    }
+
+    /// Splits the given polygons where they cross the antimeridian, if requested.
+    fn split_polygons(&self, geometry: MultiPolygon<Float>) -> MultiPolygon<Float> {
+        if self.split_antimeridian {
+            split_polygons(geometry)
+        } else {
+            geometry
+        }
+    }
+
+    /// Computes the contour of the regions where the given boolean `mask` is `true`
+    /// (e.g. to vectorize a segmentation mask), skipping the thresholding step.
+    /// Returns a [`Contour`] whose threshold is set to `0.5`.
+    ///
+    /// Smoothing doesn't apply to boolean grids (the rings always pass
+    /// through the middle of the cell edges).
+    ///
+    /// # Arguments
+    ///
+    /// * `mask` - The slice of booleans to be used.
+    pub fn contour_mask(&self, mask: &[bool]) -> Result<Contour> {
+        if mask.len() != self.dx * self.dy {
+            return Err(new_error(ErrorKind::BadDimension));
+        }
+        let mut isoring = self.isoring();
+        let mut rings = isoring.compute_mask(mask)?;
+        self.transform_rings(&mut rings);
+        self.densify_rings(&mut rings);
+        let geometry = self.assemble_polygons(rings, &mut ThresholdDiagnostics::new(0.5, 0));
+        Ok(Contour {
+            bbox: polygons_bbox(&geometry),
+            class: None,
+            geometry,
+            threshold: 0.5,
+            max_threshold: None,
+            threshold_index: 0,
+            metadata: Metadata::new(),
+            part: None,
+        })
+    }
+
+    /// Computes isobands according the given input `values` and the given `thresholds`.
+    /// Returns a `Vec` of [`Band`] (that can easily be transformed
+    /// to GeoJSON Features of MultiPolygon).
+    /// The threshold value of each Feature is stored in its `value` property.
+    ///
+    /// The values (and thresholds) can be of any [`GridValue`] type (e.g. integers),
+    /// being converted to [`Float`] as they are read.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used
+    ///   (have to be equal to or greater than 2). They don't need to be sorted:
+    ///   the bands are returned in ascending order and [`Band::threshold_index`]
+    ///   gives the position of their lower threshold in this slice.
+    pub fn isobands<V: GridValue>(&self, values: &[V], thresholds: &[V]) -> Result<Vec<Band>> {
+        self.isobands_with_diagnostics(values, thresholds)
+            .map(|(bands, _)| bands)
+    }
+
+    /// Computes the region where the given input `values` lie in the window [`low`, `high`)
+    /// (e.g. the cells between 10 and 20 dBZ), i.e. the single isoband between
+    /// the two thresholds (see [`ContourBuilder::isobands`]).
+    ///
+    /// The band is returned even if its geometry is empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `low` - The lower bound of the window (included).
+    /// * `high` - The upper bound of the window (excluded).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use contour::ContourBuilder;
+    ///
+    /// let values = [0., 15., 15., 0., 5., 0.];
+    /// let band = ContourBuilder::new(3, 2, false).region(&values, 10., 20.).unwrap();
+    /// assert_eq!((band.min_v(), band.max_v()), (10., 20.));
+    /// assert_eq!(band.geometry().0.len(), 1);
+    /// ```
+    pub fn region<V: GridValue>(&self, values: &[V], low: V, high: V) -> Result<Band> {
+        let builder = ContourBuilder {
+            skip_empty: false,
+            ..self.clone()
+        };
+        builder
+            .isobands(values, &[low, high])?
+            .pop()
+            .ok_or_else(|| new_error(ErrorKind::Unexpected))
+    }
+
+    /// Computes the isobands of each of the given `grids` of values for the given
+    /// `thresholds` (see [`ContourBuilder::isobands`]), reusing the same
+    /// ring builders for all of them (see [`ContourBuilder::contours_multi`]).
+    ///
+    /// Returns the isobands of each grid, in the order of the grids.
+    ///
+    /// # Arguments
+    ///
+    /// * `grids` - The slices of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used
+    ///   (have to be equal to or greater than 2).
+    pub fn isobands_multi<V: GridValue>(
+        &self,
+        grids: &[&[V]],
+        thresholds: &[V],
+    ) -> Result<Vec<Vec<Band>>> {
+        let thresholds = to_floats(thresholds);
+        let mut pool = Vec::new();
+        grids
+            .iter()
+            .map(|values| {
+                self.with_grid(values, |builder, grid| {
+                    builder
+                        .isobands_in(&mut pool, grid, &thresholds)
+                        .map(|(bands, _)| bands)
+                })
+            })
+            .collect()
+    }
+
+    /// Computes isobands according the given input `values` and the given `thresholds`
+    /// (see [`ContourBuilder::isobands`]), returning the [`Diagnostics`] of the computation
+    /// of each threshold alongside them (in ascending order of the thresholds,
+    /// the rings assembled into a band being counted with its lower threshold).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used
+    ///   (have to be equal to or greater than 2).
+    pub fn isobands_with_diagnostics<V: GridValue>(
+        &self,
+        values: &[V],
+        thresholds: &[V],
+    ) -> Result<(Vec<Band>, Diagnostics)> {
+        // We will compute rings as previously, but we will
+        // iterate over the contours in pairs and use the paths from the lower threshold
+        // and the path from the upper threshold to create the isoband.
+        let thresholds = to_floats(thresholds);
+        self.with_grid(values, |builder, grid| {
+            builder.isobands_in(&mut Vec::new(), grid, &thresholds)
+        })
+    }
+
+    /// Computes the isobands of the (unpadded) `grid` for the given `thresholds`
+    /// (at least 2), reusing the [`IsoRingBuilder`]s of the given `pool`.
+    pub(crate) fn isobands_in(
+        &self,
+        pool: &mut Vec<IsoRingBuilder>,
+        grid: Grid<'_>,
+        thresholds: &[Float],
+    ) -> Result<(Vec<Band>, Diagnostics)> {
+        if thresholds.len() < 2 {
+            return Err(new_error(ErrorKind::Unexpected));
+        }
+        if let Some(builder) = self.jointly(grid, thresholds)? {
+            return builder.isobands_in(pool, grid, thresholds);
+        }
+        let world = self.has_transform() || self.densify.is_some() || self.split_antimeridian;
+        if self.seam.is_some() && world {
+            // The rings crossing the seam are cut there in grid space, where it is straight
+            let (bands, diagnostics) = self.grid_space().isobands_in(pool, grid, thresholds)?;
+            let bands = bands.into_iter().map(|b| self.world_band(b)).collect();
+            return Ok((bands, diagnostics));
+        }
+        let fingerprint = self.cache_fingerprint(grid);
+        let distinct = self.duplicates.distinct(thresholds)?;
+        let thresholds = &distinct.values;
+
+        let rings = self.map_thresholds_in(pool, thresholds.len(), |isoring, i| {
+            let start = Instant::now();
+            let threshold = thresholds[i];
+            let mut diagnostics = ThresholdDiagnostics::new(threshold, i);
+            // Compute the rings for the current threshold
+            let tied = self.ties.apply(threshold);
+            let mut rings = self.compute_rings(isoring, grid, tied, fingerprint)?;
+            diagnostics.rings = rings.len();
+            // Smooth the rings and compute their coordinates if needed
+            self.finalize_rings(&mut rings, grid, tied);
+            if self.seam.is_some() {
+                join_paths(&mut rings);
+            }
+            let rings = rings
+                .into_iter()
+                .map(|mut ring| {
+                    ring.dedup();
+                    ring
+                })
+                .filter(|ring| {
+                    let degenerate = ring.len() <= 3;
+                    if degenerate {
+                        diagnostics.drop_ring(Warning::DegenerateRing { points: ring.len() });
+                    }
+                    !degenerate
+                })
+                .collect::<Vec<Ring>>();
+            diagnostics.duration = start.elapsed();
+            Ok(((rings, threshold, i), diagnostics))
+        })?;
+        let mut rings = distinct.expand(rings, |((_, _, ix), diagnostics), i| {
+            *ix = i;
+            diagnostics.threshold_index = i;
+        });
+        // Thresholds are processed in ascending order, keeping track of their
+        // position in the request so that each band can be mapped back to it.
+        rings.sort_by(|((_, a, _), _), ((_, b, _), _)| a.total_cmp(b));
+        let (rings, mut diagnostics): (Vec<_>, Vec<_>) = rings.into_iter().unzip();
+
+        // We now have the rings for each isolines for all the given thresholds,
+        // we can iterate over them in pairs to compute the isobands.
+        let b = rings
+            .windows(2)
+            .map(|rings| {
+                let ((lower_path, min_v, ix), (upper_path, max_v, _)) = (&rings[0], &rings[1]);
+                // The rings shared by both contours (e.g. around the cells without data)
+                // don't bound the band, as it lies on the same side of both copies
+                let shared =
+                    |ring: &Ring, others: &[Ring]| others.iter().any(|o| same_ring(ring, o));
+                let mut concatenated = (lower_path.iter().filter(|r| !shared(r, upper_path)))
+                    .chain(upper_path.iter().filter(|r| !shared(r, lower_path)))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                // The rings of a contour crossing the seam inside a strip of the other one
+                // are cut there as well
+                if let Some(period) = self.seam {
+                    cut_at_seam(&mut concatenated, period as Float, false);
+                }
+                (concatenated, min_v, max_v, *ix)
+            })
+            .collect::<Vec<_>>();
+
+        let mut bands: Vec<Band> = Vec::new();
+        // Reconstruction of the polygons
+        b.into_iter().zip(diagnostics.iter_mut()).for_each(
+            |((rings, min_v, max_v, threshold_index), diagnostics)| {
+                let start = Instant::now();
+                let mut rings_and_area = rings
+                    .into_iter()
+                    .map(|ring| {
+                        let area = area(&ring);
+                        (ring, area)
+                    })
+                    .collect::<Vec<_>>();
+
+                rings_and_area.sort_by_key(|(_, area)| area.abs() as u64);
+
+                let mut enclosed_by_n = FxHashMap::default();
+
+                for (i, (ring, area)) in rings_and_area.iter().enumerate() {
+                    let mut enclosed_by_j = 0;
+                    for (j, (ring_test, area_test)) in rings_and_area.iter().enumerate() {
+                        if i == j {
+                            continue;
+                        }
+                        // A ring lying on the other one (e.g. both following the edges
+                        // of the grid) is enclosed by it if it is the smaller one
+                        let enclosed = match contains(ring_test, ring) {
+                            0 => area_test.abs() > area.abs(),
+                            c => c == 1,
+                        };
+                        if enclosed {
+                            enclosed_by_j += 1;
+                        }
+                    }
+                    enclosed_by_n.insert(i, enclosed_by_j);
+                }
+
+                let mut polygons: Vec<Polygon<Float>> = Vec::new();
+                let mut depths: Vec<usize> = Vec::new();
+                let mut interior_rings: Vec<LineString<Float>> = Vec::new();
+
+                for (i, (ring, _)) in rings_and_area.into_iter().enumerate() {
+                    let depth = *enclosed_by_n.get(&i).unwrap();
+                    if depth % 2 == 0 {
+                        polygons.push(Polygon::<Float>::new(ring.into(), vec![]));
+                        depths.push(depth);
+                    } else {
+                        interior_rings.push(ring.into());
+                    }
+                }
+                'holes: for interior_ring in interior_rings.into_iter() {
+                    for polygon in polygons.iter_mut() {
+                        if contains(&polygon.exterior().0, &interior_ring.0) != -1 {
+                            polygon.interiors_push(interior_ring);
+                            diagnostics.holes += 1;
+                            continue 'holes;
+                        }
+                    }
+                    diagnostics.drop_ring(Warning::OrphanHole {
+                        ring: interior_ring,
+                    });
+                }
+
+                polygons.reverse();
+                depths.reverse();
+
+                let (geometry, depths) = self.split_nested(polygons, depths, |polygons| {
+                    self.split_polygons(self.mask_polygons(polygons))
+                });
+                bands.push(Band {
+                    bbox: polygons_bbox(&geometry),
+                    geometry,
+                    min_v: *min_v,
+                    max_v: *max_v,
+                    threshold_index,
+                    metadata: Metadata::new(),
+                    label: self.band_labels.as_ref().map(|f| (f.0)(*min_v, *max_v)),
+                    depths,
+                    part: None,
+                });
+                diagnostics.duration += start.elapsed();
+            },
+        );
+
+        Ok((
+            self.without_empty(bands, |b| b.geometry.0.is_empty()),
+            Diagnostics {
+                thresholds: diagnostics,
+            },
+        ))
+    }
+
+    /// Computes isolines according the given input `values` and the given `levels`,
+    /// each level being a threshold value and the [`Metadata`] to attach to the
+    /// resulting [`Line`] (and to its GeoJSON properties).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used (of any [`GridValue`] type).
+    /// * `levels` - The slice of (threshold, metadata) to be used.
+    pub fn lines_with_metadata<V: GridValue>(
+        &self,
+        values: &[V],
+        levels: &[(V, Metadata)],
+    ) -> Result<Vec<Line>> {
+        let thresholds = levels.iter().map(|(t, _)| *t).collect::<Vec<_>>();
+        let mut lines = self.lines(values, &thresholds)?;
+        lines
+            .iter_mut()
+            .for_each(|line| line.metadata = levels[line.threshold_index].1.clone());
+        Ok(lines)
+    }
+
+    /// Computes contours according the given input `values` and the given `levels`,
+    /// each level being a threshold value and the [`Metadata`] to attach to the
+    /// resulting [`Contour`] (and to its GeoJSON properties).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used (of any [`GridValue`] type).
+    /// * `levels` - The slice of (threshold, metadata) to be used.
+    pub fn contours_with_metadata<V: GridValue>(
+        &self,
+        values: &[V],
+        levels: &[(V, Metadata)],
+    ) -> Result<Vec<Contour>> {
+        let thresholds = levels.iter().map(|(t, _)| *t).collect::<Vec<_>>();
+        let mut contours = self.contours(values, &thresholds)?;
+        contours
+            .iter_mut()
+            .for_each(|contour| contour.metadata = levels[contour.threshold_index].1.clone());
+        Ok(contours)
+    }
+
+    /// Computes isobands according the given input `values` and the given `levels`,
+    /// each level being a threshold value and its [`Metadata`].
+    /// Each resulting [`Band`] carries the metadata of its lower threshold
+    /// (so the metadata of the last level is not used).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used (of any [`GridValue`] type).
+    /// * `levels` - The slice of (threshold, metadata) to be used
+    ///   (have to be equal to or greater than 2).
+    pub fn isobands_with_metadata<V: GridValue>(
+        &self,
+        values: &[V],
+        levels: &[(V, Metadata)],
+    ) -> Result<Vec<Band>> {
+        let thresholds = levels.iter().map(|(t, _)| *t).collect::<Vec<_>>();
+        let mut bands = self.isobands(values, &thresholds)?;
+        bands
+            .iter_mut()
+            .for_each(|band| band.metadata = levels[band.threshold_index].1.clone());
         Ok(bands)
     }
+
+    /// Returns a copy of this builder computing the geometries in grid space
+    /// (i.e. with the identity as transform, without densification or splitting,
+    /// the mask being expressed in grid coordinates).
+    pub(crate) fn grid_space(&self) -> ContourBuilder {
+        ContourBuilder {
+            x_origin: 0.,
+            y_origin: 0.,
+            x_step: 1.,
+            y_step: 1.,
+            x_skew: 0.,
+            y_skew: 0.,
+            x_coords: None,
+            y_coords: None,
+            curvilinear: None,
+            edge_precision: None,
+            densify: None,
+            split_antimeridian: false,
+            transform: None,
+            mask: self.mask.as_ref().map(|mask| self.grid_mask(mask)),
+            ..self.clone()
+        }
+    }
+
+    /// Transforms the given isolines, computed in grid space, to world space.
+    fn world_line(&self, mut line: Line) -> Line {
+        let mut rings = line.geometry.0.into_iter().map(|l| l.0).collect::<Vec<_>>();
+        self.transform_rings(&mut rings);
+        self.densify_rings(&mut rings);
+        line.geometry = self.line_geometry(rings);
+        line.update_bbox();
+        if line.depressions.is_some() {
+            line.depressions = Some(
+                line.geometry
+                    .0
+                    .iter()
+                    .map(|l| self.is_depression(&l.0))
+                    .collect(),
+            );
+        }
+        line
+    }
+
+    /// Applies `f` to each of the given polygons, the parts they may be split
+    /// (or clipped) into keeping the nesting depth of their polygon.
+    fn split_nested(
+        &self,
+        polygons: Vec<Polygon<Float>>,
+        depths: Vec<usize>,
+        f: impl Fn(MultiPolygon<Float>) -> MultiPolygon<Float>,
+    ) -> (MultiPolygon<Float>, Vec<usize>) {
+        if !self.split_antimeridian && self.mask.is_none() {
+            return (f(MultiPolygon(polygons)), depths);
+        }
+        let (polygons, depths) = polygons
+            .into_iter()
+            .zip(depths)
+            .flat_map(|(polygon, depth)| {
+                f(MultiPolygon(vec![polygon]))
+                    .0
+                    .into_iter()
+                    .map(move |p| (p, depth))
+            })
+            .unzip();
+        (MultiPolygon(polygons), depths)
+    }
+
+    /// Transforms the given polygons, computed in grid space, to world space
+    /// (clipping them with the mask, if any).
+    fn world_polygons(&self, mut geometry: MultiPolygon<Float>) -> MultiPolygon<Float> {
+        if self.has_transform() {
+            for_each_coord_mut(&mut geometry, |pt| self.transform_point(pt));
+        }
+        if let Some(densify) = &self.densify {
+            geometry.0.iter_mut().for_each(|polygon| {
+                polygon.exterior_mut(|ring| densify.apply(&mut ring.0));
+                polygon.interiors_mut(|rings| {
+                    rings.iter_mut().for_each(|ring| densify.apply(&mut ring.0))
+                });
+            });
+        }
+        self.split_polygons(self.mask_polygons(geometry))
+    }
+
+    /// Computes isolines according the given input `values` and the given `thresholds`
+    /// (see [`ContourBuilder::lines`]), returning both their geometry in grid space
+    /// (i.e. in the coordinates of the cells, as computed with the default origin and step)
+    /// and their geometry in world space from a single computation of the rings.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used (of any [`GridValue`] type).
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn lines_grid_and_world<V: GridValue>(
+        &self,
+        values: &[V],
+        thresholds: &[V],
+    ) -> Result<(Vec<Line>, Vec<Line>)> {
+        let grid = self.grid_space().lines(values, thresholds)?;
+        let world = grid.iter().cloned().map(|l| self.world_line(l)).collect();
+        Ok((grid, world))
+    }
+
+    /// Computes contours according the given input `values` and the given `thresholds`
+    /// (see [`ContourBuilder::contours`]), returning both their geometry in grid space
+    /// (i.e. in the coordinates of the cells, as computed with the default origin and step)
+    /// and their geometry in world space from a single computation of the rings.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used (of any [`GridValue`] type).
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn contours_grid_and_world<V: GridValue>(
+        &self,
+        values: &[V],
+        thresholds: &[V],
+    ) -> Result<(Vec<Contour>, Vec<Contour>)> {
+        let grid = self.grid_space().contours(values, thresholds)?;
+        let world = grid
+            .iter()
+            .cloned()
+            .map(|mut contour| {
+                contour.geometry = self.world_polygons(contour.geometry);
+                contour.update_bbox();
+                contour
+            })
+            .collect();
+        Ok((grid, world))
+    }
+
+    /// Computes isobands according the given input `values` and the given `thresholds`
+    /// (see [`ContourBuilder::isobands`]), returning both their geometry in grid space
+    /// (i.e. in the coordinates of the cells, as computed with the default origin and step)
+    /// and their geometry in world space from a single computation of the rings.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used (of any [`GridValue`] type).
+    /// * `thresholds` - The slice of thresholds values to be used
+    ///   (have to be equal to or greater than 2).
+    pub fn isobands_grid_and_world<V: GridValue>(
+        &self,
+        values: &[V],
+        thresholds: &[V],
+    ) -> Result<(Vec<Band>, Vec<Band>)> {
+        let grid = self.grid_space().isobands(values, thresholds)?;
+        let world = grid.iter().cloned().map(|b| self.world_band(b)).collect();
+        Ok((grid, world))
+    }
+
+    /// Transforms the given isoband, computed in grid space, to world space.
+    fn world_band(&self, mut band: Band) -> Band {
+        let depths = std::mem::take(&mut band.depths);
+        (band.geometry, band.depths) =
+            self.split_nested(band.geometry.0, depths, |p| self.world_polygons(p));
+        band.update_bbox();
+        band
+    }
+
+    /// Computes the unsmoothed rings for the given `threshold` as exact integer
+    /// coordinates on the half-step lattice of the grid (i.e. grid coordinates
+    /// multiplied by 2), before any transform.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used (of any [`GridValue`] type).
+    /// * `threshold` - The threshold value to use.
+    pub fn lattice_rings<V: GridValue>(
+        &self,
+        values: &[V],
+        threshold: V,
+    ) -> Result<Vec<Vec<[i64; 2]>>> {
+        // The grid isn't padded, so that the lattice coordinates are those of the cells
+        let source;
+        let grid = match V::as_floats(values) {
+            Some(values) => self.grid(values)?,
+            None => {
+                if values.len() != self.dx * self.dy {
+                    return Err(new_error(ErrorKind::BadDimension));
+                }
+                source = Converted::new(values, self.dx, self.dy, self.order);
+                self.source_grid(&source)?
+            }
+        };
+        let threshold = self.ties.apply(threshold.to_float());
+        let mut isoring = self.isoring();
+        Ok(isoring
+            .compute_with(|i| grid.get(i) >= threshold)?
+            .iter()
+            .map(|ring| {
+                ring.iter()
+                    .map(|pt| {
+                        let (x, y) = lattice_coords(pt);
+                        [x, y]
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+}
+
+/// The unsmoothed vertices whose smoothing is reverted, by (tied) threshold
+/// (see [`ContourBuilder::jointly`]).
+type Reverted = FxHashMap<u64, FxHashSet<(i64, i64)>>;
+
+/// Returns the key of the given (tied) threshold in [`Reverted`].
+#[allow(clippy::unnecessary_cast)]
+fn threshold_key(threshold: Float) -> u64 {
+    (threshold as f64).to_bits()
+}
+
+/// Returns the doubled integer coordinates of a point lying on the half-step lattice.
+#[allow(clippy::unnecessary_cast)]
+fn lattice_coords(point: &Pt) -> (i64, i64) {
+    (
+        (point.x as f64 * 2.).round() as i64,
+        (point.y as f64 * 2.).round() as i64,
+    )
+}
+
+/// Returns true if the given closed rings have the same vertices in the same order,
+/// whichever vertex they start from.
+fn same_ring(a: &[Pt], b: &[Pt]) -> bool {
+    let n = a.len().saturating_sub(1);
+    n > 0 && a.len() == b.len() && (0..n).any(|k| (0..n).all(|i| a[(k + i) % n] == b[i]))
 }
@@ -0,0 +1,241 @@
+use crate::error::Result;
+use crate::index::SpatialIndex;
+use crate::snap::Snapper;
+use crate::{Band, Contour, ContourBuilder, Float, Line};
+use std::sync::OnceLock;
+
+/// The configuration of a [`ContourBuilder`], as stored
+/// in a [`ContourSet`] to record how its content was produced.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct BuilderConfig {
+    /// The number of columns in the grid
+    pub dx: usize,
+    /// The number of rows in the grid
+    pub dy: usize,
+    /// Whether the contours were smoothed
+    pub smooth: bool,
+    /// The horizontal coordinate for the origin of the grid.
+    pub x_origin: Float,
+    /// The vertical coordinate for the origin of the grid.
+    pub y_origin: Float,
+    /// The horizontal step for the grid
+    pub x_step: Float,
+    /// The vertical step for the grid
+    pub y_step: Float,
+    /// The hash of the other options of the builder changing the results
+    /// (e.g. its nodata value, mask or geotransform, see [`ContourBuilder::fingerprint`])
+    pub options: u64,
+}
+
+impl From<&ContourBuilder> for BuilderConfig {
+    fn from(builder: &ContourBuilder) -> Self {
+        BuilderConfig {
+            dx: builder.dx,
+            dy: builder.dy,
+            smooth: builder.smooth,
+            x_origin: builder.x_origin,
+            y_origin: builder.y_origin,
+            x_step: builder.x_step,
+            y_step: builder.y_step,
+            options: builder.options_fingerprint(),
+        }
+    }
+}
+
+impl From<&BuilderConfig> for ContourBuilder {
+    fn from(config: &BuilderConfig) -> Self {
+        ContourBuilder::new(config.dx, config.dy, config.smooth)
+            .x_origin(config.x_origin)
+            .y_origin(config.y_origin)
+            .x_step(config.x_step)
+            .y_step(config.y_step)
+    }
+}
+
+/// A set of results (lines, contours and / or bands) bundled with the
+/// configuration of the builder and the thresholds used to produce them.
+///
+/// With the `serde` feature enabled, a `ContourSet` can be (de)serialized, so
+/// that cached results can later be validated against the request (see
+/// [`ContourSet::matches`]) or re-styled.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContourSet {
+    pub(crate) config: BuilderConfig,
+    pub(crate) thresholds: Vec<Float>,
+    pub(crate) lines: Vec<Line>,
+    pub(crate) contours: Vec<Contour>,
+    pub(crate) bands: Vec<Band>,
+    /// The builder used to produce this set, if known (it isn't serialized)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) builder: Option<ContourBuilder>,
+    /// The spatial indexes of the contours and of the bands, built on the first query
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) index: OnceLock<(SpatialIndex, SpatialIndex)>,
+}
+
+/// The result of a point query on a [`ContourSet`] (see [`ContourSet::locate`]).
+#[derive(Debug, Clone)]
+pub struct Location<'a> {
+    /// The contours containing the point.
+    pub contours: Vec<&'a Contour>,
+    /// The band containing the point, if any.
+    pub band: Option<&'a Band>,
+}
+
+impl ContourSet {
+    /// Constructs a new empty set for the given `builder` and `thresholds`.
+    pub fn new(builder: &ContourBuilder, thresholds: &[Float]) -> Self {
+        Self::from_config(builder.into(), thresholds.to_vec(), Some(builder.clone()))
+    }
+
+    pub(crate) fn from_config(
+        config: BuilderConfig,
+        thresholds: Vec<Float>,
+        builder: Option<ContourBuilder>,
+    ) -> Self {
+        ContourSet {
+            config,
+            thresholds,
+            lines: Vec::new(),
+            contours: Vec::new(),
+            bands: Vec::new(),
+            builder,
+            index: OnceLock::new(),
+        }
+    }
+
+    /// Sets the lines of this set.
+    pub fn with_lines(mut self, lines: Vec<Line>) -> Self {
+        self.lines = lines;
+        self.index = OnceLock::new();
+        self
+    }
+
+    /// Sets the contours of this set.
+    pub fn with_contours(mut self, contours: Vec<Contour>) -> Self {
+        self.contours = contours;
+        self.index = OnceLock::new();
+        self
+    }
+
+    /// Sets the bands of this set.
+    pub fn with_bands(mut self, bands: Vec<Band>) -> Self {
+        self.bands = bands;
+        self.index = OnceLock::new();
+        self
+    }
+
+    /// Get the configuration of the builder used to produce this set.
+    pub fn config(&self) -> &BuilderConfig {
+        &self.config
+    }
+
+    /// Get the thresholds used to produce this set.
+    pub fn thresholds(&self) -> &[Float] {
+        &self.thresholds
+    }
+
+    /// Borrow the lines of this set.
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// Borrow the contours of this set.
+    pub fn contours(&self) -> &[Contour] {
+        &self.contours
+    }
+
+    /// Borrow the bands of this set.
+    pub fn bands(&self) -> &[Band] {
+        &self.bands
+    }
+
+    /// Returns true if this set was produced by a builder configured as
+    /// `builder` using the given `thresholds`.
+    ///
+    /// All the options changing the results are compared (see [`BuilderConfig::options`]),
+    /// the closures (e.g. [`ContourBuilder::transform`]) being identified by their address:
+    /// a deserialized set using them never matches.
+    pub fn matches(&self, builder: &ContourBuilder, thresholds: &[Float]) -> bool {
+        self.config == BuilderConfig::from(builder) && self.thresholds == thresholds
+    }
+
+    /// Returns the contours and the band containing the point (x, y)
+    /// (expressed in the coordinate system of the results).
+    ///
+    /// A spatial index of the polygons is built on the first call and reused
+    /// by the subsequent calls, so that repeated queries (e.g. on every mouse move)
+    /// stay cheap.
+    pub fn locate(&self, x: Float, y: Float) -> Location<'_> {
+        let (contours_index, bands_index) = self.index.get_or_init(|| {
+            (
+                SpatialIndex::new(self.contours.iter().map(|c| &c.geometry.0[..])),
+                SpatialIndex::new(self.bands.iter().map(|b| &b.geometry.0[..])),
+            )
+        });
+        let mut contours = contours_index
+            .query(x, y, |item, i| &self.contours[item].geometry.0[i])
+            .map(|(item, _)| item)
+            .collect::<Vec<_>>();
+        contours.sort_unstable();
+        contours.dedup();
+        Location {
+            contours: contours.into_iter().map(|i| &self.contours[i]).collect(),
+            band: bands_index
+                .query(x, y, |item, i| &self.bands[item].geometry.0[i])
+                .map(|(item, _)| &self.bands[item])
+                .next(),
+        }
+    }
+
+    /// Snaps the vertices of the lines, contours and bands of this set lying within
+    /// `tolerance` of each other onto identical coordinates, eliminating the
+    /// hairline gaps between features (e.g. between lines and band edges,
+    /// or between adjacent bands).
+    pub fn snap_vertices(&mut self, tolerance: Float) {
+        let mut snapper = Snapper::new(tolerance);
+        snapper.snap_bands(&mut self.bands);
+        snapper.snap_contours(&mut self.contours);
+        snapper.snap_lines(&mut self.lines);
+        self.index = OnceLock::new();
+    }
+
+    /// Get a new [`ContourBuilder`] configured as the one used to produce this set.
+    ///
+    /// Only the options recorded as fields of the [`BuilderConfig`] (the dimensions,
+    /// the smoothing, and the origin and steps of the grid) are restored if the set
+    /// was deserialized.
+    pub fn builder(&self) -> ContourBuilder {
+        match &self.builder {
+            Some(builder) => builder.clone(),
+            None => (&self.config).into(),
+        }
+    }
+}
+
+impl ContourBuilder {
+    /// Computes lines, contours and isobands according to the given input `values`
+    /// and the given `thresholds`, and bundles them in a [`ContourSet`]
+    /// with the configuration of this builder.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used
+    ///   (isobands are only computed if there is at least 2 thresholds).
+    pub fn contour_set(&self, values: &[Float], thresholds: &[Float]) -> Result<ContourSet> {
+        let bands = if thresholds.len() >= 2 {
+            self.isobands(values, thresholds)?
+        } else {
+            Vec::new()
+        };
+        let (lines, contours) = self.lines_and_contours(values, thresholds)?;
+        Ok(ContourSet::new(self, thresholds)
+            .with_lines(lines)
+            .with_contours(contours)
+            .with_bands(bands))
+    }
+}
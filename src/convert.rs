@@ -0,0 +1,166 @@
+use crate::error::{new_error, ErrorKind, Result};
+use crate::order::GridOrder;
+use crate::{Float, GridSource};
+use std::borrow::Cow;
+use std::fmt::Display;
+
+/// A type of the values of a grid that can be contoured directly (see
+/// [`ContourBuilder::contours`](crate::ContourBuilder::contours)), the values
+/// being converted to [`Float`] as they are read instead of being copied.
+///
+/// The conversion may round the values that can't be represented exactly
+/// (e.g. the 64-bit integers greater than 2^53, see [`try_grid_from`] to detect them).
+pub trait GridValue: Copy + Sync {
+    /// Converts the value to a [`Float`].
+    fn to_float(self) -> Float;
+
+    /// Returns the given values as a slice of [`Float`] if they already are
+    /// (the slices of other types being read through [`to_float`](Self::to_float)).
+    fn as_floats(values: &[Self]) -> Option<&[Float]> {
+        let _ = values;
+        None
+    }
+}
+
+/// A value that can be converted (if representable exactly) to a [`Float`].
+pub trait TryToFloat: Copy + Display {
+    /// Converts the value to a [`Float`], returning `None` if it can't be
+    /// represented exactly (`NaN` values are kept as `NaN`).
+    fn try_to_float(self) -> Option<Float>;
+}
+
+// The casts are unnecessary when the source type is the same as `Float`.
+#[allow(clippy::unnecessary_cast)]
+mod impls {
+    use super::{GridValue, TryToFloat};
+    use crate::Float;
+
+    macro_rules! impl_float {
+        ($($t:ty),*) => {
+            $(impl TryToFloat for $t {
+                fn try_to_float(self) -> Option<Float> {
+                    let v = self as Float;
+                    (v as $t == self || self.is_nan()).then_some(v)
+                }
+            })*
+        };
+    }
+
+    macro_rules! impl_int {
+        ($($t:ty),*) => {
+            $(impl TryToFloat for $t {
+                fn try_to_float(self) -> Option<Float> {
+                    let v = self as Float;
+                    // The float value may be out of the range of the integer type
+                    // (e.g. i64::MAX rounds up to 2^63), in which case the cast back saturates.
+                    (v.is_finite() && v as $t == self && (v as f64) < <$t>::MAX as f64 + 1.)
+                        .then_some(v)
+                }
+            })*
+        };
+    }
+
+    impl_float!(f32, f64);
+    impl_int!(i8, i16, i32, i64, u8, u16, u32, u64, isize, usize);
+
+    macro_rules! impl_grid_value {
+        ($($t:ty),*) => {
+            $(impl GridValue for $t {
+                fn to_float(self) -> Float {
+                    self as Float
+                }
+            })*
+        };
+    }
+
+    impl_grid_value!(i8, i16, i32, i64, u8, u16, u32, u64);
+
+    #[cfg(not(feature = "f32"))]
+    impl_grid_value!(f32);
+    #[cfg(feature = "f32")]
+    impl_grid_value!(f64);
+
+    impl GridValue for Float {
+        fn to_float(self) -> Float {
+            self
+        }
+
+        fn as_floats(values: &[Float]) -> Option<&[Float]> {
+            Some(values)
+        }
+    }
+}
+
+/// Returns the given values as [`Float`] values, converting them if needed.
+pub(crate) fn to_floats<V: GridValue>(values: &[V]) -> Cow<'_, [Float]> {
+    match V::as_floats(values) {
+        Some(values) => Cow::Borrowed(values),
+        None => Cow::Owned(values.iter().map(|v| v.to_float()).collect()),
+    }
+}
+
+/// A grid of values of another type than [`Float`], converted as they are read.
+pub(crate) struct Converted<'a, V> {
+    values: &'a [V],
+    dx: usize,
+    dy: usize,
+    order: GridOrder,
+}
+
+impl<'a, V: GridValue> Converted<'a, V> {
+    pub(crate) fn new(values: &'a [V], dx: usize, dy: usize, order: GridOrder) -> Self {
+        Converted {
+            values,
+            dx,
+            dy,
+            order,
+        }
+    }
+}
+
+impl<V: GridValue> GridSource for Converted<'_, V> {
+    fn width(&self) -> usize {
+        self.dx
+    }
+
+    fn height(&self) -> usize {
+        self.dy
+    }
+
+    fn get(&self, x: usize, y: usize) -> Float {
+        match self.order {
+            GridOrder::RowMajor => self.values[y * self.dx + x].to_float(),
+            GridOrder::ColumnMajor => self.values[x * self.dy + y].to_float(),
+        }
+    }
+}
+
+/// Converts the given `values` to a grid of [`Float`], failing with
+/// [`ErrorKind::BadCast`] (reporting the index and the value) on the first
+/// value that can't be represented exactly (instead of silently losing precision).
+///
+/// # Example
+///
+/// ```
+/// use contour::{try_grid_from, ErrorKind};
+///
+/// let grid = try_grid_from(&[0_i64, 1, 2, 3]).unwrap();
+/// assert_eq!(grid, vec![0., 1., 2., 3.]);
+///
+/// let err = try_grid_from(&[0_i64, 1, i64::MAX]).unwrap_err();
+/// assert!(matches!(err.kind(), ErrorKind::BadCast { index: 2, .. }));
+/// ```
+pub fn try_grid_from<T: TryToFloat>(values: &[T]) -> Result<Vec<Float>> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, v)| {
+            v.try_to_float().ok_or_else(|| {
+                new_error(ErrorKind::BadCast {
+                    index,
+                    value: v.to_string(),
+                })
+            })
+        })
+        .collect()
+}
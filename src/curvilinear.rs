@@ -0,0 +1,207 @@
+use crate::order::{to_row_major, GridOrder};
+use crate::{ContourBuilder, Float};
+use std::sync::Arc;
+
+/// The coordinates of each sample of a curvilinear grid
+/// (see [`ContourBuilder::curvilinear_coords`]).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Curvilinear {
+    pub(crate) x: Arc<[Float]>,
+    pub(crate) y: Arc<[Float]>,
+}
+
+impl Curvilinear {
+    pub(crate) fn new(x: &[Float], y: &[Float]) -> Self {
+        Curvilinear {
+            x: x.into(),
+            y: y.into(),
+        }
+    }
+
+    /// Whether both arrays hold the given number of samples.
+    pub(crate) fn has_len(&self, len: usize) -> bool {
+        self.x.len() == len && self.y.len() == len
+    }
+}
+
+impl ContourBuilder {
+    /// Returns the coordinates of the sample of column `i` and row `j`.
+    fn sample_coords(&self, grid: &Curvilinear, i: usize, j: usize) -> (Float, Float) {
+        let k = match self.order {
+            GridOrder::RowMajor => j * self.dx + i,
+            GridOrder::ColumnMajor => i * self.dy + j,
+        };
+        (grid.x[k], grid.y[k])
+    }
+
+    /// Returns the cell of the samples used to map the given (sample) position along
+    /// an axis of `n` samples, and the position relative to its first sample (the
+    /// positions outside of the grid being extrapolated from the cells of its edges).
+    fn cell(u: Float, n: usize) -> (usize, usize, Float) {
+        let i = (u.floor().max(0.) as usize).min(n.saturating_sub(2));
+        (i, (i + 1).min(n - 1), u - i as Float)
+    }
+
+    /// Maps the given grid coordinates (the sample `(i, j)` lying at `(i + 0.5, j + 0.5)`)
+    /// to the output coordinates, interpolating bilinearly between the samples.
+    pub(crate) fn curvilinear_map(
+        &self,
+        grid: &Curvilinear,
+        gx: Float,
+        gy: Float,
+    ) -> (Float, Float) {
+        self.curvilinear_jacobian(grid, gx - 0.5, gy - 0.5).0
+    }
+
+    /// Returns the coordinates mapped from the given sample position, along with
+    /// the partial derivatives of the mapping along both axes.
+    #[allow(clippy::type_complexity)]
+    fn curvilinear_jacobian(
+        &self,
+        grid: &Curvilinear,
+        u: Float,
+        v: Float,
+    ) -> ((Float, Float), (Float, Float), (Float, Float)) {
+        let (i0, i1, s) = Self::cell(u, self.dx);
+        let (j0, j1, t) = Self::cell(v, self.dy);
+        let p00 = self.sample_coords(grid, i0, j0);
+        let p10 = self.sample_coords(grid, i1, j0);
+        let p01 = self.sample_coords(grid, i0, j1);
+        let p11 = self.sample_coords(grid, i1, j1);
+        let lerp = |a: Float, b: Float, t: Float| a + (b - a) * t;
+        let at = |c: fn((Float, Float)) -> Float| {
+            let (c00, c10, c01, c11) = (c(p00), c(p10), c(p01), c(p11));
+            (
+                lerp(lerp(c00, c10, s), lerp(c01, c11, s), t),
+                lerp(c10 - c00, c11 - c01, t),
+                lerp(c01 - c00, c11 - c10, s),
+            )
+        };
+        let (x, dx_du, dx_dv) = at(|p| p.0);
+        let (y, dy_du, dy_dv) = at(|p| p.1);
+        ((x, y), (dx_du, dy_du), (dx_dv, dy_dv))
+    }
+
+    /// Maps the given output coordinates back to (fractional) grid coordinates,
+    /// using Newton's method on the bilinear interpolation of the samples.
+    pub(crate) fn curvilinear_unmap(
+        &self,
+        grid: &Curvilinear,
+        x: Float,
+        y: Float,
+    ) -> (Float, Float) {
+        let center = ((self.dx as Float - 1.) / 2., (self.dy as Float - 1.) / 2.);
+        let (u, v) = self.newton(grid, x, y, center).unwrap_or_else(|| {
+            // Start again from the nearest sample
+            let nearest = (0..self.dx * self.dy)
+                .map(|k| (k % self.dx, k / self.dx))
+                .min_by(|&(i, j), &(k, l)| {
+                    let distance = |(px, py): (Float, Float)| (px - x).hypot(py - y);
+                    distance(self.sample_coords(grid, i, j))
+                        .total_cmp(&distance(self.sample_coords(grid, k, l)))
+                })
+                .map_or((0., 0.), |(i, j)| (i as Float, j as Float));
+            self.newton(grid, x, y, nearest).unwrap_or(nearest)
+        });
+        (u + 0.5, v + 0.5)
+    }
+
+    /// Solves the mapping of the sample position `(u, v)` to `(x, y)`
+    /// from the given starting position, or returns `None` if it doesn't converge.
+    fn newton(
+        &self,
+        grid: &Curvilinear,
+        x: Float,
+        y: Float,
+        (mut u, mut v): (Float, Float),
+    ) -> Option<(Float, Float)> {
+        let tolerance = Float::EPSILON.sqrt();
+        for _ in 0..32 {
+            let ((px, py), (xu, yu), (xv, yv)) = self.curvilinear_jacobian(grid, u, v);
+            let det = xu * yv - xv * yu;
+            if det == 0. || !det.is_finite() {
+                return None;
+            }
+            let (rx, ry) = (x - px, y - py);
+            let (du, dv) = ((rx * yv - xv * ry) / det, (xu * ry - rx * yu) / det);
+            u += du;
+            v += dv;
+            if du.abs() < tolerance && dv.abs() < tolerance {
+                return Some((u, v));
+            }
+        }
+        None
+    }
+
+    /// Whether the curvilinear mapping reverses the winding order of the rings,
+    /// judged from the orientation of the corners of the grid.
+    pub(crate) fn curvilinear_reverses(&self, grid: &Curvilinear) -> bool {
+        let (last_x, last_y) = (self.dx.saturating_sub(1), self.dy.saturating_sub(1));
+        let (x0, y0) = self.sample_coords(grid, 0, 0);
+        let (x1, y1) = self.sample_coords(grid, last_x, 0);
+        let (x2, y2) = self.sample_coords(grid, 0, last_y);
+        (x1 - x0) * (y2 - y0) - (y1 - y0) * (x2 - x0) < 0.
+    }
+
+    /// Returns the longest diagonal of the cells of the curvilinear grid.
+    pub(crate) fn curvilinear_max_diagonal(&self, grid: &Curvilinear) -> Float {
+        let distance =
+            |(ax, ay): (Float, Float), (bx, by): (Float, Float)| (bx - ax).hypot(by - ay);
+        let mut max: Float = 0.;
+        for j in 0..self.dy.saturating_sub(1) {
+            for i in 0..self.dx.saturating_sub(1) {
+                let p = |i, j| self.sample_coords(grid, i, j);
+                max = max
+                    .max(distance(p(i, j), p(i + 1, j + 1)))
+                    .max(distance(p(i + 1, j), p(i, j + 1)));
+            }
+        }
+        max
+    }
+
+    /// Returns the coordinates of the samples stored row by row.
+    pub(crate) fn curvilinear_row_major(&self, grid: &Curvilinear) -> Curvilinear {
+        match self.order {
+            GridOrder::RowMajor => grid.clone(),
+            GridOrder::ColumnMajor => Curvilinear::new(
+                &to_row_major(&grid.x, self.dx, self.dy),
+                &to_row_major(&grid.y, self.dx, self.dy),
+            ),
+        }
+    }
+
+    /// Extends the grid by `n` samples on each side, extrapolated from the cells
+    /// of its edges, stored row by row (see [`ContourBuilder::padding`]).
+    pub(crate) fn curvilinear_pad(&self, grid: &Curvilinear, n: usize) -> Curvilinear {
+        let (dx, dy) = (self.dx + 2 * n, self.dy + 2 * n);
+        let (x, y): (Vec<_>, Vec<_>) = (0..dx * dy)
+            .map(|k| {
+                let (i, j) = ((k % dx) as Float, (k / dx) as Float);
+                self.curvilinear_map(grid, i - n as Float + 0.5, j - n as Float + 0.5)
+            })
+            .unzip();
+        Curvilinear::new(&x, &y)
+    }
+
+    /// Averages the coordinates of the blocks of `factor` * `factor` samples,
+    /// stored row by row.
+    pub(crate) fn curvilinear_downsample(&self, grid: &Curvilinear, factor: usize) -> Curvilinear {
+        let factor = factor.max(1);
+        let (cdx, cdy) = (self.dx.div_ceil(factor), self.dy.div_ceil(factor));
+        let mut sums = vec![(0., 0., 0usize); cdx * cdy];
+        for j in 0..self.dy {
+            for i in 0..self.dx {
+                let (x, y) = self.sample_coords(grid, i, j);
+                let sum = &mut sums[(j / factor) * cdx + i / factor];
+                sum.0 += x;
+                sum.1 += y;
+                sum.2 += 1;
+            }
+        }
+        let (x, y): (Vec<_>, Vec<_>) = sums
+            .into_iter()
+            .map(|(x, y, n)| (x / n as Float, y / n as Float))
+            .unzip();
+        Curvilinear::new(&x, &y)
+    }
+}
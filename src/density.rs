@@ -0,0 +1,219 @@
+use crate::{ContourBuilder, Float};
+
+impl ContourBuilder {
+    /// Bins the given `points` into a grid matching the geometry of this builder
+    /// (dimensions, origin and step), counting the number of points falling in each cell.
+    ///
+    /// The returned `Vec` has `dx * dy` values and can directly be passed to
+    /// [`contours`](ContourBuilder::contours), [`lines`](ContourBuilder::lines)
+    /// or [`isobands`](ContourBuilder::isobands).
+    /// Points falling outside of the grid are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - The (x, y) coordinates of the points, expressed in the same
+    ///   coordinate system as the output geometries.
+    pub fn bin_points(&self, points: &[(Float, Float)]) -> Vec<Float> {
+        let mut grid = vec![0.; self.dx * self.dy];
+        points.iter().for_each(|&(x, y)| {
+            if let Some(ix) = self.cell_index(x, y) {
+                grid[ix] += 1.;
+            }
+        });
+        grid
+    }
+
+    /// Bins the given weighted `points` into a grid matching the geometry of this builder
+    /// (dimensions, origin and step), summing the weights of the points falling in each cell.
+    ///
+    /// Points falling outside of the grid are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - The (x, y, weight) values of the points, expressed in the same
+    ///   coordinate system as the output geometries.
+    pub fn bin_weighted_points(&self, points: &[(Float, Float, Float)]) -> Vec<Float> {
+        let mut grid = vec![0.; self.dx * self.dy];
+        points.iter().for_each(|&(x, y, weight)| {
+            if let Some(ix) = self.cell_index(x, y) {
+                grid[ix] += weight;
+            }
+        });
+        grid
+    }
+
+    /// Returns the index, in the flat grid, of the cell containing the given point
+    /// (or `None` if the point is outside of the grid).
+    pub(crate) fn cell_index(&self, x: Float, y: Float) -> Option<usize> {
+        let (gx, gy) = self.to_grid(x, y);
+        if !(gx >= 0. && gy >= 0.) {
+            return None;
+        }
+        let (i, j) = (gx.floor() as usize, gy.floor() as usize);
+        if i < self.dx && j < self.dy {
+            Some(j * self.dx + i)
+        } else {
+            None
+        }
+    }
+}
+
+/// The bandwidth (standard deviation of the gaussian kernel, expressed in the
+/// coordinate system of the output geometries) to use for kernel density estimation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bandwidth {
+    /// A fixed bandwidth, used for both axes (a zero, negative or non-finite bandwidth
+    /// falling back to `1.0`, see [`Bandwidth::estimate`]).
+    Fixed(Float),
+    /// Silverman's rule of thumb (`0.9 * min(σ, IQR / 1.34) * n^(-1/5)`), computed on each axis.
+    Silverman,
+    /// Scott's rule (`σ * n^(-1/6)` for bivariate data), computed on each axis.
+    Scott,
+    /// Leave-one-out likelihood cross-validation over a range of multiples of Scott's bandwidth.
+    ///
+    /// Note that this is quadratic in the number of points.
+    CrossValidation,
+}
+
+impl Bandwidth {
+    /// Estimates the (x, y) bandwidth to use for the given `points`.
+    ///
+    /// Falls back to a bandwidth of `1.0` on an axis where the points
+    /// have no spread (e.g. when there is less than two points), or if the fixed
+    /// bandwidth isn't a positive finite number (which would give a grid of `NaN`).
+    pub fn estimate(&self, points: &[(Float, Float)]) -> (Float, Float) {
+        let xs = points.iter().map(|p| p.0).collect::<Vec<_>>();
+        let ys = points.iter().map(|p| p.1).collect::<Vec<_>>();
+        let n = points.len() as Float;
+        let (hx, hy) = match *self {
+            Bandwidth::Fixed(h) => (h, h),
+            Bandwidth::Silverman => {
+                let rule = |v: &[Float]| {
+                    let spread = match iqr(v) / 1.34 {
+                        r if r > 0. => std_dev(v).min(r),
+                        _ => std_dev(v),
+                    };
+                    0.9 * spread * n.powf(-0.2)
+                };
+                (rule(&xs), rule(&ys))
+            }
+            Bandwidth::Scott => (
+                std_dev(&xs) * n.powf(-1. / 6.),
+                std_dev(&ys) * n.powf(-1. / 6.),
+            ),
+            Bandwidth::CrossValidation => {
+                let (hx, hy) = Bandwidth::Scott.estimate(points);
+                let (hx, hy) = (fallback(hx), fallback(hy));
+                let factor = (1..=20)
+                    .map(|i| i as Float * 0.1)
+                    .map(|f| (f, loo_log_likelihood(points, hx * f, hy * f)))
+                    .fold((1., Float::NEG_INFINITY), |best, cur| {
+                        if cur.1 > best.1 {
+                            cur
+                        } else {
+                            best
+                        }
+                    })
+                    .0;
+                (hx * factor, hy * factor)
+            }
+        };
+        (fallback(hx), fallback(hy))
+    }
+}
+
+fn fallback(h: Float) -> Float {
+    if h.is_finite() && h > 0. {
+        h
+    } else {
+        1.
+    }
+}
+
+fn std_dev(values: &[Float]) -> Float {
+    let n = values.len() as Float;
+    if n < 2. {
+        return 0.;
+    }
+    let mean = values.iter().sum::<Float>() / n;
+    (values.iter().map(|v| (v - mean).powi(2)).sum::<Float>() / (n - 1.)).sqrt()
+}
+
+fn iqr(values: &[Float]) -> Float {
+    if values.len() < 2 {
+        return 0.;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let quantile = |q: Float| {
+        let pos = q * (sorted.len() - 1) as Float;
+        let (lo, hi) = (pos.floor() as usize, pos.ceil() as usize);
+        sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as Float)
+    };
+    quantile(0.75) - quantile(0.25)
+}
+
+fn loo_log_likelihood(points: &[(Float, Float)], hx: Float, hy: Float) -> Float {
+    let n = points.len();
+    if n < 2 {
+        return 0.;
+    }
+    let norm = 1. / (2. * std::f64::consts::PI as Float * hx * hy * (n - 1) as Float);
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, &(xi, yi))| {
+            let density = points
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, &(xj, yj))| gaussian((xi - xj) / hx, (yi - yj) / hy))
+                .sum::<Float>()
+                * norm;
+            density.max(Float::MIN_POSITIVE).ln()
+        })
+        .sum()
+}
+
+fn gaussian(u: Float, v: Float) -> Float {
+    (-0.5 * (u * u + v * v)).exp()
+}
+
+impl ContourBuilder {
+    /// Computes a gaussian kernel density estimate of the given `points`, evaluated
+    /// at the center of each cell of a grid matching the geometry of this builder.
+    ///
+    /// The returned `Vec` has `dx * dy` values and can directly be used to compute
+    /// density contours or isobands.
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - The (x, y) coordinates of the points, expressed in the same
+    ///   coordinate system as the output geometries.
+    /// * `bandwidth` - The bandwidth to use (or the method used to estimate it).
+    pub fn kde(&self, points: &[(Float, Float)], bandwidth: Bandwidth) -> Vec<Float> {
+        let mut grid = vec![0.; self.dx * self.dy];
+        if points.is_empty() {
+            return grid;
+        }
+        let (hx, hy) = bandwidth.estimate(points);
+        // Bandwidth expressed in grid units, to only visit the cells within 4 standard deviations
+        let (gx_h, gy_h) = ((hx / self.x_step).abs(), (hy / self.y_step).abs());
+        let norm = 1. / (2. * std::f64::consts::PI as Float * hx * hy * points.len() as Float);
+        points.iter().for_each(|&(x, y)| {
+            let (gx, gy) = self.to_grid(x, y);
+            let x_min = (gx - 4. * gx_h).floor().max(0.) as usize;
+            let x_max = ((gx + 4. * gx_h).ceil().max(0.) as usize).min(self.dx);
+            let y_min = (gy - 4. * gy_h).floor().max(0.) as usize;
+            let y_max = ((gy + 4. * gy_h).ceil().max(0.) as usize).min(self.dy);
+            for j in y_min..y_max {
+                for i in x_min..x_max {
+                    let u = (i as Float + 0.5 - gx) / gx_h;
+                    let v = (j as Float + 0.5 - gy) / gy_h;
+                    grid[j * self.dx + i] += gaussian(u, v) * norm;
+                }
+            }
+        });
+        grid
+    }
+}
@@ -0,0 +1,22 @@
+use crate::area::area;
+use crate::{ContourBuilder, Float, Ring};
+
+impl ContourBuilder {
+    /// Whether the given (final) ring is a closed depression contour: a ring that
+    /// doesn't touch the edges of the grid and encloses values lower than its threshold
+    /// (i.e. wound as a hole), as opposed to a peak (enclosing higher values).
+    ///
+    /// The ring is mapped back to the grid using the origin and step (or the coordinates)
+    /// of the grid, ignoring any custom transform.
+    pub(crate) fn is_depression(&self, ring: &Ring) -> bool {
+        let eps = 1e-6;
+        let (w, h) = (self.dx as Float, self.dy as Float);
+        let closed = ring.len() > 3 && ring.first() == ring.last();
+        closed
+            && ring.iter().all(|p| {
+                let (x, y) = self.to_grid(p.x, p.y);
+                x > eps && y > eps && x < w - eps && y < h - eps
+            })
+            && (area(ring) < 0.) != self.reverses_winding()
+    }
+}
@@ -0,0 +1,126 @@
+use crate::Float;
+use geo_types::LineString;
+use std::fmt;
+use std::time::Duration;
+
+/// A non-fatal issue met while building the features of a threshold,
+/// describing the geometry that was dropped and why.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// A ring with too few distinct vertices to form a polygon was dropped
+    /// when reconstructing an isoband.
+    DegenerateRing {
+        /// The number of vertices of the ring (after removing consecutive duplicates)
+        points: usize,
+    },
+    /// No polygon containing a hole was found, so the hole was dropped.
+    OrphanHole {
+        /// The dropped hole
+        ring: LineString<Float>,
+    },
+    /// A ring with the same fill on both of its sides (according to the
+    /// [`FillRule`](crate::FillRule)) was dropped when assembling the polygons.
+    RedundantRing {
+        /// The dropped ring
+        ring: LineString<Float>,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::DegenerateRing { points } => write!(
+                f,
+                "A ring with only {} vertices was dropped as it can't form a polygon",
+                points
+            ),
+            Warning::OrphanHole { ring } => write!(
+                f,
+                "A hole with {} vertices was dropped as no polygon contains it",
+                ring.0.len()
+            ),
+            Warning::RedundantRing { ring } => write!(
+                f,
+                "A ring with {} vertices was dropped as it doesn't separate filled and unfilled regions",
+                ring.0.len()
+            ),
+        }
+    }
+}
+
+/// Counters, timing and warnings of the computation of the features of a threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThresholdDiagnostics {
+    /// The threshold value
+    pub threshold: Float,
+    /// The position of the threshold in the requested thresholds
+    pub threshold_index: usize,
+    /// The number of rings produced by the marching squares
+    pub rings: usize,
+    /// The number of rings dropped (degenerate rings, holes without a parent polygon,
+    /// or rings separating regions with the same fill)
+    pub dropped_rings: usize,
+    /// The number of holes assigned to a polygon
+    pub holes: usize,
+    /// The time spent computing the features of this threshold
+    pub duration: Duration,
+    /// The warnings describing the dropped rings
+    pub warnings: Vec<Warning>,
+}
+
+impl ThresholdDiagnostics {
+    pub(crate) fn new(threshold: Float, threshold_index: usize) -> Self {
+        ThresholdDiagnostics {
+            threshold,
+            threshold_index,
+            rings: 0,
+            dropped_rings: 0,
+            holes: 0,
+            duration: Duration::ZERO,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Records that a ring was dropped, and why.
+    pub(crate) fn drop_ring(&mut self, warning: Warning) {
+        self.dropped_rings += 1;
+        self.warnings.push(warning);
+    }
+}
+
+/// Diagnostics of a computation of lines, contours or isobands, returned alongside
+/// the results (see e.g. [`ContourBuilder::contours_with_diagnostics`](crate::ContourBuilder::contours_with_diagnostics)),
+/// to monitor performance regressions and data pathologies.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Diagnostics {
+    /// The diagnostics of each threshold
+    pub thresholds: Vec<ThresholdDiagnostics>,
+}
+
+impl Diagnostics {
+    /// Returns the total number of rings produced by the marching squares.
+    pub fn rings(&self) -> usize {
+        self.thresholds.iter().map(|t| t.rings).sum()
+    }
+
+    /// Returns the total number of rings dropped.
+    pub fn dropped_rings(&self) -> usize {
+        self.thresholds.iter().map(|t| t.dropped_rings).sum()
+    }
+
+    /// Returns the total number of holes assigned to a polygon.
+    pub fn holes(&self) -> usize {
+        self.thresholds.iter().map(|t| t.holes).sum()
+    }
+
+    /// Returns the warnings of all the thresholds, describing the dropped rings.
+    pub fn warnings(&self) -> impl Iterator<Item = &Warning> {
+        self.thresholds.iter().flat_map(|t| t.warnings.iter())
+    }
+
+    /// Returns the total time spent computing the features of all the thresholds
+    /// (which is greater than the elapsed time when using several threads).
+    pub fn duration(&self) -> Duration {
+        self.thresholds.iter().map(|t| t.duration).sum()
+    }
+}
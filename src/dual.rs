@@ -0,0 +1,128 @@
+use crate::interpolation;
+use crate::weights::Grid;
+use crate::{Float, Pt, Ring};
+
+/// Where the vertices of the rings are placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VertexPlacement {
+    /// One vertex per crossed cell edge, at the middle of the edge
+    /// (or interpolated along it when smoothing is enabled).
+    #[default]
+    Edges,
+    /// One vertex per crossed cell (dual contouring, or "surface nets" in 2D),
+    /// placed inside the cell where the tangents of the contour at its two edge
+    /// crossings (given by the gradient of the bilinear interpolation of the cell)
+    /// meet, pulled towards the middle of the crossings when they are nearly parallel.
+    /// This gives smoother rings on smooth fields, and preserves their corners,
+    /// but the rings of close thresholds may cross each other (and thus the isobands
+    /// overlap the contours of their thresholds) where they share a cell.
+    Dual,
+}
+
+/// The weight pulling the dual vertices towards the mass point of their edge crossings,
+/// which regularizes the placement when the tangents are (nearly) parallel.
+const BIAS: Float = 0.05;
+
+/// Places one vertex per cell crossed by the given (unsmoothed) `ring` or line,
+/// expressed in grid coordinates. The vertices lying on the border of the grid
+/// (in the cells straddling its edges) are kept as is.
+pub(crate) fn dual_ring(ring: &Ring, grid: Grid<'_>, dx: usize, dy: usize, value: Float) -> Ring {
+    let (w, h) = (dx as Float, dy as Float);
+    let on_border = |p: &Pt| p.x <= 0. || p.x >= w || p.y <= 0. || p.y >= h;
+    let mut dual: Ring = Vec::with_capacity(ring.len());
+    let mut push = |p: Pt| {
+        if dual.last() != Some(&p) {
+            dual.push(p);
+        }
+    };
+    for segment in ring.windows(2) {
+        let (p, q) = (segment[0], segment[1]);
+        // The samples (x, y) lie at (x + 0.5, y + 0.5) in grid coordinates
+        let cx = ((p.x + q.x) / 2. - 0.5).floor();
+        let cy = ((p.y + q.y) / 2. - 0.5).floor();
+        if cx >= 0. && cy >= 0. && cx + 1. < w && cy + 1. < h {
+            let (cx, cy) = (cx as usize, cy as usize);
+            if has_nodata(grid, dx, cx, cy) {
+                // The gradient is unknown: the vertices stay on the edges
+                push(p);
+                push(q);
+            } else {
+                push(cell_vertex(grid, dx, cx, cy, p, q, value));
+            }
+        } else {
+            [p, q].into_iter().filter(on_border).for_each(&mut push);
+        }
+    }
+    // The open lines (cut at the cells without data) are kept open
+    if let (Some(&first), true) = (dual.first(), ring.first() == ring.last()) {
+        if dual.last() != Some(&first) {
+            dual.push(first);
+        }
+    }
+    dual
+}
+
+/// Returns true if one of the corners of the cell whose top left corner
+/// is the sample (`cx`, `cy`) has no data.
+fn has_nodata(grid: Grid<'_>, dx: usize, cx: usize, cy: usize) -> bool {
+    let i = cy * dx + cx;
+    [i, i + 1, i + dx, i + dx + 1]
+        .into_iter()
+        .any(|i| grid.get(i).is_nan())
+}
+
+/// Computes the dual vertex of the segment from `p` to `q` (the middles of two edges)
+/// in the cell whose top left corner is the sample (`cx`, `cy`).
+fn cell_vertex(grid: Grid<'_>, dx: usize, cx: usize, cy: usize, p: Pt, q: Pt, value: Float) -> Pt {
+    let i = cy * dx + cx;
+    let (v00, v10, v01, v11) = (
+        grid.get(i),
+        grid.get(i + 1),
+        grid.get(i + dx),
+        grid.get(i + dx + 1),
+    );
+    let (x0, y0) = (cx as Float + 0.5, cy as Float + 0.5);
+    // Places the middle of an edge at the crossing of the contour, in the coordinates of the cell
+    let crossing = |pt: Pt| {
+        let (u, w) = (pt.x - x0, pt.y - y0);
+        if w == 0. {
+            (interpolation::linear(v00, v10, value), 0.)
+        } else if w == 1. {
+            (interpolation::linear(v01, v11, value), 1.)
+        } else if u == 0. {
+            (0., interpolation::linear(v00, v01, value))
+        } else {
+            (1., interpolation::linear(v10, v11, value))
+        }
+    };
+    let (a, b) = (crossing(p), crossing(q));
+    let mass = ((a.0 + b.0) / 2., (a.1 + b.1) / 2.);
+    // Minimizes the squared distances to the tangents at both crossings
+    // (plus the weighted squared distance to the mass point)
+    let (mut a11, mut a12, mut a22) = (BIAS, 0., BIAS);
+    let (mut b1, mut b2) = (BIAS * mass.0, BIAS * mass.1);
+    for (u, w) in [a, b] {
+        let gu = (v10 - v00) * (1. - w) + (v11 - v01) * w;
+        let gw = (v01 - v00) * (1. - u) + (v11 - v10) * u;
+        let norm = gu.hypot(gw);
+        if norm > 0. && norm.is_finite() {
+            let (nu, nw) = (gu / norm, gw / norm);
+            let d = nu * u + nw * w;
+            (a11, a12, a22) = (a11 + nu * nu, a12 + nu * nw, a22 + nw * nw);
+            (b1, b2) = (b1 + nu * d, b2 + nw * d);
+        }
+    }
+    let det = a11 * a22 - a12 * a12;
+    let (u, w) = ((a22 * b1 - a12 * b2) / det, (a11 * b2 - a12 * b1) / det);
+    let (u, w) = if u.is_finite() && w.is_finite() {
+        (u, w)
+    } else if mass.0.is_finite() && mass.1.is_finite() {
+        mass
+    } else {
+        ((p.x + q.x) / 2. - x0, (p.y + q.y) / 2. - y0)
+    };
+    Pt {
+        x: x0 + u.clamp(0., 1.),
+        y: y0 + w.clamp(0., 1.),
+    }
+}
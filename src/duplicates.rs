@@ -0,0 +1,120 @@
+use crate::error::{new_error, ErrorKind, Result};
+use crate::Float;
+use rustc_hash::FxHashMap;
+
+/// How the thresholds requested several times (e.g. when merging the thresholds
+/// of several styles) are handled.
+///
+/// Whatever the policy, the rings of a repeated threshold are only computed once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// A feature is returned for each requested threshold, the features of a repeated
+    /// threshold being copies of each other (but for their threshold index).
+    #[default]
+    Keep,
+    /// A single feature is returned for each distinct threshold, its threshold index
+    /// being the position of the first occurrence of the threshold in the request.
+    Dedupe,
+    /// A [`DuplicateThreshold`](crate::ErrorKind::DuplicateThreshold) error is returned,
+    /// with the position of the first repeated threshold.
+    Error,
+}
+
+/// The distinct values of the requested thresholds.
+pub(crate) struct DistinctThresholds {
+    /// The distinct thresholds, in the order of their first occurrence
+    pub(crate) values: Vec<Float>,
+    /// The features to return, as the position of their threshold in the request
+    /// and the position of its value in `values`
+    pub(crate) requests: Vec<(usize, usize)>,
+}
+
+impl DuplicatePolicy {
+    /// Finds the distinct values of the given `thresholds` (`0.` and `-0.` being equal),
+    /// and the features to return according to the policy.
+    // The casts to f64 are unnecessary when not using the "f32" feature.
+    #[allow(clippy::unnecessary_cast)]
+    pub(crate) fn distinct(self, thresholds: &[Float]) -> Result<DistinctThresholds> {
+        let mut positions = FxHashMap::default();
+        let mut values = Vec::with_capacity(thresholds.len());
+        let mut requests = Vec::with_capacity(thresholds.len());
+        for (i, &threshold) in thresholds.iter().enumerate() {
+            let key = ((threshold + 0.) as f64).to_bits();
+            match positions.get(&key) {
+                Some(&d) => match self {
+                    DuplicatePolicy::Keep => requests.push((i, d)),
+                    DuplicatePolicy::Dedupe => {}
+                    DuplicatePolicy::Error => {
+                        return Err(new_error(ErrorKind::DuplicateThreshold {
+                            index: i,
+                            value: threshold,
+                        }))
+                    }
+                },
+                None => {
+                    positions.insert(key, values.len());
+                    requests.push((i, values.len()));
+                    values.push(threshold);
+                }
+            }
+        }
+        Ok(DistinctThresholds { values, requests })
+    }
+}
+
+impl DistinctThresholds {
+    /// Whether the features to return are those of the distinct thresholds, in order.
+    pub(crate) fn is_identity(&self) -> bool {
+        self.requests
+            .iter()
+            .enumerate()
+            .all(|(k, &(i, d))| k == i && k == d)
+    }
+
+    /// Maps the `items` computed for each distinct threshold to the features to return,
+    /// `set_index` setting the threshold index of each of them.
+    pub(crate) fn expand<T: Clone>(
+        &self,
+        items: Vec<T>,
+        mut set_index: impl FnMut(&mut T, usize),
+    ) -> Vec<T> {
+        if self.is_identity() {
+            return items;
+        }
+        self.requests
+            .iter()
+            .map(|&(i, d)| {
+                let mut item = items[d].clone();
+                set_index(&mut item, i);
+                item
+            })
+            .collect()
+    }
+
+    /// Calls `f` with each feature to return, in order, computing the item of each distinct
+    /// threshold with `compute` (and keeping it as long as it is requested again),
+    /// `set_index` setting the threshold index of each feature.
+    pub(crate) fn for_each<T: Clone>(
+        &self,
+        mut compute: impl FnMut(usize) -> Result<T>,
+        mut set_index: impl FnMut(&mut T, usize),
+        mut f: impl FnMut(T) -> Result<()>,
+    ) -> Result<()> {
+        let mut remaining = vec![0usize; self.values.len()];
+        self.requests.iter().for_each(|&(_, d)| remaining[d] += 1);
+        let mut kept = FxHashMap::default();
+        for &(i, d) in &self.requests {
+            let mut item = match kept.remove(&d) {
+                Some(item) => item,
+                None => compute(d)?,
+            };
+            remaining[d] -= 1;
+            if remaining[d] > 0 {
+                kept.insert(d, item.clone());
+            }
+            set_index(&mut item, i);
+            f(item)?;
+        }
+        Ok(())
+    }
+}
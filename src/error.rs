@@ -31,9 +31,50 @@ impl Error {
 #[non_exhaustive]
 pub enum ErrorKind {
     BadDimension,
+    /// A value of the input grid can't be represented exactly as a [`Float`](crate::Float).
+    BadCast {
+        /// The index of the value in the grid
+        index: usize,
+        /// The value that can't be represented
+        value: String,
+    },
+    /// The grid contains an infinite value (see [`InfinityPolicy`](crate::InfinityPolicy)).
+    InfiniteValue {
+        /// The column of the value in the grid
+        x: usize,
+        /// The row of the value in the grid
+        y: usize,
+    },
+    /// A tile of a [`Mosaic`](crate::Mosaic) isn't aligned with the grid of the first tile
+    /// (i.e. it has a different step, or its origin is offset by a fraction of a cell).
+    MisalignedTile {
+        /// The position of the tile in the mosaic
+        index: usize,
+    },
+    /// A threshold is requested several times (see [`DuplicatePolicy`](crate::DuplicatePolicy)).
+    DuplicateThreshold {
+        /// The position of the repeated threshold in the requested thresholds
+        index: usize,
+        /// The repeated threshold
+        value: crate::Float,
+    },
     Unexpected,
+    /// An error occurred while writing the output (see [`CsvWriter`](crate::CsvWriter)).
+    IoError(std::io::Error),
     #[cfg(feature = "geojson")]
     JsonError(serde_json::error::Error),
+    #[cfg(feature = "tiny-skia")]
+    RenderError(String),
+    #[cfg(feature = "gpkg")]
+    SqliteError(rusqlite::Error),
+    #[cfg(feature = "geotiff")]
+    GeoTiffError(String),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Error {
+        new_error(ErrorKind::IoError(err))
+    }
 }
 
 #[cfg(feature = "geojson")]
@@ -43,13 +84,41 @@ impl From<serde_json::error::Error> for Error {
     }
 }
 
+#[cfg(feature = "gpkg")]
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Error {
+        new_error(ErrorKind::SqliteError(err))
+    }
+}
+
+#[cfg(feature = "geotiff")]
+impl From<tiff::TiffError> for Error {
+    fn from(err: tiff::TiffError) -> Error {
+        match err {
+            tiff::TiffError::IoError(err) => new_error(ErrorKind::IoError(err)),
+            err => new_error(ErrorKind::GeoTiffError(err.to_string())),
+        }
+    }
+}
+
 impl StdError for Error {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match *self.0 {
             ErrorKind::BadDimension => None,
+            ErrorKind::BadCast { .. } => None,
+            ErrorKind::InfiniteValue { .. } => None,
+            ErrorKind::MisalignedTile { .. } => None,
+            ErrorKind::DuplicateThreshold { .. } => None,
             ErrorKind::Unexpected => None,
+            ErrorKind::IoError(ref err) => Some(err),
             #[cfg(feature = "geojson")]
             ErrorKind::JsonError(ref err) => Some(err),
+            #[cfg(feature = "tiny-skia")]
+            ErrorKind::RenderError(_) => None,
+            #[cfg(feature = "gpkg")]
+            ErrorKind::SqliteError(ref err) => Some(err),
+            #[cfg(feature = "geotiff")]
+            ErrorKind::GeoTiffError(_) => None,
         }
     }
 }
@@ -61,9 +130,36 @@ impl fmt::Display for Error {
                 f,
                 "The length of provided values doesn't match the (dx, dy) dimensions of the grid"
             ),
+            ErrorKind::BadCast { index, ref value } => write!(
+                f,
+                "The value {} at index {} can't be represented exactly as a {}",
+                value,
+                index,
+                std::any::type_name::<crate::Float>()
+            ),
+            ErrorKind::InfiniteValue { x, y } => {
+                write!(f, "The grid contains an infinite value at ({}, {})", x, y)
+            }
+            ErrorKind::MisalignedTile { index } => write!(
+                f,
+                "The tile {} of the mosaic isn't aligned with the grid of its first tile",
+                index
+            ),
+            ErrorKind::DuplicateThreshold { index, value } => write!(
+                f,
+                "The threshold {} at index {} is requested several times",
+                value, index
+            ),
             ErrorKind::Unexpected => write!(f, "Unexpected error while computing contours"),
+            ErrorKind::IoError(ref err) => err.fmt(f),
             #[cfg(feature = "geojson")]
             ErrorKind::JsonError(ref err) => err.fmt(f),
+            #[cfg(feature = "tiny-skia")]
+            ErrorKind::RenderError(ref msg) => write!(f, "Error while rendering image: {}", msg),
+            #[cfg(feature = "gpkg")]
+            ErrorKind::SqliteError(ref err) => err.fmt(f),
+            #[cfg(feature = "geotiff")]
+            ErrorKind::GeoTiffError(ref msg) => write!(f, "Error while reading GeoTIFF: {}", msg),
         }
     }
 }
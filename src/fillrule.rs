@@ -0,0 +1,115 @@
+use crate::area::{area, contains};
+use crate::bbox::extend;
+use crate::diagnostics::{ThresholdDiagnostics, Warning};
+use crate::{Float, Ring};
+use geo_types::{LineString, Polygon, Rect};
+
+/// How the rings of a threshold are assembled into contour polygons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillRule {
+    /// The exterior rings are identified by their winding order, and each hole
+    /// is assigned to the first exterior ring containing it.
+    #[default]
+    Containment,
+    /// A point is filled if it is enclosed by an odd number of rings,
+    /// whatever their winding order.
+    EvenOdd,
+    /// A point is filled if the sum of the windings of the rings enclosing it
+    /// is not zero, the rings wound as exterior rings counting for 1 and the rings
+    /// wound as holes counting for -1.
+    NonZero,
+}
+
+impl FillRule {
+    /// Whether the region enclosed by the given rings (as an enclosing count
+    /// for [`FillRule::EvenOdd`], and as a sum of windings otherwise) is filled.
+    fn is_filled(self, winding: i32) -> bool {
+        match self {
+            FillRule::EvenOdd => winding % 2 != 0,
+            _ => winding != 0,
+        }
+    }
+}
+
+/// Assembles the given `rings` into polygons according to the global fill `rule`
+/// (which must not be [`FillRule::Containment`]): each ring separating an unfilled
+/// region (outside) from a filled one (inside) is an exterior ring, each ring separating
+/// a filled region from an unfilled one is a hole of the smallest exterior ring
+/// containing it, and the rings with the same fill on both sides are dropped.
+pub(crate) fn assemble(
+    rings: Vec<Ring>,
+    rule: FillRule,
+    diagnostics: &mut ThresholdDiagnostics,
+) -> Vec<Polygon<Float>> {
+    let areas = rings.iter().map(|ring| area(ring)).collect::<Vec<_>>();
+    let bboxes = rings
+        .iter()
+        .map(|ring| ring.iter().fold(None, extend))
+        .collect::<Vec<Option<Rect<Float>>>>();
+    let winding = |i: usize| match rule {
+        FillRule::EvenOdd => 1,
+        _ => areas[i].signum() as i32,
+    };
+    // The rings enclosing each ring (the larger rings containing it)
+    let enclosing = (0..rings.len())
+        .map(|i| {
+            (0..rings.len())
+                .filter(|&j| {
+                    j != i
+                        && areas[j].abs() > areas[i].abs()
+                        && matches!((bboxes[i], bboxes[j]), (Some(a), Some(b)) if covers(&b, &a))
+                        && contains(&rings[j], &rings[i]) == 1
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut shells = vec![None; rings.len()];
+    let mut polygons = Vec::new();
+    let mut holes = Vec::new();
+    for (i, ring) in rings.into_iter().enumerate() {
+        let outside = enclosing[i].iter().map(|&j| winding(j)).sum::<i32>();
+        let mut ring = LineString::new(ring);
+        match (
+            rule.is_filled(outside),
+            rule.is_filled(outside + winding(i)),
+        ) {
+            (false, true) => {
+                if areas[i] < 0. {
+                    ring.0.reverse();
+                }
+                shells[i] = Some(polygons.len());
+                polygons.push(Polygon::new(ring, vec![]));
+            }
+            (true, false) => {
+                if areas[i] > 0. {
+                    ring.0.reverse();
+                }
+                holes.push((i, ring));
+            }
+            _ => diagnostics.drop_ring(Warning::RedundantRing { ring }),
+        }
+    }
+    for (i, hole) in holes {
+        let shell = enclosing[i]
+            .iter()
+            .filter_map(|&j| shells[j].map(|polygon| (areas[j].abs(), polygon)))
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+        match shell {
+            Some((_, polygon)) => {
+                polygons[polygon].interiors_push(hole);
+                diagnostics.holes += 1;
+            }
+            None => diagnostics.drop_ring(Warning::OrphanHole { ring: hole }),
+        }
+    }
+    polygons
+}
+
+/// Whether the bounding box `a` covers the bounding box `b`.
+fn covers(a: &Rect<Float>, b: &Rect<Float>) -> bool {
+    a.min().x <= b.min().x
+        && a.min().y <= b.min().y
+        && a.max().x >= b.max().x
+        && a.max().y >= b.max().y
+}
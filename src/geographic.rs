@@ -0,0 +1,204 @@
+use crate::merge::clip_ring;
+use crate::{Float, Pt, Ring};
+use geo_types::{LineString, MultiPolygon, Polygon};
+
+/// Densification of the output segments, inserting intermediate vertices
+/// so that long segments between grid columns don't distort when reprojected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Densify {
+    /// Splits the segments (in the output coordinate system) so that no part
+    /// is longer than the given length (e.g. in degrees).
+    Linear(Float),
+    /// Splits the segments along great circles (the output coordinates being
+    /// longitudes and latitudes in degrees), so that no part spans more than
+    /// the given angular distance (in degrees).
+    GreatCircle(Float),
+}
+
+impl Densify {
+    /// Densifies the given ring (or line).
+    pub(crate) fn apply(&self, ring: &mut Ring) {
+        let max = match self {
+            Densify::Linear(max) | Densify::GreatCircle(max) => *max,
+        };
+        if ring.len() < 2 || max <= 0. || !max.is_finite() {
+            return;
+        }
+        let mut result = Vec::with_capacity(ring.len());
+        result.push(ring[0]);
+        for w in ring.windows(2) {
+            match self {
+                Densify::Linear(_) => linear(&w[0], &w[1], max, &mut result),
+                Densify::GreatCircle(_) => great_circle(&w[0], &w[1], max, &mut result),
+            }
+            result.push(w[1]);
+        }
+        *ring = result;
+    }
+}
+
+/// Pushes the intermediate points between `a` and `b` (excluded),
+/// evenly spaced along the straight segment.
+fn linear(a: &Pt, b: &Pt, max: Float, result: &mut Ring) {
+    let length = ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt();
+    let n = (length / max).ceil() as usize;
+    (1..n).for_each(|i| {
+        let t = i as Float / n as Float;
+        result.push(Pt {
+            x: a.x + t * (b.x - a.x),
+            y: a.y + t * (b.y - a.y),
+        });
+    });
+}
+
+/// Converts longitude and latitude (in degrees) to a unit vector.
+// The casts to f64 are unnecessary when not using the "f32" feature.
+#[allow(clippy::unnecessary_cast)]
+fn to_vector(p: &Pt) -> [f64; 3] {
+    let (lon, lat) = ((p.x as f64).to_radians(), (p.y as f64).to_radians());
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
+
+/// Pushes the intermediate points between `a` and `b` (excluded),
+/// evenly spaced along the great circle joining them.
+// The casts to f64 are unnecessary when not using the "f32" feature.
+#[allow(clippy::unnecessary_cast)]
+fn great_circle(a: &Pt, b: &Pt, max: Float, result: &mut Ring) {
+    let (u, v) = (to_vector(a), to_vector(b));
+    let dot = u[0] * v[0] + u[1] * v[1] + u[2] * v[2];
+    let cross = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    let sin = (cross[0].powi(2) + cross[1].powi(2) + cross[2].powi(2)).sqrt();
+    let angle = sin.atan2(dot);
+    // The great circle is undefined between identical or antipodal points
+    if sin < 1e-12 {
+        return;
+    }
+    let n = (angle.to_degrees() / max as f64).ceil() as usize;
+    let mut lon_prev = a.x as f64;
+    (1..n).for_each(|i| {
+        let t = i as f64 / n as f64;
+        let (s0, s1) = (((1. - t) * angle).sin() / sin, (t * angle).sin() / sin);
+        let p = [0, 1, 2].map(|k| s0 * u[k] + s1 * v[k]);
+        let mut lon = p[1].atan2(p[0]).to_degrees();
+        // Keep the longitudes continuous along the segment
+        lon += ((lon_prev - lon) / 360.).round() * 360.;
+        lon_prev = lon;
+        let lat = p[2]
+            .atan2((p[0].powi(2) + p[1].powi(2)).sqrt())
+            .to_degrees();
+        result.push(Pt {
+            x: lon as Float,
+            y: lat as Float,
+        });
+    });
+}
+
+/// Returns the index of the 360° wide copy of the world containing the given longitude
+/// (0 for [-180, 180[).
+fn world(lon: Float) -> i64 {
+    ((lon + 180.) / 360.).floor() as i64
+}
+
+/// Shifts the given point by the given number of 360° wide copies of the world.
+fn shift(p: &Pt, k: i64) -> Pt {
+    Pt {
+        x: p.x - 360. * k as Float,
+        y: p.y,
+    }
+}
+
+/// Splits the given line (whose longitudes are continuous) where it crosses
+/// the antimeridian, bringing each part back in the [-180, 180] range.
+pub(crate) fn split_line(line: &[Pt]) -> Vec<Ring> {
+    let mut parts = Vec::new();
+    let mut current: Ring = Vec::new();
+    let mut k_prev: Option<i64> = None;
+    for p in line {
+        let k = world(p.x);
+        if let Some(k_prev) = k_prev {
+            if k != k_prev {
+                let a = current.last().map(|q| shift(q, -k_prev)).unwrap_or(*p);
+                // Add the crossing point of each (crossed) antimeridian
+                let step = (k - k_prev).signum();
+                let mut j = k_prev;
+                while j != k {
+                    let lon = 180. + 360. * (j.min(j + step)) as Float;
+                    let t = (lon - a.x) / (p.x - a.x);
+                    let crossing = Pt {
+                        x: lon,
+                        y: a.y + t * (p.y - a.y),
+                    };
+                    current.push(shift(&crossing, j));
+                    parts.push(std::mem::take(&mut current));
+                    current.push(shift(&crossing, j + step));
+                    j += step;
+                }
+            }
+        }
+        current.push(shift(p, k));
+        k_prev = Some(k);
+    }
+    parts.push(current);
+    // The last part of a closed ring continues with its first part
+    if parts.len() > 2 && line.first() == line.last() {
+        let first = parts.remove(0);
+        if let Some(last) = parts.last_mut() {
+            last.extend(first.into_iter().skip(1));
+        }
+    }
+    parts.retain(|p| p.len() > 1);
+    parts
+}
+
+/// Splits the given polygons where they cross the antimeridian,
+/// bringing each part back in the [-180, 180] range.
+pub(crate) fn split_polygons(geometry: MultiPolygon<Float>) -> MultiPolygon<Float> {
+    let needs_split = geometry
+        .0
+        .iter()
+        .flat_map(|p| p.exterior().0.iter())
+        .any(|p| world(p.x) != 0 && p.x != 180.);
+    if !needs_split {
+        return geometry;
+    }
+    MultiPolygon(
+        geometry
+            .0
+            .into_iter()
+            .flat_map(|polygon| {
+                let (min, max) = polygon
+                    .exterior()
+                    .0
+                    .iter()
+                    .fold((i64::MAX, i64::MIN), |(min, max), p| {
+                        (min.min(world(p.x)), max.max(world(p.x)))
+                    });
+                (min..=max)
+                    .filter_map(|k| {
+                        let west = -180. + 360. * k as Float;
+                        let clip = [west, Float::NEG_INFINITY, west + 360., Float::INFINITY];
+                        let exterior = clip_ring(&polygon.exterior().0, &clip);
+                        if exterior.len() < 4 {
+                            return None;
+                        }
+                        let interiors = polygon
+                            .interiors()
+                            .iter()
+                            .map(|r| clip_ring(&r.0, &clip))
+                            .filter(|r| r.len() > 3)
+                            .map(|r| LineString(r.iter().map(|p| shift(p, k)).collect()))
+                            .collect();
+                        Some(Polygon::new(
+                            LineString(exterior.iter().map(|p| shift(p, k)).collect()),
+                            interiors,
+                        ))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect(),
+    )
+}
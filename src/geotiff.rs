@@ -0,0 +1,129 @@
+use crate::convert::try_grid_from;
+use crate::error::{new_error, ErrorKind, Result};
+use crate::{ContourBuilder, Float};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use tiff::decoder::{Decoder, DecodingResult};
+use tiff::tags::Tag;
+use tiff::ColorType;
+
+// The key of the raster type in the GeoKey directory, and its value for grids whose
+// samples are points rather than areas (their tie point is then the center of the pixel).
+const RASTER_TYPE_KEY: u16 = 1025;
+const RASTER_PIXEL_IS_POINT: u16 = 2;
+
+fn geotiff_error(msg: impl Into<String>) -> crate::error::Error {
+    new_error(ErrorKind::GeoTiffError(msg.into()))
+}
+
+impl ContourBuilder {
+    /// Reads a single-band GeoTIFF file, returning a contours generator whose dimensions,
+    /// origin and steps are those of the raster, along with its values (in row-major order,
+    /// ready to be passed to [`contours`](Self::contours) or [`isobands`](Self::isobands)).
+    ///
+    /// The geotransform is read from the `ModelPixelScale` and `ModelTiepoint` tags
+    /// (or from a `ModelTransformation` tag, that can describe rotated rasters, see
+    /// [`geotransform`](Self::geotransform)), and the nodata value
+    /// from the `GDAL_NODATA` tag if present (see [`nodata`](Self::nodata)).
+    /// The origin is the corner of the first pixel, so that the generated
+    /// coordinates match the extent of the raster.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the GeoTIFF file.
+    /// * `smooth` - Whether or not the generated rings will be smoothed using linear interpolation.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use contour::ContourBuilder;
+    ///
+    /// let (builder, values) = ContourBuilder::from_geotiff("dem.tif", true).unwrap();
+    /// let contours = builder.contours(&values, &[100., 200., 300.]).unwrap();
+    /// ```
+    pub fn from_geotiff(path: impl AsRef<Path>, smooth: bool) -> Result<(Self, Vec<Float>)> {
+        let file = File::open(path)?;
+        let mut decoder = Decoder::new(BufReader::new(file))?;
+        let (width, height) = decoder.dimensions()?;
+        if !matches!(decoder.colortype()?, ColorType::Gray(_)) {
+            return Err(geotiff_error("only single-band rasters are supported"));
+        }
+
+        let [mut x_origin, x_step, x_skew, mut y_origin, y_skew, y_step] =
+            if let Some(matrix) = decoder.find_tag(Tag::ModelTransformationTag)? {
+                let m = matrix.into_f64_vec()?;
+                if m.len() < 8 {
+                    return Err(geotiff_error("invalid ModelTransformation tag"));
+                }
+                [m[3], m[0], m[1], m[7], m[4], m[5]]
+            } else {
+                let scale = decoder
+                    .find_tag(Tag::ModelPixelScaleTag)?
+                    .ok_or_else(|| geotiff_error("missing ModelPixelScale tag"))?
+                    .into_f64_vec()?;
+                let tiepoint = decoder
+                    .find_tag(Tag::ModelTiepointTag)?
+                    .ok_or_else(|| geotiff_error("missing ModelTiepoint tag"))?
+                    .into_f64_vec()?;
+                if scale.len() < 2 || tiepoint.len() < 6 {
+                    return Err(geotiff_error("invalid georeferencing tags"));
+                }
+                // The tie point maps the raster position (i, j) to the model position (x, y),
+                // the rows going southward
+                let (i, j, x, y) = (tiepoint[0], tiepoint[1], tiepoint[3], tiepoint[4]);
+                [
+                    x - i * scale[0],
+                    scale[0],
+                    0.,
+                    y + j * scale[1],
+                    0.,
+                    -scale[1],
+                ]
+            };
+
+        if let Some(keys) = decoder.find_tag(Tag::GeoKeyDirectoryTag)? {
+            let keys = keys.into_u16_vec()?;
+            // A header of 4 values, followed by entries of (key, location, count, value)
+            let point = keys
+                .get(4..)
+                .unwrap_or_default()
+                .chunks_exact(4)
+                .any(|k| k[0] == RASTER_TYPE_KEY && k[1] == 0 && k[3] == RASTER_PIXEL_IS_POINT);
+            if point {
+                x_origin -= (x_step + x_skew) / 2.;
+                y_origin -= (y_step + y_skew) / 2.;
+            }
+        }
+
+        let nodata =
+            match decoder.find_tag(Tag::GdalNodata)? {
+                Some(value) => {
+                    let text = value.into_string()?;
+                    let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+                    Some(text.parse::<Float>().map_err(|_| {
+                        geotiff_error(format!("invalid GDAL_NODATA value: {}", text))
+                    })?)
+                }
+                None => None,
+            };
+
+        let values = match decoder.read_image()? {
+            DecodingResult::U8(v) => try_grid_from(&v)?,
+            DecodingResult::U16(v) => try_grid_from(&v)?,
+            DecodingResult::U32(v) => try_grid_from(&v)?,
+            DecodingResult::U64(v) => try_grid_from(&v)?,
+            DecodingResult::I8(v) => try_grid_from(&v)?,
+            DecodingResult::I16(v) => try_grid_from(&v)?,
+            DecodingResult::I32(v) => try_grid_from(&v)?,
+            DecodingResult::I64(v) => try_grid_from(&v)?,
+            DecodingResult::F32(v) => try_grid_from(&v)?,
+            DecodingResult::F64(v) => try_grid_from(&v)?,
+        };
+
+        let builder = ContourBuilder::new(width as usize, height as usize, smooth)
+            .geotransform([x_origin, x_step, x_skew, y_origin, y_skew, y_step].map(|v| v as Float))
+            .nodata(nodata);
+        Ok((builder, values))
+    }
+}
@@ -0,0 +1,273 @@
+use crate::error::Result;
+use crate::{Contour, Float};
+use geo_types::{MultiPolygon, Rect};
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+// The tables required in every GeoPackage, and the extensions table.
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS gpkg_spatial_ref_sys (
+    srs_name TEXT NOT NULL,
+    srs_id INTEGER PRIMARY KEY,
+    organization TEXT NOT NULL,
+    organization_coordsys_id INTEGER NOT NULL,
+    definition TEXT NOT NULL,
+    description TEXT
+);
+CREATE TABLE IF NOT EXISTS gpkg_contents (
+    table_name TEXT NOT NULL PRIMARY KEY,
+    data_type TEXT NOT NULL,
+    identifier TEXT UNIQUE,
+    description TEXT DEFAULT '',
+    last_change DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+    min_x DOUBLE,
+    min_y DOUBLE,
+    max_x DOUBLE,
+    max_y DOUBLE,
+    srs_id INTEGER,
+    CONSTRAINT fk_gc_r_srs_id FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys(srs_id)
+);
+CREATE TABLE IF NOT EXISTS gpkg_geometry_columns (
+    table_name TEXT NOT NULL,
+    column_name TEXT NOT NULL,
+    geometry_type_name TEXT NOT NULL,
+    srs_id INTEGER NOT NULL,
+    z TINYINT NOT NULL,
+    m TINYINT NOT NULL,
+    CONSTRAINT pk_geom_cols PRIMARY KEY (table_name, column_name),
+    CONSTRAINT uk_gc_table_name UNIQUE (table_name),
+    CONSTRAINT fk_gc_tn FOREIGN KEY (table_name) REFERENCES gpkg_contents(table_name),
+    CONSTRAINT fk_gc_srs FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys (srs_id)
+);
+CREATE TABLE IF NOT EXISTS gpkg_extensions (
+    table_name TEXT,
+    column_name TEXT,
+    extension_name TEXT NOT NULL,
+    definition TEXT NOT NULL,
+    scope TEXT NOT NULL,
+    CONSTRAINT ge_tce UNIQUE (table_name, column_name, extension_name)
+);
+INSERT OR IGNORE INTO gpkg_spatial_ref_sys VALUES
+    ('Undefined cartesian SRS', -1, 'NONE', -1, 'undefined',
+     'undefined cartesian coordinate reference system'),
+    ('Undefined geographic SRS', 0, 'NONE', 0, 'undefined',
+     'undefined geographic coordinate reference system'),
+    ('WGS 84 geodetic', 4326, 'EPSG', 4326,
+     'GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563,AUTHORITY["EPSG","7030"]],AUTHORITY["EPSG","6326"]],PRIMEM["Greenwich",0,AUTHORITY["EPSG","8901"]],UNIT["degree",0.0174532925199433,AUTHORITY["EPSG","9122"]],AXIS["Latitude",NORTH],AXIS["Longitude",EAST],AUTHORITY["EPSG","4326"]]',
+     'longitude/latitude coordinates in decimal degrees on the WGS 84 spheroid');
+"#;
+
+// The triggers of the spatial index (see the `gpkg_rtree_index` extension), with `{t}`
+// standing for the (quoted) name of the table and `{rtree}` for the (escaped) name
+// of the spatial index, which prefixes the names of the triggers.
+const RTREE_TRIGGERS: &str = r#"
+CREATE TRIGGER "{rtree}_insert" AFTER INSERT ON {t}
+WHEN (NEW.geom NOT NULL AND NOT ST_IsEmpty(NEW.geom))
+BEGIN
+    INSERT OR REPLACE INTO "{rtree}" VALUES (
+        NEW.fid, ST_MinX(NEW.geom), ST_MaxX(NEW.geom), ST_MinY(NEW.geom), ST_MaxY(NEW.geom)
+    );
+END;
+CREATE TRIGGER "{rtree}_update1" AFTER UPDATE OF geom ON {t}
+WHEN OLD.fid = NEW.fid AND (NEW.geom NOTNULL AND NOT ST_IsEmpty(NEW.geom))
+BEGIN
+    INSERT OR REPLACE INTO "{rtree}" VALUES (
+        NEW.fid, ST_MinX(NEW.geom), ST_MaxX(NEW.geom), ST_MinY(NEW.geom), ST_MaxY(NEW.geom)
+    );
+END;
+CREATE TRIGGER "{rtree}_update2" AFTER UPDATE OF geom ON {t}
+WHEN OLD.fid = NEW.fid AND (NEW.geom ISNULL OR ST_IsEmpty(NEW.geom))
+BEGIN
+    DELETE FROM "{rtree}" WHERE id = OLD.fid;
+END;
+CREATE TRIGGER "{rtree}_update3" AFTER UPDATE ON {t}
+WHEN OLD.fid != NEW.fid AND (NEW.geom NOTNULL AND NOT ST_IsEmpty(NEW.geom))
+BEGIN
+    DELETE FROM "{rtree}" WHERE id = OLD.fid;
+    INSERT OR REPLACE INTO "{rtree}" VALUES (
+        NEW.fid, ST_MinX(NEW.geom), ST_MaxX(NEW.geom), ST_MinY(NEW.geom), ST_MaxY(NEW.geom)
+    );
+END;
+CREATE TRIGGER "{rtree}_update4" AFTER UPDATE ON {t}
+WHEN OLD.fid != NEW.fid AND (NEW.geom ISNULL OR ST_IsEmpty(NEW.geom))
+BEGIN
+    DELETE FROM "{rtree}" WHERE id IN (OLD.fid, NEW.fid);
+END;
+CREATE TRIGGER "{rtree}_delete" AFTER DELETE ON {t}
+WHEN OLD.geom NOT NULL
+BEGIN
+    DELETE FROM "{rtree}" WHERE id = OLD.fid;
+END;
+"#;
+
+/// Writes the given contours as a new layer (a feature table of MultiPolygons, with
+/// their `threshold`, `max_threshold` and `threshold_index` as attributes, and a spatial
+/// index) in the GeoPackage at `path` (created if it doesn't exist), in an undefined
+/// cartesian coordinate reference system.
+///
+/// # Arguments
+///
+/// * `path` - The path of the GeoPackage.
+/// * `layer_name` - The name of the layer (which must not exist in the GeoPackage).
+/// * `contours` - The contours to be written.
+pub fn write_gpkg(path: impl AsRef<Path>, layer_name: &str, contours: &[Contour]) -> Result<()> {
+    write_gpkg_with_srs(path, layer_name, contours, -1, "undefined")
+}
+
+/// Writes the given contours as a new layer in the GeoPackage at `path`
+/// (see [`write_gpkg`]), in the coordinate reference system with the given EPSG code
+/// and definition (as WKT), which is registered in the GeoPackage if needed.
+///
+/// # Arguments
+///
+/// * `path` - The path of the GeoPackage.
+/// * `layer_name` - The name of the layer (which must not exist in the GeoPackage).
+/// * `contours` - The contours to be written.
+/// * `epsg` - The EPSG code of the coordinate reference system of the contours.
+/// * `definition` - The WKT definition of the coordinate reference system of the contours.
+// The casts to f64 are unnecessary when not using the "f32" feature.
+#[allow(clippy::unnecessary_cast)]
+pub fn write_gpkg_with_srs(
+    path: impl AsRef<Path>,
+    layer_name: &str,
+    contours: &[Contour],
+    epsg: i32,
+    definition: &str,
+) -> Result<()> {
+    let mut conn = Connection::open(path)?;
+    conn.pragma_update(None, "application_id", 0x4750_4B47)?;
+    conn.pragma_update(None, "user_version", 10400)?;
+    let tx = conn.transaction()?;
+    tx.execute_batch(SCHEMA)?;
+    tx.execute(
+        "INSERT OR IGNORE INTO gpkg_spatial_ref_sys VALUES (?1, ?2, 'EPSG', ?2, ?3, NULL)",
+        params![format!("EPSG:{}", epsg), epsg, definition],
+    )?;
+
+    let table = quote(layer_name);
+    let rtree_name = format!("rtree_{}_geom", layer_name).replace('"', "\"\"");
+    let rtree = format!("\"{}\"", rtree_name);
+    tx.execute_batch(&format!(
+        "CREATE TABLE {table} (
+            fid INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+            geom MULTIPOLYGON,
+            threshold DOUBLE,
+            max_threshold DOUBLE,
+            threshold_index INTEGER
+        );
+        CREATE VIRTUAL TABLE {rtree} USING rtree(id, minx, maxx, miny, maxy);"
+    ))?;
+
+    let mut extent: Option<Rect<Float>> = None;
+    {
+        let mut insert = tx.prepare(&format!(
+            "INSERT INTO {table} (geom, threshold, max_threshold, threshold_index)
+            VALUES (?1, ?2, ?3, ?4)"
+        ))?;
+        let mut index = tx.prepare(&format!("INSERT INTO {rtree} VALUES (?1, ?2, ?3, ?4, ?5)"))?;
+        for contour in contours {
+            let bbox = contour.bbox();
+            insert.execute(params![
+                geometry_blob(contour.geometry(), bbox, epsg),
+                contour.threshold() as f64,
+                contour.max_threshold().map(|t| t as f64),
+                contour.threshold_index() as i64,
+            ])?;
+            if let Some(bbox) = bbox {
+                let (min, max) = (bbox.min(), bbox.max());
+                index.execute(params![
+                    tx.last_insert_rowid(),
+                    min.x as f64,
+                    max.x as f64,
+                    min.y as f64,
+                    max.y as f64
+                ])?;
+                extent = Some(match extent {
+                    None => bbox,
+                    Some(e) => Rect::new(
+                        (e.min().x.min(min.x), e.min().y.min(min.y)),
+                        (e.max().x.max(max.x), e.max().y.max(max.y)),
+                    ),
+                });
+            }
+        }
+    }
+
+    tx.execute(
+        "INSERT INTO gpkg_contents
+        (table_name, data_type, identifier, min_x, min_y, max_x, max_y, srs_id)
+        VALUES (?1, 'features', ?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            layer_name,
+            extent.map(|e| e.min().x as f64),
+            extent.map(|e| e.min().y as f64),
+            extent.map(|e| e.max().x as f64),
+            extent.map(|e| e.max().y as f64),
+            epsg
+        ],
+    )?;
+    tx.execute(
+        "INSERT INTO gpkg_geometry_columns VALUES (?1, 'geom', 'MULTIPOLYGON', ?2, 0, 0)",
+        params![layer_name, epsg],
+    )?;
+    tx.execute(
+        "INSERT INTO gpkg_extensions VALUES (?1, 'geom', 'gpkg_rtree_index',
+        'http://www.geopackage.org/spec120/#extension_rtree', 'write-only')",
+        params![layer_name],
+    )?;
+    // The triggers are created last, as they rely on functions (`ST_IsEmpty`, `ST_MinX`, ...)
+    // that are provided by the GeoPackage implementations, not by SQLite itself
+    tx.execute_batch(
+        &RTREE_TRIGGERS
+            .replace("{rtree}", &rtree_name)
+            .replace("{t}", &table),
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Quotes an SQL identifier.
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// Encodes a geometry as a GeoPackage geometry blob (a header, with the envelope
+/// of the geometry, followed by its little-endian WKB representation).
+// The casts to f64 are unnecessary when not using the "f32" feature.
+#[allow(clippy::unnecessary_cast)]
+fn geometry_blob(
+    geometry: &MultiPolygon<Float>,
+    bbox: Option<Rect<Float>>,
+    srs_id: i32,
+) -> Vec<u8> {
+    let mut blob = b"GP\x00".to_vec();
+    // Little endian, with an [minx, maxx, miny, maxy] envelope or flagged as empty
+    blob.push(if bbox.is_some() {
+        0b0000_0011
+    } else {
+        0b0001_0001
+    });
+    blob.extend(srs_id.to_le_bytes());
+    if let Some(bbox) = bbox {
+        for v in [bbox.min().x, bbox.max().x, bbox.min().y, bbox.max().y] {
+            blob.extend((v as f64).to_le_bytes());
+        }
+    }
+    let header = |blob: &mut Vec<u8>, kind: u32, len: usize| {
+        blob.push(1);
+        blob.extend(kind.to_le_bytes());
+        blob.extend((len as u32).to_le_bytes());
+    };
+    header(&mut blob, 6, geometry.0.len());
+    for polygon in &geometry.0 {
+        header(&mut blob, 3, polygon.interiors().len() + 1);
+        for ring in std::iter::once(polygon.exterior()).chain(polygon.interiors()) {
+            blob.extend((ring.0.len() as u32).to_le_bytes());
+            for pt in &ring.0 {
+                blob.extend((pt.x as f64).to_le_bytes());
+                blob.extend((pt.y as f64).to_le_bytes());
+            }
+        }
+    }
+    blob
+}
@@ -0,0 +1,129 @@
+use crate::error::Result;
+use crate::{ContourBuilder, Float};
+
+/// The operator used to estimate the gradient of a grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GradientOperator {
+    /// Central differences between the two neighbouring samples
+    /// (one-sided differences at the edges of the grid or next to a missing value).
+    #[default]
+    CentralDifferences,
+    /// Sobel operator: the central differences of the row (or column) of the sample
+    /// and of both neighbouring rows (or columns), weighted 1-2-1, which smooths out the noise.
+    Sobel,
+}
+
+/// The partial derivatives of the values of a grid, expressed in the units of
+/// the values per unit of the output coordinates (see [`ContourBuilder::gradient`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    /// The derivatives along the x axis (∂v/∂x), as a grid of the same size as the values
+    pub x: Vec<Float>,
+    /// The derivatives along the y axis (∂v/∂y), as a grid of the same size as the values
+    pub y: Vec<Float>,
+}
+
+impl Gradient {
+    /// Computes the magnitude of the gradient (i.e. the slope) of each sample.
+    pub fn magnitude(&self) -> Vec<Float> {
+        self.x
+            .iter()
+            .zip(&self.y)
+            .map(|(x, y)| x.hypot(*y))
+            .collect()
+    }
+}
+
+impl ContourBuilder {
+    /// Computes the gradient of the given `values` (∂v/∂x and ∂v/∂y at each sample),
+    /// taking the step of the grid into account, so that it is aligned with the
+    /// geometries computed by this builder (e.g. for slope-aware labeling or hachures).
+    ///
+    /// Non-finite values, and the values equal to `nodata`, are missing: the derivatives
+    /// next to them are estimated from the available neighbours, and are `NaN` at the
+    /// missing samples themselves (or where no neighbour is available).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `operator` - The operator used to estimate the derivatives.
+    /// * `nodata` - The value of the missing samples, if any.
+    pub fn gradient(
+        &self,
+        values: &[Float],
+        operator: GradientOperator,
+        nodata: Option<Float>,
+    ) -> Result<Gradient> {
+        let grid = self.grid(values)?;
+        let (dx, dy) = (self.dx, self.dy);
+        let value = |x: usize, y: usize| {
+            let v = grid.get(y * dx + x);
+            (v.is_finite() && Some(v) != nodata).then_some(v)
+        };
+        // The difference per sample at a sample, given its value and the values
+        // of the previous and of the next sample along an axis
+        let difference = |at: Option<Float>, before: Option<Float>, after: Option<Float>| match (
+            at, before, after,
+        ) {
+            (Some(_), Some(b), Some(a)) => Some((a - b) / 2.),
+            (Some(v), None, Some(a)) => Some(a - v),
+            (Some(v), Some(b), None) => Some(v - b),
+            _ => None,
+        };
+        let dvdx = |x: usize, y: usize| {
+            difference(
+                value(x, y),
+                x.checked_sub(1).and_then(|x| value(x, y)),
+                (x + 1 < dx).then(|| value(x + 1, y)).flatten(),
+            )
+        };
+        let dvdy = |x: usize, y: usize| {
+            difference(
+                value(x, y),
+                y.checked_sub(1).and_then(|y| value(x, y)),
+                (y + 1 < dy).then(|| value(x, y + 1)).flatten(),
+            )
+        };
+        // Averages the differences of the neighbouring rows (or columns)
+        // with the weights of the Sobel operator, skipping the missing ones
+        let sobel = |center: Option<Float>, sides: [Option<Float>; 2]| {
+            center?;
+            let (sum, weight) = [(center, 2.), (sides[0], 1.), (sides[1], 1.)]
+                .into_iter()
+                .filter_map(|(d, w)| d.map(|d| (d * w, w)))
+                .fold((0., 0.), |(s, t), (d, w)| (s + d, t + w));
+            Some(sum / weight)
+        };
+
+        let mut gradient = Gradient {
+            x: Vec::with_capacity(dx * dy),
+            y: Vec::with_capacity(dx * dy),
+        };
+        for y in 0..dy {
+            for x in 0..dx {
+                let (gx, gy) = match operator {
+                    GradientOperator::CentralDifferences => (dvdx(x, y), dvdy(x, y)),
+                    GradientOperator::Sobel => (
+                        sobel(
+                            dvdx(x, y),
+                            [
+                                y.checked_sub(1).and_then(|y| dvdx(x, y)),
+                                (y + 1 < dy).then(|| dvdx(x, y + 1)).flatten(),
+                            ],
+                        ),
+                        sobel(
+                            dvdy(x, y),
+                            [
+                                x.checked_sub(1).and_then(|x| dvdy(x, y)),
+                                (x + 1 < dx).then(|| dvdy(x + 1, y)).flatten(),
+                            ],
+                        ),
+                    ),
+                };
+                gradient.x.push(gx.map_or(Float::NAN, |g| g / self.x_step));
+                gradient.y.push(gy.map_or(Float::NAN, |g| g / self.y_step));
+            }
+        }
+        Ok(gradient)
+    }
+}
@@ -0,0 +1,137 @@
+use crate::error::Result;
+use crate::gradient::{Gradient, GradientOperator};
+use crate::{ContourBuilder, Float, Line, Pt};
+use geo_types::{LineString, MultiLineString};
+
+impl ContourBuilder {
+    /// Generates hachures along the given isolines (computed by this builder from
+    /// the given `values`): short tick marks perpendicular to the lines, pointing
+    /// downslope (e.g. inwards for depressions), as used in cartography to depict
+    /// depressions and steepness.
+    ///
+    /// Returns a [`Line`] per given isoline (with the same threshold and metadata),
+    /// whose geometry has a 2-point line string per tick, from the isoline to the end
+    /// of the tick. The ticks are oriented using the gradient of the values (see
+    /// [`ContourBuilder::gradient`]) and no tick is placed where it is missing or flat,
+    /// nor along the edges of the grid (where the isolines are closed).
+    /// No tick is placed if `spacing` isn't strictly positive.
+    ///
+    /// The ticks are placed using the origin and step of the grid, ignoring
+    /// any custom [`transform`](ContourBuilder::transform).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values used to compute the isolines.
+    /// * `lines` - The isolines along which the ticks are placed.
+    /// * `spacing` - The distance between the ticks along the lines
+    ///   (in the output coordinate system), the first tick being at half of it.
+    /// * `length` - The length of the ticks (in the output coordinate system).
+    pub fn hachures(
+        &self,
+        values: &[Float],
+        lines: &[Line],
+        spacing: Float,
+        length: Float,
+    ) -> Result<Vec<Line>> {
+        let gradient = self.gradient(values, GradientOperator::CentralDifferences, None)?;
+        Ok(lines
+            .iter()
+            .map(|line| {
+                let ticks = if spacing > 0. && spacing.is_finite() {
+                    line.geometry
+                        .0
+                        .iter()
+                        .flat_map(|ls| self.ticks(&gradient, &ls.0, spacing, length))
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                let geometry = MultiLineString(ticks);
+                Line {
+                    bbox: crate::bbox::lines_bbox(&geometry),
+                    geometry,
+                    depressions: None,
+                    ..line.clone()
+                }
+            })
+            .collect())
+    }
+
+    /// Places the ticks along the given line, every `spacing` from `spacing / 2`.
+    fn ticks(
+        &self,
+        gradient: &Gradient,
+        line: &[Pt],
+        spacing: Float,
+        length: Float,
+    ) -> Vec<LineString<Float>> {
+        let mut ticks = Vec::new();
+        // The distance along the line to the next tick
+        let mut next = spacing / 2.;
+        for w in line.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            let (sx, sy) = (b.x - a.x, b.y - a.y);
+            let len = sx.hypot(sy);
+            if self.on_edge(&a) && self.on_edge(&b) {
+                // The isoline follows the edges of the grid: start over after them
+                next = spacing / 2.;
+                continue;
+            }
+            while next <= len && len > 0. {
+                let t = next / len;
+                let p = Pt {
+                    x: a.x + sx * t,
+                    y: a.y + sy * t,
+                };
+                let (gx, gy) = self.gradient_at(gradient, &p);
+                // The normal of the segment, oriented downslope
+                let (mut nx, mut ny) = (-sy / len, sx / len);
+                let dot = nx * gx + ny * gy;
+                if dot.is_finite() && dot != 0. {
+                    if dot > 0. {
+                        (nx, ny) = (-nx, -ny);
+                    }
+                    ticks.push(LineString(vec![
+                        p,
+                        Pt {
+                            x: p.x + nx * length,
+                            y: p.y + ny * length,
+                        },
+                    ]));
+                }
+                next += spacing;
+            }
+            next -= len;
+        }
+        ticks
+    }
+
+    /// Whether the given point lies on an edge of the grid (the segments of the
+    /// isolines whose both ends are on the edges are the closing parts of the rings).
+    pub(crate) fn on_edge(&self, p: &Pt) -> bool {
+        let eps = 1e-6;
+        let (x, y) = self.to_grid(p.x, p.y);
+        x <= eps || y <= eps || x >= self.dx as Float - eps || y >= self.dy as Float - eps
+    }
+
+    /// Interpolates (bilinearly) the gradient at the given point, expressed
+    /// in the output coordinate system.
+    pub(crate) fn gradient_at(&self, gradient: &Gradient, p: &Pt) -> (Float, Float) {
+        // The samples lie at the center of the cells
+        let (x, y) = self.to_grid(p.x, p.y);
+        let max = |n: usize| n.saturating_sub(1) as Float;
+        let (x, y) = (
+            (x - 0.5).clamp(0., max(self.dx)),
+            (y - 0.5).clamp(0., max(self.dy)),
+        );
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(self.dx - 1), (y0 + 1).min(self.dy - 1));
+        let (tx, ty) = (x - x0 as Float, y - y0 as Float);
+        let at = |grid: &[Float]| {
+            let top = grid[y0 * self.dx + x0] * (1. - tx) + grid[y0 * self.dx + x1] * tx;
+            let bottom = grid[y1 * self.dx + x0] * (1. - tx) + grid[y1 * self.dx + x1] * tx;
+            top * (1. - ty) + bottom * ty
+        };
+        (at(&gradient.x), at(&gradient.y))
+    }
+}
@@ -0,0 +1,113 @@
+use crate::area::polygon_contains;
+use crate::{Float, Pt};
+use geo_types::Polygon;
+
+/// A prepared spatial index over the polygons of some results, using a regular
+/// grid of buckets referencing the polygons whose bounding box intersects them.
+#[derive(Debug, Clone)]
+pub(crate) struct SpatialIndex {
+    /// The (x_min, y_min, x_max, y_max) extent of the indexed polygons
+    extent: [Float; 4],
+    /// The number of buckets in each dimension
+    n: usize,
+    /// For each bucket, the indexes of the entries whose bounding box intersects it
+    buckets: Vec<Vec<usize>>,
+    /// For each entry, its bounding box and a reference (item, polygon) to the indexed polygon
+    entries: Vec<([Float; 4], usize, usize)>,
+}
+
+fn bbox(polygon: &Polygon<Float>) -> Option<[Float; 4]> {
+    polygon.exterior().0.iter().fold(None, |acc, pt| {
+        Some(match acc {
+            None => [pt.x, pt.y, pt.x, pt.y],
+            Some([x0, y0, x1, y1]) => [x0.min(pt.x), y0.min(pt.y), x1.max(pt.x), y1.max(pt.y)],
+        })
+    })
+}
+
+impl SpatialIndex {
+    /// Builds the index for the given items, each item being a list of polygons.
+    pub(crate) fn new<'a>(items: impl Iterator<Item = &'a [Polygon<Float>]>) -> Self {
+        let entries = items
+            .enumerate()
+            .flat_map(|(item, polygons)| {
+                polygons
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(i, p)| bbox(p).map(|b| (b, item, i)))
+            })
+            .collect::<Vec<_>>();
+        let extent = entries.iter().fold(
+            [
+                Float::INFINITY,
+                Float::INFINITY,
+                Float::NEG_INFINITY,
+                Float::NEG_INFINITY,
+            ],
+            |e, (b, _, _)| {
+                [
+                    e[0].min(b[0]),
+                    e[1].min(b[1]),
+                    e[2].max(b[2]),
+                    e[3].max(b[3]),
+                ]
+            },
+        );
+        let n = ((entries.len() as Float).sqrt().ceil() as usize).clamp(1, 256);
+        let mut index = SpatialIndex {
+            extent,
+            n,
+            buckets: vec![Vec::new(); n * n],
+            entries,
+        };
+        for (ix, (b, _, _)) in index.entries.iter().enumerate() {
+            let (i0, j0) = index.bucket(b[0], b[1]);
+            let (i1, j1) = index.bucket(b[2], b[3]);
+            for j in j0..=j1 {
+                for i in i0..=i1 {
+                    index.buckets[j * n + i].push(ix);
+                }
+            }
+        }
+        index
+    }
+
+    fn bucket(&self, x: Float, y: Float) -> (usize, usize) {
+        let [x0, y0, x1, y1] = self.extent;
+        let pos = |v: Float, lo: Float, hi: Float| {
+            let t = if hi > lo { (v - lo) / (hi - lo) } else { 0. };
+            ((t * self.n as Float) as usize).min(self.n - 1)
+        };
+        (pos(x, x0, x1), pos(y, y0, y1))
+    }
+
+    /// Returns the (item, polygon) references of the indexed polygons containing the point (x, y).
+    pub(crate) fn query<'a>(
+        &'a self,
+        x: Float,
+        y: Float,
+        polygon: impl Fn(usize, usize) -> &'a Polygon<Float> + 'a,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let [x0, y0, x1, y1] = self.extent;
+        let candidates = if x >= x0 && x <= x1 && y >= y0 && y <= y1 {
+            let (i, j) = self.bucket(x, y);
+            &self.buckets[j * self.n + i][..]
+        } else {
+            &[]
+        };
+        let pt = Pt { x, y };
+        candidates.iter().filter_map(move |&ix| {
+            let (b, item, i) = &self.entries[ix];
+            if x >= b[0]
+                && x <= b[2]
+                && y >= b[1]
+                && y <= b[3]
+                && polygon_contains(polygon(*item, *i), &pt)
+            {
+                Some((*item, *i))
+            } else {
+                None
+            }
+        })
+    }
+}
@@ -0,0 +1,14 @@
+/// How the infinite values of the grid (e.g. coming from masked arrays) are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InfinityPolicy {
+    /// `+inf` is inside the contours of every threshold and `-inf` outside,
+    /// the vertices on the edges between a finite and an infinite value
+    /// being placed on the finite sample when smoothing.
+    #[default]
+    Clamp,
+    /// Every infinite value is outside the contours of every threshold (as `-inf`).
+    Outside,
+    /// An [`InfiniteValue`](crate::ErrorKind::InfiniteValue) error is returned,
+    /// with the location of the first infinite value of the grid.
+    Error,
+}
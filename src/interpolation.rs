@@ -0,0 +1,127 @@
+use crate::Float;
+
+/// The interpolation used to place the vertices along the cell edges
+/// when smoothing is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// Linear interpolation between the values at both ends of the edge.
+    #[default]
+    Linear,
+    /// Fits a quadratic along the edge, using the values of the neighbouring cells
+    /// (averaging the fits on both sides when available), which improves the placement
+    /// of the contours on smooth fields sampled coarsely.
+    /// Falls back to linear interpolation at the edges of the grid or when the
+    /// quadratic has no root on the edge.
+    Quadratic,
+}
+
+/// Returns the root of the quadratic through (-1, `a`), (0, `b`) and (1, `c`)
+/// lying in [`lo`, `hi`] (the closest to `guess` if there are two), if any.
+fn quadratic_root(
+    a: Float,
+    b: Float,
+    c: Float,
+    value: Float,
+    lo: Float,
+    hi: Float,
+    guess: Float,
+) -> Option<Float> {
+    let qa = (a - 2. * b + c) / 2.;
+    let qb = (c - a) / 2.;
+    let qc = b - value;
+    let eps = 1e-9;
+    let in_range = |s: &Float| s.is_finite() && *s >= lo - eps && *s <= hi + eps;
+    if qa.abs() < Float::EPSILON * (a.abs() + b.abs() + c.abs()).max(1.) {
+        return Some(-qc / qb).filter(in_range);
+    }
+    let discriminant = qb * qb - 4. * qa * qc;
+    if discriminant < 0. {
+        return None;
+    }
+    let sqrt = discriminant.sqrt();
+    [(-qb - sqrt) / (2. * qa), (-qb + sqrt) / (2. * qa)]
+        .into_iter()
+        .filter(in_range)
+        .min_by(|s, t| (s - guess).abs().total_cmp(&(t - guess).abs()))
+}
+
+/// Computes the position `t` (in [0, 1]) of `value` along an edge between
+/// the values `v0` and `v1` by linear interpolation, the position tending to
+/// the finite end of the edge if the other one is infinite, and in the middle
+/// of the edge if one of them is `NaN` (no data).
+// Written with selects rather than branches so that loops over it auto-vectorize.
+#[inline]
+pub(crate) fn linear(v0: Float, v1: Float, value: Float) -> Float {
+    let (inf0, inf1) = (v0.is_infinite(), v1.is_infinite());
+    let t = (value - v0) / (v1 - v0);
+    let t = if inf1 { 0. } else { t };
+    let both = if inf1 { 0.5 } else { 1. };
+    let t = if inf0 { both } else { t };
+    if v0.is_nan() || v1.is_nan() {
+        0.5
+    } else {
+        t
+    }
+}
+
+/// Computes the position `t` (in [0, 1]) of `value` along an edge one end of which
+/// has no data (`NaN`), extrapolating linearly from the valid end and the value beyond it
+/// (`before` on the side of `v0`, `after` on the side of `v1`), clamped to the half
+/// of the edge on the valid side (the middle of the edge if the extrapolated
+/// values don't reach `value` before it).
+pub(crate) fn valid_side(
+    before: Option<Float>,
+    v0: Float,
+    v1: Float,
+    after: Option<Float>,
+    value: Float,
+) -> Float {
+    let (valid, beyond) = if v1.is_nan() {
+        (v0, before)
+    } else {
+        (v1, after)
+    };
+    // The distance from the valid end to the crossing (the value beyond being one step away)
+    let distance = beyond
+        .map(|b| (value - valid) / (valid - b))
+        .filter(|d| d.is_finite() && *d >= 0.)
+        .map_or(0.5, |d| d.min(0.5));
+    if v1.is_nan() {
+        distance
+    } else {
+        1. - distance
+    }
+}
+
+/// Computes the position `t` (in [0, 1]) of `value` along an edge between
+/// the values `v0` and `v1`, using the values `before` (on the side of `v0`)
+/// and `after` (on the side of `v1`) of the neighbouring cells if available.
+pub(crate) fn quadratic(
+    before: Option<Float>,
+    v0: Float,
+    v1: Float,
+    after: Option<Float>,
+    value: Float,
+) -> Float {
+    let linear = linear(v0, v1, value);
+    if !v0.is_finite() || !v1.is_finite() {
+        return linear;
+    }
+    let (before, after) = (
+        before.filter(|b| b.is_finite()),
+        after.filter(|a| a.is_finite()),
+    );
+    let estimates = [
+        before.and_then(|b| quadratic_root(b, v0, v1, value, 0., 1., linear)),
+        after.and_then(|a| quadratic_root(v0, v1, a, value, -1., 0., linear - 1.).map(|s| s + 1.)),
+    ];
+    let (sum, n) = estimates
+        .iter()
+        .flatten()
+        .fold((0., 0.), |(sum, n), t| (sum + t, n + 1.));
+    if n > 0. {
+        (sum / n).clamp(0., 1.)
+    } else {
+        linear
+    }
+}
@@ -0,0 +1,375 @@
+use crate::area::{area, contains};
+use crate::error::Result;
+use crate::{ContourBuilder, Float, Pt};
+use geo_types::{LineString, MultiLineString, MultiPolygon};
+use rustc_hash::FxHashMap;
+use std::fmt;
+
+/// The thresholds used by [`ContourBuilder::fuzz_invariants`] (the values of the
+/// random grids being between `0.` and `1.`).
+const FUZZ_THRESHOLDS: [Float; 6] = [0., 0.25, 0.3, 0.5, 0.8, 1.];
+
+/// An invariant of the contours, isobands and isolines broken on a grid
+/// (see [`ContourBuilder::check_invariants`]).
+///
+/// The rings are designated by the position of their polygon in the geometry and
+/// by their own position in the polygon (`1..` for the holes).
+#[derive(Debug, Clone, PartialEq)]
+pub enum InvariantIssue {
+    /// The hole isn't inside exactly one polygon (its own) of its contour or band.
+    HoleContainment {
+        /// Whether the hole belongs to a band (or to a contour)
+        band: bool,
+        threshold_index: usize,
+        polygon: usize,
+        ring: usize,
+        /// The number of polygons whose filled area contains the hole
+        containing: usize,
+    },
+    /// The total area of the bands isn't the area between the contours of the
+    /// lowest and highest thresholds.
+    BandArea {
+        /// The area covered by the contour of the lowest threshold but not by
+        /// the contour of the highest one
+        expected: f64,
+        /// The total area of the bands
+        actual: f64,
+    },
+    /// A vertex of an isoline isn't on the boundary of the contour of the same threshold.
+    LineOffBoundary {
+        threshold_index: usize,
+        /// The position of the line in the geometry of the isolines
+        line: usize,
+        point: Pt,
+    },
+}
+
+impl fmt::Display for InvariantIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvariantIssue::HoleContainment {
+                band,
+                threshold_index,
+                polygon,
+                ring,
+                containing,
+            } => write!(
+                f,
+                "The hole {} of the polygon {} of the {} {} is inside {} polygon(s)",
+                ring,
+                polygon,
+                if *band { "band" } else { "contour" },
+                threshold_index,
+                containing
+            ),
+            InvariantIssue::BandArea { expected, actual } => write!(
+                f,
+                "The bands cover an area of {} instead of {}",
+                actual, expected
+            ),
+            InvariantIssue::LineOffBoundary {
+                threshold_index,
+                line,
+                point,
+            } => write!(
+                f,
+                "The vertex ({}, {}) of the line {} of the isoline {} isn't on the contour boundary",
+                point.x, point.y, line, threshold_index
+            ),
+        }
+    }
+}
+
+/// An invariant broken on a grid, found by [`ContourBuilder::check_invariants`]
+/// or [`ContourBuilder::fuzz_invariants`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvariantViolation {
+    /// The seed of the random grid (see [`random_grid`]), or `None` for the grid
+    /// given to [`ContourBuilder::check_invariants`]
+    pub seed: Option<u64>,
+    /// The broken invariant
+    pub issue: InvariantIssue,
+}
+
+/// The report of the invariants checked on one or more grids.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct InvariantReport {
+    /// The number of grids checked
+    pub grids: usize,
+    /// The broken invariants, in the order of the grids
+    pub violations: Vec<InvariantViolation>,
+}
+
+impl InvariantReport {
+    /// Returns true if no invariant was broken.
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl fmt::Display for InvariantReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} violation(s) on {} grid(s)",
+            self.violations.len(),
+            self.grids
+        )?;
+        for violation in &self.violations {
+            match violation.seed {
+                Some(seed) => write!(f, "\n[seed {}] {}", seed, violation.issue)?,
+                None => write!(f, "\n{}", violation.issue)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns a random grid of `dx` by `dy` values between `0.` and `1.` (half of them
+/// being multiples of `0.125`, to produce ties with the thresholds), the same `seed`
+/// always giving the same grid.
+pub fn random_grid(dx: usize, dy: usize, seed: u64) -> Vec<Float> {
+    // SplitMix64
+    let mut state = seed;
+    let mut next = move || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+    (0..dx * dy)
+        .map(|_| {
+            let r = next();
+            if r & 1 == 0 {
+                ((r >> 1) % 9) as Float / 8.
+            } else {
+                (r >> 11) as Float / (1u64 << 53) as Float
+            }
+        })
+        .collect()
+}
+
+impl ContourBuilder {
+    /// Checks the invariants of the contours, isobands and isolines computed with this
+    /// builder on the given `values` and `thresholds` (at least 2), returning the report
+    /// of the broken ones:
+    ///
+    /// * every hole is inside exactly one polygon (its own) of its contour or band,
+    /// * the bands cover the area between the contours of the lowest and highest thresholds,
+    /// * every vertex of the isolines is on the boundary of the contour of the same threshold.
+    ///
+    /// The thresholds are expected to be sorted in ascending order.
+    pub fn check_invariants(
+        &self,
+        values: &[Float],
+        thresholds: &[Float],
+    ) -> Result<InvariantReport> {
+        let mut violations = Vec::new();
+        self.check_grid(values, thresholds, |issue| {
+            violations.push(InvariantViolation { seed: None, issue })
+        })?;
+        Ok(InvariantReport {
+            grids: 1,
+            violations,
+        })
+    }
+
+    /// Checks the invariants (see [`ContourBuilder::check_invariants`]) on `grids`
+    /// random grids with the dimensions of this builder, the `k`-th grid being
+    /// `random_grid(dx, dy, seed + k)` (see [`random_grid`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use contour::ContourBuilder;
+    ///
+    /// let report = ContourBuilder::new(8, 6, true).fuzz_invariants(42, 20).unwrap();
+    /// assert!(report.is_ok(), "{}", report);
+    /// assert_eq!(report.grids, 20);
+    /// ```
+    pub fn fuzz_invariants(&self, seed: u64, grids: usize) -> Result<InvariantReport> {
+        let mut violations = Vec::new();
+        for k in 0..grids {
+            let seed = seed.wrapping_add(k as u64);
+            let values = random_grid(self.dx, self.dy, seed);
+            self.check_grid(&values, &FUZZ_THRESHOLDS, |issue| {
+                violations.push(InvariantViolation {
+                    seed: Some(seed),
+                    issue,
+                })
+            })?;
+        }
+        Ok(InvariantReport { grids, violations })
+    }
+
+    // The casts to `f64` are needed with the "f32" feature
+    #[allow(clippy::unnecessary_cast)]
+    fn check_grid(
+        &self,
+        values: &[Float],
+        thresholds: &[Float],
+        mut report: impl FnMut(InvariantIssue),
+    ) -> Result<()> {
+        let contours = self.contours(values, thresholds)?;
+        let bands = self.isobands(values, thresholds)?;
+        let lines = self.lines(values, thresholds)?;
+        for contour in &contours {
+            holes_containment(contour.geometry(), |polygon, ring, containing| {
+                report(InvariantIssue::HoleContainment {
+                    band: false,
+                    threshold_index: contour.threshold_index(),
+                    polygon,
+                    ring,
+                    containing,
+                })
+            });
+        }
+        for band in &bands {
+            holes_containment(band.geometry(), |polygon, ring, containing| {
+                report(InvariantIssue::HoleContainment {
+                    band: true,
+                    threshold_index: band.threshold_index(),
+                    polygon,
+                    ring,
+                    containing,
+                })
+            });
+        }
+        if let (Some(first), Some(last)) = (contours.first(), contours.last()) {
+            let expected = polygons_area(first.geometry()) - polygons_area(last.geometry());
+            let actual = bands
+                .iter()
+                .map(|b| polygons_area(b.geometry()))
+                .sum::<f64>();
+            let tolerance = (Float::EPSILON as f64).sqrt() * expected.abs().max(1.);
+            if (expected - actual).abs() > tolerance {
+                report(InvariantIssue::BandArea { expected, actual });
+            }
+        }
+        for line in &lines {
+            let index = line.threshold_index();
+            let Some(contour) = contours.iter().find(|c| c.threshold_index() == index) else {
+                continue;
+            };
+            lines_on_boundary(line.geometry(), contour.geometry(), |line, point| {
+                report(InvariantIssue::LineOffBoundary {
+                    threshold_index: index,
+                    line,
+                    point,
+                })
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Returns the area of the given polygons (the area of their exterior rings minus
+/// the area of their holes).
+fn polygons_area(polygons: &MultiPolygon<Float>) -> f64 {
+    polygons
+        .0
+        .iter()
+        .map(|polygon| {
+            let holes = polygon.interiors().iter().map(|r| area(&r.0).abs());
+            (area(&polygon.exterior().0).abs() - holes.sum::<f64>()) / 2.
+        })
+        .sum()
+}
+
+/// Calls `f` with the polygon, the ring and the number of containing polygons of each
+/// hole which isn't inside the filled area of exactly one polygon (its own).
+fn holes_containment(polygons: &MultiPolygon<Float>, mut f: impl FnMut(usize, usize, usize)) {
+    let inside = |ring: &LineString<Float>, hole: &LineString<Float>| {
+        ring.0.len() >= 4 && contains(&ring.0, &hole.0) == 1
+    };
+    for (p, polygon) in polygons.0.iter().enumerate() {
+        for (r, hole) in polygon.interiors().iter().enumerate() {
+            let mut containing = 0;
+            let mut own = false;
+            for (q, other) in polygons.0.iter().enumerate() {
+                let filled = inside(other.exterior(), hole)
+                    && !other.interiors().iter().any(|h| inside(h, hole));
+                if filled {
+                    containing += 1;
+                    own |= q == p;
+                }
+            }
+            if containing != 1 || !own {
+                f(p, r + 1, containing);
+            }
+        }
+    }
+}
+
+/// Calls `f` with the line and the vertex of each vertex of the given `lines` which
+/// isn't on the boundary of the given `polygons`.
+fn lines_on_boundary(
+    lines: &MultiLineString<Float>,
+    polygons: &MultiPolygon<Float>,
+    mut f: impl FnMut(usize, Pt),
+) {
+    let segments = polygons
+        .0
+        .iter()
+        .flat_map(|p| std::iter::once(p.exterior()).chain(p.interiors()))
+        .flat_map(|ring| ring.0.windows(2).map(|s| (s[0], s[1])))
+        .collect::<Vec<_>>();
+    // A point on a segment is closer to its middle than the longest segment:
+    // the segments are bucketed by cells of this size
+    let size = segments
+        .iter()
+        .map(|(a, b)| (b.x - a.x).abs().max((b.y - a.y).abs()))
+        .fold(0., Float::max);
+    let (min, max) = segments
+        .iter()
+        .fold((Float::MAX, Float::MIN), |(min, max), (a, _)| {
+            (min.min(a.x).min(a.y), max.max(a.x).max(a.y))
+        });
+    let tolerance = Float::EPSILON.sqrt() * (max - min).abs().max(1.);
+    let bucket = |x: Float, y: Float| {
+        if size > 0. {
+            ((x / size).floor() as i64, (y / size).floor() as i64)
+        } else {
+            (0, 0)
+        }
+    };
+    let mut buckets: FxHashMap<(i64, i64), Vec<usize>> = FxHashMap::default();
+    for (k, (a, b)) in segments.iter().enumerate() {
+        let key = bucket((a.x + b.x) / 2., (a.y + b.y) / 2.);
+        buckets.entry(key).or_default().push(k);
+    }
+    for (l, line) in lines.0.iter().enumerate() {
+        // The rings without area (e.g. collapsed around a sample equal to the
+        // threshold) don't bound the contour
+        if line.is_closed() && area(&line.0) == 0. {
+            continue;
+        }
+        for &point in &line.0 {
+            let (bx, by) = bucket(point.x, point.y);
+            let on_boundary = (-1..=1)
+                .flat_map(|ox| (-1..=1).map(move |oy| (bx + ox, by + oy)))
+                .filter_map(|key| buckets.get(&key))
+                .flatten()
+                .any(|&k| {
+                    let (a, b) = segments[k];
+                    distance(point, a, b) <= tolerance
+                });
+            if !on_boundary {
+                f(l, point);
+            }
+        }
+    }
+}
+
+/// Returns the distance between the point `p` and the segment [a, b].
+fn distance(p: Pt, a: Pt, b: Pt) -> Float {
+    let (sx, sy) = (b.x - a.x, b.y - a.y);
+    let length = sx * sx + sy * sy;
+    let t = if length > 0. {
+        (((p.x - a.x) * sx + (p.y - a.y) * sy) / length).clamp(0., 1.)
+    } else {
+        0.
+    };
+    (p.x - a.x - t * sx).hypot(p.y - a.y - t * sy)
+}
@@ -1,8 +1,13 @@
 use crate::error::{new_error, ErrorKind, Result};
-use crate::{Float, Pt, Ring};
+use crate::{Float, GridSource, Pt, Ring};
 use lazy_static::lazy_static;
 use rustc_hash::FxHashMap;
 use slab::Slab;
+use smallvec::{smallvec, SmallVec};
+use std::ops::Range;
+
+/// The closed rings and the open fragments of rings computed in a part of the grid.
+type Strip<R> = (Vec<R>, Vec<Fragment<R>>);
 
 lazy_static! {
     #[rustfmt::skip]
@@ -32,11 +37,100 @@ lazy_static! {
     ];
 }
 
+/// A ring being stitched, along with the (lattice) indexes of its ends.
 #[derive(Clone, Debug)]
-struct Fragment {
-    start: usize,
-    end: usize,
-    ring: Ring,
+pub(crate) struct Fragment<R> {
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    pub(crate) ring: R,
+}
+
+/// Allocation strategy for the rings built by an [`IsoRingBuilder`].
+///
+/// Only the points of the rings are allocated this way: the default [`GlobalRings`]
+/// allocates them on the heap, while (with the `bumpalo` feature) a `&bumpalo::Bump`
+/// allocates them in an arena that can be reset once they are no longer needed.
+/// The slab and hash maps used to stitch the segments, as well as the returned `Vec`
+/// of rings, are always allocated on the heap (the former being reused by the builder
+/// between calls).
+pub trait RingAllocator {
+    /// The type of the allocated rings.
+    type Ring;
+    /// The type of the rings while they are being stitched.
+    type Buffer;
+    /// Allocates a new ring for the segment between `start` and `end`.
+    fn new_ring(&self, start: Pt, end: Pt) -> Self::Buffer;
+    /// Appends a point at the end of the ring.
+    fn push(ring: &mut Self::Buffer, point: Pt);
+    /// Inserts a point at the start of the ring.
+    fn prepend(ring: &mut Self::Buffer, point: Pt);
+    /// Appends all the points of `other` at the end of the ring.
+    fn append(ring: &mut Self::Buffer, other: Self::Buffer);
+    /// Converts a closed ring to its final type.
+    fn finish(ring: Self::Buffer) -> Self::Ring;
+}
+
+/// Allocates the rings on the heap, as a [`Ring`].
+///
+/// While being stitched, the rings are stored inline (up to 16 points),
+/// avoiding the successive reallocations of the many small rings of noisy grids:
+/// each closed ring is then moved to the heap with a single allocation of its exact
+/// size (the rings that outgrew the inline storage keep their heap buffer).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GlobalRings;
+
+impl RingAllocator for GlobalRings {
+    type Ring = Ring;
+    type Buffer = SmallVec<[Pt; 16]>;
+
+    fn new_ring(&self, start: Pt, end: Pt) -> Self::Buffer {
+        smallvec![start, end]
+    }
+
+    fn push(ring: &mut Self::Buffer, point: Pt) {
+        ring.push(point);
+    }
+
+    fn prepend(ring: &mut Self::Buffer, point: Pt) {
+        ring.insert(0, point);
+    }
+
+    fn append(ring: &mut Self::Buffer, other: Self::Buffer) {
+        ring.extend(other);
+    }
+
+    fn finish(ring: Self::Buffer) -> Ring {
+        ring.into_vec()
+    }
+}
+
+#[cfg(feature = "bumpalo")]
+impl<'b> RingAllocator for &'b bumpalo::Bump {
+    type Ring = bumpalo::collections::Vec<'b, Pt>;
+    type Buffer = Self::Ring;
+
+    fn new_ring(&self, start: Pt, end: Pt) -> Self::Buffer {
+        let mut ring = bumpalo::collections::Vec::with_capacity_in(4, self);
+        ring.push(start);
+        ring.push(end);
+        ring
+    }
+
+    fn push(ring: &mut Self::Buffer, point: Pt) {
+        ring.push(point);
+    }
+
+    fn prepend(ring: &mut Self::Buffer, point: Pt) {
+        ring.insert(0, point);
+    }
+
+    fn append(ring: &mut Self::Buffer, other: Self::Buffer) {
+        ring.extend(other);
+    }
+
+    fn finish(ring: Self::Buffer) -> Self::Ring {
+        ring
+    }
 }
 
 /// Computes isoring for the given `Slice` of `values` according to the `threshold` value
@@ -59,13 +153,204 @@ pub fn contour_rings(
     isoring.compute(values, threshold)
 }
 
+/// Computes isoring for the values of the given `source` according to the `threshold` value
+/// (see [`contour_rings`]), reading them in place (see [`GridSource`]).
+///
+/// # Arguments
+///
+/// * `source` - The grid of values to be used.
+/// * `threshold` - The threshold value.
+pub fn contour_rings_from_source(source: &impl GridSource, threshold: Float) -> Result<Vec<Ring>> {
+    let mut isoring = IsoRingBuilder::new(source.width(), source.height());
+    isoring.compute_source(source, threshold)
+}
+
+/// Computes isoring for the given fixed-size grid of `values` (`H` rows of `W` columns)
+/// according to the `threshold` value (see [`contour_rings`]).
+///
+/// The values are read directly from the rows (rather than through a generic view
+/// of the grid), each row being thresholded once in a loop of known length, which
+/// makes it a bit faster than [`contour_rings`] for small grids (e.g. fog-of-war
+/// or influence maps recomputed every frame): most of the time is still spent
+/// stitching the rings.
+///
+/// # Arguments
+///
+/// * `values` - The rows of values to be used.
+/// * `threshold` - The threshold value.
+///
+/// # Example
+///
+/// ```
+/// use contour::contour_rings_fixed;
+///
+/// let values = [
+///     [0., 0., 0., 0.],
+///     [0., 1., 1., 0.],
+///     [0., 1., 1., 0.],
+///     [0., 0., 0., 0.],
+/// ];
+/// let rings = contour_rings_fixed(&values, 0.5).unwrap();
+/// assert_eq!(rings.len(), 1);
+/// ```
+pub fn contour_rings_fixed<const W: usize, const H: usize>(
+    values: &[[Float; W]; H],
+    threshold: Float,
+) -> Result<Vec<Ring>> {
+    let mut isoring = IsoRingBuilder::new(W, H);
+    isoring.compute_fixed(values, threshold)
+}
+
+/// Computes the marching-squares case index of each cell of the grid for the given
+/// `threshold` value, as used to build the isorings (see [`contour_rings`]).
+///
+/// The cells are the squares between four adjacent samples, including the cells
+/// straddling the edges of the grid (whose samples outside of the grid are considered
+/// below the threshold): the cell at index `(y + 1) * (dx + 1) + (x + 1)` of the returned
+/// `Vec` (of length `(dx + 1) * (dy + 1)`) has the samples `(x, y)` to `(x + 1, y + 1)`
+/// as corners, for `x` in `-1..dx` and `y` in `-1..dy`.
+///
+/// Each bit of the case index (from 0 to 15) tells whether a corner of the cell is
+/// greater than or equal to the threshold: `1` for `(x, y + 1)`, `2` for `(x + 1, y + 1)`,
+/// `4` for `(x + 1, y)` and `8` for `(x, y)`.
+///
+/// # Arguments
+///
+/// * `values` - The slice of values to be used.
+/// * `threshold` - The threshold value.
+/// * `dx` - The number of columns in the grid.
+/// * `dy` - The number of rows in the grid.
+pub fn cell_cases(values: &[Float], threshold: Float, dx: usize, dy: usize) -> Result<Vec<u8>> {
+    if values.len() != dx * dy {
+        return Err(new_error(ErrorKind::BadDimension));
+    }
+    let (w, h) = (dx as i64, dy as i64);
+    let inside = |x: i64, y: i64| {
+        (x >= 0 && y >= 0 && x < w && y < h && values[(y * w + x) as usize] >= threshold) as u8
+    };
+    let mut cases = Vec::with_capacity((dx + 1) * (dy + 1));
+    for y in -1..h {
+        for x in -1..w {
+            cases.push(
+                inside(x, y + 1)
+                    | inside(x + 1, y + 1) << 1
+                    | inside(x + 1, y) << 2
+                    | inside(x, y) << 3,
+            );
+        }
+    }
+    Ok(cases)
+}
+
+/// Computes isoring for the given `Slice` of `values` according to the `threshold` value,
+/// allocating the points of the rings in the given `bump` arena (see [`contour_rings`]).
+///
+/// The `Vec` of rings and the temporary structures used to stitch them are still
+/// allocated on the heap.
+///
+/// # Arguments
+///
+/// * `values` - The slice of values to be used.
+/// * `threshold` - The threshold value.
+/// * `dx` - The number of columns in the grid.
+/// * `dy` - The number of rows in the grid.
+/// * `bump` - The arena in which the points of the rings are allocated.
+#[cfg(feature = "bumpalo")]
+pub fn contour_rings_in<'b>(
+    values: &[Float],
+    threshold: Float,
+    dx: usize,
+    dy: usize,
+    bump: &'b bumpalo::Bump,
+) -> Result<Vec<bumpalo::collections::Vec<'b, Pt>>> {
+    let mut isoring = IsoRingBuilder::new_in(dx, dy, bump);
+    isoring.compute(values, threshold)
+}
+
+/// Computes the rings according to the `inside` predicate, splitting the grid into
+/// `strips` horizontal strips processed in parallel, and linking the fragments
+/// of the rings crossing the strip boundaries.
+pub(crate) fn compute_strips(
+    dx: usize,
+    dy: usize,
+    strips: usize,
+    inside: impl Fn(usize) -> bool + Sync,
+) -> Result<Vec<Ring>> {
+    // The rows of cells, from -1 to dy - 1
+    let rows = dy + 1;
+    let size = rows.div_ceil(strips.clamp(1, rows));
+    let strips = std::thread::scope(|scope| {
+        let handles = (-1..dy as i64)
+            .step_by(size)
+            .map(|y0| {
+                let inside = &inside;
+                let ys = y0..(y0 + size as i64).min(dy as i64);
+                scope.spawn(move || IsoRingBuilder::new(dx, dy).compute_rows(inside, ys))
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| match handle.join() {
+                Ok(strip) => strip,
+                Err(e) => std::panic::resume_unwind(e),
+            })
+            .collect::<Result<Vec<_>>>()
+    })?;
+    let mut rings = Vec::new();
+    let mut open = Vec::new();
+    for (closed, fragments) in strips {
+        rings.extend(closed);
+        open.extend(fragments);
+    }
+    rings.extend(link_fragments(open)?);
+    Ok(rings)
+}
+
+/// Links the given open fragments end to start, until the rings they form are closed.
+pub(crate) fn link_fragments(open: Vec<Fragment<Ring>>) -> Result<Vec<Ring>> {
+    let by_start = open
+        .iter()
+        .enumerate()
+        .map(|(i, f)| (f.start, i))
+        .collect::<FxHashMap<_, _>>();
+    let mut fragments = open.into_iter().map(Some).collect::<Vec<_>>();
+    let mut rings = Vec::new();
+    for i in 0..fragments.len() {
+        let Some(Fragment {
+            start,
+            mut end,
+            mut ring,
+        }) = fragments[i].take()
+        else {
+            continue;
+        };
+        while end != start {
+            let next = by_start
+                .get(&end)
+                .and_then(|&j| fragments[j].take())
+                .ok_or_else(|| new_error(ErrorKind::Unexpected))?;
+            ring.extend_from_slice(&next.ring[1..]);
+            end = next.end;
+        }
+        rings.push(ring);
+    }
+    Ok(rings)
+}
+
 /// Isoring generator to compute marching squares with isolines stitched into rings.
-pub struct IsoRingBuilder {
+///
+/// The points of the rings are allocated using the given [`RingAllocator`] (on the heap
+/// by default), its other structures always being allocated on the heap.
+pub struct IsoRingBuilder<A: RingAllocator = GlobalRings> {
     fragment_by_start: FxHashMap<usize, usize>,
     fragment_by_end: FxHashMap<usize, usize>,
-    f: Slab<Fragment>,
+    f: Slab<Fragment<A::Buffer>>,
+    alloc: A,
     dx: usize,
     dy: usize,
+    block_width: Option<usize>,
+    /// The number of columns after which the grid wraps around, if periodic in x
+    period: Option<usize>,
     is_empty: bool,
 }
 
@@ -76,16 +361,54 @@ impl IsoRingBuilder {
     /// * `dx` - The number of columns in the grid.
     /// * `dy` - The number of rows in the grid.
     pub fn new(dx: usize, dy: usize) -> Self {
+        IsoRingBuilder::new_in(dx, dy, GlobalRings)
+    }
+}
+
+impl<A: RingAllocator> IsoRingBuilder<A> {
+    /// Constructs a new IsoRing generator for a grid with `dx` * `dy` dimension,
+    /// allocating the rings with the given allocator.
+    /// # Arguments
+    ///
+    /// * `dx` - The number of columns in the grid.
+    /// * `dy` - The number of rows in the grid.
+    /// * `alloc` - The allocator of the rings.
+    pub fn new_in(dx: usize, dy: usize, alloc: A) -> Self {
         IsoRingBuilder {
             fragment_by_start: FxHashMap::default(),
             fragment_by_end: FxHashMap::default(),
             f: Slab::new(),
+            alloc,
             dx,
             dy,
+            block_width: None,
+            period: None,
             is_empty: true,
         }
     }
 
+    /// Sets the number of columns after which the grid wraps around (none by default):
+    /// the grid holding a copy of its first column after the last one, the cells
+    /// before the first column and after the copy aren't traversed, and the rings
+    /// are stitched across the seam (the vertices on the copied column being the same
+    /// as the vertices on the first one).
+    pub(crate) fn period(mut self, period: Option<usize>) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// Sets the width (in cells) of the vertical blocks in which the grid is traversed
+    /// (by default, or if `None`, the grid is traversed row by row).
+    ///
+    /// Each block is traversed row by row, the fragments of the rings being stitched
+    /// across the block edges, so that the rows of very wide grids are processed
+    /// in cache-sized chunks. The same rings are computed, but their starting
+    /// vertex and their order may differ.
+    pub fn block_width(mut self, width: Option<usize>) -> Self {
+        self.block_width = width.filter(|w| *w > 0);
+        self
+    }
+
     /// Computes isoring for the given slice of `values` according to the `threshold` value
     /// (the inside of the isoring is the surface where input `values` are greater than or equal
     /// to the given threshold value).
@@ -94,7 +417,37 @@ impl IsoRingBuilder {
     ///
     /// * `values` - The slice of values to be used.
     /// * `threshold` - The threshold value to use.
-    pub fn compute(&mut self, values: &[Float], threshold: Float) -> Result<Vec<Ring>> {
+    pub fn compute(&mut self, values: &[Float], threshold: Float) -> Result<Vec<A::Ring>> {
+        self.compute_with(|i| values[i] >= threshold)
+    }
+
+    /// Computes isoring for the values of the given `source` according to the `threshold`
+    /// value, reading them in place.
+    pub(crate) fn compute_source(
+        &mut self,
+        source: &impl GridSource,
+        threshold: Float,
+    ) -> Result<Vec<A::Ring>> {
+        if source.width() != self.dx || source.height() != self.dy {
+            return Err(new_error(ErrorKind::BadDimension));
+        }
+        let dx = self.dx;
+        self.compute_with(|i| source.get(i % dx, i / dx) >= threshold)
+    }
+
+    /// Computes isoring for the given boolean `mask` (the inside of the isoring
+    /// is the surface where the mask is `true`), skipping the thresholding step.
+    ///
+    /// # Arguments
+    ///
+    /// * `mask` - The slice of booleans to be used.
+    pub fn compute_mask(&mut self, mask: &[bool]) -> Result<Vec<A::Ring>> {
+        self.compute_with(|i| mask[i])
+    }
+
+    /// Computes isoring according to the `inside` predicate, telling whether
+    /// the cell at the given index (in the flat grid) is inside the isoring.
+    pub(crate) fn compute_with(&mut self, inside: impl Fn(usize) -> bool) -> Result<Vec<A::Ring>> {
         macro_rules! case_stitch {
             ($ix:expr, $x:ident, $y:ident, $result:expr) => {
                 CASES[$ix]
@@ -110,6 +463,23 @@ impl IsoRingBuilder {
         let mut result = Vec::new();
         let dx = self.dx as i64;
         let dy = self.dy as i64;
+
+        if self.period.is_some() {
+            self.traverse(&inside, 0..dx - 1, -1..dy, &mut result)?;
+            self.is_empty = false;
+            return Ok(result);
+        }
+        if let Some(width) = self.block_width.filter(|w| (*w as i64) < dx + 1) {
+            let mut x0 = -1;
+            while x0 < dx {
+                let x1 = (x0 + width as i64).min(dx);
+                self.traverse(&inside, x0..x1, -1..dy, &mut result)?;
+                x0 = x1;
+            }
+            self.is_empty = false;
+            return Ok(result);
+        }
+
         let mut x = -1;
         let mut y = -1;
         let mut t0;
@@ -118,12 +488,12 @@ impl IsoRingBuilder {
         let mut t3;
 
         // Special case for the first row (y = -1, t2 = t3 = 0).
-        t1 = (values[0] >= threshold) as usize;
+        t1 = inside(0) as usize;
         case_stitch!(t1 << 1, x, y, &mut result);
         x += 1;
         while x < dx - 1 {
             t0 = t1;
-            t1 = (values[(x + 1) as usize] >= threshold) as usize;
+            t1 = inside((x + 1) as usize) as usize;
             case_stitch!(t0 | t1 << 1, x, y, &mut result);
             x += 1;
         }
@@ -133,15 +503,15 @@ impl IsoRingBuilder {
         y += 1;
         while y < dy - 1 {
             x = -1;
-            t1 = (values[(y * dx + dx) as usize] >= threshold) as usize;
-            t2 = (values[(y * dx) as usize] >= threshold) as usize;
+            t1 = inside((y * dx + dx) as usize) as usize;
+            t2 = inside((y * dx) as usize) as usize;
             case_stitch!(t1 << 1 | t2 << 2, x, y, &mut result);
             x += 1;
             while x < dx - 1 {
                 t0 = t1;
-                t1 = (values[(y * dx + dx + x + 1) as usize] >= threshold) as usize;
+                t1 = inside((y * dx + dx + x + 1) as usize) as usize;
                 t3 = t2;
-                t2 = (values[(y * dx + x + 1) as usize] >= threshold) as usize;
+                t2 = inside((y * dx + x + 1) as usize) as usize;
                 case_stitch!(t0 | t1 << 1 | t2 << 2 | t3 << 3, x, y, &mut result);
                 x += 1;
             }
@@ -151,12 +521,12 @@ impl IsoRingBuilder {
 
         // Special case for the last row (y = dy - 1, t0 = t1 = 0).
         x = -1;
-        t2 = (values[(y * dx) as usize] >= threshold) as usize;
+        t2 = inside((y * dx) as usize) as usize;
         case_stitch!(t2 << 2, x, y, &mut result);
         x += 1;
         while x < dx - 1 {
             t3 = t2;
-            t2 = (values[(y * dx + x + 1) as usize] >= threshold) as usize;
+            t2 = inside((y * dx + x + 1) as usize) as usize;
             case_stitch!(t2 << 2 | t3 << 3, x, y, &mut result);
             x += 1;
         }
@@ -165,9 +535,114 @@ impl IsoRingBuilder {
         Ok(result)
     }
 
+    /// Computes isoring for the given fixed-size grid of `values` according to the
+    /// `threshold` value, its size (`H` rows of `W` columns) being the size of the grid
+    /// of this builder.
+    pub(crate) fn compute_fixed<const W: usize, const H: usize>(
+        &mut self,
+        values: &[[Float; W]; H],
+        threshold: Float,
+    ) -> Result<Vec<A::Ring>> {
+        debug_assert_eq!((self.dx, self.dy), (W, H));
+        if !self.is_empty {
+            self.clear();
+        }
+        let mut result = Vec::new();
+        // Whether each sample of a row is inside (the rows outside of the grid being empty)
+        let inside = |y: i64| -> [usize; W] {
+            match usize::try_from(y).ok().and_then(|y| values.get(y)) {
+                Some(row) => std::array::from_fn(|x| (row[x] >= threshold) as usize),
+                None => [0; W],
+            }
+        };
+        let mut top = [0; W];
+        for y in -1..H as i64 {
+            let bottom = inside(y + 1);
+            // The corners of the cells from x = -1 (whose left corners are outside)
+            // to x = W - 1 (whose right corners are outside)
+            let (mut t0, mut t3) = (0, 0);
+            let corners = bottom.iter().zip(&top).chain(std::iter::once((&0, &0)));
+            for (x, (&t1, &t2)) in (-1..).zip(corners) {
+                for line in CASES[t0 | t1 << 1 | t2 << 2 | t3 << 3].iter() {
+                    self.stitch(line, x, y, &mut result)?;
+                }
+                (t0, t3) = (t1, t2);
+            }
+            top = bottom;
+        }
+        self.is_empty = false;
+        Ok(result)
+    }
+
+    /// Stitches the segments of the cells in the given ranges of columns and rows
+    /// (from -1, for the cells straddling the edges of the grid), row by row.
+    fn traverse(
+        &mut self,
+        inside: &impl Fn(usize) -> bool,
+        xs: Range<i64>,
+        ys: Range<i64>,
+        result: &mut Vec<A::Ring>,
+    ) -> Result<()> {
+        let (dx, dy) = (self.dx as i64, self.dy as i64);
+        let at = |x: i64, y: i64| {
+            (x >= 0 && y >= 0 && x < dx && y < dy && inside((y * dx + x) as usize)) as usize
+        };
+        for y in ys {
+            let (mut t0, mut t3) = (at(xs.start, y + 1), at(xs.start, y));
+            for x in xs.clone() {
+                let (t1, t2) = (at(x + 1, y + 1), at(x + 1, y));
+                for line in CASES[t0 | t1 << 1 | t2 << 2 | t3 << 3].iter() {
+                    self.stitch(line, x, y, result)?;
+                }
+                (t0, t3) = (t1, t2);
+            }
+        }
+        Ok(())
+    }
+
+    /// Computes the rings lying in the given range of rows of cells (from -1,
+    /// for the cells straddling the top edge of the grid) according to the `inside`
+    /// predicate, returning the closed rings and the open fragments of the others.
+    pub(crate) fn compute_rows(
+        &mut self,
+        inside: &impl Fn(usize) -> bool,
+        ys: Range<i64>,
+    ) -> Result<Strip<A::Ring>> {
+        self.compute_cells(inside, -1..self.dx as i64, ys)
+    }
+
+    /// Computes the rings lying in the given ranges of columns and rows of cells
+    /// (see [`IsoRingBuilder::compute_rows`]), returning the closed rings
+    /// and the open fragments of the others.
+    pub(crate) fn compute_cells(
+        &mut self,
+        inside: &impl Fn(usize) -> bool,
+        xs: Range<i64>,
+        ys: Range<i64>,
+    ) -> Result<Strip<A::Ring>> {
+        self.clear();
+        let mut result = Vec::new();
+        self.traverse(inside, xs, ys, &mut result)?;
+        let fragments = self
+            .f
+            .drain()
+            .map(|f| Fragment {
+                start: f.start,
+                end: f.end,
+                ring: A::finish(f.ring),
+            })
+            .collect();
+        self.clear();
+        Ok((result, fragments))
+    }
+
     #[allow(clippy::unnecessary_cast)]
     fn index(&self, point: &Pt) -> usize {
-        (point.x as f64 * 2.0 + point.y as f64 * ((self.dx + 1) * 4) as f64) as usize
+        let x = match self.period {
+            Some(period) if point.x >= period as Float + 0.5 => point.x - period as Float,
+            _ => point.x,
+        };
+        (x as f64 * 2.0 + point.y as f64 * ((self.dx + 1) * 4) as f64) as usize
     }
 
     // Stitchs segments to rings.
@@ -176,7 +651,7 @@ impl IsoRingBuilder {
         line: &[Vec<Float>],
         x: i64,
         y: i64,
-        result: &mut Vec<Ring>,
+        result: &mut Vec<A::Ring>,
     ) -> Result<()> {
         let start = Pt {
             x: line[0][0] + x as Float,
@@ -200,12 +675,12 @@ impl IsoRingBuilder {
                     .ok_or_else(|| new_error(ErrorKind::Unexpected))?;
                 if f_ix == g_ix {
                     let mut f = self.f.remove(f_ix);
-                    f.ring.push(end);
-                    result.push(f.ring);
+                    A::push(&mut f.ring, end);
+                    result.push(A::finish(f.ring));
                 } else {
                     let mut f = self.f.remove(f_ix);
                     let g = self.f.remove(g_ix);
-                    f.ring.extend(g.ring);
+                    A::append(&mut f.ring, g.ring);
                     let ix = self.f.insert(Fragment {
                         start: f.start,
                         end: g.end,
@@ -223,7 +698,7 @@ impl IsoRingBuilder {
                     .f
                     .get_mut(f_ix)
                     .ok_or_else(|| new_error(ErrorKind::Unexpected))?;
-                f.ring.push(end);
+                A::push(&mut f.ring, end);
                 f.end = end_index;
                 self.fragment_by_end.insert(end_index, f_ix);
             }
@@ -239,12 +714,12 @@ impl IsoRingBuilder {
                     .ok_or_else(|| new_error(ErrorKind::Unexpected))?;
                 if f_ix == g_ix {
                     let mut f = self.f.remove(f_ix);
-                    f.ring.push(end);
-                    result.push(f.ring);
+                    A::push(&mut f.ring, end);
+                    result.push(A::finish(f.ring));
                 } else {
                     let f = self.f.remove(f_ix);
                     let mut g = self.f.remove(g_ix);
-                    g.ring.extend(f.ring);
+                    A::append(&mut g.ring, f.ring);
                     let ix = self.f.insert(Fragment {
                         start: g.start,
                         end: f.end,
@@ -262,7 +737,7 @@ impl IsoRingBuilder {
                     .f
                     .get_mut(f_ix)
                     .ok_or_else(|| new_error(ErrorKind::Unexpected))?;
-                f.ring.insert(0, start);
+                A::prepend(&mut f.ring, start);
                 f.start = start_index;
                 self.fragment_by_start.insert(start_index, f_ix);
             }
@@ -270,7 +745,7 @@ impl IsoRingBuilder {
             let ix = self.f.insert(Fragment {
                 start: start_index,
                 end: end_index,
-                ring: vec![start, end],
+                ring: self.alloc.new_ring(start, end),
             });
             self.fragment_by_start.insert(start_index, ix);
             self.fragment_by_end.insert(end_index, ix);
@@ -0,0 +1,54 @@
+/// The names of the GeoJSON properties used when converting [`Line`](crate::Line),
+/// [`Contour`](crate::Contour) and [`Band`](crate::Band) to GeoJSON
+/// (e.g. to match the property names expected by existing map styles or tile schemas).
+///
+/// # Example
+///
+/// ```
+/// use contour::GeoJsonKeys;
+///
+/// let keys = GeoJsonKeys {
+///     threshold: "level".into(),
+///     min_v: "lo".into(),
+///     max_v: "hi".into(),
+///     ..Default::default()
+/// };
+/// assert_eq!(keys.max_threshold, "max_threshold");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeoJsonKeys {
+    /// The threshold of lines and contours (`"threshold"` by default)
+    pub threshold: String,
+    /// The next threshold above the one of contours (`"max_threshold"` by default)
+    pub max_threshold: String,
+    /// The minimum value of bands (`"min_v"` by default)
+    pub min_v: String,
+    /// The maximum value of bands (`"max_v"` by default)
+    pub max_v: String,
+    /// The label of bands (`"label"` by default)
+    pub label: String,
+    /// The flags telling which rings of lines are closed depression contours
+    /// (`"depressions"` by default, see [`Line::depressions`](crate::Line::depressions))
+    pub depressions: String,
+    /// The class of lines and contours (`"class"` by default, see
+    /// [`ContourBuilder::index_interval`](crate::ContourBuilder::index_interval))
+    pub class: String,
+    /// The position of the polygon of contours and bands split by polygon (`"part"` by default,
+    /// see [`Contour::into_polygons`](crate::Contour::into_polygons))
+    pub part: String,
+}
+
+impl Default for GeoJsonKeys {
+    fn default() -> Self {
+        GeoJsonKeys {
+            threshold: "threshold".to_string(),
+            max_threshold: "max_threshold".to_string(),
+            min_v: "min_v".to_string(),
+            max_v: "max_v".to_string(),
+            label: "label".to_string(),
+            depressions: "depressions".to_string(),
+            class: "class".to_string(),
+            part: "part".to_string(),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::snap::Snapper;
+use crate::{Band, Contour, Float, Line};
+use std::cmp::Ordering;
+
+/// An item of a [`ContourLayer`].
+#[derive(Debug, Clone, Copy)]
+pub enum LayerItem<'a> {
+    Band(&'a Band),
+    Contour(&'a Contour),
+    Line(&'a Line),
+}
+
+impl LayerItem<'_> {
+    #[cfg(feature = "geojson")]
+    /// Convert the item to a struct from the `geojson` crate.
+    pub fn to_geojson(&self) -> geojson::Feature {
+        self.to_geojson_with_keys(&crate::GeoJsonKeys::default())
+    }
+
+    #[cfg(feature = "geojson")]
+    /// Convert the item to a struct from the `geojson` crate,
+    /// using the given names for its properties.
+    pub fn to_geojson_with_keys(&self, keys: &crate::GeoJsonKeys) -> geojson::Feature {
+        match self {
+            LayerItem::Band(band) => band.to_geojson_with_keys(keys),
+            LayerItem::Contour(contour) => contour.to_geojson_with_keys(keys),
+            LayerItem::Line(line) => line.to_geojson_with_keys(keys),
+        }
+    }
+
+    // The key used to order the items: filled geometries are painted first
+    // (from the lowest value, covering the largest area, to the highest value,
+    // contours before the bands starting at the same value) then lines are painted on top.
+    fn paint_key(&self) -> (u8, Float, u8) {
+        match self {
+            LayerItem::Contour(contour) => (0, contour.threshold(), 0),
+            LayerItem::Band(band) => (0, band.min_v(), 1),
+            LayerItem::Line(line) => (1, line.threshold(), 0),
+        }
+    }
+}
+
+/// A layer bundling lines, contours and bands (possibly computed
+/// using different sets of thresholds) and exposing them in paint order.
+#[derive(Debug, Clone, Default)]
+pub struct ContourLayer {
+    bands: Vec<Band>,
+    contours: Vec<Contour>,
+    lines: Vec<Line>,
+}
+
+impl ContourLayer {
+    /// Constructs a new empty layer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the given bands to the layer.
+    pub fn bands(mut self, bands: impl IntoIterator<Item = Band>) -> Self {
+        self.bands.extend(bands);
+        self
+    }
+
+    /// Adds the given contours to the layer.
+    pub fn contours(mut self, contours: impl IntoIterator<Item = Contour>) -> Self {
+        self.contours.extend(contours);
+        self
+    }
+
+    /// Adds the given lines to the layer.
+    pub fn lines(mut self, lines: impl IntoIterator<Item = Line>) -> Self {
+        self.lines.extend(lines);
+        self
+    }
+
+    /// Returns the number of items in the layer.
+    pub fn len(&self) -> usize {
+        self.bands.len() + self.contours.len() + self.lines.len()
+    }
+
+    /// Returns true if the layer doesn't contain any item.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Snaps the vertices of the items of this layer lying within `tolerance`
+    /// of each other onto identical coordinates, eliminating the hairline gaps
+    /// between features (e.g. between lines and band edges, or between adjacent bands).
+    pub fn snap_vertices(&mut self, tolerance: Float) {
+        let mut snapper = Snapper::new(tolerance);
+        snapper.snap_bands(&mut self.bands);
+        snapper.snap_contours(&mut self.contours);
+        snapper.snap_lines(&mut self.lines);
+    }
+
+    /// Returns the items of the layer in paint order: the filled contours and bands
+    /// first, from the lowest to the highest value (so that the larger polygons are
+    /// painted below the smaller ones), then the lines on top of them.
+    pub fn iter(&self) -> impl Iterator<Item = LayerItem<'_>> {
+        let mut items = self
+            .contours
+            .iter()
+            .map(LayerItem::Contour)
+            .chain(self.bands.iter().map(LayerItem::Band))
+            .chain(self.lines.iter().map(LayerItem::Line))
+            .collect::<Vec<_>>();
+        items.sort_by(|a, b| {
+            let (ka, kb) = (a.paint_key(), b.paint_key());
+            ka.0.cmp(&kb.0)
+                .then(ka.1.partial_cmp(&kb.1).unwrap_or(Ordering::Equal))
+                .then(ka.2.cmp(&kb.2))
+        });
+        items.into_iter()
+    }
+
+    #[cfg(feature = "geojson")]
+    /// Convert the layer to a FeatureCollection from the `geojson` crate,
+    /// with the features in paint order.
+    pub fn to_geojson(&self) -> geojson::FeatureCollection {
+        self.to_geojson_with_keys(&crate::GeoJsonKeys::default())
+    }
+
+    #[cfg(feature = "geojson")]
+    /// Convert the layer to a FeatureCollection from the `geojson` crate,
+    /// with the features in paint order, using the given names for their properties.
+    pub fn to_geojson_with_keys(&self, keys: &crate::GeoJsonKeys) -> geojson::FeatureCollection {
+        geojson::FeatureCollection {
+            bbox: None,
+            features: self
+                .iter()
+                .map(|item| item.to_geojson_with_keys(keys))
+                .collect(),
+            foreign_members: None,
+        }
+    }
+}
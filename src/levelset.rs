@@ -0,0 +1,169 @@
+use crate::area::ring_contains;
+use crate::error::Result;
+use crate::{Contour, ContourBuilder, Float};
+
+/// A connected component of a superlevel set (i.e. one of the polygons
+/// of a [`Contour`]), in a [`LevelSetTree`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component {
+    pub(crate) contour_index: usize,
+    pub(crate) polygon_index: usize,
+    pub(crate) threshold: Float,
+    pub(crate) parent: Option<usize>,
+    pub(crate) children: Vec<usize>,
+}
+
+impl Component {
+    /// Get the position of the contour of this component in [`LevelSetTree::contours`]
+    /// (which is also the position of its threshold in the slice of thresholds).
+    pub fn contour_index(&self) -> usize {
+        self.contour_index
+    }
+
+    /// Get the position of the polygon of this component in the geometry of its contour.
+    pub fn polygon_index(&self) -> usize {
+        self.polygon_index
+    }
+
+    /// Get the threshold of this component.
+    pub fn threshold(&self) -> Float {
+        self.threshold
+    }
+
+    /// Get the component, at the threshold just below, containing this component
+    /// (as an index in [`LevelSetTree::components`]).
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    /// Get the components, at the threshold just above, contained in this component
+    /// (as indexes in [`LevelSetTree::components`]).
+    pub fn children(&self) -> &[usize] {
+        &self.children
+    }
+}
+
+/// An event in the topology of the superlevel sets as the level rises
+/// (the components being given as indexes in [`LevelSetTree::components`]).
+#[derive(Debug, Clone, PartialEq)]
+pub enum LevelEvent {
+    /// A component appears at the lowest threshold
+    /// (or at a threshold where it isn't contained in any component of the previous one).
+    Appear { component: usize },
+    /// A component splits into several components at the next threshold
+    /// (i.e. several components merge as the level falls).
+    Split { component: usize, into: Vec<usize> },
+    /// A component vanishes before the next threshold (there is a peak in it).
+    Vanish { component: usize },
+}
+
+/// The tree of the connected components of the superlevel sets (the regions where
+/// the values are greater than or equal to each threshold), built by
+/// [`ContourBuilder::level_set_tree`]: each component is nested in a component
+/// of the threshold just below (its parent), which gives a merge-tree style summary
+/// of where components appear, split and vanish as the level rises.
+#[derive(Debug, Clone)]
+pub struct LevelSetTree {
+    pub(crate) contours: Vec<Contour>,
+    pub(crate) components: Vec<Component>,
+    pub(crate) events: Vec<LevelEvent>,
+}
+
+impl LevelSetTree {
+    /// Borrow the contours the components belong to (in the order of the thresholds).
+    pub fn contours(&self) -> &[Contour] {
+        &self.contours
+    }
+
+    /// Borrow the components, in ascending order of threshold.
+    pub fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    /// Borrow the events, in ascending order of threshold.
+    pub fn events(&self) -> &[LevelEvent] {
+        &self.events
+    }
+
+    /// Iterate over the components vanishing before the next threshold
+    /// (each of them containing at least one peak).
+    pub fn peaks(&self) -> impl Iterator<Item = &Component> {
+        self.components.iter().filter(|c| c.children.is_empty())
+    }
+}
+
+impl ContourBuilder {
+    /// Computes contours according the given input `values` and the given `thresholds`
+    /// and the tree of their connected components (see [`LevelSetTree`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used
+    ///   (they don't need to be sorted).
+    pub fn level_set_tree(&self, values: &[Float], thresholds: &[Float]) -> Result<LevelSetTree> {
+        let contours = self.contours(values, thresholds)?;
+        let mut order = (0..thresholds.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| thresholds[a].total_cmp(&thresholds[b]));
+
+        let mut components: Vec<Component> = Vec::new();
+        let mut events = Vec::new();
+        // The components of the previous threshold (as indexes in `components`)
+        let mut previous: Vec<usize> = Vec::new();
+        for &ix in &order {
+            let contour = &contours[ix];
+            let current =
+                (components.len()..components.len() + contour.geometry.0.len()).collect::<Vec<_>>();
+            for (polygon_index, polygon) in contour.geometry.0.iter().enumerate() {
+                // The parent is the component of the previous threshold
+                // containing the most vertices of this one
+                let exterior = &polygon.exterior().0;
+                let parent = previous
+                    .iter()
+                    .map(|&p| {
+                        let parent = &contours[components[p].contour_index].geometry.0
+                            [components[p].polygon_index];
+                        let count = exterior
+                            .iter()
+                            .filter(|pt| ring_contains(&parent.exterior().0, pt) != -1)
+                            .count();
+                        (p, count)
+                    })
+                    .filter(|(_, count)| *count > 0)
+                    .max_by_key(|(_, count)| *count)
+                    .map(|(p, _)| p);
+                let index = components.len();
+                match parent {
+                    Some(p) => components[p].children.push(index),
+                    None => events.push(LevelEvent::Appear { component: index }),
+                }
+                components.push(Component {
+                    contour_index: ix,
+                    polygon_index,
+                    threshold: contour.threshold,
+                    parent,
+                    children: Vec::new(),
+                });
+            }
+            previous
+                .iter()
+                .for_each(|&p| match components[p].children.len() {
+                    0 => events.push(LevelEvent::Vanish { component: p }),
+                    1 => {}
+                    _ => events.push(LevelEvent::Split {
+                        component: p,
+                        into: components[p].children.clone(),
+                    }),
+                });
+            previous = current;
+        }
+        previous
+            .iter()
+            .for_each(|&p| events.push(LevelEvent::Vanish { component: p }));
+        Ok(LevelSetTree {
+            contours,
+            components,
+            events,
+        })
+    }
+}
@@ -8,6 +8,10 @@
 //! - contour polygons, as a Vec of [`Contour`],
 //! - isobands, as a Vec of [`Band`].
 //!
+//! The [`ContourBuilder`] can also bin a set of points into a density grid
+//! (see [`ContourBuilder::bin_points`]) or compute a kernel density estimate
+//! (see [`ContourBuilder::kde`]) before computing contours on it.
+//!
 //! The [`contour_rings`] function is a convenience function to compute ring (isoline) coordinates
 //! for a single threshold.
 //!
@@ -55,13 +59,84 @@
 //! [`contour_rings`]: fn.contour_rings.html
 //! [`ContourBuilder`]: struct.ContourBuilder.html
 
+mod approximate;
 mod area;
+#[cfg(feature = "ndarray")]
+mod array;
+mod arrows;
+mod axis;
 mod band;
+mod bbox;
+mod bitplanes;
+mod border;
+mod cache;
+mod categorical;
+mod class;
+mod color;
 mod contour;
 mod contourbuilder;
+mod contourset;
+mod convert;
+mod curvilinear;
+mod density;
+mod depression;
+mod diagnostics;
+mod dual;
+mod duplicates;
 mod error;
+mod fillrule;
+mod geographic;
+#[cfg(feature = "geotiff")]
+mod geotiff;
+#[cfg(feature = "gpkg")]
+mod gpkg;
+mod gradient;
+mod hachures;
+mod index;
+mod infinity;
+mod interpolation;
+mod invariants;
 mod isoringbuilder;
+#[cfg(feature = "geojson")]
+mod keys;
+mod layer;
+mod levelset;
 mod line;
+mod lookup;
+#[cfg(feature = "image")]
+mod luma;
+mod mask;
+mod merge;
+mod metadata;
+mod mosaic;
+mod nodata;
+mod order;
+mod overviews;
+mod padding;
+mod periodic;
+mod presets;
+mod profile;
+#[cfg(feature = "tiny-skia")]
+mod render;
+mod resample;
+mod sampler;
+mod sanitize;
+mod snap;
+mod source;
+mod sparse;
+mod stats;
+mod streaming;
+#[cfg(feature = "geojson")]
+mod style;
+mod tabular;
+mod ties;
+mod tiled;
+mod topology;
+mod tracking;
+mod validity;
+mod weights;
+#[cfg(feature = "geojson")]
+mod writer;
 
 #[cfg(feature = "f32")]
 pub type Float = f32;
@@ -74,18 +149,88 @@ pub type Pt = geo_types::Coord;
 
 pub type Ring = Vec<Pt>;
 
+pub use crate::arrows::{Arrow, FlowDirection};
 pub use crate::band::Band;
+pub use crate::bitplanes::BitPlanes;
+pub use crate::border::BorderPolicy;
+pub use crate::categorical::ClassRegion;
+pub use crate::class::ContourClass;
+pub use crate::color::{ColorRamp, Rgba};
 pub use crate::contour::Contour;
 pub use crate::contourbuilder::ContourBuilder;
+pub use crate::contourset::{BuilderConfig, ContourSet, Location};
+pub use crate::convert::{try_grid_from, GridValue, TryToFloat};
+pub use crate::density::Bandwidth;
+pub use crate::diagnostics::{Diagnostics, ThresholdDiagnostics, Warning};
+pub use crate::dual::VertexPlacement;
+pub use crate::duplicates::DuplicatePolicy;
 pub use crate::error::{Error, ErrorKind, Result};
-pub use crate::isoringbuilder::contour_rings;
+pub use crate::fillrule::FillRule;
+pub use crate::geographic::Densify;
+#[cfg(feature = "gpkg")]
+pub use crate::gpkg::{write_gpkg, write_gpkg_with_srs};
+pub use crate::gradient::{Gradient, GradientOperator};
+pub use crate::infinity::InfinityPolicy;
+pub use crate::interpolation::Interpolation;
+pub use crate::invariants::{random_grid, InvariantIssue, InvariantReport, InvariantViolation};
+#[cfg(feature = "bumpalo")]
+pub use crate::isoringbuilder::contour_rings_in;
+pub use crate::isoringbuilder::{
+    cell_cases, contour_rings, contour_rings_fixed, contour_rings_from_source,
+};
+#[cfg(feature = "geojson")]
+pub use crate::keys::GeoJsonKeys;
+pub use crate::layer::{ContourLayer, LayerItem};
+pub use crate::levelset::{Component, LevelEvent, LevelSetTree};
 pub use crate::line::Line;
+pub use crate::lookup::{ThresholdLookup, ThresholdRange};
+pub use crate::merge::merge_results;
+pub use crate::metadata::{Metadata, MetadataValue};
+pub use crate::mosaic::{Mosaic, OverlapRule};
+pub use crate::nodata::NodataSmoothing;
+pub use crate::order::GridOrder;
+pub use crate::overviews::Overviews;
+pub use crate::padding::Padding;
+pub use crate::presets::Registration;
+pub use crate::profile::{crossings, Crossing};
+#[cfg(feature = "tiny-skia")]
+pub use crate::render::render_png;
+pub use crate::sampler::GridSampler;
+pub use crate::sanitize::{sanitize, InvalidKind, InvalidValue, SanitizePolicy, SanitizeReport};
+pub use crate::source::{GridSource, StridedGrid};
+pub use crate::sparse::SparseGrid;
+pub use crate::stats::{std_dev_thresholds, GridStats};
+pub use crate::streaming::StreamingContours;
+#[cfg(feature = "geojson")]
+pub use crate::style::MapLibreStyle;
+pub use crate::tabular::CsvWriter;
+pub use crate::ties::TiePolicy;
+pub use crate::tiled::TiledContours;
+pub use crate::tracking::{ContourTracker, TrackEvent, TrackLink, TrackedFrame};
+pub use crate::validity::{ValidityIssue, ValidityReport};
+#[cfg(feature = "geojson")]
+pub use crate::writer::{GeoJsonLayout, GeoJsonWriter};
 
 #[cfg(test)]
 mod tests {
-    use crate::{ContourBuilder, Float};
+    use crate::{
+        random_grid, Bandwidth, BorderPolicy, ColorRamp, ContourBuilder, ContourLayer, CsvWriter,
+        Float, InvariantIssue, InvariantReport, InvariantViolation, LayerItem, Metadata, Rgba,
+    };
     use geo_types::{line_string, polygon, MultiLineString, MultiPolygon};
 
+    /// A 4 x 4 grid of zeros with a square of ones in its center.
+    fn square() -> Vec<Float> {
+        #[rustfmt::skip]
+        let values = vec![
+            0., 0., 0., 0.,
+            0., 1., 1., 0.,
+            0., 1., 1., 0.,
+            0., 0., 0., 0.,
+        ];
+        values
+    }
+
     #[test]
     fn test_empty_polygons() {
         let c = ContourBuilder::new(10, 10, true);
@@ -741,4 +886,3952 @@ mod tests {
             _ => panic!(""),
         };
     }
+
+    #[test]
+    fn test_bin_points() {
+        let c = ContourBuilder::new(4, 3, false)
+            .x_origin(10.)
+            .y_origin(20.)
+            .x_step(2.)
+            .y_step(-1.);
+        let grid = c.bin_points(&[
+            (10.5, 19.5),
+            (11.9, 19.1),
+            (17.5, 17.5),
+            (9.9, 19.5),
+            (18.1, 19.5),
+        ]);
+        #[rustfmt::skip]
+        assert_eq!(grid, vec![
+            2., 0., 0., 0.,
+            0., 0., 0., 0.,
+            0., 0., 0., 1.,
+        ]);
+        let grid = c.bin_weighted_points(&[(10.5, 19.5, 2.5), (11.9, 19.1, 0.5)]);
+        assert_eq!(grid[0], 3.);
+        let res = ContourBuilder::new(4, 3, false)
+            .contours(&grid, &[1.])
+            .unwrap();
+        assert_eq!(res[0].geometry().0.len(), 1);
+    }
+
+    #[test]
+    fn test_kde_bandwidth() {
+        let points = (0..50)
+            .map(|i| ((i % 7) as Float + 5., (i % 5) as Float + 5.))
+            .collect::<Vec<_>>();
+        let (sx, sy) = Bandwidth::Scott.estimate(&points);
+        let (vx, vy) = Bandwidth::Silverman.estimate(&points);
+        let (cx, cy) = Bandwidth::CrossValidation.estimate(&points);
+        assert!(sx > sy && sy > 0.);
+        assert!(vx > 0. && vy > 0.);
+        assert!(cx > 0. && cy > 0.);
+        assert_eq!(Bandwidth::Fixed(2.).estimate(&points), (2., 2.));
+        assert_eq!(Bandwidth::Scott.estimate(&[(1., 1.)]), (1., 1.));
+        for h in [0., -2., Float::NAN] {
+            assert_eq!(Bandwidth::Fixed(h).estimate(&points), (1., 1.));
+            let grid = ContourBuilder::new(20, 20, true).kde(&points, Bandwidth::Fixed(h));
+            assert!(grid.iter().all(|v| v.is_finite()));
+        }
+
+        let c = ContourBuilder::new(20, 20, true);
+        let grid = c.kde(&points, Bandwidth::Scott);
+        let total = grid.iter().sum::<Float>();
+        assert!((total - 1.).abs() < 0.05);
+        let max = grid.iter().cloned().fold(0., Float::max);
+        let res = c.contours(&grid, &[max / 2.]).unwrap();
+        assert_eq!(res[0].geometry().0.len(), 1);
+    }
+
+    #[test]
+    fn test_threshold_metadata() {
+        let c = ContourBuilder::new(4, 4, false);
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0.,
+            0., 1., 2., 0.,
+            0., 1., 2., 0.,
+            0., 0., 0., 0.,
+        ];
+        let levels = [0.5, 1.5, 2.5]
+            .iter()
+            .enumerate()
+            .map(|(i, t)| {
+                let mut m = Metadata::new();
+                m.insert("label".to_string(), format!("level {}", i).into());
+                m.insert("style_id".to_string(), (i as i64).into());
+                m.insert("threshold".to_string(), "reserved".into());
+                m.insert("min_v".to_string(), true.into());
+                (*t, m)
+            })
+            .collect::<Vec<_>>();
+        let contours = c.contours_with_metadata(&values, &levels).unwrap();
+        assert_eq!(contours.len(), 3);
+        assert_eq!(contours[1].threshold(), 1.5);
+        assert_eq!(contours[1].metadata(), &levels[1].1);
+        let lines = c.lines_with_metadata(&values, &levels).unwrap();
+        assert_eq!(lines[2].metadata(), &levels[2].1);
+        let bands = c.isobands_with_metadata(&values, &levels).unwrap();
+        assert_eq!(bands.len(), 2);
+        assert_eq!(bands[0].metadata(), &levels[0].1);
+        assert_eq!(bands[1].metadata(), &levels[1].1);
+        #[cfg(feature = "geojson")]
+        {
+            let feature = contours[1].to_geojson();
+            assert_eq!(
+                feature.property("label"),
+                Some(&serde_json::json!("level 1"))
+            );
+            assert_eq!(feature.property("style_id"), Some(&serde_json::json!(1.)));
+            assert_eq!(feature.property("threshold"), Some(&serde_json::json!(1.5)));
+            assert_eq!(feature.property("min_v"), Some(&serde_json::json!(true)));
+            let feature = bands[1].to_geojson();
+            assert_eq!(feature.property("min_v"), Some(&serde_json::json!(1.5)));
+            assert_eq!(
+                feature.property("threshold"),
+                Some(&serde_json::json!("reserved"))
+            );
+        }
+    }
+
+    #[test]
+    fn test_color_ramp() {
+        assert_eq!(ColorRamp::Viridis.color_at(0.).to_hex(), "#440154");
+        assert_eq!(ColorRamp::Viridis.color_at(1.).to_hex(), "#fde725");
+        assert_eq!(ColorRamp::Turbo.color_at(2.).to_hex(), "#7a0403");
+        let ramp = ColorRamp::Stops(vec![
+            (0., Rgba::rgb(0, 0, 0)),
+            (1., Rgba::from_hex("#ffffff80").unwrap()),
+        ]);
+        assert_eq!(
+            ramp.color_at(0.5),
+            Rgba {
+                r: 128,
+                g: 128,
+                b: 128,
+                a: 192
+            }
+        );
+        assert_eq!(Rgba::from_hex("#12345"), None);
+
+        let c = ContourBuilder::new(4, 4, false);
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0.,
+            0., 1., 2., 0.,
+            0., 1., 2., 0.,
+            0., 0., 0., 0.,
+        ];
+        let bands = c.isobands(&values, &[0., 1., 2., 3.]).unwrap();
+        let colors = ramp.band_colors(&bands);
+        assert_eq!(colors[0].r, 43);
+        assert_eq!(colors[1].r, 128);
+        assert_eq!(colors[2].r, 213);
+    }
+
+    #[cfg(feature = "tiny-skia")]
+    #[test]
+    fn test_render_png() {
+        let c = ContourBuilder::new(4, 4, true);
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0.,
+            0., 1., 2., 0.,
+            0., 1., 2., 0.,
+            0., 0., 0., 0.,
+        ];
+        let bands = c.isobands(&values, &[0., 1., 2., 3.]).unwrap();
+        let path = std::env::temp_dir().join("contour_test_render.png");
+        crate::render_png(&path, &bands, 64, 64).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[1..4], b"PNG");
+        std::fs::remove_file(&path).unwrap();
+        assert!(crate::render_png(&path, &bands, 0, 64).is_err());
+    }
+
+    #[test]
+    fn test_layer_paint_order() {
+        let c = ContourBuilder::new(4, 4, false);
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0.,
+            0., 1., 2., 0.,
+            0., 1., 2., 0.,
+            0., 0., 0., 0.,
+        ];
+        let layer = ContourLayer::new()
+            .lines(c.lines(&values, &[1.5, 0.5]).unwrap())
+            .bands(c.isobands(&values, &[1., 2., 3.]).unwrap())
+            .contours(c.contours(&values, &[2., 0.5]).unwrap());
+        assert_eq!(layer.len(), 6);
+        let order = layer
+            .iter()
+            .map(|item| match item {
+                LayerItem::Band(b) => ("band", b.min_v()),
+                LayerItem::Contour(c) => ("contour", c.threshold()),
+                LayerItem::Line(l) => ("line", l.threshold()),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            vec![
+                ("contour", 0.5),
+                ("band", 1.),
+                ("contour", 2.),
+                ("band", 2.),
+                ("line", 0.5),
+                ("line", 1.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_contour_set() {
+        let c = ContourBuilder::new(4, 4, true).x_origin(10.).x_step(2.);
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0.,
+            0., 1., 2., 0.,
+            0., 1., 2., 0.,
+            0., 0., 0., 0.,
+        ];
+        let set = c.contour_set(&values, &[0.5, 1.5]).unwrap();
+        assert_eq!(set.lines().len(), 2);
+        assert_eq!(set.contours().len(), 2);
+        assert_eq!(set.bands().len(), 1);
+        assert_eq!(set.config().x_origin, 10.);
+        assert!(set.matches(&c, &[0.5, 1.5]));
+        assert!(!set.matches(&c, &[0.5]));
+        assert!(!set.matches(&ContourBuilder::new(4, 4, true), &[0.5, 1.5]));
+        assert!(set.matches(&set.builder(), &[0.5, 1.5]));
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&set).unwrap();
+            let set2: crate::ContourSet = serde_json::from_str(&json).unwrap();
+            assert!(set2.matches(&c, &[0.5, 1.5]));
+            assert_eq!(set2.contours()[1].geometry(), set.contours()[1].geometry());
+        }
+    }
+
+    #[test]
+    fn test_builder_getters() {
+        let c = ContourBuilder::new(4, 3, true)
+            .x_origin(10.)
+            .y_origin(20.)
+            .x_step(2.)
+            .y_step(-1.);
+        assert_eq!((c.dx(), c.dy(), c.is_smooth()), (4, 3, true));
+        assert_eq!(c.origin(), (10., 20.));
+        assert_eq!(c.step(), (2., -1.));
+        let c2 = c.clone();
+        assert_eq!(c, c2);
+        assert_ne!(c, c2.x_step(3.));
+        assert!(format!("{:?}", c).starts_with("ContourBuilder"));
+    }
+
+    #[test]
+    fn test_contour_set_locate() {
+        let c = ContourBuilder::new(6, 6, false);
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 1., 0.,
+            0., 1., 2., 2., 1., 0.,
+            0., 1., 2., 2., 1., 0.,
+            0., 1., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0., 0.,
+        ];
+        let set = c.contour_set(&values, &[0.5, 1.5, 2.5]).unwrap();
+        let loc = set.locate(3., 3.);
+        assert_eq!(
+            loc.contours
+                .iter()
+                .map(|c| c.threshold())
+                .collect::<Vec<_>>(),
+            vec![0.5, 1.5]
+        );
+        assert_eq!(loc.band.unwrap().min_v(), 1.5);
+        let loc = set.locate(1.5, 1.5);
+        assert_eq!(loc.contours.len(), 1);
+        assert_eq!(loc.band.unwrap().min_v(), 0.5);
+        let loc = set.locate(0.2, 0.2);
+        assert!(loc.contours.is_empty());
+        assert!(loc.band.is_none());
+        assert!(set.locate(100., 100.).band.is_none());
+    }
+
+    #[test]
+    fn test_crossings() {
+        let c = ContourBuilder::new(6, 6, false);
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 1., 0.,
+            0., 1., 2., 2., 1., 0.,
+            0., 1., 2., 2., 1., 0.,
+            0., 1., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0., 0.,
+        ];
+        let set = c.contour_set(&values, &[0.5, 1.5]).unwrap();
+        let res = set.crossings(&line_string![(x: 0., y: 3.2), (x: 3., y: 3.2), (x: 6., y: 3.2)]);
+        assert_eq!(
+            res.iter().map(|c| c.threshold).collect::<Vec<_>>(),
+            vec![0.5, 1.5, 1.5, 0.5]
+        );
+        assert_eq!(
+            res.iter().map(|c| c.distance).collect::<Vec<_>>(),
+            vec![1., 2., 4., 5.]
+        );
+        assert_eq!(res[0].point, crate::Pt { x: 1., y: 3.2 });
+    }
+
+    #[test]
+    fn test_contour_mask() {
+        let c = ContourBuilder::new(6, 5, false).x_step(2.).y_origin(10.);
+        #[rustfmt::skip]
+        let mask = [
+            false, false, false, false, false, false,
+            false, true,  true,  true,  false, false,
+            false, true,  false, true,  false, true,
+            false, true,  true,  true,  false, false,
+            false, false, false, false, false, false,
+        ];
+        let values = mask.iter().map(|&b| b as u8 as Float).collect::<Vec<_>>();
+        let res = c.contour_mask(&mask).unwrap();
+        let expected = c.contours(&values, &[0.5]).unwrap();
+        assert_eq!(res.geometry(), expected[0].geometry());
+        assert_eq!(res.geometry().0.len(), 2);
+        assert_eq!(
+            res.geometry()
+                .0
+                .iter()
+                .map(|p| p.interiors().len())
+                .sum::<usize>(),
+            1
+        );
+        assert!(c.contour_mask(&mask[1..]).is_err());
+    }
+
+    #[test]
+    fn test_preserve_topology() {
+        // Pseudo-random grids with many saddles, for which the smoothing can make
+        // some rings touch or cross each other
+        let mut seed = 42u64;
+        let mut rand = || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            ((seed >> 33) % 5) as Float / 4.
+        };
+        let mut broken = 0;
+        for _ in 0..200 {
+            let values = (0..64).map(|_| rand()).collect::<Vec<_>>();
+            let smoothed = ContourBuilder::new(8, 8, true)
+                .lines(&values, &[0.5])
+                .unwrap();
+            let rings = smoothed[0]
+                .geometry()
+                .0
+                .iter()
+                .map(|l| l.0.clone())
+                .collect::<Vec<_>>();
+            if !crate::topology::find_intersections(&rings).is_empty() {
+                broken += 1;
+            }
+            let preserved = ContourBuilder::new(8, 8, true)
+                .preserve_topology(true)
+                .lines(&values, &[0.5])
+                .unwrap();
+            let rings = preserved[0]
+                .geometry()
+                .0
+                .iter()
+                .map(|l| l.0.clone())
+                .collect::<Vec<_>>();
+            assert!(crate::topology::find_intersections(&rings).is_empty());
+        }
+        assert!(broken > 0);
+
+        // The rings of the different thresholds don't cross each other either,
+        // so that the isobands lie between the contours
+        let builder = ContourBuilder::new(9, 7, true).preserve_topology(true);
+        let report = builder.fuzz_invariants(1, 200).unwrap();
+        assert!(report.is_ok(), "{}", report);
+    }
+
+    #[test]
+    fn test_snap_vertices() {
+        let c = ContourBuilder::new(6, 6, true);
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 1., 0.,
+            0., 1., 2., 2.3, 1., 0.,
+            0., 1., 2.7, 2., 1., 0.,
+            0., 1., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0., 0.,
+        ];
+        let mut set = c.contour_set(&values, &[0.5, 1.5, 2.5]).unwrap();
+        // Slightly shift the lines to simulate hairline gaps
+        set.lines.iter_mut().for_each(|l| {
+            l.geometry.0.iter_mut().for_each(|ls| {
+                ls.0.iter_mut().for_each(|pt| pt.x += 1e-6);
+            })
+        });
+        let band_vertices = set.bands()[0]
+            .geometry()
+            .0
+            .iter()
+            .flat_map(|p| p.exterior().0.iter().copied())
+            .collect::<Vec<_>>();
+        assert!(!band_vertices.contains(&set.lines()[0].geometry().0[0].0[0]));
+        set.snap_vertices(1e-3);
+        let band_vertices = set.bands()[0]
+            .geometry()
+            .0
+            .iter()
+            .flat_map(|p| p.exterior().0.iter().copied())
+            .collect::<Vec<_>>();
+        assert!(set.lines()[0]
+            .geometry()
+            .0
+            .iter()
+            .flat_map(|ls| ls.0.iter())
+            .all(|pt| band_vertices.contains(pt)));
+    }
+
+    #[test]
+    fn test_lattice_coordinates() {
+        let c = ContourBuilder::new(4, 4, false)
+            .x_origin(0.5)
+            .y_origin(-0.75)
+            .x_step(0.25)
+            .y_step(0.375)
+            .lattice_coordinates(true);
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0.,
+            0., 1., 2., 0.,
+            0., 1., 2., 0.,
+            0., 0., 0., 0.,
+        ];
+        let rings = c.lattice_rings(&values, 0.5).unwrap();
+        assert_eq!(rings.len(), 1);
+        assert!(rings[0].contains(&[2, 3]));
+        let lines = c.lines(&values, &[0.5]).unwrap();
+        let first = lines[0].geometry().0[0].0[0];
+        let [lx, ly] = rings[0][0];
+        assert_eq!(first.x, 0.5 + lx as Float * 0.125);
+        assert_eq!(first.y, -0.75 + ly as Float * 0.1875);
+        let contours = c.contours(&values, &[0.5]).unwrap();
+        assert_eq!(contours[0].geometry().0[0].exterior().0[0], first);
+    }
+
+    #[test]
+    fn test_contour_max_threshold() {
+        let c = ContourBuilder::new(4, 4, false);
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0.,
+            0., 1., 2., 0.,
+            0., 1., 2., 0.,
+            0., 0., 0., 0.,
+        ];
+        let res = c.contours(&values, &[0.5, 1.5, 1., 2.5]).unwrap();
+        assert_eq!(
+            res.iter().map(|c| c.max_threshold()).collect::<Vec<_>>(),
+            vec![Some(1.), Some(2.5), Some(1.5), None]
+        );
+        let res = c.contours(&values, &[0.5]).unwrap();
+        assert_eq!(res[0].max_threshold(), None);
+        #[cfg(feature = "geojson")]
+        {
+            let res = c.contours(&values, &[0.5, 1.5]).unwrap();
+            assert_eq!(
+                res[0].to_geojson().property("max_threshold"),
+                Some(&serde_json::json!(1.5))
+            );
+            assert_eq!(res[1].to_geojson().property("max_threshold"), None);
+        }
+    }
+
+    #[test]
+    fn test_unsorted_thresholds() {
+        let c = ContourBuilder::new(4, 4, false);
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0.,
+            0., 1., 2., 0.,
+            0., 1., 2., 0.,
+            0., 0., 0., 0.,
+        ];
+        let sorted = c.isobands(&values, &[0.5, 1.5, 2.5]).unwrap();
+        let unsorted = c.isobands(&values, &[1.5, 2.5, 0.5]).unwrap();
+        assert_eq!(unsorted.len(), 2);
+        for (a, b) in sorted.iter().zip(unsorted.iter()) {
+            assert_eq!(a.geometry(), b.geometry());
+            assert_eq!((a.min_v(), a.max_v()), (b.min_v(), b.max_v()));
+        }
+        assert_eq!(unsorted[0].threshold_index(), 2);
+        assert_eq!(unsorted[1].threshold_index(), 0);
+        let lines = c.lines(&values, &[1.5, 0.5]).unwrap();
+        assert_eq!(lines[1].threshold_index(), 1);
+        let contours = c.contours(&values, &[1.5, 0.5]).unwrap();
+        assert_eq!(contours[1].threshold_index(), 1);
+        assert_eq!(contours[1].threshold(), 0.5);
+    }
+
+    #[test]
+    fn test_merge_results() {
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 1., 1., 1., 0.,
+            0., 1., 2., 2., 1., 2., 1., 0.,
+            0., 1., 2., 2., 2., 2., 1., 0.,
+            0., 1., 1., 1., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0.,
+        ];
+        let thresholds = [0.5, 1.5];
+        for smooth in [false, true] {
+            let c = ContourBuilder::new(8, 6, smooth);
+            let expected = c.contour_set(&values, &thresholds).unwrap();
+            // Split the grid in two tiles overlapping by one column
+            let tile = |from: usize, to: usize| {
+                let v = (0..6)
+                    .flat_map(|row| values[row * 8 + from..row * 8 + to].to_vec())
+                    .collect::<Vec<_>>();
+                ContourBuilder::new(to - from, 6, smooth)
+                    .x_origin(from as Float)
+                    .contour_set(&v, &thresholds)
+                    .unwrap()
+            };
+            let merged = crate::merge_results(&[tile(0, 5), tile(4, 8)], None);
+            assert_eq!(merged.config().dx, 8);
+            assert_eq!(merged.config().dy, 6);
+            assert_eq!(merged.contours().len(), 2);
+            assert_eq!(merged.bands().len(), 1);
+            let area = |mp: &geo_types::MultiPolygon<Float>| {
+                mp.0.iter()
+                    .map(|p| {
+                        crate::area::area(&p.exterior().0)
+                            + p.interiors()
+                                .iter()
+                                .map(|r| crate::area::area(&r.0))
+                                .sum::<f64>()
+                    })
+                    .sum::<f64>()
+            };
+            for (a, b) in merged.contours().iter().zip(expected.contours()) {
+                assert_eq!(a.geometry().0.len(), b.geometry().0.len());
+                assert_eq!(
+                    a.geometry().0[0].interiors().len(),
+                    b.geometry().0[0].interiors().len()
+                );
+                assert!((area(a.geometry()) - area(b.geometry())).abs() < 1e-4);
+            }
+            let (a, b) = (&merged.bands()[0], &expected.bands()[0]);
+            assert_eq!(a.geometry().0.len(), b.geometry().0.len());
+            assert!((area(a.geometry()) - area(b.geometry())).abs() < 1e-4);
+            for (a, b) in merged.lines().iter().zip(expected.lines()) {
+                assert_eq!(a.geometry().0.len(), b.geometry().0.len());
+                assert_eq!(a.geometry().0[0].0.len(), b.geometry().0[0].0.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_tile_edge_precision() {
+        // The ones reach the right edge, shared with the next tile
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0.,
+            0., 1., 1., 1.,
+            0., 1., 1., 1.,
+            0., 0., 0., 0.,
+        ];
+        // Neighbouring tiles whose origins are computed with rounding errors
+        let (left, right) = (0.1 * 3., 0.1 * 19.);
+        let step = 0.4;
+        let edge = |x_origin: Float, precision: Option<Float>| {
+            let res = ContourBuilder::new(4, 4, true)
+                .x_origin(x_origin)
+                .x_step(step)
+                .y_origin(0.7)
+                .y_step(0.1)
+                .tile_edge_precision(precision)
+                .isobands(&values, &[-1., 0.5])
+                .unwrap();
+            res[0].geometry().0[0]
+                .exterior()
+                .0
+                .iter()
+                .copied()
+                .filter(|p| (p.x - 1.9).abs() < 1e-3)
+                .map(|p| (p.x, p.y))
+                .collect::<Vec<_>>()
+        };
+        assert_ne!(edge(left, None), edge(right, None));
+        let mut a = edge(left, Some(1e-6));
+        let mut b = edge(right, Some(1e-6));
+        assert!(!a.is_empty());
+        a.sort_by(|p, q| p.1.total_cmp(&q.1));
+        b.sort_by(|p, q| p.1.total_cmp(&q.1));
+        a.dedup();
+        b.dedup();
+        assert_eq!(a, b);
+    }
+
+    #[cfg(feature = "bumpalo")]
+    #[test]
+    fn test_contour_rings_in() {
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0.,
+            0., 1., 1., 0.,
+            0., 1., 0., 0.,
+            0., 0., 0., 1.,
+        ];
+        let mut bump = bumpalo::Bump::new();
+        for _ in 0..2 {
+            let rings = crate::contour_rings_in(&values, 0.5, 4, 4, &bump).unwrap();
+            let expected = crate::contour_rings(&values, 0.5, 4, 4).unwrap();
+            assert_eq!(rings.len(), expected.len());
+            for (a, b) in rings.iter().zip(expected.iter()) {
+                assert_eq!(a.as_slice(), b.as_slice());
+            }
+            drop(rings);
+            bump.reset();
+        }
+    }
+
+    #[test]
+    fn test_ring_cache() {
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 1., 2., 1., 0.,
+            0., 2., 3., 2., 0.,
+            0., 1., 2., 1., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let c = ContourBuilder::new(5, 5, true).ring_cache(true);
+        let expected = ContourBuilder::new(5, 5, true)
+            .contours(&values, &[0.5, 1.5, 2.5])
+            .unwrap();
+        let res = c.contours(&values, &[0.5, 1.5]).unwrap();
+        assert_eq!(c.cache.as_ref().unwrap().len(), 2);
+        // Only the new threshold is computed
+        let res2 = c.isobands(&values, &[0.5, 1.5, 2.5]).unwrap();
+        assert_eq!(c.cache.as_ref().unwrap().len(), 3);
+        assert_eq!(res2.len(), 2);
+        let res3 = c.contours(&values, &[0.5, 1.5, 2.5]).unwrap();
+        assert_eq!(c.cache.as_ref().unwrap().len(), 3);
+        for (a, b) in res3.iter().zip(expected.iter()) {
+            assert_eq!(a.geometry(), b.geometry());
+        }
+        assert_eq!(res[1].geometry(), expected[1].geometry());
+        // A different grid doesn't reuse the cached rings
+        let other = values.map(|v| v * 2.);
+        c.lines(&other, &[0.5]).unwrap();
+        assert_eq!(c.cache.as_ref().unwrap().len(), 4);
+        c.clear_cache();
+        assert_eq!(c.cache.as_ref().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_threads() {
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 1., 2., 1., 0.,
+            0., 2., 3., 2., 0.,
+            0., 1., 2., 1., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let thresholds = [2.5, 0.5, 1.5, 0.25, 1.75];
+        let c = ContourBuilder::new(5, 5, true);
+        for threads in [2, 3, 8] {
+            let p = c.clone().threads(threads);
+            let (a, b) = (
+                c.lines(&values, &thresholds).unwrap(),
+                p.lines(&values, &thresholds).unwrap(),
+            );
+            assert!(a
+                .iter()
+                .zip(&b)
+                .all(|(a, b)| a.geometry() == b.geometry() && a.threshold() == b.threshold()));
+            let (a, b) = (
+                c.contours(&values, &thresholds).unwrap(),
+                p.contours(&values, &thresholds).unwrap(),
+            );
+            assert!(a.iter().zip(&b).all(|(a, b)| a.geometry() == b.geometry()
+                && a.threshold_index() == b.threshold_index()
+                && a.max_threshold() == b.max_threshold()));
+            let (a, b) = (
+                c.isobands(&values, &thresholds).unwrap(),
+                p.isobands(&values, &thresholds).unwrap(),
+            );
+            assert_eq!(a.len(), b.len());
+            assert!(a
+                .iter()
+                .zip(&b)
+                .all(|(a, b)| a.geometry() == b.geometry()
+                    && a.threshold_index() == b.threshold_index()));
+        }
+    }
+
+    #[test]
+    fn test_try_grid_from() {
+        use crate::{try_grid_from, ErrorKind};
+
+        assert_eq!(
+            try_grid_from(&[0_u8, 255]).unwrap(),
+            vec![0. as Float, 255.]
+        );
+        assert_eq!(try_grid_from(&[-1.5_f32]).unwrap(), vec![-1.5 as Float]);
+        assert!(try_grid_from(&[Float::NAN]).unwrap()[0].is_nan());
+        let err = try_grid_from(&[0, 1, u64::MAX]).unwrap_err();
+        match err.kind() {
+            ErrorKind::BadCast { index, value } => {
+                assert_eq!(*index, 2);
+                assert_eq!(value, &u64::MAX.to_string());
+            }
+            _ => panic!("unexpected error kind"),
+        }
+        assert!(err.to_string().contains("at index 2"));
+        #[cfg(feature = "f32")]
+        assert!(matches!(
+            try_grid_from(&[0.5_f64, 0.1]).unwrap_err().kind(),
+            ErrorKind::BadCast { index: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_lines_and_contours() {
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0., 0.,
+            0., 1., 2., 1., 0., 0.,
+            0., 2., 3., 2., 0., 0.,
+            0., 1., 2., 1., 1., 0.,
+            0., 0., 0., 1., 2., 0.,
+            0., 0., 0., 0., 0., 0.,
+        ];
+        let c = ContourBuilder::new(6, 6, true).x_step(0.3).y_origin(-2.);
+        let thresholds = [0.5, 1.5, 2.5];
+        let (lines, contours) = c.lines_and_contours(&values, &thresholds).unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(contours.len(), 3);
+        for (line, contour) in lines.iter().zip(&contours) {
+            assert_eq!(line.threshold_index(), contour.threshold_index());
+            // Each isoline is exactly one of the rings of the contour
+            let mut rings = contour
+                .geometry()
+                .0
+                .iter()
+                .flat_map(|p| std::iter::once(p.exterior()).chain(p.interiors()))
+                .collect::<Vec<_>>();
+            assert_eq!(rings.len(), line.geometry().0.len());
+            for ls in &line.geometry().0 {
+                let i = rings.iter().position(|r| *r == ls).unwrap();
+                rings.swap_remove(i);
+            }
+        }
+        for (a, b) in lines.iter().zip(c.lines(&values, &thresholds).unwrap()) {
+            assert_eq!(a.geometry(), b.geometry());
+        }
+    }
+
+    #[test]
+    fn test_densify() {
+        use crate::Densify;
+
+        let values = square();
+        let c = ContourBuilder::new(4, 4, false)
+            .x_origin(-180.)
+            .x_step(90.)
+            .y_origin(-80.)
+            .y_step(40.);
+        let plain = c.lines(&values, &[0.5]).unwrap();
+        let linear = c
+            .clone()
+            .densify(Some(Densify::Linear(10.)))
+            .lines(&values, &[0.5])
+            .unwrap();
+        let (a, b) = (&plain[0].geometry().0[0].0, &linear[0].geometry().0[0].0);
+        assert!(b.len() > a.len());
+        assert_eq!(a[0], b[0]);
+        assert!(b
+            .windows(2)
+            .all(|w| ((w[1].x - w[0].x).powi(2) + (w[1].y - w[0].y).powi(2)).sqrt() <= 10.001));
+        // Along a parallel, the great circle goes closer to the pole
+        let great_circle = c
+            .densify(Some(Densify::GreatCircle(5.)))
+            .lines(&values, &[0.5])
+            .unwrap();
+        let ring = &great_circle[0].geometry().0[0].0;
+        assert!(ring.len() > a.len());
+        let max_lat = ring
+            .iter()
+            .map(|p| p.y)
+            .fold(Float::NEG_INFINITY, Float::max);
+        assert!(max_lat > 40.);
+        assert!(ring.iter().all(|p| p.x >= -180. && p.x <= 180.));
+    }
+
+    #[test]
+    fn test_split_antimeridian() {
+        let values = square();
+        let c = ContourBuilder::new(4, 4, false)
+            .x_origin(90.)
+            .x_step(60.)
+            .y_origin(-60.)
+            .y_step(30.);
+        let area = |mp: &MultiPolygon<Float>| {
+            mp.0.iter()
+                .map(|p| crate::area::area(&p.exterior().0).abs())
+                .sum::<f64>()
+        };
+        let whole = c.contours(&values, &[0.5]).unwrap();
+        assert_eq!(whole[0].geometry().0.len(), 1);
+        let c = c.split_antimeridian(true);
+        let split = c.contours(&values, &[0.5]).unwrap();
+        let polygons = &split[0].geometry().0;
+        assert_eq!(polygons.len(), 2);
+        assert!(polygons
+            .iter()
+            .flat_map(|p| p.exterior().0.iter())
+            .all(|p| p.x >= -180. && p.x <= 180.));
+        assert!((area(whole[0].geometry()) - area(split[0].geometry())).abs() < 1e-6);
+        let lines = c.lines(&values, &[0.5]).unwrap();
+        let parts = &lines[0].geometry().0;
+        assert_eq!(parts.len(), 2);
+        assert!(parts
+            .iter()
+            .flat_map(|l| l.0.iter())
+            .all(|p| p.x >= -180. && p.x <= 180.));
+        let bands = c.isobands(&values, &[0.5, 2.]).unwrap();
+        assert_eq!(bands[0].geometry().0.len(), 2);
+    }
+
+    #[test]
+    fn test_level_set_tree() {
+        use crate::LevelEvent;
+
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 1., 1., 0.,
+            0., 1., 3., 1., 2., 1., 0.,
+            0., 1., 1., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 2., 0.,
+            0., 0., 0., 0., 0., 0., 0.,
+        ];
+        let c = ContourBuilder::new(7, 7, false);
+        let tree = c.level_set_tree(&values, &[1.5, 0.5, 2.5]).unwrap();
+        assert_eq!(tree.contours().len(), 3);
+        // 2 components at 0.5, 3 at 1.5, 1 at 2.5
+        assert_eq!(tree.components().len(), 6);
+        let events = tree.events();
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| matches!(e, LevelEvent::Appear { .. }))
+                .count(),
+            2
+        );
+        let split = events
+            .iter()
+            .find_map(|e| match e {
+                LevelEvent::Split { component, into } => Some((*component, into.clone())),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(tree.components()[split.0].threshold(), 0.5);
+        assert_eq!(split.1.len(), 2);
+        // The peaks at 2 (twice) and at 3
+        let peaks = tree.peaks().map(|p| p.threshold()).collect::<Vec<_>>();
+        assert_eq!(peaks, vec![1.5, 1.5, 2.5]);
+        let top = tree.peaks().last().unwrap();
+        assert_eq!(top.contour_index(), 2);
+        assert_eq!(
+            tree.components()[top.parent().unwrap()].children(),
+            &[tree.components().len() - 1]
+        );
+    }
+
+    #[test]
+    fn test_quadratic_interpolation() {
+        use crate::Interpolation;
+
+        // Values varying as the square of the (cell center) x coordinate
+        let values = (0..3)
+            .flat_map(|_| (0..6).map(|i| (i as Float + 0.5).powi(2)))
+            .collect::<Vec<_>>();
+        let threshold: Float = 8.;
+        let expected = threshold.sqrt();
+        let crossing = |interpolation| {
+            let c = ContourBuilder::new(6, 3, true).interpolation(interpolation);
+            let lines = c.lines(&values, &[threshold]).unwrap();
+            lines[0].geometry().0[0]
+                .0
+                .iter()
+                .find(|p| p.y == 1.5 && p.x > 2. && p.x < 4.)
+                .unwrap()
+                .x
+        };
+        let linear = crossing(Interpolation::Linear);
+        let quadratic = crossing(Interpolation::Quadratic);
+        assert!((linear - expected).abs() > 0.01);
+        assert!((quadratic - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_grid_stats() {
+        use crate::GridStats;
+
+        let values = [1., Float::NAN, 3., Float::INFINITY, 5., 7.];
+        let stats = GridStats::from_values(&values).unwrap();
+        assert_eq!(stats.count, 4);
+        assert_eq!((stats.min, stats.max, stats.mean), (1., 7., 4.));
+        assert_eq!(stats.std_dev, (5. as Float).sqrt());
+        assert_eq!(
+            stats.std_dev_thresholds(1),
+            vec![4. - stats.std_dev, 4. + stats.std_dev]
+        );
+        assert!(GridStats::from_values(&[Float::NAN]).is_none());
+        assert!(crate::std_dev_thresholds(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn test_skip_empty() {
+        // A peak in a corner, whose maximum is one of the thresholds
+        #[rustfmt::skip]
+        let values = [
+            2., 1., 0., 0.,
+            1., 1., 0., 0.,
+            0., 0., 0., 0.,
+            0., 0., 0., 0.,
+        ];
+        let thresholds = [-5., 0.5, 2., 10., 20.];
+        let c = ContourBuilder::new(4, 4, false).skip_empty(true);
+        let contours = c.contours(&values, &thresholds).unwrap();
+        assert_eq!(
+            contours
+                .iter()
+                .map(|c| c.threshold_index())
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        let lines = c.lines(&values, &thresholds).unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2].threshold(), 2.);
+        let bands = c.isobands(&values, &thresholds).unwrap();
+        assert_eq!(
+            bands
+                .iter()
+                .map(|b| b.threshold_index())
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        let all = ContourBuilder::new(4, 4, false)
+            .contours(&values, &thresholds)
+            .unwrap();
+        assert_eq!(all.len(), 5);
+    }
+
+    #[test]
+    fn test_index() {
+        // Two peaks of different heights
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 2., 0., 1., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let thresholds = [1.5, 10., 0.5];
+        let c = ContourBuilder::new(5, 3, true).skip_empty(true).threads(2);
+        let contours = c.contours(&values, &thresholds).unwrap();
+        assert_eq!(
+            contours
+                .iter()
+                .map(|c| c.threshold_index())
+                .collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+        assert!(contours
+            .iter()
+            .all(|c| thresholds[c.threshold_index()] == c.threshold()));
+        let lines = c.lines(&values, &thresholds).unwrap();
+        assert!(lines
+            .iter()
+            .all(|l| thresholds[l.threshold_index()] == l.threshold()));
+        let bands = c.isobands(&values, &thresholds).unwrap();
+        assert!(bands
+            .iter()
+            .all(|b| thresholds[b.threshold_index()] == b.min_v()));
+        #[allow(deprecated)]
+        let index = contours[1].index();
+        assert_eq!(index, 2);
+    }
+
+    #[test]
+    fn test_band_labels() {
+        // A depression, the labels having to format negative values
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0.,
+            0., -1., -2., 0.,
+            0., -1., -1., 0.,
+            0., 0., 0., 0.,
+        ];
+        let c = ContourBuilder::new(4, 4, false).band_labels(|min, max| format!("{min}–{max} m"));
+        let bands = c.isobands(&values, &[-2.5, -1.5, -0.5]).unwrap();
+        assert_eq!(bands[0].label(), Some("-2.5–-1.5 m"));
+        assert_eq!(bands[1].label(), Some("-1.5–-0.5 m"));
+        #[cfg(feature = "geojson")]
+        assert_eq!(
+            bands[0].to_geojson().property("label"),
+            Some(&serde_json::json!("-2.5–-1.5 m"))
+        );
+        let bands = ContourBuilder::new(4, 4, false)
+            .isobands(&values, &[-1.5, -0.5])
+            .unwrap();
+        assert_eq!(bands[0].label(), None);
+    }
+
+    #[cfg(feature = "geojson")]
+    #[test]
+    fn test_geojson_keys() {
+        use crate::GeoJsonKeys;
+
+        let values = square();
+        let keys = GeoJsonKeys {
+            threshold: "level".into(),
+            min_v: "lo".into(),
+            max_v: "hi".into(),
+            ..Default::default()
+        };
+        let c = ContourBuilder::new(4, 4, false);
+        let contour = &c.contours(&values, &[0.5, 1.5]).unwrap()[0];
+        let feature = contour.to_geojson_with_keys(&keys);
+        assert_eq!(feature.property("level"), Some(&serde_json::json!(0.5)));
+        assert_eq!(
+            feature.property("max_threshold"),
+            Some(&serde_json::json!(1.5))
+        );
+        assert!(feature.property("threshold").is_none());
+        let band = &c.isobands(&values, &[0.5, 1.5]).unwrap()[0];
+        let feature = band.to_geojson_with_keys(&keys);
+        assert_eq!(feature.property("lo"), Some(&serde_json::json!(0.5)));
+        assert_eq!(feature.property("hi"), Some(&serde_json::json!(1.5)));
+        assert_eq!(
+            band.to_geojson(),
+            band.to_geojson_with_keys(&GeoJsonKeys::default())
+        );
+    }
+
+    #[test]
+    fn test_padding() {
+        let f = [2., 3., 2., 1.];
+        let values = (0..16).map(|i| f[i % 4] + f[i / 4]).collect::<Vec<Float>>();
+        let vertices = |builder: ContourBuilder| {
+            let contours = builder.contours(&values, &[4.25]).unwrap();
+            assert_eq!(contours[0].geometry().0.len(), 1);
+            contours[0].geometry().0[0].exterior().0.clone()
+        };
+        // Truncated at the edges of the grid
+        let truncated = vertices(ContourBuilder::new(4, 4, true));
+        assert!(truncated.iter().any(|pt| pt.x == 0.) && truncated.iter().any(|pt| pt.y == 0.));
+        // Closing naturally outside of the grid
+        let padded = vertices(
+            ContourBuilder::new(4, 4, true)
+                .x_origin(10.)
+                .padding(1, crate::Padding::Linear),
+        );
+        assert!(padded.iter().all(|pt| pt.x > 9. && pt.y > -1.));
+        assert!(padded.iter().any(|pt| pt.x < 10.) && padded.iter().any(|pt| pt.y < 0.));
+        assert!(padded.iter().all(|pt| pt.x < 15. && pt.y < 5.));
+    }
+
+    #[test]
+    fn test_weights() {
+        let values = (0..25)
+            .map(|i| (i % 5 + i / 5) as Float)
+            .collect::<Vec<_>>();
+        let weights = (0..25)
+            .map(|i| [0.5, 1., 2.][i % 3])
+            .collect::<Vec<Float>>();
+        let weighted = values
+            .iter()
+            .zip(&weights)
+            .map(|(v, w)| v * w)
+            .collect::<Vec<_>>();
+        let thresholds = [1.5, 3.5, 6.];
+        let expected = ContourBuilder::new(5, 5, true)
+            .isobands(&weighted, &thresholds)
+            .unwrap();
+        let builder = ContourBuilder::new(5, 5, true).weights(Some(weights.clone()));
+        let bands = builder.isobands(&values, &thresholds).unwrap();
+        for (band, expected) in bands.iter().zip(&expected) {
+            assert_eq!(band.geometry(), expected.geometry());
+        }
+
+        let summed = values
+            .iter()
+            .zip(&weights)
+            .map(|(v, w)| v + w)
+            .collect::<Vec<_>>();
+        let builder = ContourBuilder::new(5, 5, false).weights_with(weights, |v, w| v + w);
+        assert_eq!(
+            builder.lines(&values, &thresholds).unwrap()[1].geometry(),
+            ContourBuilder::new(5, 5, false)
+                .lines(&summed, &thresholds)
+                .unwrap()[1]
+                .geometry()
+        );
+
+        let builder = ContourBuilder::new(5, 5, false).weights(Some(vec![1.; 20]));
+        assert!(builder.contours(&values, &thresholds).is_err());
+    }
+
+    #[test]
+    fn test_cell_cases() {
+        let cases = crate::cell_cases(&[1., 0., 0., 0.], 0.5, 2, 2).unwrap();
+        assert_eq!(cases, vec![2, 1, 0, 4, 8, 0, 0, 0, 0]);
+        assert!(crate::cell_cases(&[1., 0., 0.], 0.5, 2, 2).is_err());
+
+        // Each case with segments gives the segments of the rings
+        let values = (0..30).map(|i| ((i * 7) % 5) as Float).collect::<Vec<_>>();
+        let cases = crate::cell_cases(&values, 2.5, 6, 5).unwrap();
+        let rings = crate::contour_rings(&values, 2.5, 6, 5).unwrap();
+        let segments = cases
+            .iter()
+            .map(|c| match c {
+                0 | 15 => 0,
+                5 | 10 => 2,
+                _ => 1,
+            })
+            .sum::<usize>();
+        assert_eq!(segments, rings.iter().map(|r| r.len() - 1).sum::<usize>());
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 0.,
+            0., 1., 0., 1., 0.,
+            0., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let builder = ContourBuilder::new(5, 5, false);
+        let (contours, diagnostics) = builder
+            .contours_with_diagnostics(&values, &[0.5, 2.])
+            .unwrap();
+        assert_eq!(
+            contours[0].geometry(),
+            builder.contours(&values, &[0.5, 2.]).unwrap()[0].geometry()
+        );
+        assert_eq!(diagnostics.thresholds.len(), 2);
+        assert_eq!(diagnostics.thresholds[0].rings, 2);
+        assert_eq!(diagnostics.thresholds[0].holes, 1);
+        assert_eq!(diagnostics.thresholds[1].threshold_index, 1);
+        assert_eq!(diagnostics.thresholds[1].rings, 0);
+        assert_eq!((diagnostics.rings(), diagnostics.dropped_rings()), (2, 0));
+
+        let (lines, diagnostics) = builder.lines_with_diagnostics(&values, &[0.5]).unwrap();
+        assert_eq!(lines[0].geometry().0.len(), diagnostics.rings());
+
+        let (bands, diagnostics) = builder
+            .isobands_with_diagnostics(&values, &[2., 0.5, -1.])
+            .unwrap();
+        assert_eq!(bands.len(), 2);
+        let thresholds = diagnostics
+            .thresholds
+            .iter()
+            .map(|t| (t.threshold_index, t.rings, t.holes))
+            .collect::<Vec<_>>();
+        assert_eq!(thresholds, vec![(2, 1, 1), (1, 2, 1), (0, 0, 0)]);
+    }
+
+    #[test]
+    fn test_warnings() {
+        let values = [0., 0., 0., 0., 1., 0., 0., 0., 0.];
+        let builder = ContourBuilder::new(3, 3, true);
+        // The ring of the upper threshold collapses onto the center of the grid
+        let (bands, diagnostics) = builder
+            .isobands_with_diagnostics(&values, &[0.5, 1.])
+            .unwrap();
+        assert_eq!(bands[0].geometry().0.len(), 1);
+        assert_eq!(diagnostics.dropped_rings(), 1);
+        let warnings = diagnostics.warnings().collect::<Vec<_>>();
+        assert_eq!(
+            warnings,
+            vec![&crate::Warning::DegenerateRing { points: 1 }]
+        );
+        assert_eq!(diagnostics.thresholds[1].warnings.len(), 1);
+        assert!(warnings[0].to_string().contains("dropped"));
+    }
+
+    #[test]
+    fn test_tie_policy() {
+        use crate::TiePolicy;
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 0.,
+            0., 1., 2., 1., 0.,
+            0., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let geometry = |builder: ContourBuilder, threshold: Float| {
+            builder.contours(&values, &[threshold]).unwrap()[0]
+                .geometry()
+                .clone()
+        };
+        let builder = ContourBuilder::new(5, 5, false);
+        assert_eq!(
+            geometry(builder.clone(), 1.),
+            geometry(builder.clone(), 0.5)
+        );
+        assert_eq!(
+            geometry(builder.clone().tie_policy(TiePolicy::Outside), 1.),
+            geometry(builder.clone(), 1.5)
+        );
+        assert_eq!(
+            geometry(builder.clone().tie_policy(TiePolicy::Perturb), 1.),
+            geometry(builder, 0.5)
+        );
+
+        // A single sample equal to the threshold
+        let values = [0., 0., 0., 0., 1., 0., 0., 0., 0.];
+        let polygons = |ties: TiePolicy| {
+            let builder = ContourBuilder::new(3, 3, false).tie_policy(ties);
+            builder.contours(&values, &[1.]).unwrap()[0]
+                .geometry()
+                .0
+                .len()
+        };
+        assert_eq!(polygons(TiePolicy::Inside), 1);
+        assert_eq!(polygons(TiePolicy::Outside), 0);
+        assert_eq!(polygons(TiePolicy::Perturb), 1);
+
+        // The isolines around the values equal to the threshold follow the contours
+        let builder = ContourBuilder::new(9, 7, true).tie_policy(TiePolicy::Perturb);
+        let report = builder.fuzz_invariants(1, 200).unwrap();
+        assert!(report.is_ok(), "{}", report);
+    }
+
+    #[test]
+    fn test_infinity_policy() {
+        use crate::{ErrorKind, InfinityPolicy, Interpolation};
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0.,
+            0., Float::INFINITY, 1., 0.,
+            0., 1., Float::NEG_INFINITY, 0.,
+            0., 0., 0., 0.,
+        ];
+        for interpolation in [Interpolation::Linear, Interpolation::Quadratic] {
+            let builder = ContourBuilder::new(4, 4, true).interpolation(interpolation);
+            let bands = builder.isobands(&values, &[0.5, 2., 3.]).unwrap();
+            assert!(bands.iter().all(|band| band
+                .geometry()
+                .0
+                .iter()
+                .flat_map(|p| p
+                    .exterior()
+                    .0
+                    .iter()
+                    .chain(p.interiors().iter().flat_map(|r| &r.0)))
+                .all(|pt| pt.x.is_finite() && pt.y.is_finite())));
+            // The ring around the +inf sample passes through the neighbouring finite samples
+            let contours = builder.contours(&values, &[2.]).unwrap();
+            let exterior = contours[0].geometry().0[0].exterior();
+            assert!(exterior.0.contains(&geo_types::coord! { x: 0.5, y: 1.5 }));
+            assert!(exterior.0.contains(&geo_types::coord! { x: 1.5, y: 0.5 }));
+        }
+
+        let builder = ContourBuilder::new(4, 4, true).infinity_policy(InfinityPolicy::Outside);
+        let contours = builder.contours(&values, &[2.]).unwrap();
+        assert!(contours[0].geometry().0.is_empty());
+
+        let builder = ContourBuilder::new(4, 4, true).infinity_policy(InfinityPolicy::Error);
+        let err = builder.lines(&values, &[0.5]).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::InfiniteValue { x: 1, y: 1 }
+        ));
+    }
+
+    #[test]
+    fn test_sanitize() {
+        use crate::{sanitize, InvalidKind, SanitizePolicy};
+        let original = vec![
+            1.,
+            Float::INFINITY,
+            -5.,
+            Float::NEG_INFINITY,
+            Float::NAN,
+            3.,
+        ];
+
+        let mut values = original.clone();
+        let report = sanitize(&mut values, 3, Some(-5.), SanitizePolicy::Report).unwrap();
+        assert!(!report.is_clean());
+        assert_eq!(report.count(InvalidKind::Infinite), 2);
+        assert_eq!(report.count(InvalidKind::NoData), 1);
+        assert_eq!(report.count(InvalidKind::NaN), 1);
+        assert_eq!((report.invalid[2].x, report.invalid[2].y), (0, 1));
+        assert!(values[4].is_nan());
+
+        let mut values = original.clone();
+        sanitize(&mut values, 3, Some(-5.), SanitizePolicy::Clamp).unwrap();
+        assert_eq!(values, vec![1., 3., 1., 1., 1., 3.]);
+
+        let mut values = vec![0., 1., 2.];
+        assert!(sanitize(&mut values, 3, None, SanitizePolicy::Clamp)
+            .unwrap()
+            .is_clean());
+        assert!(sanitize(&mut values, 2, None, SanitizePolicy::Report).is_err());
+    }
+
+    #[test]
+    fn test_map_coords() {
+        let values = [0., 0., 0., 0., 1., 0., 0., 0., 0.];
+        let builder = ContourBuilder::new(3, 3, false);
+        let scale = |pt: crate::Pt| geo_types::coord! { x: pt.x * 1000., y: -pt.y };
+
+        let mut lines = builder.lines(&values, &[0.5]).unwrap();
+        let original = lines[0].geometry().0[0].0.clone();
+        lines[0].map_coords(scale);
+        assert_eq!(lines[0].threshold(), 0.5);
+        assert_eq!(
+            lines[0].geometry().0[0].0,
+            original.into_iter().map(scale).collect::<Vec<_>>()
+        );
+
+        let mut contours = builder.contours(&values, &[0.5]).unwrap();
+        contours[0].map_coords(scale);
+        assert_eq!(
+            contours[0].geometry().0[0].exterior().0,
+            lines[0].geometry().0[0].0
+        );
+
+        let mut bands = builder.isobands(&values, &[0.5, 2.]).unwrap();
+        bands[0].map_coords(scale);
+        assert_eq!((bands[0].min_v(), bands[0].max_v()), (0.5, 2.));
+        assert!(bands[0].geometry().0[0]
+            .exterior()
+            .0
+            .iter()
+            .all(|pt| pt.x >= 500. && pt.y <= -0.5));
+    }
+
+    #[test]
+    fn test_transform() {
+        let values = [0., 0., 0., 0., 1., 0., 0., 0., 0.];
+        let builder = ContourBuilder::new(3, 3, true).x_origin(10.).y_step(2.);
+        let expected = builder.contours(&values, &[0.25]).unwrap()[0].geometry().0[0]
+            .exterior()
+            .0
+            .iter()
+            .map(|pt| geo_types::coord! { x: pt.x * 2., y: pt.y + pt.x })
+            .collect::<Vec<_>>();
+        let transformed = builder.transform(|x, y| (x * 2., y + x));
+        let contours = transformed.contours(&values, &[0.25]).unwrap();
+        assert_eq!(contours[0].geometry().0[0].exterior().0, expected);
+        assert_eq!(contours[0].threshold(), 0.25);
+    }
+
+    #[test]
+    fn test_grid_and_world() {
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0.,
+            0., 2., 1., 0.,
+            0., 1., 0., 0.,
+            0., 0., 0., 0.,
+        ];
+        let builder = ContourBuilder::new(4, 4, true)
+            .x_origin(100.)
+            .y_origin(50.)
+            .x_step(10.)
+            .y_step(5.);
+        let thresholds = [0.5, 1.5];
+
+        let (grid, world) = builder.lines_grid_and_world(&values, &thresholds).unwrap();
+        let expected = ContourBuilder::new(4, 4, true)
+            .lines(&values, &thresholds)
+            .unwrap();
+        for (i, line) in expected.iter().enumerate() {
+            assert_eq!(grid[i].geometry(), line.geometry());
+        }
+        let expected = builder.lines(&values, &thresholds).unwrap();
+        for (i, line) in expected.iter().enumerate() {
+            assert_eq!(world[i].geometry(), line.geometry());
+        }
+
+        let (grid, world) = builder
+            .contours_grid_and_world(&values, &thresholds)
+            .unwrap();
+        let expected = ContourBuilder::new(4, 4, true)
+            .contours(&values, &thresholds)
+            .unwrap();
+        assert_eq!(grid[1].geometry(), expected[1].geometry());
+        let expected = builder.contours(&values, &thresholds).unwrap();
+        assert_eq!(world[1].geometry(), expected[1].geometry());
+        assert_eq!(world[1].threshold(), 1.5);
+
+        let (grid, world) = builder
+            .isobands_grid_and_world(&values, &thresholds)
+            .unwrap();
+        let expected = ContourBuilder::new(4, 4, true)
+            .isobands(&values, &thresholds)
+            .unwrap();
+        assert_eq!(grid[0].geometry(), expected[0].geometry());
+        let expected = builder.isobands(&values, &thresholds).unwrap();
+        assert_eq!(world[0].geometry(), expected[0].geometry());
+    }
+
+    #[test]
+    fn test_bit_planes() {
+        let values = (0..100)
+            .map(|i| ((i % 10) as Float - 4.5).powi(2) + ((i / 10) as Float - 4.5).powi(2))
+            .collect::<Vec<_>>();
+        let builder = ContourBuilder::new(10, 10, true).x_origin(5.);
+        let planes = builder.bit_planes(&values, &[2., 8., 16.]).unwrap();
+        assert_eq!(planes.levels(), &[2., 8., 16.]);
+        // Precomputed levels and other thresholds give the same results
+        let thresholds = [16., 2., 5.];
+        let lines = planes.lines(&thresholds).unwrap();
+        let expected = builder.lines(&values, &thresholds).unwrap();
+        for (line, expected) in lines.iter().zip(&expected) {
+            assert_eq!(line.geometry(), expected.geometry());
+            assert_eq!(line.threshold_index(), expected.threshold_index());
+        }
+        let contours = planes.contours(&thresholds).unwrap();
+        let expected = builder.contours(&values, &thresholds).unwrap();
+        for (contour, expected) in contours.iter().zip(&expected) {
+            assert_eq!(contour.geometry(), expected.geometry());
+            assert_eq!(contour.max_threshold(), expected.max_threshold());
+        }
+        assert!(builder.bit_planes(&values[1..], &[2.]).is_err());
+    }
+
+    #[test]
+    fn test_block_width() {
+        let values = (0..600)
+            .map(|i| (((i % 30) as Float) / 3.).sin() + (((i / 30) as Float) / 2.).cos())
+            .collect::<Vec<_>>();
+        let areas = |builder: ContourBuilder| {
+            let mut areas = builder
+                .contours(&values, &[0.3, 1.2])
+                .unwrap()
+                .iter()
+                .flat_map(|c| c.geometry().0.iter())
+                .map(|p| {
+                    let mut pts = p.exterior().0.clone();
+                    pts.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+                    // The rings may start on a different vertex
+                    pts.dedup();
+                    let area = (crate::area::area(&p.exterior().0) * 1000.).round() as i64;
+                    (area, p.interiors().len(), pts)
+                })
+                .collect::<Vec<_>>();
+            areas.sort_by_key(|a| (a.0, a.1));
+            areas
+        };
+        let expected = areas(ContourBuilder::new(30, 20, true));
+        for width in [1, 4, 7, 31] {
+            let builder = ContourBuilder::new(30, 20, true).block_width(Some(width));
+            assert_eq!(areas(builder), expected);
+        }
+    }
+
+    #[test]
+    fn test_strips() {
+        let values = (0..1200)
+            .map(|i| (((i % 30) as Float) / 3.).sin() + (((i / 30) as Float) / 2.).cos())
+            .collect::<Vec<_>>();
+        let areas = |builder: ContourBuilder| {
+            let mut areas = builder
+                .contours(&values, &[0.3, 1.2])
+                .unwrap()
+                .iter()
+                .flat_map(|c| c.geometry().0.iter())
+                .map(|p| {
+                    let mut pts = p.exterior().0.clone();
+                    pts.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+                    // The rings may start on a different vertex
+                    pts.dedup();
+                    let area = (crate::area::area(&p.exterior().0) * 1000.).round() as i64;
+                    (area, p.interiors().len(), pts)
+                })
+                .collect::<Vec<_>>();
+            areas.sort_by_key(|a| (a.0, a.1));
+            areas
+        };
+        let expected = areas(ContourBuilder::new(30, 40, true));
+        for strips in [2, 3, 7, 50] {
+            let builder = ContourBuilder::new(30, 40, true).strips(strips);
+            assert_eq!(areas(builder), expected);
+        }
+    }
+
+    #[test]
+    fn test_contour_rings_fixed() {
+        let mut values = [[0.; 7]; 5];
+        for (y, row) in values.iter_mut().enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                *value = ((x as Float) / 1.5).sin() * ((y as Float) / 1.2).cos();
+            }
+        }
+        for threshold in [-0.5, 0., 0.25, 0.8] {
+            assert_eq!(
+                crate::contour_rings_fixed(&values, threshold).unwrap(),
+                crate::contour_rings(&values.concat(), threshold, 7, 5).unwrap()
+            );
+        }
+        assert_eq!(
+            crate::contour_rings_fixed(&[[1.]], 0.5).unwrap(),
+            vec![vec![
+                crate::Pt { x: 1., y: 0.5 },
+                crate::Pt { x: 0.5, y: 0. },
+                crate::Pt { x: 0., y: 0.5 },
+                crate::Pt { x: 0.5, y: 1. },
+                crate::Pt { x: 1., y: 0.5 },
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_fill_rule() {
+        use crate::diagnostics::ThresholdDiagnostics;
+        use crate::fillrule::{assemble, FillRule};
+        let square = |x0: Float, x1: Float| {
+            vec![
+                crate::Pt { x: x0, y: x0 },
+                crate::Pt { x: x0, y: x1 },
+                crate::Pt { x: x1, y: x1 },
+                crate::Pt { x: x1, y: x0 },
+                crate::Pt { x: x0, y: x0 },
+            ]
+        };
+        // Two nested rings, both wound as exterior rings
+        let rings = vec![square(0., 8.), square(2., 6.)];
+        let mut diagnostics = ThresholdDiagnostics::new(0.5, 0);
+        let polygons = assemble(rings.clone(), FillRule::EvenOdd, &mut diagnostics);
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].interiors().len(), 1);
+        assert!(crate::area::area(&polygons[0].interiors()[0].0) < 0.);
+        assert_eq!(diagnostics.holes, 1);
+
+        let mut diagnostics = ThresholdDiagnostics::new(0.5, 0);
+        let polygons = assemble(rings, FillRule::NonZero, &mut diagnostics);
+        assert_eq!(polygons.len(), 1);
+        assert!(polygons[0].interiors().is_empty());
+        assert_eq!(diagnostics.dropped_rings, 1);
+        assert!(matches!(
+            diagnostics.warnings[0],
+            crate::Warning::RedundantRing { .. }
+        ));
+
+        // On regular rings, the fill rules give the same polygons as the containment test
+        let mut values = vec![0.; 100];
+        for (x, y) in (1..9).flat_map(|x| [(x, 1), (x, 8), (1, x), (8, x)]) {
+            values[y * 10 + x] = 1.;
+        }
+        values[55] = 1.;
+        let builder = ContourBuilder::new(10, 10, true);
+        let expected = builder.contours(&values, &[0.5]).unwrap();
+        for rule in [FillRule::EvenOdd, FillRule::NonZero] {
+            let contours = builder
+                .clone()
+                .fill_rule(rule)
+                .contours(&values, &[0.5])
+                .unwrap();
+            assert_eq!(contours[0].geometry(), expected[0].geometry());
+        }
+    }
+
+    #[test]
+    fn test_dual_vertex_placement() {
+        // A cone centered on (10, 10) in grid coordinates (the samples lying at the
+        // center of the pixels), whose contour at 4 is a circle of radius 6
+        let values = (0..400)
+            .map(|i| {
+                let (x, y) = ((i % 20) as Float + 0.5, (i / 20) as Float + 0.5);
+                10. - (x - 10.).hypot(y - 10.)
+            })
+            .collect::<Vec<_>>();
+        let error = |builder: ContourBuilder| {
+            let lines = builder.lines(&values, &[4.]).unwrap();
+            let ring = lines[0].geometry().0[0].0.clone();
+            let error = ring
+                .iter()
+                .map(|p| ((p.x - 10.).hypot(p.y - 10.) - 6.).abs())
+                .fold(0., Float::max);
+            (ring, error)
+        };
+        let (edges, edges_error) = error(ContourBuilder::new(20, 20, false));
+        let (dual, dual_error) = error(
+            ContourBuilder::new(20, 20, false).vertex_placement(crate::VertexPlacement::Dual),
+        );
+        assert_eq!(dual.first(), dual.last());
+        assert_eq!(dual.len(), edges.len());
+        assert!(dual_error < edges_error / 4., "{dual_error} {edges_error}");
+        // The vertices are inside the crossed cells, not on their edges
+        assert!(dual.iter().all(|p| !edges.contains(p)));
+
+        // The isolines follow the contours, whose holes are inside their polygons
+        // (unlike the isobands, whose rings of different thresholds may cross)
+        let builder =
+            ContourBuilder::new(9, 7, true).vertex_placement(crate::VertexPlacement::Dual);
+        let report = builder.fuzz_invariants(1, 200).unwrap();
+        assert!(!report.is_ok());
+        assert!(report.violations.iter().all(|v| matches!(
+            v.issue,
+            crate::InvariantIssue::BandArea { .. }
+                | crate::InvariantIssue::HoleContainment { band: true, .. }
+        )));
+    }
+
+    #[test]
+    fn test_contains() {
+        // A ring of high values around a low value
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 2., 2., 2., 0.,
+            0., 2., 0., 2., 0.,
+            0., 2., 2., 2., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let builder = ContourBuilder::new(5, 5, false);
+        let contour = &builder.contours(&values, &[1.]).unwrap()[0];
+        assert_eq!(contour.geometry().0[0].interiors().len(), 1);
+        assert!(contour.contains(1.5, 1.5));
+        assert!(contour.contains(3.5, 2.5));
+        assert!(!contour.contains(2.5, 2.5));
+        assert!(!contour.contains(0.2, 0.2));
+        // On the exterior boundary
+        assert!(contour.contains(1., 2.5));
+        // Far away, level with the first vertex of the exterior ring
+        let first = contour.geometry().0[0].exterior().0[0];
+        assert!(!contour.contains(-10., first.y));
+
+        let bands = builder.isobands(&values, &[-1., 1., 3.]).unwrap();
+        assert!(bands[0].contains(2.5, 2.5));
+        assert!(bands[0].contains(0.6, 4.4));
+        assert!(!bands[0].contains(1.5, 1.5));
+        assert!(bands[1].contains(1.5, 1.5));
+        assert!(!bands[1].contains(2.5, 2.5));
+    }
+
+    #[test]
+    fn test_bbox() {
+        let mut values = vec![0.; 100];
+        for y in 2..5 {
+            for x in 3..8 {
+                values[y * 10 + x] = 2.;
+            }
+        }
+        let builder = ContourBuilder::new(10, 10, false).x_origin(100.).x_step(2.);
+        let expected = geo_types::Rect::new((106., 2.), (116., 5.));
+        let lines = builder.lines(&values, &[1.]).unwrap();
+        assert_eq!(lines[0].bbox(), Some(expected));
+        let mut contour = builder.contours(&values, &[1.]).unwrap().remove(0);
+        assert_eq!(contour.bbox(), Some(expected));
+        let bands = builder.isobands(&values, &[1., 3.]).unwrap();
+        assert_eq!(bands[0].bbox(), Some(expected));
+
+        // The bounding box follows the changes of the geometry
+        contour.map_coords(|pt| crate::Pt {
+            x: pt.x * 2.,
+            y: pt.y,
+        });
+        assert_eq!(
+            contour.bbox(),
+            Some(geo_types::Rect::new((212., 2.), (232., 5.)))
+        );
+    }
+
+    #[test]
+    fn test_gradient() {
+        use crate::GradientOperator;
+        let builder = ContourBuilder::new(8, 6, false)
+            .x_origin(10.)
+            .x_step(2.)
+            .y_step(0.5);
+        // A plane v = 3x + 2y (in output coordinates)
+        let mut values = (0..48)
+            .map(|i| 3. * (10. + (i % 8) as Float * 2.) + 2. * ((i / 8) as Float * 0.5))
+            .collect::<Vec<_>>();
+        values[18] = Float::NAN;
+        values[29] = -9999.;
+        for operator in [
+            GradientOperator::CentralDifferences,
+            GradientOperator::Sobel,
+        ] {
+            let gradient = builder.gradient(&values, operator, Some(-9999.)).unwrap();
+            for i in 0..48 {
+                if i == 18 || i == 29 {
+                    assert!(gradient.x[i].is_nan() && gradient.y[i].is_nan());
+                } else {
+                    assert!((gradient.x[i] - 3.).abs() < 1e-5, "{operator:?} {i}");
+                    assert!((gradient.y[i] - 2.).abs() < 1e-5, "{operator:?} {i}");
+                }
+            }
+            assert!((gradient.magnitude()[0] - 13f32.sqrt() as Float).abs() < 1e-5);
+        }
+        assert!(builder
+            .gradient(&values[1..], GradientOperator::Sobel, None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_hachures() {
+        // A cone centered on (10, 10) and its negation (a depression)
+        let hill = (0..400)
+            .map(|i| {
+                let (x, y) = ((i % 20) as Float + 0.5, (i / 20) as Float + 0.5);
+                10. - (x - 10.).hypot(y - 10.)
+            })
+            .collect::<Vec<_>>();
+        let pit = hill.iter().map(|v| -v).collect::<Vec<_>>();
+        let builder = ContourBuilder::new(20, 20, true);
+        for (values, threshold, outwards) in [(&hill, 4., true), (&pit, -4., false)] {
+            let lines = builder.lines(values, &[threshold]).unwrap();
+            let hachures = builder.hachures(values, &lines, 2., 0.5).unwrap();
+            assert_eq!(hachures.len(), 1);
+            assert_eq!(hachures[0].threshold(), threshold);
+            // The circle of radius 6 has a perimeter of about 37.7
+            let ticks = &hachures[0].geometry().0;
+            assert!((18..=19).contains(&ticks.len()), "{}", ticks.len());
+            for tick in ticks {
+                let (a, b) = (tick.0[0], tick.0[1]);
+                assert!(((b.x - a.x).hypot(b.y - a.y) - 0.5).abs() < 1e-4);
+                let (ra, rb) = ((a.x - 10.).hypot(a.y - 10.), (b.x - 10.).hypot(b.y - 10.));
+                // Pointing (approximately radially) downslope
+                assert_eq!(rb > ra, outwards);
+                assert!(((rb - ra).abs() - 0.5).abs() < 0.05);
+            }
+        }
+        let none = builder.hachures(&hill, &builder.lines(&hill, &[4.]).unwrap(), 0., 1.);
+        assert!(none.unwrap()[0].geometry().0.is_empty());
+    }
+
+    #[test]
+    fn test_depressions() {
+        // A peak and a pit, far from the edges of the grid
+        let values = (0..400)
+            .map(|i| {
+                let (x, y) = ((i % 20) as Float, (i / 20) as Float);
+                let peak = 5. - (x - 5.).hypot(y - 10.);
+                let pit = -5. + (x - 14.).hypot(y - 10.);
+                peak.max(0.) + pit.min(0.)
+            })
+            .collect::<Vec<_>>();
+        for builder in [
+            ContourBuilder::new(20, 20, true),
+            ContourBuilder::new(20, 20, true).y_origin(20.).y_step(-1.),
+        ] {
+            assert!(builder.lines(&values, &[2.]).unwrap()[0]
+                .depressions()
+                .is_none());
+            let builder = builder.depressions(true);
+            let lines = builder.lines(&values, &[-2., 2.]).unwrap();
+            // The pit, and the ring closed along the edges of the grid (not a depression)
+            let depressions = lines[0].depressions().unwrap();
+            assert_eq!(depressions.len(), 2);
+            assert_eq!(depressions.iter().filter(|d| **d).count(), 1);
+            assert_eq!(lines[1].depressions(), Some(&[false][..]));
+            #[cfg(feature = "geojson")]
+            assert_eq!(
+                lines[1].to_geojson().property("depressions"),
+                Some(&serde_json::json!([false]))
+            );
+        }
+    }
+
+    #[test]
+    fn test_index_interval() {
+        use crate::ContourClass::{self, Index, Intermediate};
+        let values = (0..400)
+            .map(|i| ((i % 20) as Float) * 10. + (i / 20) as Float)
+            .collect::<Vec<_>>();
+        let thresholds = [20., 40., 60., 80., 100., 120., 140., 160., 180., 200.];
+        let builder = ContourBuilder::new(20, 20, false);
+        assert!(builder.lines(&values, &thresholds).unwrap()[0]
+            .class()
+            .is_none());
+
+        let builder = builder.index_interval(Some(100.));
+        let classes = builder
+            .lines(&values, &thresholds)
+            .unwrap()
+            .iter()
+            .map(|l| l.class().unwrap())
+            .collect::<Vec<_>>();
+        let expected = thresholds.map(|t| if t % 100. == 0. { Index } else { Intermediate });
+        assert_eq!(classes, expected);
+        let contours = builder.contours(&values, &thresholds).unwrap();
+        assert!(contours
+            .iter()
+            .all(|c| c.class() == Some(expected[c.threshold_index()])));
+        // Fractional intervals
+        assert_eq!(ContourClass::classify(0.3, 0.1), Index);
+        assert_eq!(ContourClass::classify(-1.5, 0.5), Index);
+        assert_eq!(ContourClass::classify(0.25, 0.1), Intermediate);
+        #[cfg(feature = "geojson")]
+        assert_eq!(
+            contours[4].to_geojson().property("class"),
+            Some(&serde_json::json!("index"))
+        );
+    }
+
+    #[test]
+    fn test_grid_sampler() {
+        let (dx, dy) = (6, 5);
+        let values = (0..dx * dy)
+            .map(|i| (i % dx) as Float + 10. * (i / dx) as Float)
+            .collect::<Vec<_>>();
+        let builder = ContourBuilder::new(dx, dy, true)
+            .x_origin(100.)
+            .y_origin(50.)
+            .x_step(2.)
+            .y_step(-1.);
+        let sampler = builder.sampler(&values).unwrap();
+        // Samples lie at the center of the cells
+        assert_eq!(sampler.sample(101., 49.5), Some(0.));
+        assert_eq!(sampler.value(2, 3), Some(32.));
+        assert_eq!(sampler.value(6, 0), None);
+        assert!((sampler.sample(104., 47.).unwrap() - 26.5).abs() < 1e-4);
+        // Values are extended up to the edges of the grid, not beyond
+        assert_eq!(sampler.sample(100., 50.), Some(0.));
+        assert_eq!(sampler.sample(99.9, 50.), None);
+        assert_eq!(sampler.sample(100., 44.9), None);
+        // The value under every vertex of an isoline (but those following the
+        // edges of the grid) is its threshold
+        let lines = builder.lines(&values, &[23.5]).unwrap();
+        let vertices = lines[0]
+            .geometry()
+            .0
+            .iter()
+            .flat_map(|l| l.0.iter())
+            .filter(|pt| pt.x > 100. && pt.x < 112. && pt.y > 45. && pt.y < 50.)
+            .collect::<Vec<_>>();
+        assert!(!vertices.is_empty());
+        for pt in vertices {
+            assert!((sampler.sample(pt.x, pt.y).unwrap() - 23.5).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_threshold_lookup() {
+        use crate::ThresholdLookup;
+
+        #[rustfmt::skip]
+        let values = [
+            0., 1., 2., 3., 4.,
+            1., 2., 3., 4., 5.,
+            2., 3., 4., 5., 6.,
+            3., 4., 5., 6., 7.,
+        ];
+        let thresholds = [4., 2., 6., 0.];
+        let builder = ContourBuilder::new(5, 4, false);
+        let bands = builder.isobands(&values, &thresholds).unwrap();
+        let contours = builder.contours(&values, &thresholds).unwrap();
+        let lookup = ThresholdLookup::new(&thresholds);
+        for (i, &v) in values.iter().enumerate() {
+            let (x, y) = ((i % 5) as Float + 0.5, (i / 5) as Float + 0.5);
+            let range = lookup.lookup(v).unwrap();
+            assert!(range.min <= v && range.max.is_none_or(|max| v < max));
+            // The band at the index contains the sample, and only this one
+            for band in &bands {
+                assert_eq!(band.contains(x, y), band.threshold_index() == range.index);
+            }
+            // The innermost contour containing the sample is at the index
+            let innermost = contours
+                .iter()
+                .filter(|c| c.contains(x, y))
+                .max_by(|a, b| a.threshold().total_cmp(&b.threshold()))
+                .unwrap();
+            assert_eq!(innermost.threshold_index(), range.index);
+        }
+        assert_eq!(lookup.lookup(7.).unwrap().max, None);
+        assert_eq!(lookup.lookup(-0.5), None);
+        assert_eq!(lookup.lookup(Float::NAN), None);
+        // NaN thresholds are ignored
+        let lookup = ThresholdLookup::new(&[Float::NAN, 1.]);
+        assert_eq!(lookup.lookup(2.).map(|r| (r.index, r.max)), Some((1, None)));
+    }
+
+    #[test]
+    fn test_mosaic() {
+        use crate::{ErrorKind, Mosaic, OverlapRule};
+
+        // A 6x4 grid split into two overlapping tiles (columns 0..4 and 3..6)
+        let (dx, dy) = (6, 4);
+        let values = (0..dx * dy)
+            .map(|i| ((i % dx) as Float - 2.5).powi(2) + (i / dx) as Float)
+            .collect::<Vec<_>>();
+        let tile = |x0: usize, x1: usize| {
+            (0..dy)
+                .flat_map(|y| values[y * dx + x0..y * dx + x1].to_vec())
+                .collect::<Vec<_>>()
+        };
+        let (left, right) = (tile(0, 4), tile(3, 6));
+        let mut mosaic = Mosaic::new();
+        mosaic
+            .add_tile(&right, 3, dy, (16., 20.), (2., -1.))
+            .unwrap();
+        mosaic
+            .add_tile(&left, 4, dy, (10., 20.), (2., -1.))
+            .unwrap();
+        let (builder, merged) = mosaic.grid(true).unwrap();
+        assert_eq!(merged, values);
+        assert_eq!((builder.origin(), builder.step()), ((10., 20.), (2., -1.)));
+        let expected = ContourBuilder::new(dx, dy, true)
+            .x_origin(10.)
+            .y_origin(20.)
+            .x_step(2.)
+            .y_step(-1.)
+            .contours(&values, &[2., 4.])
+            .unwrap();
+        let contours = builder.contours(&merged, &[2., 4.]).unwrap();
+        for (a, b) in contours.iter().zip(&expected) {
+            assert_eq!(a.geometry(), b.geometry());
+        }
+
+        // Overlap rules, and gaps between the tiles
+        let (a, b) = ([1., 5.], [3., Float::NAN]);
+        let merge = |rule| {
+            let mut mosaic = Mosaic::new().overlap(rule);
+            mosaic.add_tile(&a, 2, 1, (0., 0.), (1., 1.)).unwrap();
+            mosaic.add_tile(&b, 2, 1, (1., 0.), (1., 1.)).unwrap();
+            mosaic.add_tile(&a, 2, 1, (4., 0.), (1., 1.)).unwrap();
+            mosaic.grid(false).unwrap().1
+        };
+        let rules = [
+            (OverlapRule::First, 5.),
+            (OverlapRule::Last, 3.),
+            (OverlapRule::Mean, 4.),
+            (OverlapRule::Min, 3.),
+            (OverlapRule::Max, 5.),
+        ];
+        for (rule, expected) in rules {
+            let merged = merge(rule);
+            assert_eq!(merged.len(), 6);
+            assert_eq!((merged[0], merged[1], merged[4]), (1., expected, 1.));
+            assert!(merged[2].is_nan() && merged[3].is_nan());
+        }
+
+        // Misaligned tiles
+        let err = mosaic.add_tile(&left, 4, dy, (11., 20.), (2., -1.));
+        assert!(matches!(
+            err.unwrap_err().kind(),
+            ErrorKind::MisalignedTile { index: 2 }
+        ));
+        let err = mosaic.add_tile(&left, 4, dy, (10., 20.), (1., -1.));
+        assert!(err.is_err());
+        assert!(Mosaic::new().grid(false).is_err());
+    }
+
+    #[test]
+    fn test_overviews() {
+        use crate::Overviews;
+
+        // A cone, and its overviews averaging 2x2 and 4x4 blocks
+        let cone = |n: usize| {
+            (0..n * n)
+                .map(|i| {
+                    let (x, y) = ((i % n) as Float + 0.5, (i / n) as Float + 0.5);
+                    let s = 32. / n as Float;
+                    10. - ((x * s - 16.).powi(2) + (y * s - 16.).powi(2)).sqrt() / 2.
+                })
+                .collect::<Vec<_>>()
+        };
+        let overview = |values: &[Float], n: usize, f: usize| {
+            let m = n / f;
+            (0..m * m)
+                .map(|i| {
+                    let (x, y) = (i % m * f, i / m * f);
+                    let sum = (0..f * f)
+                        .map(|k| values[(y + k / f) * n + x + k % f])
+                        .sum::<Float>();
+                    sum / (f * f) as Float
+                })
+                .collect::<Vec<_>>()
+        };
+        let fine = cone(32);
+        let (half, quarter) = (overview(&fine, 32, 2), overview(&fine, 32, 4));
+        let level = |n: usize| {
+            let step = 32. / n as Float;
+            ContourBuilder::new(n, n, true).x_step(step).y_step(step)
+        };
+        let mut overviews = Overviews::new();
+        overviews.add_level(level(16), &half).unwrap();
+        overviews.add_level(level(32), &fine).unwrap();
+        overviews.add_level(level(8), &quarter).unwrap();
+        let thresholds = [4., 6.];
+        // Finer levels are needed as the tolerance decreases
+        assert_eq!(overviews.select(&thresholds, 0.).unwrap(), 1);
+        assert_eq!(overviews.select(&thresholds, 0.5).unwrap(), 2);
+        let selected = (0..=100)
+            .map(|i| overviews.select(&thresholds, i as Float * 0.005).unwrap())
+            .collect::<Vec<_>>();
+        assert!(selected.contains(&0));
+        let rank = |ix: usize| [1, 0, 2][ix];
+        assert!(selected.windows(2).all(|w| rank(w[0]) <= rank(w[1])));
+        // Contours are computed on the selected level
+        let expected = level(8).contours(&quarter, &thresholds).unwrap();
+        let contours = overviews.contours(&thresholds, 0.5).unwrap();
+        assert_eq!(contours[1].geometry(), expected[1].geometry());
+
+        assert!(overviews.add_level(level(8), &fine).is_err());
+        assert!(Overviews::new().select(&thresholds, 1.).is_err());
+    }
+
+    #[test]
+    fn test_contour_tracker() {
+        use crate::{ContourTracker, TrackEvent::*};
+
+        // Blocks of ones (columns start..end, rows 1..4) in a 12x5 grid
+        let frame = |blocks: &[(usize, usize)]| {
+            let mut values = vec![0.; 60];
+            for &(start, end) in blocks {
+                for y in 1..4 {
+                    values[y * 12 + start..y * 12 + end].fill(1.);
+                }
+            }
+            values
+        };
+        let builder = ContourBuilder::new(12, 5, true).x_step(2.);
+        let mut tracker = ContourTracker::new(builder, &[0.5]);
+
+        let first = tracker.push(&frame(&[(2, 8)])).unwrap();
+        assert_eq!(first.ids(0), &[0]);
+        assert_eq!(
+            first.events(),
+            &[Appear {
+                threshold_index: 0,
+                id: 0
+            }]
+        );
+        assert!(first.links().is_empty());
+
+        // Split: the largest part keeps the id
+        let second = tracker.push(&frame(&[(2, 4), (5, 9)])).unwrap();
+        let polygons = &second.contours()[0].geometry().0;
+        let left = if polygons[0].exterior().0[0].x < 10. {
+            0
+        } else {
+            1
+        };
+        assert_eq!(second.ids(0)[left], 1);
+        assert_eq!(second.ids(0)[1 - left], 0);
+        assert_eq!(
+            second.events(),
+            &[Split {
+                threshold_index: 0,
+                id: 0,
+                into: vec![0, 1]
+            }]
+        );
+        let areas = second
+            .links()
+            .iter()
+            .map(|l| (l.to, l.area))
+            .collect::<Vec<_>>();
+        assert_eq!(areas, vec![(0, 18.), (1, 12.)]);
+
+        // Merge into the largest predecessor
+        let third = tracker.push(&frame(&[(3, 9)])).unwrap();
+        assert_eq!(third.ids(0), &[0]);
+        assert_eq!(
+            third.events(),
+            &[Merge {
+                threshold_index: 0,
+                from: vec![0, 1],
+                into: 0
+            }]
+        );
+
+        // Vanish, then appear with a new id
+        let fourth = tracker.push(&frame(&[])).unwrap();
+        assert_eq!(
+            fourth.events(),
+            &[Vanish {
+                threshold_index: 0,
+                id: 0
+            }]
+        );
+        let fifth = tracker.push(&frame(&[(0, 2)])).unwrap();
+        assert_eq!(
+            fifth.events(),
+            &[Appear {
+                threshold_index: 0,
+                id: 2
+            }]
+        );
+    }
+
+    #[cfg(feature = "geojson")]
+    #[test]
+    fn test_geojson_writer() {
+        use crate::{ContourLayer, GeoJsonKeys, GeoJsonWriter};
+
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 2., 2., 2., 0.,
+            0., 2., 0., 2., 0.,
+            0., 2., 2., 1., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let builder = ContourBuilder::new(5, 5, true)
+            .x_origin(0.1)
+            .x_step(0.3)
+            .index_interval(Some(1.));
+        let layer = ContourLayer::new()
+            .lines(builder.lines(&values, &[0.5, 1.5]).unwrap())
+            .contours(builder.contours(&values, &[0.5, 1.5]).unwrap())
+            .bands(builder.isobands(&values, &[0., 0.5, 1.5, 2.5]).unwrap());
+        let keys = GeoJsonKeys {
+            threshold: "level".into(),
+            ..Default::default()
+        };
+        let mut writer = GeoJsonWriter::with_keys(Vec::new(), keys.clone()).unwrap();
+        writer.write_layer(&layer).unwrap();
+        assert_eq!(writer.len(), layer.len());
+        let output = writer.finish().unwrap();
+        let expected = layer.to_geojson_with_keys(&keys).to_string();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&output).unwrap(),
+            serde_json::from_str::<serde_json::Value>(&expected).unwrap()
+        );
+
+        let output = GeoJsonWriter::new(Vec::new()).unwrap().finish().unwrap();
+        assert_eq!(output, br#"{"type":"FeatureCollection","features":[]}"#);
+    }
+
+    #[cfg(feature = "geojson")]
+    #[test]
+    fn test_geojson_sequences() {
+        use crate::{GeoJsonKeys, GeoJsonLayout, GeoJsonWriter};
+
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 2., 2., 2., 0.,
+            0., 2., 0., 2., 0.,
+            0., 2., 2., 1., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let thresholds = [0.5, 1.5, 3.];
+        let builder = ContourBuilder::new(5, 5, true).skip_empty(true);
+        let contours = builder.contours(&values, &thresholds).unwrap();
+        let lines = builder.lines(&values, &thresholds).unwrap();
+
+        let keys = GeoJsonKeys::default();
+        let layout = GeoJsonLayout::TextSequence;
+        let mut writer = GeoJsonWriter::with_layout(Vec::new(), keys.clone(), layout).unwrap();
+        builder
+            .for_each_contour(&values, &thresholds, |c| writer.write_contour(&c))
+            .unwrap();
+        builder
+            .for_each_line(&values, &thresholds, |l| writer.write_line(&l))
+            .unwrap();
+        assert_eq!(writer.len(), 4);
+        let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+        let expected = contours
+            .iter()
+            .map(|c| c.to_geojson())
+            .chain(lines.iter().map(|l| l.to_geojson()));
+        let records = output.split('\x1e').collect::<Vec<_>>();
+        assert_eq!(records[0], "");
+        assert_eq!(records.len(), 5);
+        for (record, feature) in records[1..].iter().zip(expected) {
+            assert!(record.ends_with('\n') && record.matches('\n').count() == 1);
+            let record = serde_json::from_str::<serde_json::Value>(record).unwrap();
+            assert_eq!(record, serde_json::to_value(feature).unwrap());
+        }
+
+        let layout = GeoJsonLayout::NewlineDelimited;
+        let mut writer = GeoJsonWriter::with_layout(Vec::new(), keys, layout).unwrap();
+        contours
+            .iter()
+            .try_for_each(|c| writer.write_contour(c))
+            .unwrap();
+        let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(output.lines().count(), 2);
+        assert!(!output.contains('\x1e'));
+    }
+
+    #[cfg(feature = "gpkg")]
+    #[test]
+    fn test_write_gpkg() {
+        use crate::{write_gpkg, write_gpkg_with_srs};
+
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 2., 2., 2., 0.,
+            0., 2., 0., 2., 0.,
+            0., 2., 2., 1., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let builder = ContourBuilder::new(5, 5, false).x_origin(100.).x_step(2.);
+        let contours = builder.contours(&values, &[0.5, 1.5, 3.]).unwrap();
+        let path = std::env::temp_dir().join(format!("contour-{}.gpkg", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        write_gpkg(&path, "contours", &contours).unwrap();
+        // Another layer can be added, but not one with the same name
+        write_gpkg_with_srs(&path, "more", &contours[..1], 4326, "").unwrap();
+        assert!(write_gpkg(&path, "contours", &contours).is_err());
+
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        let query = |sql: &str| conn.query_row(sql, [], |row| row.get::<_, i64>(0)).unwrap();
+        assert_eq!(query("PRAGMA application_id"), 0x4750_4B47);
+        assert_eq!(query("SELECT count(*) FROM gpkg_contents"), 2);
+        assert_eq!(query("SELECT count(*) FROM gpkg_spatial_ref_sys"), 3);
+        assert_eq!(
+            query("SELECT srs_id FROM gpkg_geometry_columns WHERE table_name = 'more'"),
+            4326
+        );
+        assert_eq!(
+            query("SELECT count(*) FROM sqlite_master WHERE type = 'trigger'"),
+            12
+        );
+        // The empty contour isn't indexed
+        assert_eq!(query("SELECT count(*) FROM contours"), 3);
+        assert_eq!(query("SELECT count(*) FROM rtree_contours_geom"), 2);
+        let (min_x, max_x) = conn
+            .query_row(
+                "SELECT min_x, max_x FROM gpkg_contents WHERE table_name = 'contours'",
+                [],
+                |row| Ok((row.get::<_, f64>(0)?, row.get::<_, f64>(1)?)),
+            )
+            .unwrap();
+        assert_eq!((min_x, max_x), (102., 108.));
+
+        let mut statement = conn
+            .prepare("SELECT geom, threshold, max_threshold FROM contours ORDER BY fid")
+            .unwrap();
+        let rows = statement
+            .query_map([], |row| {
+                let (geom, threshold) = (row.get::<_, Vec<u8>>(0)?, row.get::<_, f64>(1)?);
+                Ok((geom, threshold, row.get::<_, Option<f64>>(2)?))
+            })
+            .unwrap()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(rows[0].1, 0.5);
+        assert_eq!(rows[0].2, Some(1.5));
+        assert_eq!(rows[2].2, None);
+        for ((geom, _, _), contour) in rows.iter().zip(&contours) {
+            assert_eq!(&geom[..2], b"GP");
+            let f64_at = |i: usize| f64::from_le_bytes(geom[i..i + 8].try_into().unwrap());
+            let u32_at = |i: usize| u32::from_le_bytes(geom[i..i + 4].try_into().unwrap());
+            match contour.bbox() {
+                Some(bbox) => {
+                    assert_eq!(geom[3], 0b11);
+                    assert_eq!(f64_at(8) as Float, bbox.min().x);
+                    assert_eq!(f64_at(32) as Float, bbox.max().y);
+                    // A multipolygon (after the header and its envelope)
+                    assert_eq!((geom[40], u32_at(41)), (1, 6));
+                    assert_eq!(u32_at(45) as usize, contour.geometry().0.len());
+                }
+                None => {
+                    assert_eq!(geom[3], 0b1_0001);
+                    assert_eq!((geom[8], u32_at(9), u32_at(13)), (1, 6, 0));
+                }
+            }
+        }
+        drop(statement);
+        drop(conn);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "geojson")]
+    #[test]
+    fn test_maplibre_style() {
+        use crate::{ColorRamp, GeoJsonKeys, MapLibreStyle};
+        use serde_json::json;
+
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 2., 2., 2., 0.,
+            0., 2., 4., 2., 0.,
+            0., 2., 2., 1., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let builder = ContourBuilder::new(5, 5, true).index_interval(Some(2.));
+        let bands = builder.isobands(&values, &[3., 0., 1., 5.]).unwrap();
+        let lines = builder.lines(&values, &[1., 2., 3.]).unwrap();
+        let keys = GeoJsonKeys {
+            min_v: "lo".into(),
+            ..Default::default()
+        };
+        let style = MapLibreStyle::new("dem")
+            .ramp(ColorRamp::Turbo)
+            .line_widths(2., 1.)
+            .keys(keys);
+        let overlay = style.overlay(&bands, &lines);
+
+        // A step expression on the min value of the bands, with the colors of the ramp
+        let colors = ColorRamp::Turbo.band_colors(&bands);
+        let fill = &overlay["layers"][0];
+        assert_eq!(fill["source"], "dem-bands");
+        assert_eq!(
+            fill["paint"]["fill-color"],
+            json!([
+                "step",
+                ["get", "lo"],
+                colors[0].to_hex(),
+                1.,
+                colors[1].to_hex(),
+                3.,
+                colors[2].to_hex()
+            ])
+        );
+        let line = &overlay["layers"][1];
+        assert_eq!(line["source"], "dem-lines");
+        assert_eq!(
+            line["paint"]["line-width"],
+            json!(["match", ["get", "class"], "index", 2., 1.])
+        );
+        // The sources hold the features, with the properties the layers refer to
+        let data = &overlay["sources"]["dem-bands"]["data"];
+        assert_eq!(data["features"].as_array().unwrap().len(), 3);
+        assert_eq!(data["features"][1]["properties"]["lo"], json!(1.));
+        let data = &overlay["sources"]["dem-lines"]["data"];
+        assert_eq!(data["features"][1]["properties"]["class"], "index");
+    }
+
+    #[test]
+    fn test_nodata() {
+        let n = Float::NAN;
+        #[rustfmt::skip]
+        let values = vec![
+            n, 0., 0., 0., 0.,
+            n, 2., 2., 0., 0.,
+            n, 2., 2., 0., 0.,
+            n, 2., 2., 0., 0.,
+            n, 0., 0., 0., 0.,
+        ];
+        let coords = |lines: &[crate::Line]| {
+            lines
+                .iter()
+                .flat_map(|l| l.geometry().0.iter().flat_map(|ls| ls.0.clone()))
+                .collect::<Vec<_>>()
+        };
+        // The isolines are cut where they would follow the cells without data
+        let builder = ContourBuilder::new(5, 5, false);
+        let lines = builder.lines(&values, &[1.]).unwrap();
+        let pts = coords(&lines);
+        assert!(!pts.is_empty());
+        assert!(pts.iter().all(|p| p.x > 1.));
+        assert!(lines[0].geometry().0.iter().all(|ls| !ls.is_closed()));
+        // The contours don't cover the cells without data
+        let contours = builder.contours(&values, &[1.]).unwrap();
+        assert!(!contours[0].contains(0.5, 2.5));
+        assert!(contours[0].contains(1.5, 2.5));
+        // Smoothing never produces NaN coordinates
+        for placement in [crate::VertexPlacement::Edges, crate::VertexPlacement::Dual] {
+            let builder = ContourBuilder::new(5, 5, true).vertex_placement(placement);
+            let lines = builder.lines(&values, &[1.]).unwrap();
+            assert!(coords(&lines)
+                .iter()
+                .all(|p| p.x.is_finite() && p.y.is_finite()));
+            let bands = builder.isobands(&values, &[-1., 1., 3.]).unwrap();
+            assert!(bands.iter().all(|b| b
+                .geometry()
+                .0
+                .iter()
+                .flat_map(|p| p.exterior().0.iter())
+                .all(|p| p.x.is_finite() && p.y.is_finite())));
+        }
+        // A sentinel value is handled like NaN
+        let sentinel = values
+            .iter()
+            .map(|v| if v.is_nan() { -9999. } else { *v })
+            .collect::<Vec<_>>();
+        let builder = ContourBuilder::new(5, 5, true).nodata(Some(-9999.));
+        let expected = ContourBuilder::new(5, 5, true)
+            .lines(&values, &[1.])
+            .unwrap();
+        let lines = builder.lines(&sentinel, &[1.]).unwrap();
+        assert_eq!(coords(&lines), coords(&expected));
+        let (both, _) = builder.lines_and_contours(&sentinel, &[1.]).unwrap();
+        assert_eq!(coords(&both), coords(&expected));
+
+        // The hole around a cell without data belongs to the band around it
+        #[rustfmt::skip]
+        let values = vec![
+            0., 0., 0., 0., 0.,
+            0., 2., 2., 2., 0.,
+            0., 2., n, 2., 0.,
+            0., 2., 2., 2., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let builder = ContourBuilder::new(5, 5, true);
+        let bands = builder.isobands(&values, &[-1., 1., 3.]).unwrap();
+        assert_eq!(bands[0].geometry().0.len(), 1);
+        assert_eq!(bands[0].geometry().0[0].interiors().len(), 1);
+        assert!(!bands[0].contains(2.5, 2.5));
+        assert_eq!(bands[1].geometry().0.len(), 1);
+        assert_eq!(bands[1].geometry().0[0].interiors().len(), 1);
+        assert!(bands[1].contains(1.5, 2.5) && !bands[1].contains(2.5, 2.5));
+        let report = builder.check_invariants(&values, &[-1., 1., 3.]).unwrap();
+        assert!(report.is_ok(), "{}", report);
+        let report = builder.nodata(Some(0.5)).fuzz_invariants(1, 200).unwrap();
+        assert!(report.is_ok(), "{}", report);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn test_from_array() {
+        use ndarray::{s, Array2};
+        #[rustfmt::skip]
+        let values = vec![
+            0., 0., 0., 0., 0.,
+            0., 1., 2., 1., 0.,
+            0., 1., 3., 1., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let array = Array2::from_shape_vec((4, 5), values.clone()).unwrap();
+        // The dimensions of the grid are those of the array
+        let builder = ContourBuilder::new(1, 1, true);
+        let expected = ContourBuilder::new(5, 4, true);
+        let contours = builder.contours_from_array(array.view(), &[0.5]).unwrap();
+        let flat = expected.contours(&values, &[0.5]).unwrap();
+        assert_eq!(contours[0].geometry(), flat[0].geometry());
+        let lines = builder.lines_from_array(array.view(), &[1.5]).unwrap();
+        let flat = expected.lines(&values, &[1.5]).unwrap();
+        assert_eq!(lines[0].geometry(), flat[0].geometry());
+        // Non-contiguous views are read in their logical order
+        let transposed = array.t().to_owned();
+        let bands = builder
+            .isobands_from_array(transposed.t(), &[0.5, 1.5, 3.5])
+            .unwrap();
+        let flat = expected.isobands(&values, &[0.5, 1.5, 3.5]).unwrap();
+        assert_eq!(bands.len(), flat.len());
+        assert!(bands
+            .iter()
+            .zip(&flat)
+            .all(|(a, b)| a.geometry() == b.geometry()));
+        let strided = array.slice(s![.., ..;2]);
+        let lines = builder.lines_from_array(strided, &[0.5]).unwrap();
+        let flat = ContourBuilder::new(3, 4, true)
+            .lines(&strided.iter().copied().collect::<Vec<_>>(), &[0.5])
+            .unwrap();
+        assert_eq!(lines[0].geometry(), flat[0].geometry());
+    }
+
+    #[test]
+    fn test_csv_writer() {
+        #[rustfmt::skip]
+        let values = vec![
+            0., 0., 0., 0., 0.,
+            0., 2., 2., 2., 0.,
+            0., 2., 0., 2., 0.,
+            0., 2., 2., 2., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let builder = ContourBuilder::new(5, 5, false);
+        let contours = builder.contours(&values, &[1., 3.]).unwrap();
+        let lines = builder.lines(&values, &[1.]).unwrap();
+        let mut writer = CsvWriter::new(Vec::new()).unwrap();
+        writer.write_contour(&contours[0]).unwrap();
+        writer.write_line(&lines[0]).unwrap();
+        writer.write_contour(&contours[1]).unwrap();
+        assert_eq!(writer.len(), 3);
+        let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+        let rows = output.lines().collect::<Vec<_>>();
+        assert_eq!(rows[0], "threshold,min,max,area,wkt");
+        // The area of the ring around the plateau, minus its hole
+        assert!(rows[1].starts_with("1,1,3,8,\"MULTIPOLYGON (((4 3.5, "));
+        assert_eq!(rows[1].matches("), (").count(), 1);
+        assert!(rows[2].starts_with("1,,,,\"MULTILINESTRING (("));
+        assert_eq!(rows[3], "3,3,,0,\"MULTIPOLYGON EMPTY\"");
+        assert_eq!(contours[0].to_wkt(), rows[1][9..rows[1].len() - 1]);
+    }
+
+    #[test]
+    fn test_grid_source() {
+        use crate::{contour_rings, contour_rings_from_source, GridSource, StridedGrid};
+        #[rustfmt::skip]
+        let values = vec![
+            0., 0., 0., 0., 0.,
+            0., 1., 2., 1., 0.,
+            0., 1., 3., 1., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let builder = ContourBuilder::new(5, 4, true);
+        let rows = values.chunks(5).map(|r| r.to_vec()).collect::<Vec<_>>();
+        assert_eq!((rows.width(), rows.height(), rows.get(2, 2)), (5, 4, 3.));
+        // The rows, and a column-major copy of the grid, give the same results
+        let column_major = (0..20)
+            .map(|i| values[(i % 4) * 5 + i / 4])
+            .collect::<Vec<_>>();
+        let strided = StridedGrid::new(&column_major, 5, 4).strides(1, 4);
+        let expected = builder.contours(&values, &[0.5, 2.]).unwrap();
+        for source in [&rows as &dyn GridSource, &strided] {
+            let contours = builder.contours_from_source(&source, &[0.5, 2.]).unwrap();
+            assert_eq!(contours.len(), expected.len());
+            assert!(contours
+                .iter()
+                .zip(&expected)
+                .all(|(a, b)| a.geometry() == b.geometry()));
+            let lines = builder.lines_from_source(&source, &[1.5]).unwrap();
+            let flat = builder.lines(&values, &[1.5]).unwrap();
+            assert_eq!(lines[0].geometry(), flat[0].geometry());
+            let bands = builder
+                .isobands_from_source(&source, &[0.5, 2., 4.])
+                .unwrap();
+            let flat = builder.isobands(&values, &[0.5, 2., 4.]).unwrap();
+            assert_eq!(bands[1].geometry(), flat[1].geometry());
+            assert_eq!(
+                contour_rings_from_source(&source, 0.5).unwrap(),
+                contour_rings(&values, 0.5, 5, 4).unwrap()
+            );
+        }
+        // The settings of the builder apply to the sources too
+        let padded = builder.clone().padding(1, crate::Padding::Replicate);
+        let contours = padded.contours_from_source(&strided, &[0.5]).unwrap();
+        let flat = padded.contours(&values, &[0.5]).unwrap();
+        assert_eq!(contours[0].geometry(), flat[0].geometry());
+        // The dimensions of the source have to match the ones of the builder
+        assert!(ContourBuilder::new(4, 5, false)
+            .contours_from_source(&strided, &[0.5])
+            .is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        use crate::ValidityIssue;
+        #[rustfmt::skip]
+        let values = vec![
+            0., 0., 0., 0., 0., 0.,
+            0., 2., 2., 2., 2., 0.,
+            0., 2., 0., 0., 2., 0.,
+            0., 2., 0., 0., 2., 0.,
+            0., 2., 2., 2., 2., 0.,
+            0., 0., 0., 0., 0., 0.,
+        ];
+        for smooth in [false, true] {
+            let builder = ContourBuilder::new(6, 6, smooth).y_step(-1.);
+            let contours = builder.contours(&values, &[1.]).unwrap();
+            assert!(contours[0].validate().is_valid());
+            let bands = builder.isobands(&values, &[-1., 1., 3.]).unwrap();
+            // The rings of the bands keep the winding they have as isolines
+            assert!(bands
+                .iter()
+                .flat_map(|b| b.validate().issues)
+                .all(|issue| matches!(issue, ValidityIssue::WrongWinding { .. })));
+        }
+        let contour = &ContourBuilder::new(6, 6, false)
+            .contours(&values, &[1.])
+            .unwrap()[0];
+        // A hole wound like its exterior ring
+        let mut invalid = contour.clone();
+        invalid.geometry.0[0].interiors_mut(|holes| holes[0].0.reverse());
+        assert_eq!(
+            invalid.validate().issues,
+            vec![ValidityIssue::WrongWinding {
+                polygon: 0,
+                ring: 1
+            }]
+        );
+        // A hole outside of its exterior ring
+        let mut invalid = contour.clone();
+        invalid.geometry.0[0]
+            .interiors_mut(|holes| holes[0].0.iter_mut().for_each(|pt| pt.x += 10.));
+        assert_eq!(
+            invalid.validate().issues,
+            vec![ValidityIssue::HoleOutside {
+                polygon: 0,
+                ring: 1
+            }]
+        );
+        // Two swapped vertices make the exterior ring cross itself
+        let mut invalid = contour.clone();
+        invalid.geometry.0[0].exterior_mut(|exterior| exterior.0.swap(3, 4));
+        let issues = invalid.validate().issues;
+        assert!(!issues.is_empty());
+        assert!(issues.iter().all(|issue| matches!(
+            issue,
+            ValidityIssue::SelfIntersection {
+                polygon: 0,
+                ring: 0,
+                other: 0,
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    fn test_column_major() {
+        use crate::{GridOrder, InfinityPolicy, Padding};
+        #[rustfmt::skip]
+        let values = vec![
+            0., 0., 0., 0., 0.,
+            0., 1., 2., 1., 0.,
+            0., 1., 3., 4., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let weights = (0..20).map(|i| 1. + (i % 3) as Float).collect::<Vec<_>>();
+        let transpose = |v: &[Float]| (0..20).map(|i| v[(i % 4) * 5 + i / 4]).collect::<Vec<_>>();
+        let row_major = ContourBuilder::new(5, 4, true).weights(Some(weights.clone()));
+        let column_major = ContourBuilder::new(5, 4, true)
+            .weights(Some(transpose(&weights)))
+            .order(GridOrder::ColumnMajor);
+        let fortran = transpose(&values);
+        let expected = row_major.contours(&values, &[0.5, 2.5]).unwrap();
+        let contours = column_major.contours(&fortran, &[0.5, 2.5]).unwrap();
+        assert!(contours
+            .iter()
+            .zip(&expected)
+            .all(|(a, b)| a.geometry() == b.geometry()));
+        let expected = row_major.lines(&values, &[1.5]).unwrap();
+        let lines = column_major.lines(&fortran, &[1.5]).unwrap();
+        assert_eq!(lines[0].geometry(), expected[0].geometry());
+        let expected = row_major.isobands(&values, &[0.5, 2.5, 9.]).unwrap();
+        let bands = column_major.isobands(&fortran, &[0.5, 2.5, 9.]).unwrap();
+        assert_eq!(bands[1].geometry(), expected[1].geometry());
+        // With padding
+        let expected = row_major
+            .clone()
+            .padding(2, Padding::Linear)
+            .contours(&values, &[3.5])
+            .unwrap();
+        let contours = column_major
+            .clone()
+            .padding(2, Padding::Linear)
+            .contours(&fortran, &[3.5])
+            .unwrap();
+        assert_eq!(contours[0].geometry(), expected[0].geometry());
+        // The infinite values are located in the grid
+        let mut infinite = fortran.clone();
+        infinite[3 * 4 + 2] = Float::INFINITY;
+        let err = ContourBuilder::new(5, 4, false)
+            .order(GridOrder::ColumnMajor)
+            .infinity_policy(InfinityPolicy::Error)
+            .contours(&infinite, &[0.5])
+            .unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            crate::ErrorKind::InfiniteValue { x: 3, y: 2 }
+        ));
+    }
+
+    #[test]
+    fn test_close_rings() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        #[rustfmt::skip]
+        let values = vec![
+            0., 0., 0., 0., 0.,
+            0., 2., 2., 2., 0.,
+            0., 2., 0., 2., 0.,
+            0., 2., 2., 2., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        // A transform moving the first vertex it is called with
+        let builder = |close: bool| {
+            let calls = Arc::new(AtomicUsize::new(0));
+            ContourBuilder::new(5, 5, false)
+                .close_rings(close)
+                .transform(move |x, y| match calls.fetch_add(1, Ordering::Relaxed) {
+                    0 => (x + 0.25, y),
+                    _ => (x, y),
+                })
+        };
+        let lines = builder(true).lines(&values, &[1.]).unwrap();
+        assert!(lines[0].geometry().0.iter().all(|l| l.is_closed()));
+        let lines = builder(false).lines(&values, &[1.]).unwrap();
+        assert!(!lines[0].geometry().0.iter().all(|l| l.is_closed()));
+        // The polygon rings are closed without an additional vertex
+        let contours = builder(true).contours(&values, &[1.]).unwrap();
+        let polygon = &contours[0].geometry().0[0];
+        assert!(polygon.exterior().is_closed());
+        assert_eq!(polygon.exterior().0.len(), 13);
+        assert!(contours[0].validate().is_valid());
+    }
+
+    #[cfg(feature = "geotiff")]
+    #[test]
+    fn test_geotiff() {
+        use tiff::encoder::{colortype, TiffEncoder};
+        use tiff::tags::Tag;
+        #[rustfmt::skip]
+        let values: Vec<f32> = vec![
+            0., 0., 0., 0.,
+            0., 2., 2., -9999.,
+            0., 2., 2., 0.,
+        ];
+        let path = std::env::temp_dir().join(format!("contour-{}.tif", std::process::id()));
+        {
+            let file = std::fs::File::create(&path).unwrap();
+            let mut encoder = TiffEncoder::new(file).unwrap();
+            let mut image = encoder.new_image::<colortype::Gray32Float>(4, 3).unwrap();
+            let tags = image.encoder();
+            tags.write_tag(Tag::ModelPixelScaleTag, &[10., 5., 0.][..])
+                .unwrap();
+            tags.write_tag(Tag::ModelTiepointTag, &[0., 0., 0., 100., 200., 0.][..])
+                .unwrap();
+            tags.write_tag(Tag::GdalNodata, "-9999").unwrap();
+            image.write_data(&values).unwrap();
+        }
+        let (builder, grid) = ContourBuilder::from_geotiff(&path, false).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!((builder.dx(), builder.dy()), (4, 3));
+        assert_eq!(builder.origin(), (100., 200.));
+        assert_eq!(builder.step(), (10., -5.));
+        assert_eq!(grid.len(), 12);
+        assert_eq!(grid[7], -9999.);
+        let contours = builder.contours(&grid, &[1.]).unwrap();
+        let points = contours[0].geometry().0[0].exterior().0.clone();
+        assert!(points
+            .iter()
+            .all(|p| (110.0..=130.).contains(&p.x) && (185.0..=195.).contains(&p.y)));
+        assert!(contours[0].validate().is_valid());
+
+        let missing = std::env::temp_dir().join("contour-missing.tif");
+        let err = ContourBuilder::from_geotiff(missing, false).unwrap_err();
+        assert!(matches!(err.kind(), crate::ErrorKind::IoError(_)));
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let values = vec![0., 1., 2., 1., 3., 1., 2., 1., 0.];
+        let builder = ContourBuilder::new(3, 3, true);
+        let fingerprint = builder.fingerprint(&values).unwrap();
+        assert_eq!(builder.clone().fingerprint(&values).unwrap(), fingerprint);
+        assert_eq!(
+            ContourBuilder::new(3, 3, true)
+                .fingerprint(&values)
+                .unwrap(),
+            fingerprint
+        );
+        let mut other = values.clone();
+        other[4] = 3.5;
+        assert_ne!(builder.fingerprint(&other).unwrap(), fingerprint);
+        // The options changing the results change the fingerprint
+        let changed = [
+            ContourBuilder::new(3, 3, false),
+            ContourBuilder::new(3, 3, true).y_step(-1.),
+            ContourBuilder::new(3, 3, true).nodata(Some(3.)),
+            ContourBuilder::new(3, 3, true).order(crate::GridOrder::ColumnMajor),
+            ContourBuilder::new(3, 3, true).tie_policy(crate::TiePolicy::Outside),
+        ];
+        for builder in &changed {
+            assert_ne!(builder.fingerprint(&values).unwrap(), fingerprint);
+        }
+        // But not the other ones
+        let same = ContourBuilder::new(3, 3, true).threads(2).ring_cache(true);
+        assert_eq!(same.fingerprint(&values).unwrap(), fingerprint);
+        assert!(builder.fingerprint(&values[1..]).is_err());
+    }
+
+    #[test]
+    fn test_approximate() {
+        let (dx, dy) = (40, 30);
+        let values = (0..dx * dy)
+            .map(|i| {
+                let (x, y) = ((i % dx) as Float, (i / dx) as Float);
+                (x / 5.).sin() * 3. + (y / 7.).cos() * 2. + x / 10.
+            })
+            .collect::<Vec<_>>();
+        let builder = ContourBuilder::new(dx, dy, true)
+            .x_origin(100.)
+            .x_step(2.)
+            .y_step(-1.);
+        let thresholds = [0., 1.5, 3.];
+        let exact = builder.lines(&values, &thresholds).unwrap();
+        // Without downsampling, the lines are exact
+        let (lines, _) = builder.lines_approximate(&values, &thresholds, 1).unwrap();
+        for (a, b) in lines.iter().zip(&exact) {
+            assert_eq!(a.geometry(), b.geometry());
+        }
+
+        let distance = |p: geo_types::Coord<Float>, lines: &MultiLineString<Float>| {
+            lines
+                .0
+                .iter()
+                .flat_map(|l| l.lines())
+                .map(|s| {
+                    let (d, e) = (s.delta(), p - s.start);
+                    let len = d.x * d.x + d.y * d.y;
+                    let t = if len > 0. {
+                        ((e.x * d.x + e.y * d.y) / len).clamp(0., 1.)
+                    } else {
+                        0.
+                    };
+                    (e.x - t * d.x).hypot(e.y - t * d.y)
+                })
+                .fold(Float::INFINITY, Float::min)
+        };
+        for factor in [2, 3, 4] {
+            let (lines, error) = builder
+                .lines_approximate(&values, &thresholds, factor)
+                .unwrap();
+            assert!(error.is_finite() && error > 0.);
+            for (a, b) in lines.iter().zip(&exact) {
+                for (from, to) in [(a, b), (b, a)] {
+                    for p in from.geometry().0.iter().flat_map(|l| l.0.iter()) {
+                        assert!(distance(*p, to.geometry()) <= error);
+                    }
+                }
+            }
+        }
+        let (contours, error) = builder
+            .contours_approximate(&values, &thresholds, 4)
+            .unwrap();
+        assert_eq!(contours.len(), 3);
+        assert_eq!(
+            error,
+            builder
+                .lines_approximate(&values, &thresholds, 4)
+                .unwrap()
+                .1
+        );
+        let (bands, _) = builder
+            .isobands_approximate(&values, &thresholds, 4)
+            .unwrap();
+        assert_eq!(bands.len(), 2);
+
+        // A peak vanishing in the downsampled grid
+        let mut values = vec![0.; 64];
+        values[27] = 10.;
+        let builder = ContourBuilder::new(8, 8, false);
+        let (lines, error) = builder.lines_approximate(&values, &[1.], 4).unwrap();
+        assert!(lines[0].geometry().0.is_empty());
+        assert_eq!(error, Float::INFINITY);
+        let (_, error) = builder.lines_approximate(&values, &[20.], 4).unwrap();
+        assert_eq!(error, 0.);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_from_luma() {
+        use image::{GrayImage, ImageBuffer, Luma};
+        let image = GrayImage::from_fn(7, 5, |x, y| Luma([(x * 30 + y * 10) as u8]));
+        let values = image.pixels().map(|p| p.0[0] as Float).collect::<Vec<_>>();
+        let builder = ContourBuilder::new(1, 1, true);
+        let expected = ContourBuilder::new(7, 5, true)
+            .contours(&values, &[50., 100.])
+            .unwrap();
+        let contours = builder.contours_from_luma(&image, &[50., 100.]).unwrap();
+        for (a, b) in contours.iter().zip(&expected) {
+            assert_eq!(a.geometry(), b.geometry());
+        }
+        // The rows are read from the top of the image
+        let mut image = GrayImage::new(4, 4);
+        image.put_pixel(1, 0, Luma([255]));
+        let line = &builder.lines_from_luma(&image, &[128.]).unwrap()[0];
+        assert!(line.geometry().0[0].0.iter().all(|p| p.y <= 1.));
+
+        let wide: ImageBuffer<Luma<u16>, Vec<u16>> =
+            ImageBuffer::from_fn(7, 5, |x, y| Luma([(x * 3000 + y * 1000) as u16]));
+        let bands = builder.isobands_from_luma(&wide, &[5000., 10000.]).unwrap();
+        let expected = ContourBuilder::new(7, 5, true)
+            .isobands(&values, &[50., 100.])
+            .unwrap();
+        assert_eq!(bands.len(), 1);
+        let scaled = bands[0]
+            .geometry()
+            .0
+            .iter()
+            .map(|p| p.exterior().0.len())
+            .collect::<Vec<_>>();
+        let unscaled = expected[0]
+            .geometry()
+            .0
+            .iter()
+            .map(|p| p.exterior().0.len())
+            .collect::<Vec<_>>();
+        assert_eq!(scaled, unscaled);
+    }
+
+    #[test]
+    fn test_grid_value() {
+        use crate::{GridOrder, Padding};
+        #[rustfmt::skip]
+        let counts: Vec<u8> = vec![
+            0, 0, 0, 0, 0,
+            0, 3, 5, 3, 0,
+            0, 5, 9, 5, 0,
+            0, 3, 5, 3, 0,
+            0, 0, 0, 0, 0,
+        ];
+        let values = counts.iter().map(|&v| v as Float).collect::<Vec<_>>();
+        let builders = [
+            ContourBuilder::new(5, 5, true),
+            ContourBuilder::new(5, 5, true).padding(1, Padding::Replicate),
+            ContourBuilder::new(5, 5, false).order(GridOrder::ColumnMajor),
+        ];
+        for builder in &builders {
+            let contours = builder.contours(&counts, &[2, 4]).unwrap();
+            let expected = builder.contours(&values, &[2., 4.]).unwrap();
+            for (a, b) in contours.iter().zip(&expected) {
+                assert_eq!(a.geometry(), b.geometry());
+                assert_eq!(a.threshold(), b.threshold());
+            }
+            let lines = builder.lines(&counts, &[4]).unwrap();
+            let expected = builder.lines(&values, &[4.]).unwrap();
+            assert_eq!(lines[0].geometry(), expected[0].geometry());
+            let bands = builder.isobands(&counts, &[2, 4, 10]).unwrap();
+            let expected = builder.isobands(&values, &[2., 4., 10.]).unwrap();
+            for (a, b) in bands.iter().zip(&expected) {
+                assert_eq!(a.geometry(), b.geometry());
+            }
+        }
+        let elevations = counts.iter().map(|&v| v as i16 - 4).collect::<Vec<_>>();
+        let mut streamed = Vec::new();
+        ContourBuilder::new(5, 5, true)
+            .for_each_contour(&elevations, &[-2, 0], |c| {
+                streamed.push(c.threshold());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(streamed, vec![-2., 0.]);
+        assert!(ContourBuilder::new(4, 5, true)
+            .lines(&counts, &[1])
+            .is_err());
+        let builder = ContourBuilder::new(5, 5, false).x_origin(10.);
+        let (grid, world) = builder.isobands_grid_and_world(&counts, &[2, 10]).unwrap();
+        let expected = builder
+            .isobands_grid_and_world(&values, &[2., 10.])
+            .unwrap();
+        assert_eq!(grid[0].geometry(), expected.0[0].geometry());
+        assert_eq!(world[0].geometry(), expected.1[0].geometry());
+        let levels = [(4, Metadata::from([("name".to_string(), "high".into())]))];
+        let lines = builder.lines_with_metadata(&counts, &levels).unwrap();
+        assert_eq!(lines[0].metadata().get("name"), Some(&"high".into()));
+        assert_eq!(
+            builder.lattice_rings(&counts, 4).unwrap(),
+            builder.lattice_rings(&values, 4.).unwrap()
+        );
+        assert!(ContourBuilder::new(4, 5, true)
+            .lattice_rings(&counts, 1)
+            .is_err());
+    }
+
+    #[test]
+    fn test_vectorize_classes() {
+        use crate::GridOrder;
+        #[rustfmt::skip]
+        let classes: Vec<u8> = vec![
+            4, 4, 4, 7, 7,
+            4, 9, 4, 7, 7,
+            4, 4, 4, 7, 7,
+            2, 2, 2, 2, 2,
+        ];
+        let builder = ContourBuilder::new(5, 4, true).x_origin(10.);
+        let regions = builder.vectorize_classes(&classes).unwrap();
+        assert_eq!(
+            regions.iter().map(|r| r.class()).collect::<Vec<_>>(),
+            vec![2, 4, 7, 9]
+        );
+        let area = |p: &MultiPolygon<Float>| {
+            p.0.iter()
+                .map(|p| {
+                    crate::area::area(&p.exterior().0).abs()
+                        - p.interiors()
+                            .iter()
+                            .map(|r| crate::area::area(&r.0).abs())
+                            .sum::<f64>()
+                })
+                .sum::<f64>()
+                / 2.
+        };
+        // The regions don't overlap, the corners of the cells being cut
+        // where three classes meet and on the corners of the grid
+        let areas = regions
+            .iter()
+            .map(|r| area(r.geometry()))
+            .collect::<Vec<_>>();
+        assert_eq!(areas, vec![4.5, 8., 5.5, 0.5]);
+        // The cell of class 9 is a hole of the region of class 4
+        assert_eq!(regions[1].geometry().0[0].interiors().len(), 1);
+        assert!(regions.iter().all(|r| r.validate().is_valid()));
+        let bbox = regions[2].bbox().unwrap();
+        assert_eq!((bbox.min().x, bbox.max().x), (13., 15.));
+
+        let transposed = (0..20)
+            .map(|i| classes[(i % 4) * 5 + i / 4])
+            .collect::<Vec<_>>();
+        let column_major = ContourBuilder::new(5, 4, true)
+            .x_origin(10.)
+            .order(GridOrder::ColumnMajor)
+            .vectorize_classes(&transposed)
+            .unwrap();
+        for (a, b) in regions.iter().zip(&column_major) {
+            assert_eq!(a.geometry(), b.geometry());
+        }
+        assert!(builder.vectorize_classes(&classes[1..]).is_err());
+    }
+
+    #[test]
+    fn test_axis_coords() {
+        #[rustfmt::skip]
+        let values = vec![
+            0., 0., 0., 0., 0.,
+            0., 2., 2., 2., 0.,
+            0., 2., 3., 2., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        // Coordinates matching a uniform grid give the same results as its origin and step
+        let uniform = ContourBuilder::new(5, 4, true)
+            .x_origin(10.)
+            .x_step(2.)
+            .y_origin(5.)
+            .y_step(-0.5);
+        let explicit = ContourBuilder::new(5, 4, true)
+            .x_coords(&[11., 13., 15., 17., 19.])
+            .y_coords(&[4.75, 4.25, 3.75, 3.25]);
+        let (a, b) = (
+            uniform.contours(&values, &[1.]).unwrap(),
+            explicit.contours(&values, &[1.]).unwrap(),
+        );
+        assert_eq!(a[0].geometry().0.len(), 1);
+        for (p, q) in a[0].geometry().0[0]
+            .exterior()
+            .0
+            .iter()
+            .zip(&b[0].geometry().0[0].exterior().0)
+        {
+            assert!((p.x - q.x).abs() < 1e-6 && (p.y - q.y).abs() < 1e-6);
+        }
+
+        // A stretched grid, finer on its left side
+        let xs = [0., 1., 3., 6., 10.];
+        let stretched = ContourBuilder::new(5, 4, false).x_coords(&xs);
+        let grid = ContourBuilder::new(5, 4, false);
+        let (a, b) = (
+            grid.lines(&values, &[1.]).unwrap(),
+            stretched.lines(&values, &[1.]).unwrap(),
+        );
+        let map = |x: Float| {
+            let u = x - 0.5;
+            let i = (u.floor().max(0.) as usize).min(3);
+            xs[i] + (u - i as Float) * (xs[i + 1] - xs[i])
+        };
+        for (p, q) in a[0].geometry().0[0].0.iter().zip(&b[0].geometry().0[0].0) {
+            assert_eq!((map(p.x), p.y), (q.x, q.y));
+        }
+        // The vertices on the edges of the grid are extrapolated
+        let mut edge = values.clone();
+        edge[5] = 2.;
+        let lines = stretched.lines(&edge, &[1.]).unwrap();
+        assert!(lines[0].geometry().0[0].0.iter().any(|p| p.x == -0.5));
+        let contours = stretched
+            .clone()
+            .y_coords(&[3., 2., 1., 0.])
+            .contours(&values, &[1.])
+            .unwrap();
+        assert_eq!(contours[0].geometry().0.len(), 1);
+        assert!(contours[0].validate().is_valid());
+        assert!(stretched
+            .clone()
+            .x_coords(&xs[1..])
+            .lines(&values, &[1.])
+            .is_err());
+    }
+
+    #[test]
+    fn test_region() {
+        #[rustfmt::skip]
+        let values = vec![
+            0., 0., 0., 0., 0.,
+            0., 12., 15., 25., 0.,
+            0., 15., 30., 15., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let builder = ContourBuilder::new(5, 4, true);
+        let band = builder.region(&values, 10., 20.).unwrap();
+        let expected = builder.isobands(&values, &[10., 20.]).unwrap();
+        assert_eq!(band.geometry(), expected[0].geometry());
+        assert_eq!((band.min_v(), band.max_v()), (10., 20.));
+        // Integer grids and empty windows
+        let counts = values.iter().map(|&v| v as u16).collect::<Vec<_>>();
+        let empty = builder
+            .clone()
+            .skip_empty(true)
+            .region(&counts, 40, 50)
+            .unwrap();
+        assert!(empty.geometry().0.is_empty());
+        assert_eq!(empty.min_v(), 40.);
+    }
+
+    #[test]
+    fn test_curvilinear_coords() {
+        #[rustfmt::skip]
+        let values = vec![
+            5., 5., 5., 5., 5., 5.,
+            5., 9., 9., 5., 1., 5.,
+            5., 9., 9., 5., 1., 5.,
+            5., 5., 5., 5., 5., 5.,
+        ];
+        // A sheared grid whose rows go southward
+        let affine = |u: Float, v: Float| (10. + 2. * u + 0.5 * v, 50. + 0.2 * u - v);
+        let (xs, ys): (Vec<Float>, Vec<Float>) = (0..24)
+            .map(|k| affine((k % 6) as Float, (k / 6) as Float))
+            .unzip();
+        let builder = ContourBuilder::new(6, 4, true).curvilinear_coords(&xs, &ys);
+        let grid = ContourBuilder::new(6, 4, true);
+        let (a, b) = (
+            grid.lines(&values, &[3., 7.]).unwrap(),
+            builder.lines(&values, &[3., 7.]).unwrap(),
+        );
+        for (la, lb) in a.iter().zip(&b) {
+            for (p, q) in la.geometry().0[0].0.iter().zip(&lb.geometry().0[0].0) {
+                let (x, y) = affine(p.x - 0.5, p.y - 0.5);
+                assert!((x - q.x).abs() < 1e-4 && (y - q.y).abs() < 1e-4);
+            }
+        }
+        // The winding is checked against the orientation of the grid
+        let contours = builder.contours(&values, &[3., 7.]).unwrap();
+        assert!(contours.iter().all(|c| c.validate().is_valid()));
+        assert_eq!(contours[0].geometry().0[0].interiors().len(), 1);
+        // The rings are mapped back to the grid to classify them
+        let lines = builder.depressions(true).lines(&values, &[3., 7.]).unwrap();
+        let expected = grid.depressions(true).lines(&values, &[3., 7.]).unwrap();
+        for (l, e) in lines.iter().zip(&expected) {
+            assert_eq!(l.depressions(), e.depressions());
+        }
+        assert!(lines[0].depressions().unwrap().contains(&true));
+        assert!(ContourBuilder::new(6, 4, true)
+            .curvilinear_coords(&xs[1..], &ys[1..])
+            .lines(&values, &[3.])
+            .is_err());
+    }
+
+    #[test]
+    fn test_band_depths() {
+        #[rustfmt::skip]
+        let values = vec![
+            0., 0., 0., 0., 0., 0., 0.,
+            0., 2., 2., 2., 2., 2., 0.,
+            0., 2., 0., 0., 0., 2., 0.,
+            0., 2., 0., 2., 0., 2., 0.,
+            0., 2., 0., 0., 0., 2., 0.,
+            0., 2., 2., 2., 2., 2., 0.,
+            0., 0., 0., 0., 0., 0., 0.,
+        ];
+        let builder = ContourBuilder::new(7, 7, false).x_origin(-5.);
+        let bands = builder.isobands(&values, &[1., 3.]).unwrap();
+        let band = &bands[0];
+        assert_eq!(band.geometry().0.len(), 2);
+        assert_eq!(band.geometry().0[0].interiors().len(), 1);
+        // The island lies in the hole of the outer polygon
+        assert_eq!(band.depths(), &[0, 2]);
+        assert_eq!(band.depths(), crate::band::nesting_depths(band.geometry()));
+        let (_, world) = builder.isobands_grid_and_world(&values, &[1., 3.]).unwrap();
+        assert_eq!(world[0].depths(), &[0, 2]);
+    }
+
+    #[test]
+    fn test_geotransform() {
+        #[rustfmt::skip]
+        let values = vec![
+            5., 5., 5., 5., 5., 5.,
+            5., 9., 9., 5., 1., 5.,
+            5., 9., 9., 5., 1., 5.,
+            5., 5., 5., 5., 5., 5.,
+        ];
+        // A north-up grid rotated by 30°
+        let (sin, cos) = (0.5, Float::sqrt(3.) / 2.);
+        let gt = [100., 10. * cos, 10. * sin, 200., 10. * sin, -10. * cos];
+        let affine =
+            |x: Float, y: Float| (gt[0] + x * gt[1] + y * gt[2], gt[3] + x * gt[4] + y * gt[5]);
+        let builder = ContourBuilder::new(6, 4, true).geotransform(gt);
+        let grid = ContourBuilder::new(6, 4, true);
+        for padding in [None, Some(crate::Padding::Linear)] {
+            let (grid, builder) = match padding {
+                Some(mode) => (
+                    grid.clone().padding(1, mode),
+                    builder.clone().padding(1, mode),
+                ),
+                None => (grid.clone(), builder.clone()),
+            };
+            let (a, b) = (
+                grid.lines(&values, &[3., 7.]).unwrap(),
+                builder.lines(&values, &[3., 7.]).unwrap(),
+            );
+            for (la, lb) in a.iter().zip(&b) {
+                for (p, q) in la.geometry().0[0].0.iter().zip(&lb.geometry().0[0].0) {
+                    let (x, y) = affine(p.x, p.y);
+                    assert!((x - q.x).abs() < 1e-4 && (y - q.y).abs() < 1e-4);
+                }
+            }
+        }
+        let contours = builder.contours(&values, &[3., 7.]).unwrap();
+        assert!(contours.iter().all(|c| c.validate().is_valid()));
+        // The rings are mapped back to the grid to classify them
+        let lines = builder.depressions(true).lines(&values, &[3., 7.]).unwrap();
+        let expected = grid.depressions(true).lines(&values, &[3., 7.]).unwrap();
+        for (l, e) in lines.iter().zip(&expected) {
+            assert_eq!(l.depressions(), e.depressions());
+        }
+        // Without rotation, the geotransform sets the origin and steps
+        let north_up =
+            ContourBuilder::new(6, 4, true).geotransform([100., 10., 0., 200., 0., -10.]);
+        let uniform = ContourBuilder::new(6, 4, true)
+            .x_origin(100.)
+            .x_step(10.)
+            .y_origin(200.)
+            .y_step(-10.);
+        assert_eq!(north_up, uniform);
+    }
+
+    #[test]
+    fn test_into_polygons() {
+        #[rustfmt::skip]
+        let values = vec![
+            0., 0., 0., 0., 0., 0., 0.,
+            0., 2., 2., 2., 2., 2., 0.,
+            0., 2., 0., 0., 0., 2., 0.,
+            0., 2., 0., 2., 0., 2., 0.,
+            0., 2., 0., 0., 0., 2., 0.,
+            0., 2., 2., 2., 2., 2., 0.,
+            0., 0., 0., 0., 0., 0., 0.,
+        ];
+        let builder = ContourBuilder::new(7, 7, false);
+        let contour = builder.contours(&values, &[1.]).unwrap().remove(0);
+        assert_eq!(contour.part(), None);
+        let parts = contour.clone().into_polygons();
+        assert_eq!(parts.len(), 2);
+        for (i, part) in parts.iter().enumerate() {
+            assert_eq!(part.part(), Some(i));
+            assert_eq!(part.threshold(), 1.);
+            assert_eq!(part.geometry().0, vec![contour.geometry().0[i].clone()]);
+        }
+        let mut widths = parts
+            .iter()
+            .map(|p| p.bbox().unwrap().width())
+            .collect::<Vec<_>>();
+        widths.sort_by(Float::total_cmp);
+        assert_eq!(widths, vec![1., 5.]);
+
+        let band = builder.isobands(&values, &[1., 3.]).unwrap().remove(0);
+        let parts = band.into_polygons();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].geometry().0[0].interiors().len(), 1);
+        assert_eq!((parts[1].part(), parts[1].depths()), (Some(1), &[2][..]));
+        assert_eq!((parts[1].min_v(), parts[1].max_v()), (1., 3.));
+    }
+
+    #[test]
+    fn test_duplicate_policy() {
+        use crate::{DuplicatePolicy, ErrorKind};
+        #[rustfmt::skip]
+        let values = vec![
+            0., 0., 0., 0., 0.,
+            0., 1., 2., 1., 0.,
+            0., 2., 3., 2., 0.,
+            0., 1., 2., 1., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let builder = ContourBuilder::new(5, 5, true);
+        let thresholds = [0.5, 1.5, 0.5];
+        let lines = builder.lines(&values, &thresholds).unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2].threshold_index(), 2);
+        assert_eq!(lines[2].geometry(), lines[0].geometry());
+        let mut streamed = Vec::new();
+        builder
+            .for_each_contour(&values, &thresholds, |c| {
+                streamed.push(c);
+                Ok(())
+            })
+            .unwrap();
+        let indexes = streamed
+            .iter()
+            .map(|c| c.threshold_index())
+            .collect::<Vec<_>>();
+        assert_eq!(indexes, vec![0, 1, 2]);
+        assert_eq!(streamed[2].geometry(), streamed[0].geometry());
+
+        let dedupe = builder.clone().duplicate_policy(DuplicatePolicy::Dedupe);
+        let (contours, diagnostics) = dedupe
+            .contours_with_diagnostics(&values, &[1.5, 0.5, 1.5, 2.5])
+            .unwrap();
+        let indexes = contours
+            .iter()
+            .map(|c| c.threshold_index())
+            .collect::<Vec<_>>();
+        assert_eq!(indexes, vec![0, 1, 3]);
+        assert_eq!(diagnostics.thresholds.len(), 3);
+        let bands = dedupe.isobands(&values, &[1.5, 0.5, 1.5]).unwrap();
+        assert_eq!(bands.len(), 1);
+        assert_eq!(
+            (
+                bands[0].min_v(),
+                bands[0].max_v(),
+                bands[0].threshold_index()
+            ),
+            (0.5, 1.5, 1)
+        );
+        // Keeping them gives the same bands as before, the rings being reused
+        let bands = builder.isobands(&values, &[1.5, 0.5, 1.5]).unwrap();
+        let ranges = bands
+            .iter()
+            .map(|b| (b.min_v(), b.max_v(), b.threshold_index()))
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, vec![(0.5, 1.5, 1), (1.5, 1.5, 0)]);
+        assert!(bands[1].geometry().0.is_empty());
+
+        let err = builder
+            .duplicate_policy(DuplicatePolicy::Error)
+            .lines(&values, &thresholds)
+            .unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::DuplicateThreshold { index: 2, value } if *value == 0.5
+        ));
+    }
+
+    #[test]
+    fn test_tiled_contours() {
+        let (dx, dy) = (9, 7);
+        let values = (0..dx * dy)
+            .map(|i| {
+                let (x, y) = ((i % dx) as Float - 4.2, (i / dx) as Float - 3.1);
+                (x * 0.9).sin() * 3. + (y * 1.3).cos() * 2. + x.hypot(y) * 0.2
+            })
+            .collect::<Vec<_>>();
+        let thresholds = [-1., 0.5, 2.];
+        let builder = ContourBuilder::new(dx, dy, true).x_origin(10.).x_step(2.);
+        let expected = builder.contours(&values, &thresholds).unwrap();
+        // Tiles of 5 * 4 samples (at most), overlapping by one row and column
+        let mut tiled = builder.tiled(&thresholds);
+        for (x, w) in [(0, 5), (4, 5)] {
+            for (y, h) in [(0, 4), (3, 4)] {
+                let tile = (y..y + h)
+                    .flat_map(|j| values[j * dx + x..j * dx + x + w].to_vec())
+                    .collect::<Vec<_>>();
+                tiled.add_tile(x, y, w, &tile).unwrap();
+            }
+        }
+        let contours = tiled.contours().unwrap();
+        assert_eq!(contours.len(), expected.len());
+        for (tiled, whole) in contours.iter().zip(&expected) {
+            assert_eq!(tiled.threshold(), whole.threshold());
+            let (a, b) = (tiled.geometry(), whole.geometry());
+            assert_eq!(a.0.len(), b.0.len());
+            let area = |m: &MultiPolygon<Float>| {
+                m.0.iter()
+                    .flat_map(|p| std::iter::once(p.exterior()).chain(p.interiors()))
+                    .map(|r| crate::area::area(&r.0))
+                    .sum::<f64>()
+            };
+            assert!((area(a) - area(b)).abs() < 1e-3);
+        }
+        // A missing tile leaves the fragments unstitched
+        let mut partial = builder.tiled(&thresholds);
+        partial.add_tile(0, 0, 5, &values[..5]).unwrap();
+        assert!(partial.contours().is_err());
+        assert!(builder.tiled(&[0.]).add_tile(6, 0, 5, &[0.; 5]).is_err());
+    }
+
+    #[test]
+    fn test_presets() {
+        use crate::Registration;
+        use geo_types::{coord, Rect};
+
+        let extent = Rect::new(coord! { x: 10., y: 40. }, coord! { x: 14., y: 43. });
+        let cell = ContourBuilder::for_geographic(extent, (4, 3), Registration::Cell);
+        assert_eq!(cell.origin(), (10., 43.));
+        assert_eq!(cell.step(), (1., -1.));
+        assert!(cell.is_smooth());
+        // The samples of a grid-registered grid lie on the edges of the extent
+        let point = ContourBuilder::for_geographic(extent, (5, 4), Registration::Point);
+        assert_eq!(point.origin(), (9.5, 43.5));
+        assert_eq!(point.step(), (1., -1.));
+        let values = [
+            0., 0., 0., 0., 0., //
+            0., 0., 0., 0., 0., //
+            0., 0., 0., 0., 0., //
+            0., 0., 0., 0., 1., //
+        ];
+        let contours = point.smooth(false).contours(&values, &[0.5]).unwrap();
+        let bbox = contours[0].bbox().unwrap();
+        assert_eq!((bbox.min().x, bbox.max().x), (13.5, 14.5));
+        assert_eq!((bbox.min().y, bbox.max().y), (39.5, 40.5));
+        let image = ContourBuilder::for_image(3, 2);
+        assert_eq!(
+            (image.dx(), image.dy(), image.origin(), image.step()),
+            (3, 2, (0., 0.), (1., 1.))
+        );
+    }
+
+    #[test]
+    fn test_streaming_contours() {
+        let (dx, dy) = (7, 6);
+        let values = (0..dx * dy)
+            .map(|i| {
+                let (x, y) = ((i % dx) as Float - 3.3, (i / dx) as Float - 2.4);
+                (x * 1.1).cos() * 2. + (y * 0.8).sin() * 3.
+            })
+            .collect::<Vec<_>>();
+        let thresholds = [-1.5, 0., 1.5];
+        let builder = ContourBuilder::new(dx, dy, true);
+        let expected = builder.contours(&values, &thresholds).unwrap();
+        let contours = builder
+            .contours_from_rows(values.chunks(dx), &thresholds)
+            .unwrap();
+        for (streamed, whole) in contours.iter().zip(&expected) {
+            let (a, b) = (streamed.geometry(), whole.geometry());
+            assert_eq!(a.0.len(), b.0.len());
+            let rings = |m: &MultiPolygon<Float>| {
+                let mut areas =
+                    m.0.iter()
+                        .map(|p| (crate::area::area(&p.exterior().0) * 1e6).round())
+                        .collect::<Vec<_>>();
+                areas.sort_by(f64::total_cmp);
+                areas
+            };
+            assert_eq!(rings(a), rings(b));
+        }
+        // Missing and extra rows
+        assert!(builder
+            .contours_from_rows(values.chunks(dx).take(dy - 1), &thresholds)
+            .is_err());
+        let mut streaming = builder.streaming(&thresholds);
+        values
+            .chunks(dx)
+            .for_each(|row| streaming.push_row(row).unwrap());
+        assert!(streaming.push_row(&values[..dx]).is_err());
+        // A single row
+        let single = ContourBuilder::new(3, 1, false)
+            .contours_from_rows([[0., 1., 0.]], &[0.5])
+            .unwrap();
+        assert_eq!(single[0].geometry().0.len(), 1);
+    }
+
+    #[test]
+    fn test_nodata_smoothing() {
+        use crate::NodataSmoothing;
+
+        let n = Float::NAN;
+        // The values decrease towards the masked column
+        #[rustfmt::skip]
+        let values = vec![
+            0., 0., 0., 0., 0., 0.,
+            0., 9., 6., 3., n, 0.,
+            0., 9., 6., 3., n, 0.,
+            0., 9., 6., 3., n, 0.,
+            0., 0., 0., 0., 0., 0.,
+        ];
+        let max_x = |builder: ContourBuilder, threshold: Float| {
+            let contours = builder.contours(&values, &[threshold]).unwrap();
+            contours[0]
+                .geometry()
+                .0
+                .iter()
+                .flat_map(|p| p.exterior().0.iter())
+                .map(|p| p.x)
+                .fold(Float::NEG_INFINITY, Float::max)
+        };
+        let builder = ContourBuilder::new(6, 5, true);
+        let extrapolate = builder
+            .clone()
+            .nodata_smoothing(NodataSmoothing::Extrapolate);
+        // By default, the vertices lie on the boundary of the masked cells
+        assert_eq!(max_x(builder.clone(), 2.), 4.);
+        // Extrapolated from 6 and 3, the values reach 2 a third of the way to the next sample
+        assert!((max_x(extrapolate.clone(), 2.) - (3.5 + 1. / 3.)).abs() < 1e-6);
+        // Clamped to the valid half of the edge (the values reaching 1 two thirds of the way)
+        assert_eq!(max_x(extrapolate.clone(), 1.), 4.);
+        // The vertices never cross into the masked cells, and never are NaN
+        for threshold in [0.5, 1., 2.5, 5.] {
+            let x = max_x(extrapolate.clone(), threshold);
+            assert!(x.is_finite() && x <= 4.);
+        }
+        let quadratic = extrapolate.interpolation(crate::Interpolation::Quadratic);
+        assert!((max_x(quadratic, 2.) - (3.5 + 1. / 3.)).abs() < 1e-6);
+        // The isolines follow the contours, including around the samples equal
+        // to the threshold next to the masked cells (whose rings collapse)
+        let report = ContourBuilder::new(9, 7, true)
+            .nodata(Some(0.5))
+            .nodata_smoothing(NodataSmoothing::Extrapolate)
+            .fuzz_invariants(1, 200)
+            .unwrap();
+        assert!(report.is_ok(), "{}", report);
+    }
+
+    #[test]
+    fn test_sparse_grid() {
+        use crate::{GridSource, SparseGrid};
+
+        let (dx, dy) = (150, 70);
+        let triplets = [
+            (0, 0, 3.),
+            (1, 0, 2.),
+            (40, 30, 5.),
+            (41, 30, 4.),
+            (41, 31, 1.),
+            (63, 31, 2.),
+            (64, 32, 2.),
+            (149, 69, 4.),
+            (100, 5, -3.),
+        ];
+        let grid = SparseGrid::from_triplets(dx, dy, 0., triplets).unwrap();
+        assert_eq!(grid.len(), 9);
+        let dense = (0..dx * dy)
+            .map(|i| grid.get(i % dx, i / dx))
+            .collect::<Vec<_>>();
+        // Thresholds above and below the background
+        let thresholds = [0.5, 1.5, 3.5, -1., -5.];
+        for smooth in [false, true] {
+            let builder = ContourBuilder::new(dx, dy, smooth);
+            let expected = builder.contours(&dense, &thresholds).unwrap();
+            let contours = builder.contours_sparse(&grid, &thresholds).unwrap();
+            for (sparse, whole) in contours.iter().zip(&expected) {
+                let area = |c: &crate::Contour| {
+                    c.geometry()
+                        .0
+                        .iter()
+                        .flat_map(|p| std::iter::once(p.exterior()).chain(p.interiors()))
+                        .map(|r| crate::area::area(&r.0))
+                        .sum::<f64>()
+                };
+                assert_eq!(sparse.geometry().0.len(), whole.geometry().0.len());
+                assert!((area(sparse) - area(whole)).abs() < 1e-6);
+            }
+        }
+        assert!(SparseGrid::new(2, 2, 0.).insert(2, 0, 1.).is_err());
+    }
+
+    #[test]
+    fn test_arrows() {
+        use crate::FlowDirection;
+
+        // A cone centered on (10, 10)
+        let hill = (0..400)
+            .map(|i| {
+                let (x, y) = ((i % 20) as Float + 0.5, (i / 20) as Float + 0.5);
+                10. - (x - 10.).hypot(y - 10.)
+            })
+            .collect::<Vec<_>>();
+        let builder = ContourBuilder::new(20, 20, true);
+        let lines = builder.lines(&hill, &[4.]).unwrap();
+        for (direction, clockwise) in [
+            (FlowDirection::HighOnRight, true),
+            (FlowDirection::HighOnLeft, false),
+        ] {
+            let arrows = builder.arrows(&hill, &lines, 2., direction).unwrap();
+            assert_eq!(arrows.len(), 1);
+            // The circle of radius 6 has a perimeter of about 37.7
+            assert!((18..=19).contains(&arrows[0].len()));
+            for arrow in &arrows[0] {
+                let p = arrow.point();
+                let (rx, ry) = (p.x - 10., p.y - 10.);
+                assert!(((rx.hypot(ry)) - 6.).abs() < 0.1);
+                // Tangent to the circle, the top of the hill on the requested side
+                let (dy, dx) = arrow.angle().sin_cos();
+                assert!((rx * dx + ry * dy).abs() < 0.1 * 6.);
+                assert_eq!(rx * dy - ry * dx < 0., clockwise);
+                let triangle = arrow.polygon(1.);
+                assert_eq!(triangle.exterior().0.len(), 4);
+                let tip = triangle.exterior().0[0];
+                assert!(((tip.x - p.x) - dx * 0.5).abs() < 1e-6);
+            }
+        }
+        let none = builder.arrows(&hill, &lines, 0., FlowDirection::HighOnRight);
+        assert!(none.unwrap()[0].is_empty());
+    }
+    #[test]
+    fn test_periodic_x() {
+        let extent = |c: &crate::Contour| {
+            let xs = c.geometry().0.iter().flat_map(|p| p.exterior().0.iter());
+            xs.fold(
+                (Float::MAX, Float::MAX, Float::MIN, Float::MIN),
+                |(a, b, c, d), p| (a.min(p.x), b.min(p.y), c.max(p.x), d.max(p.y)),
+            )
+        };
+        let area = |c: &crate::Contour| {
+            c.geometry()
+                .0
+                .iter()
+                .map(|p| crate::area::area(&p.exterior().0).abs() / 2.)
+                .sum::<f64>()
+        };
+        // A blob straddling the first and last columns
+        #[rustfmt::skip]
+        let values = vec![
+            0., 0., 0., 0., 0., 0.,
+            1., 0., 0., 0., 0., 1.,
+            1., 0., 0., 0., 0., 1.,
+            0., 0., 0., 0., 0., 0.,
+        ];
+        let builder = ContourBuilder::new(6, 4, true);
+        let res = builder.contours(&values, &[0.5]).unwrap();
+        assert_eq!(res[0].geometry().0.len(), 2);
+        let res = builder.periodic_x(true).contours(&values, &[0.5]).unwrap();
+        assert_eq!(res[0].geometry().0.len(), 1);
+        let (min_x, _, max_x, _) = extent(&res[0]);
+        assert_eq!((min_x, max_x), (5., 7.));
+        assert_eq!(area(&res[0]), 3.5);
+
+        // A band going around the whole grid
+        #[rustfmt::skip]
+        let values = vec![
+            0., 0., 0., 0., 0., 0.,
+            1., 1., 1., 1., 1., 1.,
+            1., 1., 1., 1., 1., 1.,
+            0., 0., 0., 0., 0., 0.,
+        ];
+        let builder = ContourBuilder::new(6, 4, true).periodic_x(true);
+        let res = builder.contours(&values, &[0.5]).unwrap();
+        assert_eq!(res[0].geometry().0.len(), 1);
+        assert_eq!(extent(&res[0]), (0., 1., 6., 3.));
+        assert_eq!(area(&res[0]), 12.);
+        let lines = builder.lines(&values, &[0.5]).unwrap();
+        assert_eq!(lines[0].geometry().0.len(), 2);
+        assert!(lines[0].geometry().0.iter().all(|l| !l.is_closed()));
+
+        // The blob straddling the seam is cut there to nest within the band around it
+        #[rustfmt::skip]
+        let values = vec![
+            1., 0., 0., 0., 1.,
+            1., 0., 0., 0., 1.,
+            1., 0., 0., 0., 1.,
+        ];
+        let builder = ContourBuilder::new(5, 3, true).periodic_x(true);
+        let res = builder.isobands(&values, &[0., 0.5]).unwrap();
+        assert_eq!(res[0].geometry().0.len(), 1);
+        let band = &res[0].geometry().0[0];
+        assert_eq!(band.interiors().len(), 2);
+        let mut xs = band
+            .interiors()
+            .iter()
+            .flat_map(|r| r.0.iter().map(|p| p.x));
+        assert!(xs.all(|x| (0. ..=5.).contains(&x)));
+        let mut xs = band.exterior().0.iter().map(|p| p.x);
+        assert!(xs.all(|x| (0. ..=5.).contains(&x)));
+
+        let report = ContourBuilder::new(9, 7, true)
+            .periodic_x(true)
+            .fuzz_invariants(1, 200)
+            .unwrap();
+        assert!(report.is_ok(), "{}", report);
+    }
+
+    #[test]
+    fn test_mask() {
+        let area = |geometry: &MultiPolygon<Float>| {
+            geometry
+                .0
+                .iter()
+                .map(|p| {
+                    let holes = p.interiors().iter().map(|r| crate::area::area(&r.0).abs());
+                    (crate::area::area(&p.exterior().0).abs() - holes.sum::<f64>()) / 2.
+                })
+                .sum::<f64>()
+        };
+        let mut values = vec![1.; 100];
+        values[0] = 5.;
+        values[55] = 3.;
+        // A square with a notch, whose area is 27
+        let mask = polygon![
+            (x: 2., y: 2.), (x: 8., y: 2.), (x: 8., y: 8.), (x: 5., y: 5.), (x: 2., y: 8.),
+        ];
+        let builder = ContourBuilder::new(10, 10, true).mask(Some(mask.clone()));
+        let res = builder.contours(&values, &[0.5, 2.]).unwrap();
+        assert_eq!(area(res[0].geometry()), 27.);
+        // The ring around the corner is ignored, and the one around the center is clipped
+        assert_eq!(res[1].geometry().0.len(), 1);
+        assert!(area(res[1].geometry()) < 0.5);
+        let lines = builder.lines(&values, &[2.]).unwrap();
+        assert_eq!(lines[0].geometry().0.len(), 2);
+        assert!(lines[0]
+            .geometry()
+            .0
+            .iter()
+            .flat_map(|l| l.0.iter())
+            .all(|p| { crate::area::polygon_contains(&mask, p) }));
+        let bands = builder.isobands(&values, &[0.5, 2., 10.]).unwrap();
+        let total = area(bands[0].geometry()) + area(bands[1].geometry());
+        assert!((total - 27.).abs() < 1e-9);
+
+        // The mask is expressed in the coordinates of the output
+        let mut mask = mask;
+        mask.exterior_mut(|ring| ring.0.iter_mut().for_each(|p| p.x = 100. + 2. * p.x));
+        let builder = ContourBuilder::new(10, 10, true)
+            .x_origin(100.)
+            .x_step(2.)
+            .mask(Some(mask));
+        let res = builder.contours(&values, &[0.5]).unwrap();
+        assert_eq!(area(res[0].geometry()), 54.);
+
+        // The masked lines follow the masked contours, including where their rings
+        // touch the corners or the edges of the mask
+        for mask in [
+            polygon![(x: 4.5, y: 0.2), (x: 8.8, y: 3.5), (x: 4.5, y: 6.8), (x: 0.2, y: 3.5)],
+            polygon![(x: 1.3, y: 1.3), (x: 7.7, y: 1.3), (x: 7.7, y: 5.7), (x: 1.3, y: 5.7)],
+            polygon![(x: 1., y: 1.), (x: 8., y: 1.), (x: 8., y: 6.), (x: 1., y: 6.)],
+        ] {
+            let builder = ContourBuilder::new(9, 7, true).mask(Some(mask));
+            let report = builder.fuzz_invariants(1, 200).unwrap();
+            assert!(report.is_ok(), "{}", report);
+        }
+    }
+
+    #[test]
+    fn test_resample_uniform() {
+        let distances = |ring: &[crate::Pt]| {
+            ring.windows(2)
+                .map(|w| (w[1].x - w[0].x).hypot(w[1].y - w[0].y))
+                .collect::<Vec<_>>()
+        };
+        let values = square();
+        let builder = ContourBuilder::new(4, 4, false);
+        let mut contour = builder.contours(&values, &[0.5]).unwrap().remove(0);
+        // The ring is an octagon whose perimeter is 4 + 2 * sqrt(2)
+        contour.resample_uniform(0.5);
+        let ring = &contour.geometry().0[0].exterior().0;
+        assert_eq!(ring.len(), 15);
+        assert_eq!(ring[0], ring[14]);
+        // The chords are shorter than the arcs around the corners
+        let step = (4. + 2. * (2. as Float).sqrt()) / 14.;
+        assert!(distances(ring).iter().all(|d| *d <= step + 1e-6));
+
+        // Along a straight line, the vertices are evenly spaced and the ends are kept
+        let line = vec![(0., 0.).into(), (1., 0.).into(), (4., 0.).into()];
+        let resampled = crate::resample::resample(&line, 0.3);
+        assert_eq!(resampled.len(), 14);
+        assert_eq!((resampled[0], resampled[13]), (line[0], line[2]));
+        assert!(distances(&resampled)
+            .iter()
+            .all(|v| (v - 4. / 13.).abs() < 1e-6));
+
+        let mut line = builder.lines(&values, &[0.5]).unwrap().remove(0);
+        let before = line.geometry().0[0].0.clone();
+        line.resample_uniform(0.);
+        assert_eq!(line.geometry().0[0].0, before);
+        line.resample_uniform(10.);
+        // A closed line keeps at least 3 distinct vertices
+        assert_eq!(line.geometry().0[0].0.len(), 4);
+        assert_eq!(line.geometry().0[0].0[0], before[0]);
+    }
+
+    #[test]
+    fn test_border_policy() {
+        // A low region touching the left edge, and another one inside the grid
+        #[rustfmt::skip]
+        let values: Vec<Float> = vec![
+            1., 1., 1., 1.,
+            0., 1., 1., 1.,
+            0., 1., 0., 1.,
+            1., 1., 1., 1.,
+        ];
+        let builder = ContourBuilder::new(4, 4, false);
+        let below = builder.contours(&values, &[0.5]).unwrap();
+        assert_eq!(below[0].geometry().0.len(), 1);
+        assert_eq!(below[0].geometry().0[0].interiors().len(), 1);
+
+        // Above the thresholds: the whole grid is covered, the left region being a hole
+        let builder = ContourBuilder::new(4, 4, false).border_policy(BorderPolicy::Above);
+        let res = builder.contours(&values, &[0.5]).unwrap();
+        let polygon = &res[0].geometry().0[0];
+        assert_eq!(res[0].geometry().0.len(), 1);
+        assert_eq!(polygon.interiors().len(), 2);
+        assert_eq!(
+            polygon
+                .exterior()
+                .0
+                .iter()
+                .map(|c| (c.x, c.y))
+                .collect::<Vec<_>>(),
+            vec![(4., 4.), (4., 0.), (0., 0.), (0., 4.), (4., 4.)]
+        );
+        assert!(builder.lines(&values, &[0.5]).unwrap()[0]
+            .geometry()
+            .0
+            .iter()
+            .all(|line| line.is_closed()));
+
+        // Mirrored: the left region is a notch, whose isoline ends on the left edge
+        let builder = ContourBuilder::new(4, 4, false).border_policy(BorderPolicy::Mirror);
+        let res = builder.contours(&values, &[0.5]).unwrap();
+        assert_eq!(res[0].geometry().0.len(), 1);
+        assert_eq!(res[0].geometry().0[0].interiors().len(), 1);
+        let lines = builder.lines(&values, &[0.5]).unwrap();
+        let notch = &lines[0].geometry().0[1];
+        assert!(!notch.is_closed());
+        assert_eq!((notch.0[0].x, notch.0[0].y), (0., 1.));
+        assert_eq!((notch.0[5].x, notch.0[5].y), (0., 3.));
+
+        // Open: the contours are unchanged, the isolines don't follow the edges
+        let builder = ContourBuilder::new(4, 4, false).border_policy(BorderPolicy::Open);
+        let res = builder.contours(&values, &[0.5]).unwrap();
+        assert_eq!(res[0].geometry(), below[0].geometry());
+        let lines = builder.lines(&values, &[0.5]).unwrap();
+        let outer = &lines[0].geometry().0[1];
+        assert!(!outer.is_closed());
+        assert_eq!(
+            outer.0.iter().map(|c| (c.x, c.y)).collect::<Vec<_>>(),
+            vec![(0.5, 1.), (1., 1.5), (1., 2.5), (0.5, 3.)]
+        );
+
+        // The rings collapsed once clipped are dropped (as are their isolines), and the
+        // rings following the edges of the grid are nested in the isobands
+        for border in [BorderPolicy::Above, BorderPolicy::Mirror] {
+            let builder = ContourBuilder::new(9, 7, true).border_policy(border);
+            let report = builder.fuzz_invariants(1, 200).unwrap();
+            assert!(report.is_ok(), "{:?}: {}", border, report);
+        }
+    }
+
+    #[test]
+    fn test_invariants() {
+        assert_eq!(random_grid(4, 3, 7), random_grid(4, 3, 7));
+        assert_ne!(random_grid(4, 3, 7), random_grid(4, 3, 8));
+        assert!(random_grid(4, 3, 7).iter().all(|v| (0. ..=1.).contains(v)));
+
+        let builder = ContourBuilder::new(5, 5, true);
+        let report = builder.fuzz_invariants(0, 50).unwrap();
+        assert!(report.is_ok(), "{}", report);
+        assert_eq!(report.grids, 50);
+
+        #[rustfmt::skip]
+        let values: Vec<Float> = vec![
+            0., 0., 0., 0., 0.,
+            0., 2., 2., 2., 0.,
+            0., 2., 0., 2., 0.,
+            0., 2., 2., 2., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let report = builder.check_invariants(&values, &[0.5, 1., 1.5]).unwrap();
+        assert!(report.is_ok(), "{}", report);
+        assert_eq!(report.to_string(), "0 violation(s) on 1 grid(s)");
+
+        let report = InvariantReport {
+            grids: 3,
+            violations: vec![InvariantViolation {
+                seed: Some(4),
+                issue: InvariantIssue::BandArea {
+                    expected: 2.,
+                    actual: 1.5,
+                },
+            }],
+        };
+        assert_eq!(
+            report.to_string(),
+            "1 violation(s) on 3 grid(s)\n[seed 4] The bands cover an area of 1.5 instead of 2"
+        );
+    }
+
+    #[test]
+    fn test_contours_multi() {
+        let grids = (0..4)
+            .map(|seed| random_grid(9, 7, seed))
+            .collect::<Vec<_>>();
+        let grids = grids.iter().map(|g| &g[..]).collect::<Vec<_>>();
+        let thresholds: [Float; 4] = [0.2, 0.4, 0.6, 0.8];
+        for builder in [
+            ContourBuilder::new(9, 7, true),
+            ContourBuilder::new(9, 7, false).threads(3),
+        ] {
+            let contours = builder.contours_multi(&grids, &thresholds).unwrap();
+            let bands = builder.isobands_multi(&grids, &thresholds).unwrap();
+            assert_eq!((contours.len(), bands.len()), (4, 4));
+            for (k, values) in grids.iter().enumerate() {
+                let expected = builder.contours(values, &thresholds).unwrap();
+                let geometries = |c: &[crate::Contour]| {
+                    c.iter().map(|c| c.geometry().clone()).collect::<Vec<_>>()
+                };
+                assert_eq!(geometries(&contours[k]), geometries(&expected));
+                let expected = builder.isobands(values, &thresholds).unwrap();
+                let geometries =
+                    |b: &[crate::Band]| b.iter().map(|b| b.geometry().clone()).collect::<Vec<_>>();
+                assert_eq!(geometries(&bands[k]), geometries(&expected));
+            }
+        }
+        let wrong = [0.; 4];
+        let builder = ContourBuilder::new(9, 7, true);
+        assert!(builder
+            .contours_multi(&[grids[0], &wrong], &thresholds)
+            .is_err());
+    }
+
+    #[test]
+    fn test_merge_row_tiles() {
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 1., 0.,
+            0., 1., 2., 2., 1., 0.,
+            0., 1., 2., 2., 1., 0.,
+            0., 1., 2., 2., 1., 0.,
+            0., 1., 2., 1., 1., 0.,
+            0., 1., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0., 0.,
+        ];
+        let area = |mp: &geo_types::MultiPolygon<Float>| {
+            mp.0.iter()
+                .map(|p| {
+                    let holes = p.interiors().iter().map(|r| crate::area::area(&r.0).abs());
+                    crate::area::area(&p.exterior().0).abs() - holes.sum::<f64>()
+                })
+                .sum::<f64>()
+                / 2.
+        };
+        // With a non-zero origin, the x and y sides of the tiles can share a value
+        for x_origin in [0., 5.] {
+            let builder = ContourBuilder::new(6, 8, false).x_origin(x_origin);
+            let expected = builder.contour_set(&values, &[0.5, 1.5]).unwrap();
+            // Split the grid in two tiles overlapping by one row
+            let tile = |from: usize, to: usize| {
+                ContourBuilder::new(6, to - from, false)
+                    .x_origin(x_origin)
+                    .y_origin(from as Float)
+                    .contour_set(&values[from * 6..to * 6], &[0.5, 1.5])
+                    .unwrap()
+            };
+            let merged = crate::merge_results(&[tile(0, 5), tile(4, 8)], None);
+            assert_eq!((merged.config().dx, merged.config().dy), (6, 8));
+            for (a, b) in merged.contours().iter().zip(expected.contours()) {
+                assert_eq!(a.geometry().0.len(), b.geometry().0.len());
+                assert!((area(a.geometry()) - area(b.geometry())).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_contour_set_options() {
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0.,
+            0., 1., -9., 0.,
+            0., 1., 2., 0.,
+            0., 0., 0., 0.,
+        ];
+        // Only the nodata value differs
+        let c = ContourBuilder::new(4, 4, true).nodata(Some(-9.));
+        let set = c.contour_set(&values, &[0.5, 1.5]).unwrap();
+        assert!(set.matches(&c, &[0.5, 1.5]));
+        assert!(!set.matches(&ContourBuilder::new(4, 4, true), &[0.5, 1.5]));
+        assert!(!set.matches(&c.clone().nodata(Some(-1.)), &[0.5, 1.5]));
+        assert_eq!(set.builder(), c);
+        assert!(set.matches(&set.builder(), &[0.5, 1.5]));
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&set).unwrap();
+            let set2: crate::ContourSet = serde_json::from_str(&json).unwrap();
+            assert!(set2.matches(&c, &[0.5, 1.5]));
+            assert!(!set2.matches(&ContourBuilder::new(4, 4, true), &[0.5, 1.5]));
+        }
+    }
 }
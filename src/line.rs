@@ -1,11 +1,19 @@
-use crate::Float;
-use geo_types::MultiLineString;
+use crate::bbox::lines_bbox;
+use crate::resample::resample;
+use crate::{ContourClass, Float, Metadata, Pt};
+use geo_types::{MultiLineString, Rect};
 
 /// A line has the geometry and threshold of a contour ring, built by [ContourBuilder](`crate::contourbuilder::ContourBuilder`).
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Line {
     pub(crate) geometry: MultiLineString<Float>,
     pub(crate) threshold: Float,
+    pub(crate) threshold_index: usize,
+    pub(crate) metadata: Metadata,
+    pub(crate) bbox: Option<Rect<Float>>,
+    pub(crate) class: Option<ContourClass>,
+    pub(crate) depressions: Option<Vec<bool>>,
 }
 
 impl Line {
@@ -14,6 +22,17 @@ impl Line {
         &self.geometry
     }
 
+    /// Get the bounding box of the geometry of this isoline (computed when it was built),
+    /// or `None` if its geometry is empty.
+    pub fn bbox(&self) -> Option<Rect<Float>> {
+        self.bbox
+    }
+
+    /// Recomputes the bounding box of this isoline after its geometry was modified.
+    pub(crate) fn update_bbox(&mut self) {
+        self.bbox = lines_bbox(&self.geometry);
+    }
+
     /// Get the owned lines and threshold of this contour.
     pub fn into_inner(self) -> (MultiLineString<Float>, Float) {
         (self.geometry, self.threshold)
@@ -24,6 +43,75 @@ impl Line {
         self.threshold
     }
 
+    /// Get the position of its threshold in the slice of thresholds
+    /// used to construct this isoline (e.g. to map it back to a legend or a style
+    /// after filtering, sorting or parallel computation).
+    pub fn threshold_index(&self) -> usize {
+        self.threshold_index
+    }
+
+    /// Get the position of its threshold in the slice of thresholds
+    /// used to construct this isoline.
+    #[deprecated(note = "use `threshold_index` instead")]
+    pub fn index(&self) -> usize {
+        self.threshold_index()
+    }
+
+    /// Applies the transform `f` to every vertex of the geometry of this isoline, in place
+    /// (e.g. to project its coordinates or convert their units), preserving its threshold.
+    pub fn map_coords(&mut self, mut f: impl FnMut(Pt) -> Pt) {
+        self.geometry
+            .0
+            .iter_mut()
+            .flat_map(|line| line.0.iter_mut())
+            .for_each(|pt| *pt = f(*pt));
+        self.update_bbox();
+    }
+
+    /// Re-distributes the vertices of the geometry of this isoline at equal arc-length
+    /// intervals along each line string, as close as possible to `spacing` apart (in the
+    /// coordinates of the geometry), e.g. before morphing or animating the geometries,
+    /// or placing labels along them.
+    ///
+    /// The new vertices lie on the original line strings, their ends being kept (for the rings
+    /// of contours and bands, their first vertex is kept and at least 3 vertices remain):
+    /// the shape is preserved up to the corners cut between two vertices, closer to it
+    /// as `spacing` decreases. Nothing is changed if `spacing` isn't strictly positive.
+    pub fn resample_uniform(&mut self, spacing: Float) {
+        self.geometry
+            .0
+            .iter_mut()
+            .for_each(|line| line.0 = resample(&line.0, spacing));
+        self.update_bbox();
+    }
+
+    /// Borrow the flags telling, for each line string of the geometry of this isoline,
+    /// whether it is a closed depression contour (enclosing values lower than the
+    /// threshold, e.g. to draw it with ticks) rather than a peak or a line reaching
+    /// the edges of the grid, if requested (see [`ContourBuilder::depressions`](crate::ContourBuilder::depressions)).
+    ///
+    /// The filled contours always enclose the values above their threshold:
+    /// their depressions are the holes of their polygons.
+    pub fn depressions(&self) -> Option<&[bool]> {
+        self.depressions.as_deref()
+    }
+
+    /// Get the class (index or intermediate) of this isoline, if requested
+    /// (see [`ContourBuilder::index_interval`](crate::ContourBuilder::index_interval)).
+    pub fn class(&self) -> Option<ContourClass> {
+        self.class
+    }
+
+    /// Borrow the metadata attached to the threshold used to construct this isoline.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Formats the geometry of this isoline as WKT (a `MULTILINESTRING`).
+    pub fn to_wkt(&self) -> String {
+        crate::tabular::multilinestring_wkt(&self.geometry)
+    }
+
     #[cfg(feature = "geojson")]
     /// Convert the line to a struct from the `geojson` crate.
     ///
@@ -52,15 +140,34 @@ impl Line {
     /// assert_eq!(&geojson_string[0..27], r#"{"geometry":{"coordinates":"#);
     /// ```
     pub fn to_geojson(&self) -> geojson::Feature {
-        let mut properties = geojson::JsonObject::with_capacity(1);
-        properties.insert("threshold".to_string(), self.threshold.into());
+        self.to_geojson_with_keys(&crate::GeoJsonKeys::default())
+    }
 
+    #[cfg(feature = "geojson")]
+    /// Convert the line to a struct from the `geojson` crate,
+    /// using the given names for its properties.
+    pub fn to_geojson_with_keys(&self, keys: &crate::GeoJsonKeys) -> geojson::Feature {
         geojson::Feature {
             bbox: None,
             geometry: Some(geojson::Geometry::from(self.geometry())),
             id: None,
-            properties: Some(properties),
+            properties: Some(self.properties(keys)),
             foreign_members: None,
         }
     }
+
+    #[cfg(feature = "geojson")]
+    /// Builds the GeoJSON properties of the line, using the given names.
+    pub(crate) fn properties(&self, keys: &crate::GeoJsonKeys) -> geojson::JsonObject {
+        let mut properties = geojson::JsonObject::with_capacity(1);
+        properties.insert(keys.threshold.clone(), self.threshold.into());
+        if let Some(depressions) = &self.depressions {
+            properties.insert(keys.depressions.clone(), depressions.clone().into());
+        }
+        if let Some(class) = self.class {
+            properties.insert(keys.class.clone(), class.as_str().into());
+        }
+        crate::metadata::insert_metadata(&mut properties, &self.metadata);
+        properties
+    }
 }
@@ -0,0 +1,70 @@
+use crate::Float;
+
+/// The threshold range a value falls in, as returned by [`ThresholdLookup::lookup`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThresholdRange {
+    /// The position, in the slice of thresholds, of the greatest threshold lower than
+    /// or equal to the value (i.e. the [`threshold_index`](crate::Contour::threshold_index)
+    /// of the innermost contour containing the value, and of the band containing it, if any).
+    pub index: usize,
+    /// The greatest threshold lower than or equal to the value.
+    pub min: Float,
+    /// The smallest threshold strictly greater than the value, if any
+    /// (`None` meaning that the value is above every isoband).
+    pub max: Option<Float>,
+}
+
+/// Maps values to the range of thresholds they fall in, using the same convention as
+/// the contouring (a value belongs to the contours of the thresholds lower than or equal
+/// to it, and to the band whose min value is lower than or equal to it and whose max value
+/// is strictly greater than it), e.g. so that legends or readouts under the cursor
+/// always agree with the computed geometries.
+///
+/// # Example
+///
+/// ```
+/// use contour::ThresholdLookup;
+///
+/// let lookup = ThresholdLookup::new(&[10., 0., 5.]);
+/// let range = lookup.lookup(5.).unwrap();
+/// assert_eq!((range.index, range.min, range.max), (2, 5., Some(10.)));
+/// assert_eq!(lookup.lookup(12.).unwrap().max, None);
+/// assert_eq!(lookup.lookup(-1.), None);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThresholdLookup {
+    // The (non-NaN) thresholds, sorted in ascending order, with their position
+    thresholds: Vec<(Float, usize)>,
+}
+
+impl ThresholdLookup {
+    /// Constructs a new lookup for the given thresholds (in the order used
+    /// to compute the contours, NaN thresholds being ignored).
+    pub fn new(thresholds: &[Float]) -> Self {
+        let mut thresholds = thresholds
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| !t.is_nan())
+            .map(|(i, &t)| (t, i))
+            .collect::<Vec<_>>();
+        thresholds.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        ThresholdLookup { thresholds }
+    }
+
+    /// Returns the threshold range the given value falls in, or `None` if the value
+    /// is NaN or lower than every threshold (i.e. outside of every contour).
+    ///
+    /// If several thresholds are equal, the last one (in the slice of thresholds) is used.
+    pub fn lookup(&self, value: Float) -> Option<ThresholdRange> {
+        if value.is_nan() {
+            return None;
+        }
+        let n = self.thresholds.partition_point(|&(t, _)| t <= value);
+        let (min, index) = *self.thresholds.get(n.checked_sub(1)?)?;
+        Some(ThresholdRange {
+            index,
+            min,
+            max: self.thresholds.get(n).map(|&(t, _)| t),
+        })
+    }
+}
@@ -0,0 +1,105 @@
+use crate::error::Result;
+use crate::{Band, Contour, ContourBuilder, Float, Line};
+use image::{ImageBuffer, Luma, Primitive};
+use std::ops::Deref;
+
+impl ContourBuilder {
+    /// Returns the builder for the dimensions of the given image, keeping all its other settings.
+    fn for_luma<T, C>(&self, image: &ImageBuffer<Luma<T>, C>) -> ContourBuilder
+    where
+        T: Primitive,
+        C: Deref<Target = [T]>,
+    {
+        let (dx, dy) = image.dimensions();
+        ContourBuilder {
+            dx: dx as usize,
+            dy: dy as usize,
+            ..self.clone()
+        }
+    }
+
+    /// Computes isolines (see [`ContourBuilder::lines`]) according to the pixels
+    /// of the given grayscale `image` (e.g. a [`GrayImage`](image::GrayImage)
+    /// or an [`ImageBuffer`] of `Luma<u16>` or `Luma<f32>` pixels), read in place,
+    /// the dimensions of the grid being those of the image.
+    ///
+    /// The rows of the image are read from the top: with the default origin and steps,
+    /// the coordinates of the output geometries are pixel coordinates, whose origin is
+    /// the top-left corner of the image, `y` growing downward (the center of the pixel
+    /// `(x, y)` being at `(x + 0.5, y + 0.5)`).
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The grayscale image to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn lines_from_luma<T, C>(
+        &self,
+        image: &ImageBuffer<Luma<T>, C>,
+        thresholds: &[Float],
+    ) -> Result<Vec<Line>>
+    where
+        T: Primitive + Into<Float> + Sync,
+        C: Deref<Target = [T]> + Sync,
+    {
+        self.for_luma(image).lines_from_source(image, thresholds)
+    }
+
+    /// Computes contours (see [`ContourBuilder::contours`]) according to the pixels
+    /// of the given grayscale `image`, read in place, the dimensions of the grid being
+    /// those of the image (see [`ContourBuilder::lines_from_luma`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The grayscale image to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use contour::ContourBuilder;
+    /// use image::GrayImage;
+    ///
+    /// // A white square on a black background
+    /// let image = GrayImage::from_fn(8, 6, |x, y| {
+    ///     image::Luma([if (2..5).contains(&x) && (1..3).contains(&y) { 255 } else { 0 }])
+    /// });
+    /// let contours = ContourBuilder::new(1, 1, false)
+    ///     .contours_from_luma(&image, &[128.])
+    ///     .unwrap();
+    /// let square = &contours[0].geometry().0[0];
+    /// // The contour surrounds the pixels from (2, 1) to (4, 2), in pixel coordinates
+    /// assert!(square.exterior().0.iter().all(|p| p.x >= 2. && p.x <= 5. && p.y >= 1. && p.y <= 3.));
+    /// ```
+    pub fn contours_from_luma<T, C>(
+        &self,
+        image: &ImageBuffer<Luma<T>, C>,
+        thresholds: &[Float],
+    ) -> Result<Vec<Contour>>
+    where
+        T: Primitive + Into<Float> + Sync,
+        C: Deref<Target = [T]> + Sync,
+    {
+        self.for_luma(image).contours_from_source(image, thresholds)
+    }
+
+    /// Computes isobands (see [`ContourBuilder::isobands`]) according to the pixels
+    /// of the given grayscale `image`, read in place, the dimensions of the grid being
+    /// those of the image (see [`ContourBuilder::lines_from_luma`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The grayscale image to be used.
+    /// * `thresholds` - The slice of thresholds values to be used
+    ///   (have to be equal to or greater than 2).
+    pub fn isobands_from_luma<T, C>(
+        &self,
+        image: &ImageBuffer<Luma<T>, C>,
+        thresholds: &[Float],
+    ) -> Result<Vec<Band>>
+    where
+        T: Primitive + Into<Float> + Sync,
+        C: Deref<Target = [T]> + Sync,
+    {
+        self.for_luma(image).isobands_from_source(image, thresholds)
+    }
+}
@@ -0,0 +1,480 @@
+use crate::area::{area, contains, ring_contains};
+use crate::merge::link_edges;
+use crate::order::GridOrder;
+use crate::{ContourBuilder, Float, Pt, Ring};
+use geo_types::{LineString, MultiPolygon, Polygon};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+type Edge = (Pt, Pt);
+
+impl ContourBuilder {
+    /// Returns a copy of the given `values` in which the samples of the grid whose cells
+    /// all lie outside of the mask (see [`ContourBuilder::mask`]) are replaced by `NaN`,
+    /// if a mask is set.
+    pub(crate) fn masked(&self, values: &[Float]) -> Option<Vec<Float>> {
+        let mask = self.mask.as_ref()?;
+        let (dx, dy) = (self.dx, self.dy);
+        let mut kept = vec![false; dx * dy];
+        let mask = self.grid_mask(mask);
+        let rings = std::iter::once(mask.exterior())
+            .chain(mask.interiors())
+            .collect::<Vec<_>>();
+        // The samples inside the mask, row by row (the sample (i, j) lying at (i + 0.5, j + 0.5))
+        let mut crossings = Vec::new();
+        for j in 0..dy {
+            let y = j as Float + 0.5;
+            crossings.clear();
+            for ring in &rings {
+                for w in ring.0.windows(2) {
+                    let (a, b) = (w[0], w[1]);
+                    if (a.y > y) != (b.y > y) {
+                        crossings.push(a.x + (y - a.y) / (b.y - a.y) * (b.x - a.x));
+                    }
+                }
+            }
+            crossings.sort_by(Float::total_cmp);
+            for pair in crossings.chunks_exact(2) {
+                let from = (pair[0] - 0.5).ceil().max(0.) as usize;
+                let to = ((pair[1] - 0.5).ceil().max(0.) as usize).min(dx);
+                (from..to).for_each(|i| kept[j * dx + i] = true);
+            }
+        }
+        // The corners of the cells crossed by the boundary of the mask (and of their
+        // neighbours, the boundary being followed by steps of half a cell)
+        let mut keep_around = |x: Float, y: Float| {
+            if !(x.is_finite() && y.is_finite()) {
+                return;
+            }
+            let (i, j) = ((x - 0.5).floor() as i64, (y - 0.5).floor() as i64);
+            for v in (j - 1).max(0)..(j + 3).min(dy as i64) {
+                for u in (i - 1).max(0)..(i + 3).min(dx as i64) {
+                    kept[v as usize * dx + u as usize] = true;
+                }
+            }
+        };
+        for w in rings.iter().flat_map(|ring| ring.0.windows(2)) {
+            let (a, b) = (w[0], w[1]);
+            let steps = ((b.x - a.x).abs().max((b.y - a.y).abs()) * 2.)
+                .ceil()
+                .min((2 * (dx + dy)) as Float) as usize;
+            for k in 0..=steps.max(1) {
+                let t = k as Float / steps.max(1) as Float;
+                keep_around(a.x + t * (b.x - a.x), a.y + t * (b.y - a.y));
+            }
+        }
+        let mut values = values.to_vec();
+        for (k, &keep) in kept.iter().enumerate() {
+            if !keep {
+                let index = match self.order {
+                    GridOrder::RowMajor => k,
+                    GridOrder::ColumnMajor => (k % dx) * dy + k / dx,
+                };
+                values[index] = Float::NAN;
+            }
+        }
+        Some(values)
+    }
+
+    /// Returns the given mask expressed in grid coordinates.
+    pub(crate) fn grid_mask(&self, mask: &Polygon<Float>) -> Polygon<Float> {
+        let to_grid = |ring: &LineString<Float>| {
+            LineString(
+                ring.0
+                    .iter()
+                    .map(|p| {
+                        let (x, y) = self.to_grid(p.x, p.y);
+                        Pt { x, y }
+                    })
+                    .collect(),
+            )
+        };
+        Polygon::new(
+            to_grid(mask.exterior()),
+            mask.interiors().iter().map(to_grid).collect(),
+        )
+    }
+
+    /// Clips the given polygons with the mask, if any.
+    pub(crate) fn mask_polygons(&self, geometry: MultiPolygon<Float>) -> MultiPolygon<Float> {
+        match &self.mask {
+            Some(mask) => MultiPolygon(
+                geometry
+                    .0
+                    .iter()
+                    .flat_map(|polygon| clip_polygon(polygon, mask))
+                    .collect(),
+            ),
+            None => geometry,
+        }
+    }
+
+    /// Clips the given lines with the mask, if any, keeping the same parts of the rings
+    /// as [`ContourBuilder::mask_polygons`]: their edges strictly inside the mask
+    /// (the rings without area, which don't bound the contours, being only kept
+    /// if they are entirely inside the mask).
+    pub(crate) fn mask_lines(&self, lines: Vec<Ring>) -> Vec<Ring> {
+        let Some(mask) = &self.mask else {
+            return lines;
+        };
+        let boundary = Edges::new(rings(mask, 1.).iter().flat_map(|r| edges(r)).collect());
+        // The edges along the boundary of the mask (up to rounding errors, e.g. where
+        // a ring touches it) aren't kept, as they don't bound the clipped contours
+        let tolerance = tolerance(mask);
+        let inside = |a: &Pt, b: &Pt| {
+            let middle = midpoint(a, b);
+            mask_side(mask, &middle) > 0 && !boundary.near(&middle, tolerance)
+        };
+        let mut result = Vec::new();
+        for line in &lines {
+            let collapsed = line.len() > 1 && line[0] == line[line.len() - 1] && area(line) == 0.;
+            if collapsed {
+                let split = boundary.split(edges(line));
+                if split.iter().all(|(a, b)| inside(a, b)) {
+                    result.push(line.clone());
+                }
+                continue;
+            }
+            let split = boundary.split(edges(&without_spikes(line)));
+            let mut current: Ring = Vec::new();
+            for (a, b) in split {
+                if inside(&a, &b) {
+                    if current.is_empty() {
+                        current.push(a);
+                    }
+                    current.push(b);
+                } else if !current.is_empty() {
+                    result.push(std::mem::take(&mut current));
+                }
+            }
+            if !current.is_empty() {
+                result.push(current);
+            }
+        }
+        result
+    }
+}
+
+/// Returns the rings of the given polygon, its exterior having the sign of the given one
+/// (the sign of the area of the exteriors) and its holes the opposite sign.
+fn rings(polygon: &Polygon<Float>, exterior: f64) -> Vec<Ring> {
+    std::iter::once((polygon.exterior(), exterior))
+        .chain(polygon.interiors().iter().map(|r| (r, -exterior)))
+        .map(|(ring, sign)| {
+            let mut ring = ring.0.clone();
+            if area(&ring) * sign < 0. {
+                ring.reverse();
+            }
+            ring
+        })
+        .collect()
+}
+
+/// Returns the given ring or line without its spikes of zero width (e.g. around
+/// a sample equal to the threshold), which don't bound any area.
+fn without_spikes(line: &[Pt]) -> Ring {
+    let mut result: Ring = Vec::with_capacity(line.len());
+    for &point in line {
+        if result.len() > 1 && result[result.len() - 2] == point {
+            result.pop();
+        } else if result.last() != Some(&point) {
+            result.push(point);
+        }
+    }
+    // The spike at the start of a closed ring
+    while result.len() > 3
+        && result[0] == result[result.len() - 1]
+        && result[1] == result[result.len() - 2]
+    {
+        result.pop();
+        result.remove(0);
+    }
+    result
+}
+
+/// Returns the (non degenerate) edges of the given ring or line.
+fn edges(ring: &[Pt]) -> impl Iterator<Item = Edge> + '_ {
+    ring.windows(2)
+        .map(|w| (w[0], w[1]))
+        .filter(|(a, b)| a != b)
+}
+
+/// Returns the distance under which the points are considered to lie on the boundary
+/// of the `mask` (or on the rings clipped with it), up to rounding errors.
+fn tolerance(mask: &Polygon<Float>) -> Float {
+    let (min, max) = (mask.exterior().0.iter()).fold((Float::MAX, Float::MIN), |(min, max), p| {
+        (min.min(p.x).min(p.y), max.max(p.x).max(p.y))
+    });
+    Float::EPSILON.sqrt() * (max - min).abs().max(1.)
+}
+
+fn midpoint(a: &Pt, b: &Pt) -> Pt {
+    Pt {
+        x: (a.x + b.x) / 2.,
+        y: (a.y + b.y) / 2.,
+    }
+}
+
+/// Returns 1 if the `point` is strictly inside the `polygon`, -1 if it is strictly
+/// outside and 0 if it is on its boundary.
+fn mask_side(polygon: &Polygon<Float>, point: &Pt) -> i32 {
+    let mut side = ring_contains(&polygon.exterior().0, point);
+    for hole in polygon.interiors() {
+        match ring_contains(&hole.0, point) {
+            0 => return 0,
+            1 => side = -1,
+            _ => {}
+        }
+    }
+    side
+}
+
+/// Clips the given polygon with the `mask`, returning the polygons of their intersection:
+/// their edges are split where they cross, the edges of each one lying inside the other
+/// being linked into the rings of the result.
+fn clip_polygon(polygon: &Polygon<Float>, mask: &Polygon<Float>) -> Vec<Polygon<Float>> {
+    let exterior = if area(&polygon.exterior().0) < 0. {
+        -1.
+    } else {
+        1.
+    };
+    let own = rings(polygon, exterior)
+        .iter()
+        .map(|r| without_spikes(r))
+        .flat_map(|r| edges(&r).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let other = rings(mask, exterior)
+        .iter()
+        .flat_map(|r| edges(r))
+        .collect::<Vec<_>>();
+    let own_split = Edges::new(other.clone()).split(own.iter().copied());
+    let boundary = Edges::new(own);
+    let other_split = boundary.split(other.iter().copied());
+    let key = |a: &Pt, b: &Pt| (a.x.to_bits(), a.y.to_bits(), b.x.to_bits(), b.y.to_bits());
+    let shared = other_split
+        .iter()
+        .map(|(a, b)| key(a, b))
+        .collect::<FxHashSet<_>>();
+    let mut kept = own_split
+        .into_iter()
+        .filter(|(a, b)| match mask_side(mask, &midpoint(a, b)) {
+            // The edges shared by both polygons are kept if they have the same direction
+            0 => shared.contains(&key(a, b)),
+            side => side > 0,
+        })
+        .collect::<Vec<_>>();
+    // Where the mask edges (almost) run along the boundary of the polygon (e.g. between
+    // two crossings around one of its vertices), which side they are on is uncertain:
+    // they are kept if they continue the kept edges leaving the mask (possibly through
+    // other uncertain edges, around the vertices of the mask), so as to close the rings
+    let tolerance = tolerance(mask);
+    let starts = kept
+        .iter()
+        .map(|(a, _)| key(a, a))
+        .collect::<FxHashSet<_>>();
+    let ends = kept
+        .iter()
+        .map(|(_, b)| key(b, b))
+        .collect::<FxHashSet<_>>();
+    let mut sides = other_split
+        .iter()
+        .map(|(a, b)| {
+            let (middle, a) = (midpoint(a, b), key(a, a));
+            if !boundary.near(&middle, tolerance) {
+                Some(mask_side(polygon, &middle) > 0)
+            } else if starts.contains(&a) || ends.contains(&a) {
+                Some(ends.contains(&a) && !starts.contains(&a))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    let previous = (other_split.iter().enumerate())
+        .map(|(k, (_, b))| (key(b, b), k))
+        .collect::<FxHashMap<_, _>>();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for k in 0..sides.len() {
+            let (a, _) = &other_split[k];
+            if let (None, Some(&j)) = (sides[k], previous.get(&key(a, a))) {
+                sides[k] = sides[j];
+                changed |= sides[k].is_some();
+            }
+        }
+    }
+    kept.extend(
+        other_split
+            .into_iter()
+            .zip(sides)
+            .filter(|((a, b), side)| {
+                side.unwrap_or_else(|| mask_side(polygon, &midpoint(a, b)) > 0)
+            })
+            .map(|(edge, _)| edge),
+    );
+    let (mut polygons, mut holes) = (Vec::new(), Vec::new());
+    for ring in link_edges(kept) {
+        if ring.len() < 4 || ring[0] != ring[ring.len() - 1] {
+            continue;
+        }
+        if area(&ring) * exterior > 0. {
+            polygons.push(Polygon::new(LineString(ring), vec![]));
+        } else {
+            holes.push(LineString(ring));
+        }
+    }
+    for hole in holes {
+        if let Some(polygon) = polygons
+            .iter_mut()
+            .find(|p| contains(&p.exterior().0, &hole.0) != -1)
+        {
+            polygon.interiors_push(hole);
+        }
+    }
+    polygons
+}
+
+/// The edges of a polygon (or of a line), bucketed by the cells of a regular grid
+/// to find the edges crossing another one.
+struct Edges {
+    edges: Vec<Edge>,
+    size: Float,
+    buckets: FxHashMap<(i64, i64), Vec<usize>>,
+}
+
+impl Edges {
+    fn new(edges: Vec<Edge>) -> Self {
+        // The cells are as large as the edges on average
+        let size = edges
+            .iter()
+            .map(|(a, b)| (b.x - a.x).abs().max((b.y - a.y).abs()))
+            .sum::<Float>()
+            / edges.len().max(1) as Float;
+        let mut result = Edges {
+            edges,
+            size,
+            buckets: FxHashMap::default(),
+        };
+        if result.size > 0. && result.size.is_finite() {
+            for (k, (a, b)) in result.edges.iter().enumerate() {
+                for cell in result.cells(a, b) {
+                    result.buckets.entry(cell).or_default().push(k);
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns the cells covered by the bounding box of the segment [a, b].
+    fn cells(&self, a: &Pt, b: &Pt) -> impl Iterator<Item = (i64, i64)> {
+        let cell = |v: Float| (v / self.size).floor() as i64;
+        let (x0, x1) = (cell(a.x.min(b.x)), cell(a.x.max(b.x)));
+        let (y0, y1) = (cell(a.y.min(b.y)), cell(a.y.max(b.y)));
+        (y0..=y1).flat_map(move |y| (x0..=x1).map(move |x| (x, y)))
+    }
+
+    /// Returns true if the point `p` is within `tolerance` of one of these edges.
+    fn near(&self, p: &Pt, tolerance: Float) -> bool {
+        if self.buckets.is_empty() {
+            return false;
+        }
+        let (a, b) = (
+            Pt {
+                x: p.x - tolerance,
+                y: p.y - tolerance,
+            },
+            Pt {
+                x: p.x + tolerance,
+                y: p.y + tolerance,
+            },
+        );
+        self.cells(&a, &b)
+            .filter_map(|cell| self.buckets.get(&cell))
+            .flatten()
+            .map(|&k| self.edges[k])
+            .any(|(c, d)| {
+                let (sx, sy) = (d.x - c.x, d.y - c.y);
+                let t = (((p.x - c.x) * sx + (p.y - c.y) * sy) / (sx * sx + sy * sy)).clamp(0., 1.);
+                (p.x - c.x - t * sx).hypot(p.y - c.y - t * sy) <= tolerance
+            })
+    }
+
+    /// Splits the given edges where they cross or touch these edges.
+    fn split(&self, edges: impl Iterator<Item = Edge>) -> Vec<Edge> {
+        let mut result = Vec::new();
+        let mut candidates: Vec<usize> = Vec::new();
+        for (a, b) in edges {
+            candidates.clear();
+            if !self.buckets.is_empty() {
+                candidates.extend(
+                    self.cells(&a, &b)
+                        .filter_map(|cell| self.buckets.get(&cell))
+                        .flatten(),
+                );
+                candidates.sort_unstable();
+                candidates.dedup();
+            }
+            let mut cuts = candidates
+                .iter()
+                .flat_map(|&k| {
+                    let (c, d) = self.edges[k];
+                    cuts(&a, &b, &c, &d)
+                })
+                .collect::<Vec<_>>();
+            cuts.sort_by(|p, q| p.0.total_cmp(&q.0));
+            let mut previous = a;
+            for (_, point) in cuts.into_iter().chain(std::iter::once((1., b))) {
+                if point != previous {
+                    result.push((previous, point));
+                    previous = point;
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Returns the points (and their positions along it) where the segment [a, b]
+/// is crossed or touched by the segment [c, d], strictly between a and b.
+fn cuts(a: &Pt, b: &Pt, c: &Pt, d: &Pt) -> Vec<(Float, Pt)> {
+    let orientation =
+        |p: &Pt, q: &Pt, r: &Pt| (q.x - p.x) * (r.y - p.y) - (q.y - p.y) * (r.x - p.x);
+    let (o1, o2) = (orientation(a, b, c), orientation(a, b, d));
+    let (o3, o4) = (orientation(c, d, a), orientation(c, d, b));
+    let along = |p: &Pt| {
+        let (sx, sy) = (b.x - a.x, b.y - a.y);
+        ((p.x - a.x) * sx + (p.y - a.y) * sy) / (sx * sx + sy * sy)
+    };
+    if o1 * o2 < 0. && o3 * o4 < 0. {
+        // The crossing point is computed in the same way for both segments
+        // (so that the edges split at it are linked together)
+        let ((p, q), (r, s)) = if (a.x, a.y, b.x, b.y) < (c.x, c.y, d.x, d.y) {
+            ((a, b), (c, d))
+        } else {
+            ((c, d), (a, b))
+        };
+        let (p, q) = if (p.x, p.y) < (q.x, q.y) {
+            (p, q)
+        } else {
+            (q, p)
+        };
+        let (r, s) = if (r.x, r.y) < (s.x, s.y) {
+            (r, s)
+        } else {
+            (s, r)
+        };
+        let (o3, o4) = (orientation(r, s, p), orientation(r, s, q));
+        let t = o3 / (o3 - o4);
+        let point = Pt {
+            x: p.x + t * (q.x - p.x),
+            y: p.y + t * (q.y - p.y),
+        };
+        return vec![(along(&point), point)];
+    }
+    // The ends of [c, d] lying on [a, b]
+    [(c, o1), (d, o2)]
+        .into_iter()
+        .filter(|(_, o)| o.abs() <= Float::EPSILON)
+        .map(|(p, _)| (along(p), *p))
+        .filter(|(t, _)| *t > 0. && *t < 1.)
+        .collect()
+}
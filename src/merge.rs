@@ -0,0 +1,499 @@
+use crate::area::{area, contains};
+use crate::band::nesting_depths;
+use crate::bbox::{lines_bbox, polygons_bbox};
+use crate::snap::Snapper;
+use crate::{Band, BuilderConfig, Contour, ContourBuilder, ContourSet, Float, Line, Pt, Ring};
+use geo_types::{LineString, MultiLineString, MultiPolygon, Polygon};
+use rustc_hash::FxHashMap;
+
+type Key = (u64, u64);
+
+#[allow(clippy::unnecessary_cast)]
+fn key(pt: &Pt) -> Key {
+    // Adding 0. normalizes -0. to 0.
+    (
+        ((pt.x + 0.) as f64).to_bits(),
+        ((pt.y + 0.) as f64).to_bits(),
+    )
+}
+
+/// The (x_min, y_min, x_max, y_max) region kept for a tile.
+pub(crate) type ClipRect = [Float; 4];
+
+/// Merges the results of several tiles, computed separately with the same thresholds,
+/// into a single [`ContourSet`]: the lines, contours and bands sharing the same
+/// threshold(s) are merged into a single feature, seamlessly joining the geometries
+/// across the tile edges.
+///
+/// Adjacent tiles must overlap by one row / column of cells (i.e. the last column of
+/// a tile is the first column of the next one) and use the same steps.
+/// Each tile is clipped at the middle of the overlap (where the geometries computed for
+/// both tiles coincide) before the geometries are merged, so that the artificial rings
+/// closing the features along the edges of each tile are removed.
+/// If the coordinates of the vertices along the shared edges may differ slightly
+/// (e.g. due to floating point errors), a snapping `tolerance` can be given
+/// to make them identical before merging.
+///
+/// The configuration of the returned set describes the grid covering all the tiles.
+pub fn merge_results(sets: &[ContourSet], tolerance: Option<Float>) -> ContourSet {
+    if sets.is_empty() {
+        return ContourSet::from_config(
+            BuilderConfig {
+                dx: 0,
+                dy: 0,
+                smooth: false,
+                x_origin: 0.,
+                y_origin: 0.,
+                x_step: 1.,
+                y_step: 1.,
+                options: 0,
+            },
+            Vec::new(),
+            None,
+        );
+    }
+    let config = merged_config(sets);
+    let global = grid_extent(&config);
+    let mut sets = sets
+        .iter()
+        .map(|set| {
+            let clip = clip_rect(&set.config, &global);
+            let mut set = set.clone();
+            set.lines.iter_mut().for_each(|l| {
+                l.geometry = MultiLineString(
+                    l.geometry
+                        .0
+                        .iter()
+                        .flat_map(|ls| clip_polyline(&ls.0, &clip))
+                        .map(LineString)
+                        .collect(),
+                );
+                l.update_bbox();
+            });
+            set.contours.iter_mut().for_each(|c| {
+                c.geometry = clip_polygons(&c.geometry, &clip);
+                c.update_bbox();
+            });
+            set.bands.iter_mut().for_each(|b| {
+                b.geometry = clip_polygons(&b.geometry, &clip);
+                b.update_bbox();
+            });
+            set
+        })
+        .collect::<Vec<_>>();
+    if let Some(tolerance) = tolerance {
+        let mut snapper = Snapper::new(tolerance);
+        sets.iter_mut().for_each(|set| {
+            snapper.snap_bands(&mut set.bands);
+            snapper.snap_contours(&mut set.contours);
+            snapper.snap_lines(&mut set.lines);
+        });
+    }
+
+    let lines = group_by(sets.iter().flat_map(|s| s.lines.iter()), |l| {
+        key(&Pt {
+            x: l.threshold,
+            y: 0.,
+        })
+    })
+    .into_iter()
+    .map(|group| {
+        let edges = group
+            .iter()
+            .flat_map(|l| l.geometry.0.iter())
+            .flat_map(|ls| ls.0.windows(2).map(|w| (w[0], w[1])))
+            .collect();
+        let geometry = MultiLineString(link_edges(edges).into_iter().map(LineString).collect());
+        Line {
+            bbox: lines_bbox(&geometry),
+            geometry,
+            // The merged lines aren't related to a grid anymore
+            depressions: None,
+            ..group[0].clone()
+        }
+    })
+    .collect();
+
+    let contours = group_by(sets.iter().flat_map(|s| s.contours.iter()), |c| {
+        key(&Pt {
+            x: c.threshold,
+            y: 0.,
+        })
+    })
+    .into_iter()
+    .map(|group| {
+        let geometry = dissolve_polygons(group.iter().map(|c| &c.geometry));
+        Contour {
+            bbox: polygons_bbox(&geometry),
+            geometry,
+            ..group[0].clone()
+        }
+    })
+    .collect();
+
+    let bands = group_by(sets.iter().flat_map(|s| s.bands.iter()), |b| {
+        key(&Pt {
+            x: b.min_v,
+            y: b.max_v,
+        })
+    })
+    .into_iter()
+    .map(|group| {
+        let geometry = dissolve_polygons(group.iter().map(|b| &b.geometry));
+        Band {
+            bbox: polygons_bbox(&geometry),
+            depths: nesting_depths(&geometry),
+            geometry,
+            ..group[0].clone()
+        }
+    })
+    .collect();
+
+    // The builder of the first tile, extended to the whole grid
+    let builder = sets[0].builder.as_ref().map(|builder| ContourBuilder {
+        dx: config.dx,
+        dy: config.dy,
+        x_origin: config.x_origin,
+        y_origin: config.y_origin,
+        ..builder.clone()
+    });
+    ContourSet::from_config(config, sets[0].thresholds.clone(), builder)
+        .with_lines(lines)
+        .with_contours(contours)
+        .with_bands(bands)
+}
+
+/// Returns the (x_min, y_min, x_max, y_max) extent of the given grid.
+fn grid_extent(c: &BuilderConfig) -> [Float; 4] {
+    let (x0, x1) = (c.x_origin, c.x_origin + c.x_step * c.dx as Float);
+    let (y0, y1) = (c.y_origin, c.y_origin + c.y_step * c.dy as Float);
+    [x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)]
+}
+
+/// Computes the configuration of the grid covering all the tiles.
+fn merged_config(sets: &[ContourSet]) -> BuilderConfig {
+    let mut config = sets[0].config.clone();
+    let [x0, y0, x1, y1] = sets.iter().map(|s| grid_extent(&s.config)).fold(
+        [
+            Float::INFINITY,
+            Float::INFINITY,
+            Float::NEG_INFINITY,
+            Float::NEG_INFINITY,
+        ],
+        |a, e| {
+            [
+                a[0].min(e[0]),
+                a[1].min(e[1]),
+                a[2].max(e[2]),
+                a[3].max(e[3]),
+            ]
+        },
+    );
+    config.x_origin = if config.x_step > 0. { x0 } else { x1 };
+    config.y_origin = if config.y_step > 0. { y0 } else { y1 };
+    config.dx = ((x1 - x0) / config.x_step.abs()).round() as usize;
+    config.dy = ((y1 - y0) / config.y_step.abs()).round() as usize;
+    config
+}
+
+/// Computes the region kept for a tile: the extent of the centers of its cells
+/// (which is where the geometries of overlapping tiles coincide), except on the sides
+/// lying on the edge of the whole grid (where nothing is clipped).
+fn clip_rect(c: &BuilderConfig, global: &[Float; 4]) -> ClipRect {
+    let [x0, y0, x1, y1] = grid_extent(c);
+    let (hx, hy) = (c.x_step.abs() / 2., c.y_step.abs() / 2.);
+    // The minimum sides are moved inwards by adding half a step, the maximum ones
+    // by subtracting it
+    let side = |v: Float, g: Float, inner: Float, h: Float, is_min: bool| {
+        if (v - g).abs() < h / 2. {
+            inner
+        } else {
+            v + if is_min { h } else { -h }
+        }
+    };
+    [
+        side(x0, global[0], Float::NEG_INFINITY, hx, true),
+        side(y0, global[1], Float::NEG_INFINITY, hy, true),
+        side(x1, global[2], Float::INFINITY, hx, false),
+        side(y1, global[3], Float::INFINITY, hy, false),
+    ]
+}
+
+fn inside(pt: &Pt, clip: &ClipRect) -> bool {
+    pt.x >= clip[0] && pt.y >= clip[1] && pt.x <= clip[2] && pt.y <= clip[3]
+}
+
+/// Clips a closed ring with the given rectangle (Sutherland–Hodgman).
+pub(crate) fn clip_ring(ring: &[Pt], clip: &ClipRect) -> Ring {
+    let mut points = ring[..ring.len().saturating_sub(1)].to_vec();
+    // For each side: whether a point is inside, and the intersection of a segment with it
+    let sides: [(usize, Float, bool); 4] = [
+        (0, clip[0], true),
+        (1, clip[1], true),
+        (0, clip[2], false),
+        (1, clip[3], false),
+    ];
+    for (axis, v, is_min) in sides {
+        if !v.is_finite() || points.is_empty() {
+            continue;
+        }
+        let coord = |p: &Pt| if axis == 0 { p.x } else { p.y };
+        let keep = |p: &Pt| if is_min { coord(p) >= v } else { coord(p) <= v };
+        let cut = |a: &Pt, b: &Pt| {
+            let t = (v - coord(a)) / (coord(b) - coord(a));
+            if axis == 0 {
+                Pt {
+                    x: v,
+                    y: a.y + t * (b.y - a.y),
+                }
+            } else {
+                Pt {
+                    x: a.x + t * (b.x - a.x),
+                    y: v,
+                }
+            }
+        };
+        let input = std::mem::take(&mut points);
+        for (i, cur) in input.iter().enumerate() {
+            let prev = &input[(i + input.len() - 1) % input.len()];
+            match (keep(prev), keep(cur)) {
+                (true, true) => points.push(*cur),
+                (true, false) => points.push(cut(prev, cur)),
+                (false, true) => {
+                    points.push(cut(prev, cur));
+                    points.push(*cur);
+                }
+                (false, false) => {}
+            }
+        }
+    }
+    points.dedup();
+    if let Some(first) = points.first().copied() {
+        points.push(first);
+    }
+    points
+}
+
+fn clip_polygons(geometry: &MultiPolygon<Float>, clip: &ClipRect) -> MultiPolygon<Float> {
+    MultiPolygon(
+        geometry
+            .0
+            .iter()
+            .filter_map(|p| {
+                let exterior = clip_ring(&p.exterior().0, clip);
+                if exterior.len() < 4 {
+                    return None;
+                }
+                let interiors = p
+                    .interiors()
+                    .iter()
+                    .map(|r| clip_ring(&r.0, clip))
+                    .filter(|r| r.len() > 3)
+                    .map(LineString)
+                    .collect();
+                Some(Polygon::new(LineString(exterior), interiors))
+            })
+            .collect(),
+    )
+}
+
+/// Clips a polyline with the given rectangle, returning the parts inside it.
+fn clip_polyline(line: &[Pt], clip: &ClipRect) -> Vec<Ring> {
+    let mut parts = Vec::new();
+    let mut current: Ring = Vec::new();
+    for pt in line {
+        if inside(pt, clip) {
+            current.push(*pt);
+        } else if !current.is_empty() {
+            parts.push(std::mem::take(&mut current));
+        }
+    }
+    parts.push(current);
+    parts.retain(|p| p.len() > 1);
+    parts
+}
+
+/// Groups the items by key, keeping the order of first appearance.
+fn group_by<'a, T: 'a>(
+    items: impl Iterator<Item = &'a T>,
+    key: impl Fn(&T) -> Key,
+) -> Vec<Vec<&'a T>> {
+    let mut groups: Vec<Vec<&T>> = Vec::new();
+    let mut position: FxHashMap<Key, usize> = FxHashMap::default();
+    items.for_each(|item| {
+        let k = key(item);
+        match position.get(&k) {
+            Some(&i) => groups[i].push(item),
+            None => {
+                position.insert(k, groups.len());
+                groups.push(vec![item]);
+            }
+        }
+    });
+    groups
+}
+
+/// Links the given directed edges into polylines (starting from the vertices
+/// without incoming edge) and closed rings.
+pub(crate) fn link_edges(edges: Vec<(Pt, Pt)>) -> Vec<Ring> {
+    let mut outgoing: FxHashMap<Key, Vec<(Pt, Pt)>> = FxHashMap::default();
+    let mut incoming: FxHashMap<Key, usize> = FxHashMap::default();
+    edges.iter().rev().for_each(|(a, b)| {
+        outgoing.entry(key(a)).or_default().push((*a, *b));
+        *incoming.entry(key(b)).or_default() += 1;
+    });
+    let starts = edges
+        .iter()
+        .map(|(a, _)| key(a))
+        .filter(|k| !incoming.contains_key(k))
+        .chain(edges.iter().map(|(a, _)| key(a)))
+        .collect::<Vec<_>>();
+    let mut result = Vec::new();
+    for start in starts {
+        let first = match outgoing.get_mut(&start).and_then(|v| v.pop()) {
+            Some(e) => e,
+            None => continue,
+        };
+        let mut ring = vec![first.0, first.1];
+        let mut current = key(&first.1);
+        while current != start {
+            match outgoing.get_mut(&current).and_then(|v| v.pop()) {
+                Some((_, b)) => {
+                    ring.push(b);
+                    current = key(&b);
+                }
+                None => break,
+            }
+        }
+        result.push(ring);
+    }
+    result
+}
+
+/// Splits the axis-parallel edges at the vertices lying on them
+/// (so that the edges shared by two tiles are split identically).
+fn split_edges(edges: Vec<(Pt, Pt)>) -> Vec<(Pt, Pt)> {
+    let mut on_x: FxHashMap<u64, Vec<Float>> = FxHashMap::default();
+    let mut on_y: FxHashMap<u64, Vec<Float>> = FxHashMap::default();
+    edges.iter().for_each(|(a, _)| {
+        let (kx, ky) = key(a);
+        on_x.entry(kx).or_default().push(a.y);
+        on_y.entry(ky).or_default().push(a.x);
+    });
+    edges
+        .into_iter()
+        .flat_map(|(a, b)| {
+            let (ka, kb) = (key(&a), key(&b));
+            let mut points = vec![a];
+            let (between, vertical): (Vec<Float>, bool) = if ka.0 == kb.0 && a.y != b.y {
+                (on_x.get(&ka.0).cloned().unwrap_or_default(), true)
+            } else if ka.1 == kb.1 && a.x != b.x {
+                (on_y.get(&ka.1).cloned().unwrap_or_default(), false)
+            } else {
+                (Vec::new(), false)
+            };
+            let (from, to) = if vertical { (a.y, b.y) } else { (a.x, b.x) };
+            let mut inner = between
+                .into_iter()
+                .filter(|v| (from < *v && *v < to) || (to < *v && *v < from))
+                .collect::<Vec<_>>();
+            inner.sort_by(|p, q| {
+                if from < to {
+                    p.total_cmp(q)
+                } else {
+                    q.total_cmp(p)
+                }
+            });
+            inner.dedup();
+            points.extend(inner.into_iter().map(|v| {
+                if vertical {
+                    Pt { x: a.x, y: v }
+                } else {
+                    Pt { x: v, y: a.y }
+                }
+            }));
+            points.push(b);
+            points.windows(2).map(|w| (w[0], w[1])).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Dissolves closed rings by removing the pairs of opposite edges they share
+/// and linking the remaining edges back into closed rings.
+fn dissolve(rings: Vec<Ring>) -> Vec<Ring> {
+    let edges = split_edges(
+        rings
+            .iter()
+            .flat_map(|ring| ring.windows(2).map(|w| (w[0], w[1])))
+            .filter(|(a, b)| key(a) != key(b))
+            .collect(),
+    );
+    // Count the directed edges, cancelling the opposite ones
+    let mut counts: FxHashMap<(Key, Key), isize> = FxHashMap::default();
+    edges.iter().for_each(|(a, b)| {
+        let (ka, kb) = (key(a), key(b));
+        match counts.get_mut(&(kb, ka)) {
+            Some(c) if *c > 0 => *c -= 1,
+            _ => *counts.entry((ka, kb)).or_insert(0) += 1,
+        }
+    });
+    let remaining = edges
+        .into_iter()
+        .filter(|(a, b)| match counts.get_mut(&(key(a), key(b))) {
+            Some(c) if *c > 0 => {
+                *c -= 1;
+                true
+            }
+            _ => false,
+        })
+        .collect();
+    link_edges(remaining)
+        .into_iter()
+        .filter(|ring| ring.len() > 3 && key(&ring[0]) == key(&ring[ring.len() - 1]))
+        .collect()
+}
+
+/// Dissolves the given multipolygons into a single multipolygon.
+fn dissolve_polygons<'a>(
+    geometries: impl Iterator<Item = &'a MultiPolygon<Float>>,
+) -> MultiPolygon<Float> {
+    let rings = geometries
+        .flat_map(|mp| mp.0.iter())
+        .flat_map(|polygon| {
+            std::iter::once(polygon.exterior().0.clone())
+                .chain(polygon.interiors().iter().map(|r| r.0.clone()))
+        })
+        .collect::<Vec<_>>();
+    let mut rings = dissolve(rings)
+        .into_iter()
+        .map(|ring| (area(&ring).abs(), ring))
+        .collect::<Vec<_>>();
+    rings.sort_by(|a, b| a.0.total_cmp(&b.0));
+    // Rings enclosed by an even number of rings are exteriors, the others are holes
+    let (exteriors, holes): (Vec<_>, Vec<_>) = rings
+        .iter()
+        .enumerate()
+        .map(|(i, (_, ring))| {
+            let depth = rings
+                .iter()
+                .enumerate()
+                .filter(|(j, (_, other))| i != *j && contains(other, ring) != -1)
+                .count();
+            (depth, ring)
+        })
+        .partition(|(depth, _)| depth % 2 == 0);
+    // Assign each hole to the smallest exterior ring containing it
+    let mut polygons = exteriors
+        .into_iter()
+        .map(|(_, ring)| Polygon::new(LineString(ring.clone()), vec![]))
+        .collect::<Vec<_>>();
+    holes.into_iter().for_each(|(_, hole)| {
+        if let Some(polygon) = polygons
+            .iter_mut()
+            .find(|p| contains(&p.exterior().0, hole) != -1)
+        {
+            polygon.interiors_push(LineString(hole.clone()));
+        }
+    });
+    MultiPolygon(polygons)
+}
@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+/// Arbitrary metadata attached to a threshold (label, color, style id, etc.)
+/// and carried through to the [`Line`](crate::Line), [`Contour`](crate::Contour)
+/// and [`Band`](crate::Band) built for it.
+///
+/// When the `geojson` feature is enabled, each entry is written
+/// as a property of the resulting GeoJSON Feature, except the entries whose key
+/// is reserved by the built-in properties of the Feature, which are skipped:
+/// - `threshold` for lines (and `depressions` or `class` when they are set),
+/// - `threshold` for contours (and `max_threshold`, `class` or `part` when they are set),
+/// - `min_v` and `max_v` for bands (and `label` or `part` when they are set),
+///
+/// or the names given for them with [`GeoJsonKeys`](crate::GeoJsonKeys).
+pub type Metadata = BTreeMap<String, MetadataValue>;
+
+/// A value stored in the [`Metadata`] of a threshold.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum MetadataValue {
+    /// A string (e.g. a label or a style id).
+    String(String),
+    /// A number (the integers being stored as `f64`).
+    Number(f64),
+    /// A boolean flag.
+    Bool(bool),
+}
+
+impl From<&str> for MetadataValue {
+    fn from(v: &str) -> Self {
+        MetadataValue::String(v.to_string())
+    }
+}
+
+impl From<String> for MetadataValue {
+    fn from(v: String) -> Self {
+        MetadataValue::String(v)
+    }
+}
+
+impl From<f64> for MetadataValue {
+    fn from(v: f64) -> Self {
+        MetadataValue::Number(v)
+    }
+}
+
+impl From<f32> for MetadataValue {
+    fn from(v: f32) -> Self {
+        MetadataValue::Number(v.into())
+    }
+}
+
+impl From<i64> for MetadataValue {
+    fn from(v: i64) -> Self {
+        MetadataValue::Number(v as f64)
+    }
+}
+
+impl From<bool> for MetadataValue {
+    fn from(v: bool) -> Self {
+        MetadataValue::Bool(v)
+    }
+}
+
+#[cfg(feature = "geojson")]
+impl From<&MetadataValue> for serde_json::Value {
+    fn from(v: &MetadataValue) -> Self {
+        match v {
+            MetadataValue::String(s) => s.clone().into(),
+            MetadataValue::Number(n) => (*n).into(),
+            MetadataValue::Bool(b) => (*b).into(),
+        }
+    }
+}
+
+/// Adds the entries of `metadata` to the given `properties`, skipping the keys
+/// of the built-in properties already written.
+#[cfg(feature = "geojson")]
+pub(crate) fn insert_metadata(properties: &mut geojson::JsonObject, metadata: &Metadata) {
+    metadata.iter().for_each(|(k, v)| {
+        properties.entry(k.clone()).or_insert_with(|| v.into());
+    });
+}
@@ -0,0 +1,214 @@
+use crate::error::{new_error, ErrorKind, Result};
+use crate::{ContourBuilder, Float};
+
+/// How the values of overlapping tiles of a [`Mosaic`] are combined.
+///
+/// Whatever the rule, missing (NaN) values never override the values of other tiles.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverlapRule {
+    /// The value of the first tile (in the order they were added) is used (the default).
+    #[default]
+    First,
+    /// The value of the last tile (in the order they were added) is used.
+    Last,
+    /// The mean of the values of the tiles is used.
+    Mean,
+    /// The lowest of the values of the tiles is used.
+    Min,
+    /// The highest of the values of the tiles is used.
+    Max,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Tile<'a> {
+    values: &'a [Float],
+    dx: usize,
+    dy: usize,
+    origin: (Float, Float),
+    step: (Float, Float),
+}
+
+/// A set of grids (e.g. adjacent DEM tiles), each with its own dimensions and origin,
+/// to be contoured as a single seamless dataset.
+///
+/// The tiles must share the same step and be aligned on the same lattice (i.e. their
+/// origins must be offset by a whole number of cells); they may overlap (see
+/// [`OverlapRule`]) or leave gaps between them, which are filled with NaN values.
+///
+/// # Example
+///
+/// ```
+/// use contour::Mosaic;
+///
+/// let mut mosaic = Mosaic::new();
+/// mosaic.add_tile(&[0., 1., 0., 1.], 2, 2, (0., 0.), (1., 1.)).unwrap();
+/// mosaic.add_tile(&[1., 0., 1., 0.], 2, 2, (2., 0.), (1., 1.)).unwrap();
+/// let (builder, values) = mosaic.grid(false).unwrap();
+/// assert_eq!((builder.dx(), builder.dy()), (4, 2));
+/// let contours = builder.contours(&values, &[0.5]).unwrap();
+/// assert_eq!(contours[0].geometry().0.len(), 1);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mosaic<'a> {
+    tiles: Vec<Tile<'a>>,
+    overlap: OverlapRule,
+}
+
+// The tolerance (relative to the size of a cell) when checking the alignment of the tiles.
+const TOLERANCE: Float = 1e-6;
+
+impl<'a> Mosaic<'a> {
+    /// Constructs a new empty mosaic.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how the values of overlapping tiles are combined (default: [`OverlapRule::First`]).
+    pub fn overlap(mut self, overlap: OverlapRule) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    /// Adds a tile to the mosaic.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The values of the tile (row-major, of length `dx` * `dy`).
+    /// * `dx` - The number of columns in the tile.
+    /// * `dy` - The number of rows in the tile.
+    /// * `origin` - The coordinates of the origin of the tile.
+    /// * `step` - The horizontal and vertical steps of the tile.
+    pub fn add_tile(
+        &mut self,
+        values: &'a [Float],
+        dx: usize,
+        dy: usize,
+        origin: (Float, Float),
+        step: (Float, Float),
+    ) -> Result<()> {
+        if dx == 0 || dy == 0 || values.len() != dx * dy {
+            return Err(new_error(ErrorKind::BadDimension));
+        }
+        let tile = Tile {
+            values,
+            dx,
+            dy,
+            origin,
+            step,
+        };
+        if let Some(first) = self.tiles.first() {
+            let same_step = |a: Float, b: Float| (a - b).abs() <= TOLERANCE * b.abs();
+            if !same_step(step.0, first.step.0)
+                || !same_step(step.1, first.step.1)
+                || first.offset(&tile).is_none()
+            {
+                return Err(new_error(ErrorKind::MisalignedTile {
+                    index: self.tiles.len(),
+                }));
+            }
+        } else if step.0 == 0. || step.1 == 0. || !step.0.is_finite() || !step.1.is_finite() {
+            return Err(new_error(ErrorKind::MisalignedTile { index: 0 }));
+        }
+        self.tiles.push(tile);
+        Ok(())
+    }
+
+    /// Returns the number of tiles in the mosaic.
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns true if the mosaic doesn't contain any tile.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Combines the tiles into a single grid, covering the extent of every tile,
+    /// and returns it with a [`ContourBuilder`] matching its geometry (which can be
+    /// further configured before computing the contours).
+    ///
+    /// # Arguments
+    ///
+    /// * `smooth` - Whether or not the generated rings will be smoothed using linear interpolation.
+    pub fn grid(&self, smooth: bool) -> Result<(ContourBuilder, Vec<Float>)> {
+        let first = self
+            .tiles
+            .first()
+            .ok_or_else(|| new_error(ErrorKind::BadDimension))?;
+        let offsets = self
+            .tiles
+            .iter()
+            .map(|tile| first.offset(tile).unwrap())
+            .collect::<Vec<_>>();
+        let (min_x, min_y) = offsets
+            .iter()
+            .fold((0, 0), |(x, y), &(ox, oy)| (x.min(ox), y.min(oy)));
+        let (max_x, max_y) =
+            offsets
+                .iter()
+                .zip(&self.tiles)
+                .fold((0, 0), |(x, y), (&(ox, oy), tile)| {
+                    (x.max(ox + tile.dx as isize), y.max(oy + tile.dy as isize))
+                });
+        let (dx, dy) = ((max_x - min_x) as usize, (max_y - min_y) as usize);
+
+        let mut values = vec![Float::NAN; dx * dy];
+        let mut counts = vec![
+            0u32;
+            if self.overlap == OverlapRule::Mean {
+                dx * dy
+            } else {
+                0
+            }
+        ];
+        for (tile, &(ox, oy)) in self.tiles.iter().zip(&offsets) {
+            let (ox, oy) = ((ox - min_x) as usize, (oy - min_y) as usize);
+            for (j, row) in tile.values.chunks_exact(tile.dx).enumerate() {
+                let start = (oy + j) * dx + ox;
+                for (k, &v) in row.iter().enumerate().filter(|(_, v)| !v.is_nan()) {
+                    let out = &mut values[start + k];
+                    *out = match self.overlap {
+                        _ if out.is_nan() => v,
+                        OverlapRule::First => *out,
+                        OverlapRule::Last => v,
+                        OverlapRule::Mean => *out + v,
+                        OverlapRule::Min => out.min(v),
+                        OverlapRule::Max => out.max(v),
+                    };
+                    if let Some(count) = counts.get_mut(start + k) {
+                        *count += 1;
+                    }
+                }
+            }
+        }
+        values
+            .iter_mut()
+            .zip(&counts)
+            .filter(|(_, &count)| count > 1)
+            .for_each(|(v, &count)| *v /= count as Float);
+
+        let builder = ContourBuilder::new(dx, dy, smooth)
+            .x_origin(first.origin.0 + min_x as Float * first.step.0)
+            .y_origin(first.origin.1 + min_y as Float * first.step.1)
+            .x_step(first.step.0)
+            .y_step(first.step.1);
+        Ok((builder, values))
+    }
+}
+
+impl Tile<'_> {
+    /// Returns the offset (in columns and rows) of the `other` tile relative to
+    /// this tile, or `None` if it isn't a whole number of cells.
+    fn offset(&self, other: &Tile) -> Option<(isize, isize)> {
+        let offset = |a: Float, b: Float, step: Float| {
+            let cells = (b - a) / step;
+            let rounded = cells.round();
+            ((cells - rounded).abs() <= TOLERANCE && rounded.is_finite())
+                .then_some(rounded as isize)
+        };
+        Some((
+            offset(self.origin.0, other.origin.0, self.step.0)?,
+            offset(self.origin.1, other.origin.1, self.step.1)?,
+        ))
+    }
+}
@@ -0,0 +1,114 @@
+use crate::area::area;
+use crate::weights::Grid;
+use crate::{ContourBuilder, Float, Pt, Ring};
+
+/// Where smoothing places the vertices lying on an edge between a sample
+/// and a cell without data (see [`ContourBuilder::nodata`](crate::ContourBuilder::nodata)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NodataSmoothing {
+    /// The vertices stay on the boundary of the cells without data
+    /// (in the middle of the edge).
+    #[default]
+    Boundary,
+    /// The position of the threshold is extrapolated linearly from the valid end
+    /// of the edge and the valid sample beyond it, the vertices being clamped
+    /// to the half of the edge on the valid side (staying on the boundary
+    /// if the values don't reach the threshold before it).
+    Extrapolate,
+}
+
+/// Returns true if the given (unsmoothed) vertex, expressed in grid coordinates,
+/// lies on an edge between two samples of the grid one of which has no data.
+fn on_nodata_edge(pt: &Pt, grid: Grid<'_>, dx: usize, dy: usize) -> bool {
+    let (xt, yt) = (pt.x.trunc() as usize, pt.y.trunc() as usize);
+    if pt.x.fract() == 0. {
+        // On a vertical edge, between the samples (xt - 1, yt) and (xt, yt)
+        xt > 0 && xt < dx && yt < dy && {
+            let i = yt * dx + xt;
+            grid.get(i - 1).is_nan() || grid.get(i).is_nan()
+        }
+    } else if pt.y.fract() == 0. {
+        // On a horizontal edge, between the samples (xt, yt - 1) and (xt, yt)
+        yt > 0 && yt < dy && xt < dx && {
+            let i = yt * dx + xt;
+            grid.get(i - dx).is_nan() || grid.get(i).is_nan()
+        }
+    } else {
+        false
+    }
+}
+
+/// Cuts the given (unsmoothed, closed) `rings` where they follow the boundary of the
+/// cells without data rather than a threshold: the vertices lying on an edge between
+/// a sample and a cell without data are removed, along with their segments, and the
+/// remaining runs of vertices are returned as open lines.
+///
+/// Returns `None` if no ring reaches a cell without data.
+pub(crate) fn cut_rings(rings: &[Ring], grid: Grid<'_>, dx: usize, dy: usize) -> Option<Vec<Ring>> {
+    cut_rings_at(rings, |pt| on_nodata_edge(pt, grid, dx, dy))
+}
+
+impl ContourBuilder {
+    /// Cuts the given (unsmoothed, closed) `rings` of the `threshold` along the cells
+    /// without data (see [`cut_rings`]), dropping first the rings reaching them which
+    /// collapse once smoothed (e.g. around a sample equal to the threshold when the
+    /// positions are extrapolated): like the contours, which they don't bound,
+    /// the isolines don't keep them.
+    pub(crate) fn cut_nodata_rings(
+        &self,
+        rings: &[Ring],
+        grid: Grid<'_>,
+        threshold: Float,
+    ) -> Option<Vec<Ring>> {
+        let (dx, dy) = (self.dx, self.dy);
+        if !self.smooth || self.nodata_smoothing != NodataSmoothing::Extrapolate {
+            return cut_rings(rings, grid, dx, dy);
+        }
+        let mut smoothed = rings.to_vec();
+        self.grid_space()
+            .finalize_rings(&mut smoothed, grid, threshold);
+        let rings = rings
+            .iter()
+            .zip(&smoothed)
+            .filter(|(ring, smoothed)| {
+                area(smoothed) != 0. || !ring.iter().any(|pt| on_nodata_edge(pt, grid, dx, dy))
+            })
+            .map(|(ring, _)| ring.clone())
+            .collect::<Vec<_>>();
+        cut_rings(&rings, grid, dx, dy)
+    }
+}
+
+/// Cuts the given (closed) `rings` at the vertices matching the `removed` predicate,
+/// which are removed along with their segments, the remaining runs of vertices
+/// being returned as open lines.
+///
+/// Returns `None` if no vertex is removed.
+pub(crate) fn cut_rings_at(rings: &[Ring], removed: impl Fn(&Pt) -> bool) -> Option<Vec<Ring>> {
+    let mut cut = false;
+    let mut result = Vec::with_capacity(rings.len());
+    for ring in rings {
+        // The closing vertex repeats the first one
+        let n = ring.len().saturating_sub(1);
+        let flags = ring[..n].iter().map(&removed).collect::<Vec<_>>();
+        let Some(start) = flags.iter().position(|&f| f) else {
+            result.push(ring.clone());
+            continue;
+        };
+        cut = true;
+        // Starts right after a removed vertex so that no run wraps around
+        let mut line: Ring = Vec::new();
+        for k in 1..=n {
+            let ix = (start + k) % n;
+            if flags[ix] {
+                if line.len() > 1 {
+                    result.push(std::mem::take(&mut line));
+                }
+                line.clear();
+            } else {
+                line.push(ring[ix]);
+            }
+        }
+    }
+    cut.then_some(result)
+}
@@ -0,0 +1,20 @@
+use crate::Float;
+
+/// The order in which the values of a grid are stored in a flat slice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GridOrder {
+    /// The values are stored row by row (C order): the value at column `x`
+    /// and row `y` is at index `y * dx + x`.
+    #[default]
+    RowMajor,
+    /// The values are stored column by column (Fortran order, e.g. NetCDF or MATLAB
+    /// arrays): the value at column `x` and row `y` is at index `x * dy + y`.
+    ColumnMajor,
+}
+
+/// Copies the given column-major `values` (of a `dx` * `dy` grid) in row-major order.
+pub(crate) fn to_row_major(values: &[Float], dx: usize, dy: usize) -> Vec<Float> {
+    (0..dx * dy)
+        .map(|i| values[(i % dx) * dy + i / dx])
+        .collect()
+}
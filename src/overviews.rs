@@ -0,0 +1,164 @@
+use crate::error::{new_error, ErrorKind, Result};
+use crate::{Band, Contour, ContourBuilder, Float, Line};
+
+/// A set of overviews (a pyramid of resolutions) of the same raster,
+/// each with the [`ContourBuilder`] describing its geometry, used to contour
+/// the coarsest level still resolving the requested thresholds (e.g. to speed up
+/// the rendering of small-scale maps).
+///
+/// # Example
+///
+/// ```
+/// use contour::{ContourBuilder, Float, Overviews};
+///
+/// // A plane, which is resolved exactly by its overview
+/// let fine = (0..16).map(|i| (i % 4) as Float).collect::<Vec<_>>();
+/// let coarse: [Float; 4] = [0.5, 2.5, 0.5, 2.5];
+/// let mut overviews = Overviews::new();
+/// overviews.add_level(ContourBuilder::new(4, 4, true), &fine).unwrap();
+/// overviews.add_level(ContourBuilder::new(2, 2, true).x_step(2.).y_step(2.), &coarse).unwrap();
+/// assert_eq!(overviews.select(&[1.5], 0.01).unwrap(), 1);
+/// let lines = overviews.lines(&[1.5], 0.01).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Overviews<'a> {
+    levels: Vec<(ContourBuilder, &'a [Float])>,
+}
+
+impl<'a> Overviews<'a> {
+    /// Constructs a new empty set of overviews.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a level (the values of the raster at a given resolution, and the
+    /// builder describing its geometry and used to contour it) to the set of overviews.
+    ///
+    /// The levels can be added in any order, the finest level (i.e. with the smallest cells)
+    /// being the reference against which the other levels are evaluated.
+    pub fn add_level(&mut self, builder: ContourBuilder, values: &'a [Float]) -> Result<()> {
+        if values.len() != builder.dx * builder.dy {
+            return Err(new_error(ErrorKind::BadDimension));
+        }
+        self.levels.push((builder, values));
+        Ok(())
+    }
+
+    /// Returns the number of levels.
+    pub fn len(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Returns true if no level was added.
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    /// Returns the position (in the order they were added) of the coarsest level
+    /// resolving the given thresholds within `tolerance`, i.e. whose (bilinearly interpolated,
+    /// see [`GridSampler`](crate::GridSampler)) values differ by at most `tolerance` from the
+    /// values of the finest level, wherever the contours of the finest level
+    /// are (i.e. in its cells crossed by one of the thresholds, which must be
+    /// covered by the extent of the level).
+    ///
+    /// The finest level is returned if no other level resolves the thresholds.
+    ///
+    /// # Arguments
+    ///
+    /// * `thresholds` - The slice of thresholds values to be used.
+    /// * `tolerance` - The maximum difference between the values (in the units of the values).
+    pub fn select(&self, thresholds: &[Float], tolerance: Float) -> Result<usize> {
+        let mut order = (0..self.levels.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| {
+            cell_area(&self.levels[b].0).total_cmp(&cell_area(&self.levels[a].0))
+        });
+        let &finest = order
+            .last()
+            .ok_or_else(|| new_error(ErrorKind::BadDimension))?;
+        let (builder, values) = &self.levels[finest];
+        let grid = builder.grid(values)?;
+
+        // The samples of the finest level around which its contours are
+        let mut thresholds = thresholds
+            .iter()
+            .copied()
+            .filter(|t| !t.is_nan())
+            .collect::<Vec<_>>();
+        thresholds.sort_by(Float::total_cmp);
+        let level = |v: Float| thresholds.partition_point(|&t| t <= v);
+        let (dx, dy) = (builder.dx, builder.dy);
+        let mut crossed = vec![false; dx * dy];
+        for y in 0..dy {
+            for x in 0..dx {
+                let i = y * dx + x;
+                let v = level(grid.get(i));
+                for j in [
+                    (x + 1 < dx).then_some(i + 1),
+                    (y + 1 < dy).then_some(i + dx),
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    if level(grid.get(j)) != v {
+                        crossed[i] = true;
+                        crossed[j] = true;
+                    }
+                }
+            }
+        }
+        let samples = crossed
+            .iter()
+            .enumerate()
+            .filter(|(_, &c)| c)
+            .map(|(i, _)| {
+                let (x, y) = ((i % dx) as Float + 0.5, (i / dx) as Float + 0.5);
+                (
+                    builder.x_origin + x * builder.x_step,
+                    builder.y_origin + y * builder.y_step,
+                    grid.get(i),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // The coarsest level resolving them
+        for &ix in &order[..order.len() - 1] {
+            let (builder, values) = &self.levels[ix];
+            let sampler = builder.sampler(values)?;
+            let resolved = samples.iter().all(|&(x, y, v)| {
+                sampler
+                    .sample(x, y)
+                    .is_some_and(|s| (s - v).abs() <= tolerance || (s.is_nan() && v.is_nan()))
+            });
+            if resolved {
+                return Ok(ix);
+            }
+        }
+        Ok(finest)
+    }
+
+    /// Computes isolines on the coarsest level resolving the given thresholds
+    /// within `tolerance` (see [`select`](Self::select)).
+    pub fn lines(&self, thresholds: &[Float], tolerance: Float) -> Result<Vec<Line>> {
+        let (builder, values) = &self.levels[self.select(thresholds, tolerance)?];
+        builder.lines(values, thresholds)
+    }
+
+    /// Computes contours on the coarsest level resolving the given thresholds
+    /// within `tolerance` (see [`select`](Self::select)).
+    pub fn contours(&self, thresholds: &[Float], tolerance: Float) -> Result<Vec<Contour>> {
+        let (builder, values) = &self.levels[self.select(thresholds, tolerance)?];
+        builder.contours(values, thresholds)
+    }
+
+    /// Computes isobands on the coarsest level resolving the given thresholds
+    /// within `tolerance` (see [`select`](Self::select)).
+    pub fn isobands(&self, thresholds: &[Float], tolerance: Float) -> Result<Vec<Band>> {
+        let (builder, values) = &self.levels[self.select(thresholds, tolerance)?];
+        builder.isobands(values, thresholds)
+    }
+}
+
+// The area of the cells of the grid of a builder.
+fn cell_area(builder: &ContourBuilder) -> Float {
+    (builder.x_step * builder.y_step).abs()
+}
@@ -0,0 +1,43 @@
+use crate::Float;
+
+/// How the values of the virtual cells around the grid are computed
+/// (see [`ContourBuilder::padding`](crate::ContourBuilder::padding)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Padding {
+    /// Replicates the values on the edge of the grid.
+    Replicate,
+    /// Linearly extrapolates the values from the two cells closest to the edge
+    /// (replicating them if the grid is only one cell wide).
+    Linear,
+}
+
+/// Extends the given `values` (of a `dx` * `dy` grid) by `n` cells on each side.
+pub(crate) fn pad(values: &[Float], dx: usize, dy: usize, n: usize, mode: Padding) -> Vec<Float> {
+    let (pdx, pdy) = (dx + 2 * n, dy + 2 * n);
+    let extrapolate = |edge: Float, inner: Option<Float>, k: usize| match (mode, inner) {
+        (Padding::Linear, Some(inner)) => edge + (edge - inner) * k as Float,
+        _ => edge,
+    };
+    let mut result = vec![0.; pdx * pdy];
+    // Extend each row horizontally
+    for y in 0..dy {
+        let row = &values[y * dx..(y + 1) * dx];
+        let out = &mut result[(y + n) * pdx..(y + n + 1) * pdx];
+        out[n..n + dx].copy_from_slice(row);
+        for k in 1..=n {
+            out[n - k] = extrapolate(row[0], row.get(1).copied(), k);
+            out[n + dx - 1 + k] = extrapolate(row[dx - 1], dx.checked_sub(2).map(|i| row[i]), k);
+        }
+    }
+    // Then extend each (extended) column vertically
+    for x in 0..pdx {
+        let at = |y: usize| result[(y + n) * pdx + x];
+        let (first, second) = (at(0), (dy > 1).then(|| at(1)));
+        let (last, before_last) = (at(dy - 1), (dy > 1).then(|| at(dy - 2)));
+        for k in 1..=n {
+            result[(n - k) * pdx + x] = extrapolate(first, second, k);
+            result[(n + dy - 1 + k) * pdx + x] = extrapolate(last, before_last, k);
+        }
+    }
+    result
+}
@@ -0,0 +1,436 @@
+use crate::area::{area, contains};
+use crate::axis::Axis;
+use crate::curvilinear::Curvilinear;
+use crate::order::{to_row_major, GridOrder};
+use crate::weights::Weights;
+use crate::{ContourBuilder, Float, Pt, Ring};
+
+impl ContourBuilder {
+    /// Returns the builder and the values of the grid extended by a copy of its first
+    /// column after the last one, whose rings are stitched across the seam,
+    /// if the grid is periodic in x (see [`ContourBuilder::periodic_x`]).
+    pub(crate) fn wrapped(&self, values: &[Float]) -> Option<(ContourBuilder, Vec<Float>)> {
+        if !self.periodic_x || self.dx < 2 {
+            return None;
+        }
+        let (dx, dy) = (self.dx, self.dy);
+        // The wrapped grid is stored row by row
+        let wrap = |values: &[Float]| {
+            let values = match self.order {
+                GridOrder::RowMajor => values.to_vec(),
+                GridOrder::ColumnMajor => to_row_major(values, dx, dy),
+            };
+            values
+                .chunks(dx)
+                .flat_map(|row| row.iter().chain(&row[..1]).copied())
+                .collect::<Vec<_>>()
+        };
+        let values = wrap(values);
+        let builder = ContourBuilder {
+            dx: dx + 1,
+            x_coords: self.x_coords.as_ref().map(|axis| {
+                let coords = axis.coords();
+                let extra = axis.map(coords.len() as Float + 0.5);
+                Axis::new(&[coords, &[extra]].concat())
+            }),
+            curvilinear: (self.curvilinear.as_ref()).map(|c| {
+                // The coordinates of the copy are extrapolated from the last columns
+                let (x, y): (Vec<_>, Vec<_>) = (0..(dx + 1) * dy)
+                    .map(|k| {
+                        let (i, j) = (k % (dx + 1), k / (dx + 1));
+                        self.curvilinear_map(c, i as Float + 0.5, j as Float + 0.5)
+                    })
+                    .unzip();
+                Curvilinear::new(&x, &y)
+            }),
+            weights: self
+                .weights
+                .as_ref()
+                .map(|w| Weights::new(wrap(&w.weights), w.op.clone())),
+            order: GridOrder::RowMajor,
+            padding: None,
+            strips: 1,
+            periodic_x: false,
+            seam: Some(dx),
+            ..self.clone()
+        };
+        Some((builder, values))
+    }
+}
+
+/// Unwraps the given (smoothed) rings of a grid periodic in x, expressed in grid
+/// coordinates: the vertices of the rings crossing the seam are shifted by the given
+/// `period` so that the rings are continuous (extending past the last column), and the
+/// rings going around the whole period are cut where they cross the seam into paths
+/// from one side of the grid (`x = 0`) to the other (`x = period`), which are moved
+/// to the end of the rings, from the top of the grid to its bottom.
+pub(crate) fn unwrap_rings(rings: &mut [Ring], period: Float) {
+    let mut paths = Vec::new();
+    for (k, ring) in rings.iter_mut().enumerate() {
+        let mut offset = 0.;
+        let (first, mut previous) = match ring.first() {
+            Some(p) => (p.x, p.x),
+            None => continue,
+        };
+        for p in ring.iter_mut().skip(1) {
+            let x = p.x;
+            if x - previous > period / 2. {
+                offset -= period;
+            } else if previous - x > period / 2. {
+                offset += period;
+            }
+            previous = x;
+            p.x += offset;
+        }
+        // The last vertex may be the copy of the first one on the other side of the seam
+        if ring.last().is_some_and(|p| p.x != first) {
+            *ring = cut_path(ring, period);
+            paths.push(k);
+        } else if ring.iter().any(|p| p.x < 0.) {
+            // The rings crossing the seam extend past the last column
+            ring.iter_mut().for_each(|p| p.x += period);
+        }
+    }
+    if paths.is_empty() {
+        return;
+    }
+    // Moves the paths to the end, in order
+    paths.sort_by(|&a, &b| left_y(&rings[a]).total_cmp(&left_y(&rings[b])));
+    let is_path = (0..rings.len())
+        .map(|k| paths.contains(&k))
+        .collect::<Vec<_>>();
+    let order = (0..rings.len())
+        .filter(|&k| !is_path[k])
+        .chain(paths)
+        .collect::<Vec<_>>();
+    let mut taken = rings.iter_mut().map(std::mem::take).collect::<Vec<_>>();
+    for (ring, k) in rings.iter_mut().zip(order) {
+        *ring = std::mem::take(&mut taken[k]);
+    }
+}
+
+/// Returns the y of the end of the given path on the left side of the grid.
+fn left_y(path: &Ring) -> Float {
+    match (path.first(), path.last()) {
+        (Some(first), Some(last)) if first.x <= last.x => first.y,
+        (_, Some(last)) => last.y,
+        _ => 0.,
+    }
+}
+
+/// Cuts the given (unwrapped) ring going around the whole period where it first crosses
+/// the seam, returning the path from `x = 0` to `x = period` (or the other way around).
+fn cut_path(ring: &Ring, period: Float) -> Ring {
+    let n = ring.len() - 1;
+    let shift = ring[n].x - ring[0].x;
+    let side = |p: &Pt| (p.x / period).floor();
+    let i = (0..n)
+        .find(|&i| side(&ring[i]) != side(&ring[i + 1]))
+        .unwrap_or(0);
+    let (a, b) = (ring[i], ring[i + 1]);
+    let seam = period * side(&a).max(side(&b));
+    let t = if b.x != a.x {
+        (seam - a.x) / (b.x - a.x)
+    } else {
+        0.
+    };
+    let q = Pt {
+        x: seam,
+        y: a.y + (b.y - a.y) * t,
+    };
+    let moved = |p: &Pt| Pt {
+        x: p.x + shift,
+        y: p.y,
+    };
+    let mut path = Vec::with_capacity(n + 2);
+    path.push(q);
+    path.extend_from_slice(&ring[i + 1..=n]);
+    path.extend(ring[1..=i].iter().map(moved));
+    path.push(moved(&q));
+    path.dedup();
+    // The path starts on the left side of the grid if it goes rightwards
+    let start = if shift > 0. { 0. } else { period };
+    path.iter_mut().for_each(|p| p.x += start - seam);
+    // Its ends lie exactly on the sides of the grid
+    let last = path.len() - 1;
+    (path[0].x, path[last].x) = (start, period - start);
+    path
+}
+
+/// Joins the paths going around the whole period of a grid periodic in x (the open
+/// rings at the end of the given rings, see [`unwrap_rings`]) into closed rings:
+/// the inside of the contours lying below the topmost path (and then between
+/// the consecutive paths), each pair of paths encloses a strip of the grid.
+pub(crate) fn join_paths(rings: &mut Vec<Ring>) {
+    let start = rings
+        .iter()
+        .position(|ring| ring.len() > 1 && ring[0] != ring[ring.len() - 1])
+        .unwrap_or(rings.len());
+    if !(rings.len() - start).is_multiple_of(2) {
+        return;
+    }
+    let leftwards = |path: &Ring| path[0].x > path[path.len() - 1].x;
+    let paths = rings.split_off(start);
+    for pair in paths.chunks(2) {
+        let (top, bottom) = (&pair[0], &pair[1]);
+        if !leftwards(top) || leftwards(bottom) {
+            // Not the boundaries of a strip: leaves the paths open
+            rings.extend_from_slice(pair);
+            continue;
+        }
+        let mut ring = bottom.clone();
+        ring.extend_from_slice(top);
+        ring.push(bottom[0]);
+        rings.push(ring);
+    }
+}
+
+/// Moves the given (unwrapped) rings of a grid periodic in x back within the grid where
+/// needed for them to nest, cutting them where they cross its sides (see [`fold`]):
+/// the strips going around the whole period (joined from the paths of the rings), and
+/// the closed rings crossing the seam which lie inside a ring within the grid (or which
+/// enclose such a ring past the seam). The other rings crossing the seam are left
+/// continuous. The parts of the rings and the paths are left `open` for the isolines.
+pub(crate) fn cut_at_seam(rings: &mut Vec<Ring>, period: Float, open: bool) {
+    let closed = |ring: &Ring| ring.len() > 1 && ring[0] == ring[ring.len() - 1];
+    let outside = |ring: &Ring| ring.iter().any(|p| p.x < 0. || p.x > period);
+    if !rings.iter().any(outside) {
+        return;
+    }
+    let start = rings.len() - rings.iter().rev().take_while(|r| !closed(r)).count();
+    let paths = rings.split_off(start);
+    // The strips are folded from their paths, their sides along the seam not being edges
+    let strips = if paths.len().is_multiple_of(2) {
+        (paths.chunks(2))
+            .flat_map(|pair| {
+                let mut strip = pair.to_vec();
+                join_paths(&mut strip);
+                match strip.as_slice() {
+                    [ring] => fold_strip(pair, area(ring) > 0., period),
+                    _ => strip,
+                }
+            })
+            .collect()
+    } else {
+        paths.clone()
+    };
+    // The rings to cut, and the closed rings within the grid (as in the contours)
+    let mut cut = (rings.iter())
+        .map(|ring| closed(ring) && outside(ring) && is_strip(ring, period))
+        .collect::<Vec<_>>();
+    let mut within = (rings.iter().zip(&cut))
+        .filter(|(ring, cut)| closed(ring) && (**cut || !outside(ring)))
+        .flat_map(|(ring, _)| fold(ring, period, false))
+        .chain(strips.iter().filter(|strip| closed(strip)).cloned())
+        .collect::<Vec<_>>();
+    loop {
+        let mut changed = false;
+        for (ring, cut) in rings.iter().zip(cut.iter_mut()) {
+            if !*cut && closed(ring) && outside(ring) && encloses(ring, &within, period) {
+                *cut = true;
+                within.extend(fold(ring, period, false));
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    let mut result = Vec::with_capacity(rings.len() + strips.len());
+    for (ring, cut) in rings.drain(..).zip(cut) {
+        if cut {
+            result.extend(fold(&ring, period, open));
+        } else {
+            result.push(ring);
+        }
+    }
+    if open {
+        for path in paths {
+            if outside(&path) {
+                result.extend(fold(&path, period, true));
+            } else {
+                result.push(path);
+            }
+        }
+    } else {
+        result.extend(strips);
+    }
+    *rings = result;
+}
+
+/// Returns whether the given ring goes around the whole period (from one side of the grid
+/// to the other, see [`join_paths`]).
+fn is_strip(ring: &Ring, period: Float) -> bool {
+    ring.iter().any(|p| p.x <= 0.) && ring.iter().any(|p| p.x >= period)
+}
+
+/// Returns whether the given closed ring crossing the seam has to be cut to nest with
+/// the given closed rings `within` the grid: whether it lies inside one of them (once
+/// moved back within the grid), or one of them lies inside its part past the seam.
+fn encloses(ring: &Ring, within: &[Ring], period: Float) -> bool {
+    let moved = |offset: Float| {
+        ring.iter()
+            .map(|p| Pt {
+                x: p.x + offset,
+                y: p.y,
+            })
+            .collect::<Vec<_>>()
+    };
+    let wrapped = (ring.iter())
+        .map(|p| Pt {
+            x: p.x - period * cell(p, period),
+            y: p.y,
+        })
+        .collect::<Vec<_>>();
+    let (left, right) = (moved(-period), moved(period));
+    within.iter().any(|other| {
+        contains(other, &wrapped) == 1
+            || contains(&left, other) == 1
+            || contains(&right, other) == 1
+    })
+}
+
+/// Returns the index of the period where the given point lies, relative to the grid
+/// (the sides of the grid being part of it).
+fn cell(p: &Pt, period: Float) -> Float {
+    if p.x < 0. {
+        (p.x / period).floor()
+    } else if p.x > period {
+        (p.x / period).ceil() - 1.
+    } else {
+        0.
+    }
+}
+
+/// Folds the given (unwrapped) ring or path of a grid periodic in x into the grid:
+/// it is cut into runs where it crosses the sides of the grid (`x = 0` and `x = period`),
+/// those past them being moved back by the period. The runs of a closed ring are closed
+/// along the sides unless `open`, each run leaving the grid on a side being followed
+/// by the one entering it at the other end of the part of the side inside the ring.
+fn fold(ring: &Ring, period: Float, open: bool) -> Vec<Ring> {
+    let n = ring.len();
+    let closed = n > 1 && ring[0] == ring[n - 1];
+    let m = if closed { n - 1 } else { n };
+    let side = |i: usize| cell(&ring[i % m], period);
+    let moved = |p: &Pt, k: Float| Pt {
+        x: p.x - period * k,
+        y: p.y,
+    };
+    let first = if closed {
+        match (0..m).find(|&i| side(i) != side(i + 1)) {
+            Some(i) => i + 1,
+            None => return vec![ring.iter().map(|p| moved(p, side(0))).collect()],
+        }
+    } else {
+        0
+    };
+    // The crossing of the segment [a, b] with the side between their periods, in the
+    // coordinates of the period of `a`
+    let cut = |a: &Pt, b: &Pt| {
+        let (ka, kb) = (cell(a, period), cell(b, period));
+        let x = period * ka.max(kb);
+        Pt {
+            x: period * (ka.max(kb) - ka),
+            y: a.y + (b.y - a.y) * (x - a.x) / (b.x - a.x),
+        }
+    };
+    let mut runs: Vec<Ring> = Vec::new();
+    let mut cells = Vec::new();
+    let mut run = Vec::new();
+    if closed {
+        run.push(cut(&ring[first % m], &ring[first - 1]));
+    }
+    for k in 0..if closed { m } else { n } {
+        let i = first + k;
+        let (a, ka) = (&ring[i % m], side(i));
+        run.push(moved(a, ka));
+        if (closed || k + 1 < n) && side(i + 1) != ka {
+            let b = &ring[(i + 1) % m];
+            run.push(cut(a, b));
+            runs.push(std::mem::take(&mut run));
+            cells.push(ka);
+            run.push(cut(b, a));
+        }
+    }
+    if !closed {
+        runs.push(run);
+        cells.push(side(n - 1));
+    }
+    runs.iter_mut().for_each(|run| run.dedup());
+    if open || !closed {
+        // Without the runs reduced to a point (where the ring touches a side)
+        runs.retain(|run| run.len() > 1);
+        return runs;
+    }
+    close_runs(&runs, &cells, area(ring) > 0., period)
+}
+
+/// Folds the given paths bounding a strip going around the whole period (see
+/// [`join_paths`]) into the grid, as [`fold`] does with a closed ring (given whether
+/// the strip is `positive`ly oriented): the sides of the grid being the same line,
+/// the runs are closed along them whatever the period they come from.
+fn fold_strip(paths: &[Ring], positive: bool, period: Float) -> Vec<Ring> {
+    let runs = (paths.iter())
+        .flat_map(|path| fold(path, period, true))
+        .collect::<Vec<_>>();
+    close_runs(&runs, &vec![0.; runs.len()], positive, period)
+}
+
+/// Closes the given runs of a ring (given whether it is `positive`ly oriented) folded
+/// into the grid, coming from the given periods, along the sides of the grid: each run
+/// leaving the grid on a side is followed by the one of the same period entering it
+/// at the other end of the part of the side inside the ring.
+fn close_runs(runs: &[Ring], cells: &[Float], positive: bool, period: Float) -> Vec<Ring> {
+    let end = |&(r, last): &(usize, bool)| {
+        let run = &runs[r];
+        if last {
+            run[run.len() - 1]
+        } else {
+            run[0]
+        }
+    };
+    let mut next = vec![None; runs.len()];
+    let mut periods = cells.to_vec();
+    periods.sort_by(Float::total_cmp);
+    periods.dedup();
+    for (c, x) in periods.iter().flat_map(|&c| [(c, 0.), (c, period)]) {
+        let mut ends = (0..runs.len())
+            .filter(|&r| cells[r] == c)
+            .flat_map(|r| [(r, false), (r, true)])
+            .filter(|e| end(e).x == x)
+            .collect::<Vec<_>>();
+        ends.sort_by(|a, b| end(a).y.total_cmp(&end(b).y));
+        // The inside of a positive ring (see [`area`]) is on the right of the runs,
+        // so above their ends on the left side of the grid and below them on its right
+        let above = (x == 0.) == positive;
+        for (i, &(r, last)) in ends.iter().enumerate() {
+            let other = if above { Some(i + 1) } else { i.checked_sub(1) };
+            if let (true, Some(&(following, false))) = (last, other.and_then(|j| ends.get(j))) {
+                next[r] = Some(following);
+            }
+        }
+    }
+    let mut parts = Vec::new();
+    let mut done = vec![false; runs.len()];
+    for r in 0..runs.len() {
+        let mut part: Ring = Vec::new();
+        let mut k = r;
+        while !done[k] {
+            done[k] = true;
+            part.extend_from_slice(&runs[k]);
+            match next[k] {
+                Some(following) => k = following,
+                None => break,
+            }
+        }
+        part.dedup();
+        if part.first() != part.last() {
+            part.push(part[0]);
+        }
+        // Without the parts reduced to a point or a segment
+        if part.len() > 3 {
+            parts.push(part);
+        }
+    }
+    parts
+}
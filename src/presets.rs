@@ -0,0 +1,81 @@
+use crate::{ContourBuilder, Float};
+use geo_types::Rect;
+
+/// How the samples of a grid relate to its extent
+/// (see [`ContourBuilder::for_geographic`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Registration {
+    /// Each sample is the value of a cell, the extent covering the cells
+    /// (the samples lying at the center of the cells, half a step inside the extent),
+    /// as in most rasters (e.g. GeoTIFF's `PixelIsArea`).
+    #[default]
+    Cell,
+    /// The samples lie on the nodes of the grid, the first and last ones lying
+    /// on the edges of the extent, as in many model outputs and DEMs
+    /// (e.g. GeoTIFF's `PixelIsPoint`, or the grid-registered grids of GMT).
+    Point,
+}
+
+impl ContourBuilder {
+    /// Constructs a smoothed contours generator for a north-up grid of `dims` (columns, rows)
+    /// samples covering the given `extent`, its first row being the northernmost one
+    /// (the y step being negative), with the given `registration` of the samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `extent` - The extent of the grid (in the coordinates of the output geometries).
+    /// * `dims` - The number of columns and rows in the grid.
+    /// * `registration` - Whether the extent covers the cells of the samples, or runs through
+    ///   the first and last samples.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use contour::{ContourBuilder, Registration};
+    /// use geo_types::{coord, Rect};
+    ///
+    /// // A 1° global grid, whose first sample is the value of the cell [-180, -179] * [89, 90]
+    /// let extent = Rect::new(coord! { x: -180., y: -90. }, coord! { x: 180., y: 90. });
+    /// let builder = ContourBuilder::for_geographic(extent, (360, 180), Registration::Cell);
+    /// assert_eq!(builder.origin(), (-180., 90.));
+    /// assert_eq!(builder.step(), (1., -1.));
+    /// ```
+    pub fn for_geographic(
+        extent: Rect<Float>,
+        dims: (usize, usize),
+        registration: Registration,
+    ) -> Self {
+        let (dx, dy) = dims;
+        let (min, max) = (extent.min(), extent.max());
+        // The number of steps between the edges of the extent
+        let steps = |n: usize| match registration {
+            Registration::Cell => n.max(1) as Float,
+            Registration::Point => n.saturating_sub(1).max(1) as Float,
+        };
+        let x_step = (max.x - min.x) / steps(dx);
+        let y_step = (min.y - max.y) / steps(dy);
+        let (x_origin, y_origin) = match registration {
+            Registration::Cell => (min.x, max.y),
+            Registration::Point => (min.x - x_step / 2., max.y - y_step / 2.),
+        };
+        ContourBuilder::new(dx, dy, true)
+            .x_origin(x_origin)
+            .y_origin(y_origin)
+            .x_step(x_step)
+            .y_step(y_step)
+    }
+
+    /// Constructs a smoothed contours generator for an image of `width` * `height` pixels,
+    /// in pixel coordinates: the pixel of column `i` and row `j` covers the square
+    /// `[i, i + 1] * [j, j + 1]` (its center lying at `(i + 0.5, j + 0.5)`), the y axis
+    /// pointing down, as when drawing the geometries over the image (e.g. in SVG or on
+    /// a canvas).
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The number of columns in the image.
+    /// * `height` - The number of rows in the image.
+    pub fn for_image(width: usize, height: usize) -> Self {
+        ContourBuilder::new(width, height, true)
+    }
+}
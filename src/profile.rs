@@ -0,0 +1,77 @@
+use crate::{ContourSet, Float, Line, Pt};
+use geo_types::LineString;
+
+/// A crossing between a user-supplied polyline and an isoline (see [`crossings`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crossing {
+    /// The intersection point.
+    pub point: Pt,
+    /// The threshold of the crossed isoline.
+    pub threshold: Float,
+    /// The distance from the start of the polyline to the intersection point,
+    /// measured along the polyline.
+    pub distance: Float,
+}
+
+/// Computes the crossings between the given `polyline` (e.g. a route or a cross-section)
+/// and the given isolines, ordered by their distance along the polyline.
+///
+/// This is the usual way to build an elevation (or concentration) profile from isolines.
+pub fn crossings(polyline: &LineString<Float>, lines: &[Line]) -> Vec<Crossing> {
+    let mut result = Vec::new();
+    let mut start_distance = 0.;
+    for segment in polyline.lines() {
+        let (a, b) = (segment.start, segment.end);
+        let length = ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt();
+        for line in lines {
+            for ls in line.geometry().0.iter() {
+                for other in ls.lines() {
+                    if let Some(t) = intersection(a, b, other.start, other.end) {
+                        result.push(Crossing {
+                            point: Pt {
+                                x: a.x + t * (b.x - a.x),
+                                y: a.y + t * (b.y - a.y),
+                            },
+                            threshold: line.threshold(),
+                            distance: start_distance + t * length,
+                        });
+                    }
+                }
+            }
+        }
+        start_distance += length;
+    }
+    result.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    // An isoline passing exactly through a vertex of the polyline
+    // (or of the isoline itself) is reported once
+    result.dedup_by(|a, b| {
+        a.threshold == b.threshold && (a.distance - b.distance).abs() < Float::EPSILON
+    });
+    result
+}
+
+/// Returns the parameter `t` along `[a, b]` of the intersection between the segments `[a, b]`
+/// and `[c, d]` (if any).
+fn intersection(a: Pt, b: Pt, c: Pt, d: Pt) -> Option<Float> {
+    let r = (b.x - a.x, b.y - a.y);
+    let s = (d.x - c.x, d.y - c.y);
+    let denom = r.0 * s.1 - r.1 * s.0;
+    if denom.abs() < Float::EPSILON {
+        return None;
+    }
+    let t = ((c.x - a.x) * s.1 - (c.y - a.y) * s.0) / denom;
+    let u = ((c.x - a.x) * r.1 - (c.y - a.y) * r.0) / denom;
+    if (0. ..=1.).contains(&t) && (0. ..=1.).contains(&u) {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+impl ContourSet {
+    /// Computes the crossings between the given `polyline` and the lines of this set,
+    /// ordered by their distance along the polyline (see [`crossings`]).
+    pub fn crossings(&self, polyline: &LineString<Float>) -> Vec<Crossing> {
+        crossings(polyline, &self.lines)
+    }
+}
@@ -0,0 +1,102 @@
+use crate::error::{new_error, ErrorKind, Result};
+use crate::{Band, ColorRamp, Float};
+use geo_types::LineString;
+use std::path::Path;
+use tiny_skia::{Color, FillRule, Paint, PathBuilder, Pixmap, Stroke, Transform as SkiaTransform};
+
+/// Renders the given `bands` to a PNG image of `width` * `height` pixels written to `path`.
+///
+/// Bands are filled using the [`ColorRamp::Viridis`] color ramp and their boundaries
+/// (i.e. the isolines) are stroked in black. The extent of the bands is fitted
+/// to the image, with the y axis pointing downward (as in grid coordinates).
+///
+/// This is mainly intended for debugging and quick-look products.
+// The casts to f32 are unnecessary when using the "f32" feature.
+#[allow(clippy::unnecessary_cast)]
+pub fn render_png<P: AsRef<Path>>(path: P, bands: &[Band], width: u32, height: u32) -> Result<()> {
+    let mut pixmap = Pixmap::new(width, height)
+        .ok_or_else(|| new_error(ErrorKind::RenderError("Invalid image size".to_string())))?;
+    pixmap.fill(Color::WHITE);
+
+    let (mut x_min, mut y_min) = (Float::INFINITY, Float::INFINITY);
+    let (mut x_max, mut y_max) = (Float::NEG_INFINITY, Float::NEG_INFINITY);
+    bands
+        .iter()
+        .flat_map(|band| band.geometry().0.iter())
+        .flat_map(|polygon| polygon.exterior().0.iter())
+        .for_each(|pt| {
+            x_min = x_min.min(pt.x);
+            y_min = y_min.min(pt.y);
+            x_max = x_max.max(pt.x);
+            y_max = y_max.max(pt.y);
+        });
+    if x_min > x_max || y_min > y_max {
+        return save(&pixmap, path.as_ref());
+    }
+    let scale = ((width as Float) / (x_max - x_min).max(Float::EPSILON))
+        .min((height as Float) / (y_max - y_min).max(Float::EPSILON));
+    let project = |x: Float, y: Float| (((x - x_min) * scale) as f32, ((y - y_min) * scale) as f32);
+
+    let colors = ColorRamp::Viridis.band_colors(bands);
+    let mut outline = Paint::default();
+    outline.set_color(Color::BLACK);
+    outline.anti_alias = true;
+    let stroke = Stroke {
+        width: 1.,
+        ..Stroke::default()
+    };
+
+    for (band, color) in bands.iter().zip(colors) {
+        let mut pb = PathBuilder::new();
+        band.geometry().0.iter().for_each(|polygon| {
+            push_ring(&mut pb, polygon.exterior(), &project);
+            polygon
+                .interiors()
+                .iter()
+                .for_each(|ring| push_ring(&mut pb, ring, &project));
+        });
+        if let Some(skia_path) = pb.finish() {
+            let mut fill = Paint::default();
+            fill.set_color_rgba8(color.r, color.g, color.b, color.a);
+            fill.anti_alias = true;
+            pixmap.fill_path(
+                &skia_path,
+                &fill,
+                FillRule::EvenOdd,
+                SkiaTransform::identity(),
+                None,
+            );
+            pixmap.stroke_path(
+                &skia_path,
+                &outline,
+                &stroke,
+                SkiaTransform::identity(),
+                None,
+            );
+        }
+    }
+    save(&pixmap, path.as_ref())
+}
+
+fn push_ring(
+    pb: &mut PathBuilder,
+    ring: &LineString<Float>,
+    project: &impl Fn(Float, Float) -> (f32, f32),
+) {
+    let mut points = ring.0.iter();
+    if let Some(first) = points.next() {
+        let (x, y) = project(first.x, first.y);
+        pb.move_to(x, y);
+        points.for_each(|pt| {
+            let (x, y) = project(pt.x, pt.y);
+            pb.line_to(x, y);
+        });
+        pb.close();
+    }
+}
+
+fn save(pixmap: &Pixmap, path: &Path) -> Result<()> {
+    pixmap
+        .save_png(path)
+        .map_err(|e| new_error(ErrorKind::RenderError(e.to_string())))
+}
@@ -0,0 +1,57 @@
+use crate::{Float, Pt, Ring};
+use geo_types::MultiPolygon;
+
+/// Returns the vertices placed at equal arc-length intervals along the given line
+/// (or closed ring), as described in [`Line::resample_uniform`](crate::Line::resample_uniform).
+///
+/// The line is returned unchanged if `spacing` isn't strictly positive and finite,
+/// or if its length is zero.
+pub(crate) fn resample(line: &[Pt], spacing: Float) -> Ring {
+    let lengths = line
+        .windows(2)
+        .map(|w| (w[1].x - w[0].x).hypot(w[1].y - w[0].y))
+        .collect::<Vec<_>>();
+    let total = lengths.iter().sum::<Float>();
+    if !(spacing > 0. && spacing.is_finite() && total > 0. && total.is_finite()) {
+        return line.to_vec();
+    }
+    let closed = line.len() > 3 && line[0] == line[line.len() - 1];
+    let min = if closed { 3 } else { 1 };
+    let n = ((total / spacing).round() as usize).max(min);
+    let step = total / n as Float;
+    let mut result = Vec::with_capacity(n + 1);
+    result.push(line[0]);
+    // The segment being followed, and the distance along the line at its start
+    let (mut k, mut start) = (0, 0.);
+    for i in 1..n {
+        let at = i as Float * step;
+        while k < lengths.len() - 1 && start + lengths[k] < at {
+            start += lengths[k];
+            k += 1;
+        }
+        let t = if lengths[k] > 0. {
+            ((at - start) / lengths[k]).clamp(0., 1.)
+        } else {
+            0.
+        };
+        let (a, b) = (line[k], line[k + 1]);
+        result.push(Pt {
+            x: a.x + t * (b.x - a.x),
+            y: a.y + t * (b.y - a.y),
+        });
+    }
+    result.push(line[line.len() - 1]);
+    result
+}
+
+/// Resamples every ring of the given `multipolygon` (see [`resample`]).
+pub(crate) fn resample_polygons(multipolygon: &mut MultiPolygon<Float>, spacing: Float) {
+    multipolygon.0.iter_mut().for_each(|polygon| {
+        polygon.exterior_mut(|ring| ring.0 = resample(&ring.0, spacing));
+        polygon.interiors_mut(|rings| {
+            rings
+                .iter_mut()
+                .for_each(|ring| ring.0 = resample(&ring.0, spacing))
+        });
+    });
+}
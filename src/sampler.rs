@@ -0,0 +1,85 @@
+use crate::error::Result;
+use crate::{ContourBuilder, Float};
+
+/// Looks up the (bilinearly interpolated) values of a grid at arbitrary coordinates,
+/// using the geometry of the [`ContourBuilder`] it was built from (see
+/// [`ContourBuilder::sampler`]), e.g. to display the value under the cursor
+/// consistently with the computed contours.
+///
+/// # Example
+///
+/// ```
+/// use contour::ContourBuilder;
+///
+/// let builder = ContourBuilder::new(2, 2, false).x_origin(10.).x_step(2.);
+/// let sampler = builder.sampler(&[0., 1., 2., 3.]).unwrap();
+/// // The samples lie at the center of the cells
+/// assert_eq!(sampler.sample(11., 0.5), Some(0.));
+/// assert_eq!(sampler.sample(12., 1.), Some(1.5));
+/// assert_eq!(sampler.sample(20., 1.), None);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridSampler {
+    values: Vec<Float>,
+    dx: usize,
+    dy: usize,
+    origin: (Float, Float),
+    step: (Float, Float),
+}
+
+impl GridSampler {
+    /// Returns the value at the given point (expressed in the coordinates of the output
+    /// geometries), interpolated bilinearly between the four nearest samples (the samples
+    /// lying at the center of the cells of the grid, the values are extended to the edges
+    /// of the grid from its outermost samples), or `None` if the point is outside the grid.
+    ///
+    /// The isolines computed with smoothing enabled pass through the points
+    /// where the interpolated value equals their threshold along the cell edges.
+    pub fn sample(&self, x: Float, y: Float) -> Option<Float> {
+        let (x, y) = (
+            (x - self.origin.0) / self.step.0,
+            (y - self.origin.1) / self.step.1,
+        );
+        let (w, h) = (self.dx as Float, self.dy as Float);
+        if !(0. ..=w).contains(&x) || !(0. ..=h).contains(&y) {
+            return None;
+        }
+        let (x, y) = ((x - 0.5).clamp(0., w - 1.), (y - 0.5).clamp(0., h - 1.));
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(self.dx - 1), (y0 + 1).min(self.dy - 1));
+        let (tx, ty) = (x - x0 as Float, y - y0 as Float);
+        let at = |x: usize, y: usize| self.values[y * self.dx + x];
+        let lerp = |a: Float, b: Float, t: Float| if t == 0. { a } else { a + (b - a) * t };
+        Some(lerp(
+            lerp(at(x0, y0), at(x1, y0), tx),
+            lerp(at(x0, y1), at(x1, y1), tx),
+            ty,
+        ))
+    }
+
+    /// Returns the value of the sample at the given column and row, if it exists.
+    pub fn value(&self, x: usize, y: usize) -> Option<Float> {
+        (x < self.dx && y < self.dy).then(|| self.values[y * self.dx + x])
+    }
+}
+
+impl ContourBuilder {
+    /// Builds a [`GridSampler`] looking up the given `values` (combined with the
+    /// weights of this builder, if any) at arbitrary coordinates, using the dimensions,
+    /// origin and step of this builder (but ignoring any custom
+    /// [`transform`](ContourBuilder::transform), which can't be inverted).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    pub fn sampler(&self, values: &[Float]) -> Result<GridSampler> {
+        let grid = self.grid(values)?;
+        Ok(GridSampler {
+            values: (0..grid.len()).map(|i| grid.get(i)).collect(),
+            dx: self.dx,
+            dy: self.dy,
+            origin: (self.x_origin, self.y_origin),
+            step: (self.x_step, self.y_step),
+        })
+    }
+}
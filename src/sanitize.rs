@@ -0,0 +1,151 @@
+use crate::error::{new_error, ErrorKind, Result};
+use crate::Float;
+use std::fmt;
+
+/// The reason why a value of the grid is invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidKind {
+    /// The value is `NaN`.
+    NaN,
+    /// The value is infinite.
+    Infinite,
+    /// The value is equal to the nodata value.
+    NoData,
+}
+
+/// An invalid value found in the grid by [`sanitize`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidValue {
+    /// The index of the value in the grid
+    pub index: usize,
+    /// The column of the value in the grid
+    pub x: usize,
+    /// The row of the value in the grid
+    pub y: usize,
+    /// The original value
+    pub value: Float,
+    /// Why the value is invalid
+    pub kind: InvalidKind,
+}
+
+/// What [`sanitize`] does with the invalid values of the grid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SanitizePolicy {
+    /// The values are left untouched (they are only reported).
+    Report,
+    /// The invalid values are replaced by the given value.
+    Fill(Float),
+    /// The infinite values are replaced by the maximum (for `+inf`) or minimum
+    /// (for `-inf`) valid value of the grid, and the `NaN` and nodata values by
+    /// its minimum valid value (or by `0.` if the grid has no valid value).
+    Clamp,
+}
+
+/// The report of the invalid values found in a grid by [`sanitize`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SanitizeReport {
+    /// The invalid values, in the order of the grid
+    pub invalid: Vec<InvalidValue>,
+}
+
+impl SanitizeReport {
+    /// Returns true if no invalid value was found.
+    pub fn is_clean(&self) -> bool {
+        self.invalid.is_empty()
+    }
+
+    /// Returns the number of invalid values of the given `kind`.
+    pub fn count(&self, kind: InvalidKind) -> usize {
+        self.invalid.iter().filter(|v| v.kind == kind).count()
+    }
+}
+
+impl fmt::Display for SanitizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} invalid value(s)", self.invalid.len())?;
+        for (i, v) in self.invalid.iter().enumerate() {
+            let kind = match v.kind {
+                InvalidKind::NaN => "NaN",
+                InvalidKind::Infinite => "infinite value",
+                InvalidKind::NoData => "nodata value",
+            };
+            write!(
+                f,
+                "{} {} at ({}, {})",
+                if i == 0 { ":" } else { "," },
+                kind,
+                v.x,
+                v.y
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Scans the given grid of `values` (with `dx` columns) for `NaN`, infinite and
+/// `nodata` values, fixing them according to the given `policy`, and returns the
+/// report of the invalid values found (e.g. to reject bad inputs with actionable messages).
+///
+/// # Example
+///
+/// ```
+/// use contour::{sanitize, Float, SanitizePolicy};
+///
+/// let mut values = vec![0., -9999., 2., Float::NAN];
+/// let report = sanitize(&mut values, 2, Some(-9999.), SanitizePolicy::Fill(0.)).unwrap();
+/// assert_eq!(report.invalid.len(), 2);
+/// assert_eq!(report.to_string(), "2 invalid value(s): nodata value at (1, 0), NaN at (1, 1)");
+/// assert_eq!(values, vec![0., 0., 2., 0.]);
+/// ```
+pub fn sanitize(
+    values: &mut [Float],
+    dx: usize,
+    nodata: Option<Float>,
+    policy: SanitizePolicy,
+) -> Result<SanitizeReport> {
+    if dx == 0 || !values.len().is_multiple_of(dx) {
+        return Err(new_error(ErrorKind::BadDimension));
+    }
+    let kind = |v: Float| {
+        if v.is_nan() {
+            Some(InvalidKind::NaN)
+        } else if v.is_infinite() {
+            Some(InvalidKind::Infinite)
+        } else if Some(v) == nodata {
+            Some(InvalidKind::NoData)
+        } else {
+            None
+        }
+    };
+    let invalid = values
+        .iter()
+        .enumerate()
+        .filter_map(|(index, &value)| {
+            kind(value).map(|kind| InvalidValue {
+                index,
+                x: index % dx,
+                y: index / dx,
+                value,
+                kind,
+            })
+        })
+        .collect::<Vec<_>>();
+    let replacement = |v: &InvalidValue, min: Float, max: Float| match policy {
+        SanitizePolicy::Fill(fill) => fill,
+        SanitizePolicy::Clamp if v.value == Float::INFINITY => max,
+        _ => min,
+    };
+    if policy != SanitizePolicy::Report && !invalid.is_empty() {
+        let (min, max) = values
+            .iter()
+            .filter(|v| kind(**v).is_none())
+            .fold(None, |acc: Option<(Float, Float)>, &v| {
+                Some(acc.map_or((v, v), |(min, max)| (min.min(v), max.max(v))))
+            })
+            .unwrap_or((0., 0.));
+        invalid
+            .iter()
+            .for_each(|v| values[v.index] = replacement(v, min, max));
+    }
+    Ok(SanitizeReport { invalid })
+}
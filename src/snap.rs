@@ -0,0 +1,68 @@
+use crate::area::for_each_coord_mut;
+use crate::{Band, Contour, Float, Line, Pt};
+use rustc_hash::FxHashMap;
+
+/// Snaps vertices onto the first previously seen vertex lying within a given tolerance,
+/// so that vertices computed independently for different features become identical.
+pub(crate) struct Snapper {
+    tolerance: Float,
+    cells: FxHashMap<(i64, i64), Vec<Pt>>,
+}
+
+impl Snapper {
+    pub(crate) fn new(tolerance: Float) -> Self {
+        Snapper {
+            tolerance,
+            cells: FxHashMap::default(),
+        }
+    }
+
+    fn key(&self, pt: &Pt) -> (i64, i64) {
+        (
+            (pt.x / self.tolerance).floor() as i64,
+            (pt.y / self.tolerance).floor() as i64,
+        )
+    }
+
+    pub(crate) fn snap(&mut self, pt: &mut Pt) {
+        let (kx, ky) = self.key(pt);
+        let tol2 = self.tolerance * self.tolerance;
+        for ox in -1..=1 {
+            for oy in -1..=1 {
+                if let Some(candidate) = self.cells.get(&(kx + ox, ky + oy)).and_then(|pts| {
+                    pts.iter()
+                        .find(|c| (c.x - pt.x).powi(2) + (c.y - pt.y).powi(2) <= tol2)
+                }) {
+                    *pt = *candidate;
+                    return;
+                }
+            }
+        }
+        self.cells.entry((kx, ky)).or_default().push(*pt);
+    }
+
+    pub(crate) fn snap_bands(&mut self, bands: &mut [Band]) {
+        bands.iter_mut().for_each(|band| {
+            for_each_coord_mut(&mut band.geometry, |pt| self.snap(pt));
+            band.update_bbox();
+        });
+    }
+
+    pub(crate) fn snap_contours(&mut self, contours: &mut [Contour]) {
+        contours.iter_mut().for_each(|contour| {
+            for_each_coord_mut(&mut contour.geometry, |pt| self.snap(pt));
+            contour.update_bbox();
+        });
+    }
+
+    pub(crate) fn snap_lines(&mut self, lines: &mut [Line]) {
+        lines.iter_mut().for_each(|line| {
+            line.geometry
+                .0
+                .iter_mut()
+                .flat_map(|ls| ls.0.iter_mut())
+                .for_each(|pt| self.snap(pt));
+            line.update_bbox();
+        });
+    }
+}
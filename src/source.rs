@@ -0,0 +1,260 @@
+use crate::error::Result;
+use crate::{Band, Contour, ContourBuilder, Float, Line};
+
+/// A rectangular grid of values, read one sample at a time, so that grids stored
+/// in other layouts than a flat row-major slice (e.g. rows, strided or memory-mapped
+/// buffers) can be contoured without being copied (see [`ContourBuilder::lines_from_source`](crate::ContourBuilder::lines_from_source),
+/// [`ContourBuilder::contours_from_source`](crate::ContourBuilder::contours_from_source),
+/// [`ContourBuilder::isobands_from_source`](crate::ContourBuilder::isobands_from_source)
+/// and [`contour_rings_from_source`](crate::contour_rings_from_source)).
+///
+/// The sources have to be [`Sync`] as the thresholds may be computed in parallel.
+///
+/// # Example
+///
+/// ```
+/// use contour::{ContourBuilder, Float, GridSource};
+///
+/// // A grid whose values are computed on the fly
+/// struct Cone;
+///
+/// impl GridSource for Cone {
+///     fn width(&self) -> usize {
+///         5
+///     }
+///     fn height(&self) -> usize {
+///         5
+///     }
+///     fn get(&self, x: usize, y: usize) -> Float {
+///         let (dx, dy) = (x as Float - 2., y as Float - 2.);
+///         3. - (dx * dx + dy * dy).sqrt()
+///     }
+/// }
+///
+/// let builder = ContourBuilder::new(5, 5, true);
+/// let contours = builder.contours_from_source(&Cone, &[2.]).unwrap();
+/// assert_eq!(contours.len(), 1);
+/// ```
+pub trait GridSource: Sync {
+    /// Returns the number of columns of the grid.
+    fn width(&self) -> usize;
+
+    /// Returns the number of rows of the grid.
+    fn height(&self) -> usize;
+
+    /// Returns the value of the sample at column `x` and row `y`.
+    fn get(&self, x: usize, y: usize) -> Float;
+}
+
+impl<T: GridSource + ?Sized> GridSource for &T {
+    fn width(&self) -> usize {
+        (**self).width()
+    }
+
+    fn height(&self) -> usize {
+        (**self).height()
+    }
+
+    fn get(&self, x: usize, y: usize) -> Float {
+        (**self).get(x, y)
+    }
+}
+
+/// The rows of the grid (which must all have the same length).
+impl GridSource for [Vec<Float>] {
+    fn width(&self) -> usize {
+        self.first().map_or(0, Vec::len)
+    }
+
+    fn height(&self) -> usize {
+        self.len()
+    }
+
+    fn get(&self, x: usize, y: usize) -> Float {
+        self[y][x]
+    }
+}
+
+/// The rows of the grid (which must all have the same length).
+impl GridSource for Vec<Vec<Float>> {
+    fn width(&self) -> usize {
+        self.as_slice().width()
+    }
+
+    fn height(&self) -> usize {
+        self.len()
+    }
+
+    fn get(&self, x: usize, y: usize) -> Float {
+        self[y][x]
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl GridSource for ndarray::ArrayView2<'_, Float> {
+    fn width(&self) -> usize {
+        self.ncols()
+    }
+
+    fn height(&self) -> usize {
+        self.nrows()
+    }
+
+    fn get(&self, x: usize, y: usize) -> Float {
+        self[[y, x]]
+    }
+}
+
+/// The pixels of a grayscale image, read row by row from the top of the image.
+#[cfg(feature = "image")]
+impl<T, C> GridSource for image::ImageBuffer<image::Luma<T>, C>
+where
+    T: image::Primitive + Into<Float> + Sync,
+    C: std::ops::Deref<Target = [T]> + Sync,
+{
+    fn width(&self) -> usize {
+        self.width() as usize
+    }
+
+    fn height(&self) -> usize {
+        self.height() as usize
+    }
+
+    fn get(&self, x: usize, y: usize) -> Float {
+        self.get_pixel(x as u32, y as u32).0[0].into()
+    }
+}
+
+/// A grid of values stored in a slice with arbitrary strides between its rows
+/// and its columns (e.g. a channel of an interleaved image, a column-major array,
+/// or a window of a larger grid).
+///
+/// # Example
+///
+/// ```
+/// use contour::{GridSource, StridedGrid};
+///
+/// // The second channel of an interleaved 2x2 grid
+/// let values = [0., 10., 1., 11., 2., 12., 3., 13.];
+/// let grid = StridedGrid::new(&values[1..], 2, 2).strides(4, 2);
+/// assert_eq!(grid.get(1, 1), 13.);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StridedGrid<'a> {
+    values: &'a [Float],
+    width: usize,
+    height: usize,
+    row_stride: usize,
+    col_stride: usize,
+}
+
+impl<'a> StridedGrid<'a> {
+    /// Constructs a new grid of `width` * `height` values stored row by row
+    /// in `values` (use [`strides`](Self::strides) to change their layout).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` holds less than `width` * `height` values.
+    pub fn new(values: &'a [Float], width: usize, height: usize) -> Self {
+        StridedGrid {
+            values,
+            width,
+            height,
+            row_stride: width,
+            col_stride: 1,
+        }
+        .strides(width, 1)
+    }
+
+    /// Sets the distances (in number of values) between two consecutive rows
+    /// and between two consecutive columns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the last value of the grid lies outside of the slice.
+    pub fn strides(mut self, row_stride: usize, col_stride: usize) -> Self {
+        if self.width > 0 && self.height > 0 {
+            let last = (self.height - 1) * row_stride + (self.width - 1) * col_stride;
+            assert!(last < self.values.len(), "the grid exceeds the slice");
+        }
+        self.row_stride = row_stride;
+        self.col_stride = col_stride;
+        self
+    }
+}
+
+impl GridSource for StridedGrid<'_> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn get(&self, x: usize, y: usize) -> Float {
+        self.values[y * self.row_stride + x * self.col_stride]
+    }
+}
+
+impl ContourBuilder {
+    /// Computes isolines (see [`ContourBuilder::lines`]) according to the values of the
+    /// given `source`, read in place (the grid being only copied if padding is enabled).
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The grid of values to be used (with the dimensions of the builder).
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn lines_from_source(
+        &self,
+        source: &impl GridSource,
+        thresholds: &[Float],
+    ) -> Result<Vec<Line>> {
+        let grid = self.source_grid(source)?;
+        if self.padding.is_some() {
+            return self.row_major().lines(&grid.to_vec(), thresholds);
+        }
+        self.lines_in(grid, thresholds).map(|(lines, _)| lines)
+    }
+
+    /// Computes contours (see [`ContourBuilder::contours`]) according to the values of the
+    /// given `source`, read in place (the grid being only copied if padding is enabled).
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The grid of values to be used (with the dimensions of the builder).
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn contours_from_source(
+        &self,
+        source: &impl GridSource,
+        thresholds: &[Float],
+    ) -> Result<Vec<Contour>> {
+        let grid = self.source_grid(source)?;
+        if self.padding.is_some() {
+            return self.row_major().contours(&grid.to_vec(), thresholds);
+        }
+        self.contours_in(&mut Vec::new(), grid, thresholds)
+            .map(|(contours, _)| contours)
+    }
+
+    /// Computes isobands (see [`ContourBuilder::isobands`]) according to the values of the
+    /// given `source`, read in place (the grid being only copied if padding is enabled).
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The grid of values to be used (with the dimensions of the builder).
+    /// * `thresholds` - The slice of thresholds values to be used
+    ///   (have to be equal to or greater than 2).
+    pub fn isobands_from_source(
+        &self,
+        source: &impl GridSource,
+        thresholds: &[Float],
+    ) -> Result<Vec<Band>> {
+        let grid = self.source_grid(source)?;
+        if self.padding.is_some() {
+            return self.row_major().isobands(&grid.to_vec(), thresholds);
+        }
+        self.isobands_in(&mut Vec::new(), grid, thresholds)
+            .map(|(bands, _)| bands)
+    }
+}
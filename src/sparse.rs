@@ -0,0 +1,206 @@
+use crate::diagnostics::ThresholdDiagnostics;
+use crate::error::{new_error, ErrorKind, Result};
+use crate::isoringbuilder::{link_fragments, IsoRingBuilder};
+use crate::weights::Grid;
+use crate::{Contour, ContourBuilder, Float, GridSource, InfinityPolicy, Ring};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// The width (in cells) of the square blocks of cells in which a sparse grid is traversed.
+const BLOCK: usize = 32;
+
+/// A grid whose samples mostly share a background value, only the other samples
+/// being stored (see [`ContourBuilder::contours_sparse`]).
+///
+/// It is a [`GridSource`], so that it can also be passed to the other methods
+/// of the builder reading the values in place.
+///
+/// # Example
+///
+/// ```
+/// use contour::{ContourBuilder, SparseGrid};
+///
+/// let grid = SparseGrid::from_triplets(1000, 1000, 0., [(10, 20, 1.), (11, 20, 1.)]).unwrap();
+/// let contours = ContourBuilder::new(1000, 1000, true)
+///     .contours_sparse(&grid, &[0.5])
+///     .unwrap();
+/// assert_eq!(contours[0].geometry().0.len(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseGrid {
+    width: usize,
+    height: usize,
+    background: Float,
+    /// The values of the stored samples, by index (in row-major order)
+    values: FxHashMap<usize, Float>,
+}
+
+impl SparseGrid {
+    /// Constructs a grid of `width` * `height` samples, all equal to `background`.
+    pub fn new(width: usize, height: usize, background: Float) -> Self {
+        SparseGrid {
+            width,
+            height,
+            background,
+            values: FxHashMap::default(),
+        }
+    }
+
+    /// Constructs a grid of `width` * `height` samples from the (column, row, value)
+    /// triplets of the samples differing from `background` (the last value
+    /// of a sample given several times being kept).
+    ///
+    /// Returns a [`BadDimension`](crate::ErrorKind::BadDimension) error if a sample
+    /// lies outside of the grid.
+    pub fn from_triplets(
+        width: usize,
+        height: usize,
+        background: Float,
+        triplets: impl IntoIterator<Item = (usize, usize, Float)>,
+    ) -> Result<Self> {
+        let mut grid = SparseGrid::new(width, height, background);
+        for (x, y, value) in triplets {
+            grid.insert(x, y, value)?;
+        }
+        Ok(grid)
+    }
+
+    /// Sets the value of the sample at column `x` and row `y`.
+    ///
+    /// Returns a [`BadDimension`](crate::ErrorKind::BadDimension) error if the sample
+    /// lies outside of the grid.
+    pub fn insert(&mut self, x: usize, y: usize, value: Float) -> Result<()> {
+        if x >= self.width || y >= self.height {
+            return Err(new_error(ErrorKind::BadDimension));
+        }
+        self.values.insert(y * self.width + x, value);
+        Ok(())
+    }
+
+    /// Returns the value of the samples which aren't stored.
+    pub fn background(&self) -> Float {
+        self.background
+    }
+
+    /// Returns the number of stored samples.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if every sample has the background value.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl GridSource for SparseGrid {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn get(&self, x: usize, y: usize) -> Float {
+        self.values
+            .get(&(y * self.width + x))
+            .copied()
+            .unwrap_or(self.background)
+    }
+}
+
+impl ContourBuilder {
+    /// Computes contours (see [`ContourBuilder::contours`]) according to the values
+    /// of the given sparse `grid`, only traversing the blocks of cells around the stored
+    /// samples which are on the other side of each threshold than the background
+    /// (and the edges of the grid, if the background is inside the contours),
+    /// so that the cost depends on the number of stored samples rather than
+    /// on the dimensions of the grid.
+    ///
+    /// The whole grid is traversed if weights or [`padding`](Self::padding) are set.
+    ///
+    /// # Arguments
+    ///
+    /// * `grid` - The sparse grid of values to be used (with the dimensions of the builder).
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn contours_sparse(&self, grid: &SparseGrid, thresholds: &[Float]) -> Result<Vec<Contour>> {
+        if self.weights.is_some() || self.padding.is_some() {
+            return self.contours_from_source(grid, thresholds);
+        }
+        let view = self.source_grid(grid)?;
+        let distinct = self.duplicates.distinct(thresholds)?;
+        let thresholds = &distinct.values;
+        let contours = self.map_thresholds(thresholds.len(), |isoring, i| {
+            let threshold = self.ties.apply(thresholds[i]);
+            let mut rings = self.sparse_rings(isoring, grid, view, threshold)?;
+            self.finalize_rings(&mut rings, view, threshold);
+            let mut diagnostics = ThresholdDiagnostics::new(thresholds[i], i);
+            diagnostics.rings = rings.len();
+            Ok(self.contour(rings, thresholds, i, &mut diagnostics))
+        })?;
+        let contours = distinct.expand(contours, |c, i| c.threshold_index = i);
+        Ok(self.without_empty(contours, |c| c.geometry.0.is_empty()))
+    }
+
+    /// Computes the rings (in grid coordinates) of the given sparse `grid` (read through
+    /// its `view`) for the given `threshold`, traversing the blocks of cells whose
+    /// corners may be on both sides of the threshold.
+    fn sparse_rings(
+        &self,
+        isoring: &mut IsoRingBuilder,
+        grid: &SparseGrid,
+        view: Grid<'_>,
+        threshold: Float,
+    ) -> Result<Vec<Ring>> {
+        let (dx, dy) = (self.dx, self.dy);
+        let background = [grid.background];
+        let background = Grid::new(
+            &background,
+            None,
+            self.infinity == InfinityPolicy::Outside,
+            self.nodata,
+        );
+        let outside = background.get(0) < threshold;
+        // The cells -1..dx are split into blocks, the cell x lying in the block (x + 1) / BLOCK
+        let mut blocks = FxHashSet::default();
+        for &k in grid.values.keys() {
+            if (view.get(k) < threshold) == outside {
+                continue;
+            }
+            let (x, y) = (k % dx, k / dx);
+            // The cells x - 1 and x (and y - 1 and y) have the sample as a corner
+            for bx in [x / BLOCK, (x + 1) / BLOCK] {
+                for by in [y / BLOCK, (y + 1) / BLOCK] {
+                    blocks.insert((bx, by));
+                }
+            }
+        }
+        if !outside {
+            // The samples outside of the grid are outside of every contour
+            let (last_x, last_y) = (dx / BLOCK, dy / BLOCK);
+            (0..=last_y).for_each(|by| {
+                blocks.insert((0, by));
+                blocks.insert((last_x, by));
+            });
+            (0..=last_x).for_each(|bx| {
+                blocks.insert((bx, 0));
+                blocks.insert((bx, last_y));
+            });
+        }
+        let mut blocks = blocks.into_iter().collect::<Vec<_>>();
+        blocks.sort_unstable_by_key(|&(bx, by)| (by, bx));
+        let cells =
+            |b: usize, n: usize| (b * BLOCK) as i64 - 1..((b + 1) * BLOCK).min(n + 1) as i64 - 1;
+        let inside = |i: usize| view.get(i) >= threshold;
+        let mut rings = Vec::new();
+        let mut open = Vec::new();
+        for (bx, by) in blocks {
+            let (closed, fragments) =
+                isoring.compute_cells(&inside, cells(bx, dx), cells(by, dy))?;
+            rings.extend(closed);
+            open.extend(fragments);
+        }
+        rings.extend(link_fragments(open)?);
+        Ok(rings)
+    }
+}
@@ -0,0 +1,68 @@
+use crate::Float;
+
+/// Summary statistics of the finite values of a grid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridStats {
+    /// The number of finite values
+    pub count: usize,
+    /// The minimum of the finite values
+    pub min: Float,
+    /// The maximum of the finite values
+    pub max: Float,
+    /// The mean of the finite values
+    pub mean: Float,
+    /// The (population) standard deviation of the finite values
+    pub std_dev: Float,
+}
+
+impl GridStats {
+    /// Computes the statistics of the finite values (ignoring `NaN` and infinite values)
+    /// of the given grid, returning `None` if there is no finite value.
+    // The casts to f64 are unnecessary when not using the "f32" feature.
+    #[allow(clippy::unnecessary_cast)]
+    pub fn from_values(values: &[Float]) -> Option<GridStats> {
+        // Welford's algorithm, in double precision
+        let (mut count, mut mean, mut m2) = (0usize, 0f64, 0f64);
+        let (mut min, mut max) = (Float::INFINITY, Float::NEG_INFINITY);
+        values.iter().filter(|v| v.is_finite()).for_each(|&v| {
+            count += 1;
+            let delta = v as f64 - mean;
+            mean += delta / count as f64;
+            m2 += delta * (v as f64 - mean);
+            min = min.min(v);
+            max = max.max(v);
+        });
+        (count > 0).then(|| GridStats {
+            count,
+            min,
+            max,
+            mean: mean as Float,
+            std_dev: (m2 / count as f64).sqrt() as Float,
+        })
+    }
+
+    /// Returns the thresholds at `mean - k * std_dev` and `mean + k * std_dev`,
+    /// for k = 1..=`n`, in ascending order (e.g. for anomaly and z-score maps).
+    pub fn std_dev_thresholds(&self, n: usize) -> Vec<Float> {
+        let below = (1..=n).rev().map(|k| self.mean - k as Float * self.std_dev);
+        let above = (1..=n).map(|k| self.mean + k as Float * self.std_dev);
+        below.chain(above).collect()
+    }
+}
+
+/// Returns the thresholds at mean ± k·σ of the finite values of the given grid,
+/// for k = 1..=`n`, in ascending order (empty if there is no finite value).
+///
+/// # Example
+///
+/// ```
+/// use contour::{std_dev_thresholds, Float};
+///
+/// let thresholds = std_dev_thresholds(&[2., 4., 4., 4., 5., 5., 7., 9., Float::NAN], 2);
+/// assert_eq!(thresholds, vec![1., 3., 7., 9.]);
+/// ```
+pub fn std_dev_thresholds(values: &[Float], n: usize) -> Vec<Float> {
+    GridStats::from_values(values)
+        .map(|stats| stats.std_dev_thresholds(n))
+        .unwrap_or_default()
+}
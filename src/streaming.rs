@@ -0,0 +1,105 @@
+use crate::error::{new_error, ErrorKind, Result};
+use crate::tiled::TiledContours;
+use crate::{Contour, ContourBuilder, Float};
+
+/// Contours a grid whose rows are read one after the other (e.g. from a file or a
+/// network stream), only the previous row being kept besides the rings of the contours
+/// (see [`ContourBuilder::streaming`]).
+///
+/// The rows are contoured by pairs as they are pushed, the fragments of the rings being
+/// stitched across the rows when the contours are built: the contours are the same as if
+/// the whole grid was contoured at once (but for the starting vertex of their rings
+/// and the order of their polygons), with the same restrictions as [`TiledContours`].
+#[derive(Debug, Clone)]
+pub struct StreamingContours {
+    tiles: TiledContours,
+    dx: usize,
+    dy: usize,
+    /// The values of the last row pushed
+    previous: Vec<Float>,
+    /// The number of rows pushed
+    rows: usize,
+}
+
+impl ContourBuilder {
+    /// Returns a [`StreamingContours`] computing the contours of the given `thresholds`
+    /// for the grid of this builder, its values being pushed row by row.
+    ///
+    /// # Arguments
+    ///
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn streaming(&self, thresholds: &[Float]) -> StreamingContours {
+        StreamingContours {
+            tiles: self.tiled(thresholds),
+            dx: self.dx,
+            dy: self.dy,
+            previous: Vec::with_capacity(self.dx),
+            rows: 0,
+        }
+    }
+
+    /// Computes contours (see [`ContourBuilder::contours`]) from the rows of the grid,
+    /// without holding the whole grid in memory (see [`StreamingContours`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The rows of the grid, from the first one to the last one.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use contour::{ContourBuilder, Float};
+    ///
+    /// let rows = (0..4).map(|y| vec![0., y as Float, y as Float, 0.]);
+    /// let builder = ContourBuilder::new(4, 4, true);
+    /// let contours = builder.contours_from_rows(rows, &[1.5]).unwrap();
+    /// assert_eq!(contours[0].geometry().0.len(), 1);
+    /// ```
+    pub fn contours_from_rows<R: AsRef<[Float]>>(
+        &self,
+        rows: impl IntoIterator<Item = R>,
+        thresholds: &[Float],
+    ) -> Result<Vec<Contour>> {
+        let mut streaming = self.streaming(thresholds);
+        for row in rows {
+            streaming.push_row(row.as_ref())?;
+        }
+        streaming.finish()
+    }
+}
+
+impl StreamingContours {
+    /// Contours the cells between the given row and the previous one.
+    ///
+    /// Returns a [`BadDimension`](crate::ErrorKind::BadDimension) error if the row
+    /// doesn't have one value per column, or if every row of the grid was already pushed.
+    pub fn push_row(&mut self, row: &[Float]) -> Result<()> {
+        if row.len() != self.dx || self.rows == self.dy {
+            return Err(new_error(ErrorKind::BadDimension));
+        }
+        if self.rows > 0 {
+            self.previous.extend_from_slice(row);
+            self.tiles
+                .add_tile(0, self.rows - 1, self.dx, &self.previous)?;
+            self.previous.drain(..self.dx);
+        } else if self.dy == 1 {
+            self.tiles.add_tile(0, 0, self.dx, row)?;
+        } else {
+            self.previous.extend_from_slice(row);
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Builds the contours once every row of the grid was pushed
+    /// (see [`TiledContours::contours`]).
+    ///
+    /// Returns a [`BadDimension`](crate::ErrorKind::BadDimension) error if rows are missing.
+    pub fn finish(self) -> Result<Vec<Contour>> {
+        if self.rows != self.dy {
+            return Err(new_error(ErrorKind::BadDimension));
+        }
+        self.tiles.contours()
+    }
+}
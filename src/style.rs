@@ -0,0 +1,178 @@
+use crate::{Band, ColorRamp, Float, GeoJsonKeys, Line, Rgba};
+use serde_json::{json, Value};
+
+/// Builds MapLibre (or Mapbox GL) style layers for bands and lines, with the fill color
+/// of each band taken from a [`ColorRamp`] and wider lines for the index contours
+/// (see [`ContourBuilder::index_interval`](crate::ContourBuilder::index_interval)),
+/// to produce a complete styled contour overlay from the results.
+///
+/// # Example
+///
+/// ```
+/// use contour::{ContourBuilder, MapLibreStyle};
+///
+/// let builder = ContourBuilder::new(3, 3, false).index_interval(Some(1.));
+/// let values = [0., 0., 0., 0., 2., 0., 0., 0., 0.];
+/// let bands = builder.isobands(&values, &[0., 0.5, 1., 3.]).unwrap();
+/// let lines = builder.lines(&values, &[0.5, 1.]).unwrap();
+/// let overlay = MapLibreStyle::new("elevation").overlay(&bands, &lines);
+/// assert_eq!(overlay["layers"][0]["id"], "elevation-bands");
+/// assert_eq!(overlay["sources"]["elevation-lines"]["type"], "geojson");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapLibreStyle {
+    id: String,
+    ramp: ColorRamp,
+    fill_opacity: Float,
+    line_color: Rgba,
+    index_width: Float,
+    intermediate_width: Float,
+    keys: GeoJsonKeys,
+}
+
+impl MapLibreStyle {
+    /// Constructs a new style, using `id` as the prefix of the ids of its sources and layers.
+    pub fn new(id: impl Into<String>) -> Self {
+        MapLibreStyle {
+            id: id.into(),
+            ramp: ColorRamp::Viridis,
+            fill_opacity: 0.8,
+            line_color: Rgba::rgb(0x33, 0x33, 0x33),
+            index_width: 1.5,
+            intermediate_width: 0.5,
+            keys: GeoJsonKeys::default(),
+        }
+    }
+
+    /// Sets the color ramp of the bands (default: [`ColorRamp::Viridis`]).
+    pub fn ramp(mut self, ramp: ColorRamp) -> Self {
+        self.ramp = ramp;
+        self
+    }
+
+    /// Sets the opacity of the bands (default: 0.8).
+    pub fn fill_opacity(mut self, opacity: Float) -> Self {
+        self.fill_opacity = opacity;
+        self
+    }
+
+    /// Sets the color of the lines (default: `#333333`).
+    pub fn line_color(mut self, color: Rgba) -> Self {
+        self.line_color = color;
+        self
+    }
+
+    /// Sets the widths (in pixels) of the index and intermediate lines
+    /// (default: 1.5 and 0.5), the lines without class being drawn as intermediate lines.
+    pub fn line_widths(mut self, index: Float, intermediate: Float) -> Self {
+        self.index_width = index;
+        self.intermediate_width = intermediate;
+        self
+    }
+
+    /// Sets the names of the properties of the features the layers refer to
+    /// (which must match the ones used to convert them to GeoJSON).
+    pub fn keys(mut self, keys: GeoJsonKeys) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Returns the id of the source of the bands (`"{id}-bands"`).
+    pub fn bands_source(&self) -> String {
+        format!("{}-bands", self.id)
+    }
+
+    /// Returns the id of the source of the lines (`"{id}-lines"`).
+    pub fn lines_source(&self) -> String {
+        format!("{}-lines", self.id)
+    }
+
+    /// Returns the fill layer of the given bands (using [`bands_source`](Self::bands_source)
+    /// as source), each band being colored according to its min value.
+    pub fn fill_layer(&self, bands: &[Band]) -> Value {
+        let mut bands = bands.iter().collect::<Vec<_>>();
+        bands.sort_by(|a, b| a.min_v().total_cmp(&b.min_v()));
+        let lo = bands.first().map_or(0., |b| b.min_v());
+        let hi = bands.iter().map(|b| b.max_v()).fold(lo, Float::max);
+        // A step expression on the min value of the bands (with the same colors
+        // as given by `ColorRamp::band_colors`)
+        let mut step = vec![json!("step"), json!(["get", self.keys.min_v])];
+        for (i, band) in bands.iter().enumerate() {
+            if i > 0 {
+                step.push(json!(band.min_v()));
+            }
+            let color = self
+                .ramp
+                .color_for_range(band.min_v(), band.max_v(), lo, hi);
+            step.push(json!(color.to_hex()));
+        }
+        let color = if bands.is_empty() {
+            json!(self.ramp.color_at(0.5).to_hex())
+        } else {
+            Value::Array(step)
+        };
+        json!({
+            "id": self.bands_source(),
+            "type": "fill",
+            "source": self.bands_source(),
+            "paint": {
+                "fill-color": color,
+                "fill-opacity": self.fill_opacity,
+            },
+        })
+    }
+
+    /// Returns the line layer of the lines (using [`lines_source`](Self::lines_source)
+    /// as source), the index lines being wider than the intermediate ones.
+    pub fn line_layer(&self) -> Value {
+        json!({
+            "id": self.lines_source(),
+            "type": "line",
+            "source": self.lines_source(),
+            "paint": {
+                "line-color": self.line_color.to_hex(),
+                "line-width": [
+                    "match", ["get", self.keys.class],
+                    "index", self.index_width,
+                    self.intermediate_width,
+                ],
+            },
+        })
+    }
+
+    /// Returns the sources (with the GeoJSON of the given bands and lines)
+    /// and the layers of the overlay, as the `sources` and `layers`
+    /// members of a style: `{"sources": {...}, "layers": [...]}`.
+    pub fn overlay(&self, bands: &[Band], lines: &[Line]) -> Value {
+        let collection = |features: Vec<geojson::Feature>| geojson::FeatureCollection {
+            bbox: None,
+            features,
+            foreign_members: None,
+        };
+        let bands_data = collection(
+            bands
+                .iter()
+                .map(|b| b.to_geojson_with_keys(&self.keys))
+                .collect(),
+        );
+        let lines_data = collection(
+            lines
+                .iter()
+                .map(|l| l.to_geojson_with_keys(&self.keys))
+                .collect(),
+        );
+        let mut sources = serde_json::Map::new();
+        sources.insert(
+            self.bands_source(),
+            json!({ "type": "geojson", "data": bands_data }),
+        );
+        sources.insert(
+            self.lines_source(),
+            json!({ "type": "geojson", "data": lines_data }),
+        );
+        json!({
+            "sources": sources,
+            "layers": [self.fill_layer(bands), self.line_layer()],
+        })
+    }
+}
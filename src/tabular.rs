@@ -0,0 +1,188 @@
+use crate::area::area;
+use crate::error::Result;
+use crate::{Band, Contour, Float, Line, Pt};
+use geo_types::{MultiLineString, MultiPolygon};
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// Writes lines, contours and bands as the rows of a CSV table to an
+/// [`io::Write`](std::io::Write), one row per feature, with their geometry
+/// as WKT (e.g. to open them in a spreadsheet or join them to other tables).
+///
+/// The columns of the table are:
+/// - `threshold`: the threshold of the line or contour (empty for a band),
+/// - `min` and `max`: the range of values covered by the contour or band
+///   (`max` being empty for the contour of the highest threshold, both being empty for a line),
+/// - `area`: the area of the polygons of the contour or band, holes excluded
+///   (in the units of the coordinates, empty for a line),
+/// - `wkt`: the geometry, as a `MULTIPOLYGON` or `MULTILINESTRING`.
+///
+/// # Example
+///
+/// ```
+/// use contour::{ContourBuilder, CsvWriter};
+///
+/// let builder = ContourBuilder::new(3, 3, false);
+/// let bands = builder
+///     .isobands(&[0., 0., 0., 0., 2., 0., 0., 0., 0.], &[1., 3.])
+///     .unwrap();
+/// let mut writer = CsvWriter::new(Vec::new()).unwrap();
+/// for band in &bands {
+///     writer.write_band(band).unwrap();
+/// }
+/// let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+/// assert_eq!(
+///     output,
+///     "threshold,min,max,area,wkt\n\
+///      ,1,3,0.5,\"MULTIPOLYGON (((2 1.5, 1.5 1, 1 1.5, 1.5 2, 2 1.5)))\"\n"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct CsvWriter<W: Write> {
+    writer: W,
+    count: usize,
+}
+
+impl<W: Write> CsvWriter<W> {
+    /// Starts writing a table to `writer` (which should be buffered,
+    /// e.g. with a [`BufWriter`](std::io::BufWriter)), beginning with its header.
+    pub fn new(mut writer: W) -> Result<Self> {
+        writer.write_all(b"threshold,min,max,area,wkt\n")?;
+        Ok(CsvWriter { writer, count: 0 })
+    }
+
+    /// Returns the number of rows written so far (header excluded).
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns true if no row was written so far.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Writes the row of a line.
+    pub fn write_line(&mut self, line: &Line) -> Result<()> {
+        let threshold = line.threshold();
+        self.write_row(Some(threshold), None, None, None, &line.to_wkt())
+    }
+
+    /// Writes the row of a contour.
+    pub fn write_contour(&mut self, contour: &Contour) -> Result<()> {
+        let threshold = contour.threshold();
+        let area = polygons_area(contour.geometry());
+        let max = contour.max_threshold();
+        self.write_row(
+            Some(threshold),
+            Some(threshold),
+            max,
+            Some(area),
+            &contour.to_wkt(),
+        )
+    }
+
+    /// Writes the row of a band.
+    pub fn write_band(&mut self, band: &Band) -> Result<()> {
+        let (min, max) = (Some(band.min_v()), Some(band.max_v()));
+        let area = polygons_area(band.geometry());
+        self.write_row(None, min, max, Some(area), &band.to_wkt())
+    }
+
+    /// Flushes the writer and returns it.
+    pub fn finish(mut self) -> Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_row(
+        &mut self,
+        threshold: Option<Float>,
+        min: Option<Float>,
+        max: Option<Float>,
+        area: Option<f64>,
+        wkt: &str,
+    ) -> Result<()> {
+        let field = |v: Option<String>| v.unwrap_or_default();
+        self.count += 1;
+        writeln!(
+            self.writer,
+            "{},{},{},{},\"{}\"",
+            field(threshold.map(|v| v.to_string())),
+            field(min.map(|v| v.to_string())),
+            field(max.map(|v| v.to_string())),
+            field(area.map(|v| v.to_string())),
+            wkt
+        )?;
+        Ok(())
+    }
+}
+
+/// Computes the area of the given polygons, holes excluded.
+fn polygons_area(polygons: &MultiPolygon<Float>) -> f64 {
+    // `area` computes twice the area of the rings
+    let doubled: f64 = polygons
+        .0
+        .iter()
+        .map(|polygon| {
+            area(&polygon.exterior().0).abs()
+                - polygon
+                    .interiors()
+                    .iter()
+                    .map(|hole| area(&hole.0).abs())
+                    .sum::<f64>()
+        })
+        .fold(0., |acc, area| acc + area);
+    doubled / 2.
+}
+
+/// Appends the coordinates of a ring or line to `wkt`, in parentheses.
+fn push_coords(wkt: &mut String, coords: &[Pt]) {
+    wkt.push('(');
+    for (i, pt) in coords.iter().enumerate() {
+        if i > 0 {
+            wkt.push_str(", ");
+        }
+        let _ = write!(wkt, "{} {}", pt.x, pt.y);
+    }
+    wkt.push(')');
+}
+
+/// Formats the given polygons as a WKT `MULTIPOLYGON`.
+pub(crate) fn multipolygon_wkt(polygons: &MultiPolygon<Float>) -> String {
+    if polygons.0.is_empty() {
+        return "MULTIPOLYGON EMPTY".to_string();
+    }
+    let mut wkt = String::from("MULTIPOLYGON (");
+    for (i, polygon) in polygons.0.iter().enumerate() {
+        if i > 0 {
+            wkt.push_str(", ");
+        }
+        wkt.push('(');
+        let rings = std::iter::once(polygon.exterior()).chain(polygon.interiors());
+        for (j, ring) in rings.enumerate() {
+            if j > 0 {
+                wkt.push_str(", ");
+            }
+            push_coords(&mut wkt, &ring.0);
+        }
+        wkt.push(')');
+    }
+    wkt.push(')');
+    wkt
+}
+
+/// Formats the given lines as a WKT `MULTILINESTRING`.
+pub(crate) fn multilinestring_wkt(lines: &MultiLineString<Float>) -> String {
+    if lines.0.is_empty() {
+        return "MULTILINESTRING EMPTY".to_string();
+    }
+    let mut wkt = String::from("MULTILINESTRING (");
+    for (i, line) in lines.0.iter().enumerate() {
+        if i > 0 {
+            wkt.push_str(", ");
+        }
+        push_coords(&mut wkt, &line.0);
+    }
+    wkt.push(')');
+    wkt
+}
@@ -0,0 +1,31 @@
+use crate::Float;
+
+/// How the values exactly equal to a threshold (e.g. plateaus of integer values)
+/// are classified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TiePolicy {
+    /// The values equal to the threshold are inside the contours (i.e. the inside
+    /// of the contours is where the values are greater than or equal to the threshold).
+    #[default]
+    Inside,
+    /// The values equal to the threshold are outside the contours (i.e. the inside
+    /// of the contours is where the values are strictly greater than the threshold).
+    Outside,
+    /// The threshold is (deterministically) lowered by one ulp, so that no value
+    /// is exactly equal to it: the values equal to the requested threshold are thus
+    /// inside the contours, but are never compared or interpolated against a tie.
+    Perturb,
+}
+
+impl TiePolicy {
+    /// Returns the threshold to use to classify (and interpolate) the values,
+    /// so that the values equal to the given `threshold` are classified
+    /// according to the policy by the `>=` rule.
+    pub(crate) fn apply(self, threshold: Float) -> Float {
+        match self {
+            TiePolicy::Inside => threshold,
+            TiePolicy::Outside => threshold.next_up(),
+            TiePolicy::Perturb => threshold.next_down(),
+        }
+    }
+}
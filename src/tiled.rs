@@ -0,0 +1,179 @@
+use crate::diagnostics::ThresholdDiagnostics;
+use crate::dual::VertexPlacement;
+use crate::error::{new_error, ErrorKind, Result};
+use crate::isoringbuilder::{link_fragments, Fragment};
+use crate::order::GridOrder;
+use crate::{Contour, ContourBuilder, Float, Ring};
+
+/// Contours a grid too large to be held in memory, its values being read tile by tile
+/// (e.g. from the blocks of a raster file): each tile is contoured on its own as soon as
+/// it is added, only the rings (and the fragments of the rings crossing its edges) being
+/// kept, and the fragments are stitched across the tile edges into seamless rings
+/// when the contours are built.
+///
+/// Adjacent tiles must overlap by one row or column of samples (the cells between
+/// the samples of two tiles being contoured with the tile holding both of them),
+/// and the tiles must cover the whole grid. With linear interpolation (the default),
+/// the contours are the same as if the whole grid was contoured at once (but for the
+/// starting vertex of their rings and the order of their polygons).
+///
+/// The weights, [`padding`](ContourBuilder::padding), grid order and
+/// [`vertex placement`](ContourBuilder::vertex_placement) of the builder are ignored
+/// (the values of the tiles being read row by row), as is the preservation of the topology
+/// of the rings.
+///
+/// # Example
+///
+/// ```
+/// use contour::ContourBuilder;
+///
+/// # #[rustfmt::skip]
+/// // Two tiles of a 5 * 3 grid, sharing its middle column
+/// let (left, right) = (
+///     [0., 0., 0.,
+///      0., 1., 1.,
+///      0., 0., 0.],
+///     [0., 0., 0.,
+///      1., 1., 0.,
+///      0., 0., 0.],
+/// );
+/// let mut tiled = ContourBuilder::new(5, 3, true).tiled(&[0.5]);
+/// tiled.add_tile(0, 0, 3, &left).unwrap();
+/// tiled.add_tile(2, 0, 3, &right).unwrap();
+/// let contours = tiled.contours().unwrap();
+/// assert_eq!(contours[0].geometry().0.len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TiledContours {
+    builder: ContourBuilder,
+    thresholds: Vec<Float>,
+    /// The closed rings computed for each threshold (smoothed, in grid coordinates)
+    rings: Vec<Vec<Ring>>,
+    /// The fragments of the rings crossing the tile edges, for each threshold
+    fragments: Vec<Vec<Fragment<Ring>>>,
+}
+
+impl ContourBuilder {
+    /// Returns a [`TiledContours`] computing the contours of the given `thresholds`
+    /// for the grid of this builder, its values being added tile by tile.
+    ///
+    /// # Arguments
+    ///
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn tiled(&self, thresholds: &[Float]) -> TiledContours {
+        TiledContours {
+            builder: self.clone(),
+            thresholds: thresholds.to_vec(),
+            rings: vec![Vec::new(); thresholds.len()],
+            fragments: vec![Vec::new(); thresholds.len()],
+        }
+    }
+}
+
+impl TiledContours {
+    /// Contours the given tile, whose top left sample is the sample at column `x`
+    /// and row `y` of the grid.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The column of the first sample of the tile.
+    /// * `y` - The row of the first sample of the tile.
+    /// * `width` - The number of columns in the tile.
+    /// * `values` - The values of the tile (row-major, of length `width` * its number of rows).
+    pub fn add_tile(&mut self, x: usize, y: usize, width: usize, values: &[Float]) -> Result<()> {
+        let (dx, dy) = (self.builder.dx, self.builder.dy);
+        let height = values.len().checked_div(width).unwrap_or(0);
+        if height == 0 || width * height != values.len() || x + width > dx || y + height > dy {
+            return Err(new_error(ErrorKind::BadDimension));
+        }
+        // The builder smoothing the rings in the coordinates of the tile
+        let local = ContourBuilder {
+            dx: width,
+            dy: height,
+            padding: None,
+            weights: None,
+            order: GridOrder::RowMajor,
+            cache: None,
+            strips: 1,
+            preserve_topology: false,
+            vertex_placement: VertexPlacement::Edges,
+            ..self.builder.grid_space()
+        };
+        let grid = local.grid(values)?;
+        // The cells between the samples of the tile, and those straddling the edges of the grid
+        let edge = |start: usize, len: usize, n: usize| {
+            let first = if start == 0 { -1 } else { start as i64 };
+            let last = if start + len == n { n } else { start + len - 1 };
+            first..last as i64
+        };
+        let (xs, ys) = (edge(x, width, dx), edge(y, height, dy));
+        let (ox, oy) = (x as Float, y as Float);
+        let shift = |ring: &mut Ring, sign: Float| {
+            ring.iter_mut().for_each(|p| {
+                p.x += sign * ox;
+                p.y += sign * oy;
+            })
+        };
+        let tiles = self
+            .builder
+            .map_thresholds(self.thresholds.len(), |isoring, i| {
+                let threshold = self.builder.ties.apply(self.thresholds[i]);
+                let inside = |k: usize| {
+                    let (gx, gy) = (k % dx, k / dx);
+                    grid.get((gy - y) * width + gx - x) >= threshold
+                };
+                let (mut rings, mut fragments) =
+                    isoring.compute_cells(&inside, xs.clone(), ys.clone())?;
+                let mut open = fragments
+                    .iter_mut()
+                    .map(|f| std::mem::take(&mut f.ring))
+                    .collect::<Vec<_>>();
+                for rings in [&mut rings, &mut open] {
+                    rings.iter_mut().for_each(|ring| shift(ring, -1.));
+                    local.finalize_rings(rings, grid, threshold);
+                    rings.iter_mut().for_each(|ring| shift(ring, 1.));
+                }
+                fragments
+                    .iter_mut()
+                    .zip(open)
+                    .for_each(|(f, ring)| f.ring = ring);
+                Ok((rings, fragments))
+            })?;
+        for (i, (rings, fragments)) in tiles.into_iter().enumerate() {
+            self.rings[i].extend(rings);
+            self.fragments[i].extend(fragments);
+        }
+        Ok(())
+    }
+
+    /// Stitches the fragments of the rings across the tile edges and builds the contours
+    /// (see [`ContourBuilder::contours`]).
+    ///
+    /// Returns an [`Unexpected`](crate::ErrorKind::Unexpected) error if the fragments
+    /// can't be stitched, i.e. if the tiles don't cover the grid.
+    pub fn contours(self) -> Result<Vec<Contour>> {
+        let builder = &self.builder;
+        let contours = self
+            .rings
+            .into_iter()
+            .zip(self.fragments)
+            .enumerate()
+            .map(|(i, (mut rings, fragments))| {
+                rings.extend(link_fragments(fragments)?);
+                builder.transform_rings(&mut rings);
+                builder.densify_rings(&mut rings);
+                // The ends of the stitched rings were computed by different tiles
+                rings
+                    .iter_mut()
+                    .filter(|ring| ring.len() > 1)
+                    .for_each(|ring| {
+                        let n = ring.len();
+                        ring[n - 1] = ring[0];
+                    });
+                let mut diagnostics = ThresholdDiagnostics::new(self.thresholds[i], i);
+                Ok(builder.contour(rings, &self.thresholds, i, &mut diagnostics))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(builder.without_empty(contours, |c| c.geometry.0.is_empty()))
+    }
+}
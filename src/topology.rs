@@ -0,0 +1,138 @@
+use crate::area::area;
+use crate::{Float, Pt, Ring};
+use rustc_hash::FxHashMap;
+
+/// Reverts the smoothing of the vertices involved in intersections (or contacts)
+/// between the segments of the given `rings`, until the rings don't intersect
+/// each other nor themselves anymore.
+///
+/// Both `rings` (smoothed) and `original` (unsmoothed) are expressed in grid coordinates
+/// and have the same structure. As the rings computed by marching squares are simple
+/// and don't touch each other, reverting all the vertices would give valid rings,
+/// so this always terminates. Because the rings never cross each other, the nesting
+/// of the rings (and thus the containment of the holes) is preserved too.
+pub(crate) fn preserve_topology(rings: &mut [Ring], original: &[Ring]) {
+    loop {
+        let offending = find_intersections(rings);
+        let mut changed = false;
+        for (r, i) in offending {
+            let ring = &mut rings[r];
+            let n = ring.len();
+            for ix in [i, (i + 1) % n] {
+                if ring[ix] != original[r][ix] {
+                    ring[ix] = original[r][ix];
+                    changed = true;
+                }
+            }
+            // Keep explicitly closed rings closed
+            if n > 1 && original[r][0] == original[r][n - 1] && ring[0] != ring[n - 1] {
+                ring[0] = original[r][0];
+                ring[n - 1] = original[r][n - 1];
+                changed = true;
+            }
+        }
+        if !changed {
+            return;
+        }
+    }
+}
+
+/// Returns the (ring, segment) references of the segments intersecting another segment.
+/// Segments are bucketed by grid cell (smoothing only moves the vertices along the
+/// edges of their cell) so that each segment is only tested against its neighbours.
+///
+/// The rings without area (collapsed around the samples equal to the threshold) are
+/// ignored, as they don't bound anything.
+pub(crate) fn find_intersections(rings: &[Ring]) -> Vec<(usize, usize)> {
+    let mut buckets: FxHashMap<(i64, i64), Vec<(usize, usize)>> = FxHashMap::default();
+    for (r, ring) in rings.iter().enumerate() {
+        if ring.len() < 2 || (ring[0] == ring[ring.len() - 1] && area(ring) == 0.) {
+            continue;
+        }
+        for i in 0..ring.len().saturating_sub(1) {
+            buckets
+                .entry(bucket(&ring[i], &ring[i + 1]))
+                .or_default()
+                .push((r, i));
+        }
+    }
+    let mut result = Vec::new();
+    for (&(bx, by), segments) in buckets.iter() {
+        for &(r, i) in segments {
+            let (a, b) = (&rings[r][i], &rings[r][i + 1]);
+            let hit = (-1..=1).any(|ox| {
+                (-1..=1).any(|oy| {
+                    buckets.get(&(bx + ox, by + oy)).is_some_and(|others| {
+                        others.iter().any(|&(r2, j)| {
+                            !adjacent(rings, (r, i), (r2, j))
+                                && intersects(a, b, &rings[r2][j], &rings[r2][j + 1])
+                        })
+                    })
+                })
+            });
+            if hit {
+                result.push((r, i));
+            }
+        }
+    }
+    result
+}
+
+fn bucket(a: &Pt, b: &Pt) -> (i64, i64) {
+    (
+        ((a.x + b.x) / 2.).floor() as i64,
+        ((a.y + b.y) / 2.).floor() as i64,
+    )
+}
+
+/// Whether the two segments are the same or consecutive segments of the same ring
+/// (and thus legitimately share a vertex), the segments of zero length between
+/// them (e.g. where smoothing merged vertices) being skipped.
+fn adjacent(rings: &[Ring], (r1, i): (usize, usize), (r2, j): (usize, usize)) -> bool {
+    if r1 != r2 {
+        return false;
+    }
+    let ring = &rings[r1];
+    let n = ring.len() - 1;
+    let closed = ring[0] == ring[n];
+    let follows = |from: usize, to: usize| {
+        let mut k = from;
+        loop {
+            k = match k + 1 {
+                next if next < n => next,
+                _ if closed => 0,
+                _ => return false,
+            };
+            if k == to {
+                return true;
+            }
+            if k == from || ring[k] != ring[k + 1] {
+                return false;
+            }
+        }
+    };
+    i == j || follows(i, j) || follows(j, i)
+}
+
+fn orientation(a: &Pt, b: &Pt, c: &Pt) -> Float {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+fn on_segment(a: &Pt, b: &Pt, p: &Pt) -> bool {
+    p.x >= a.x.min(b.x) && p.x <= a.x.max(b.x) && p.y >= a.y.min(b.y) && p.y <= a.y.max(b.y)
+}
+
+/// Whether the segments [a, b] and [c, d] share at least one point.
+fn intersects(a: &Pt, b: &Pt, c: &Pt, d: &Pt) -> bool {
+    let (o1, o2) = (orientation(a, b, c), orientation(a, b, d));
+    let (o3, o4) = (orientation(c, d, a), orientation(c, d, b));
+    if ((o1 > 0. && o2 < 0.) || (o1 < 0. && o2 > 0.))
+        && ((o3 > 0. && o4 < 0.) || (o3 < 0. && o4 > 0.))
+    {
+        return true;
+    }
+    (o1 == 0. && on_segment(a, b, c))
+        || (o2 == 0. && on_segment(a, b, d))
+        || (o3 == 0. && on_segment(c, d, a))
+        || (o4 == 0. && on_segment(c, d, b))
+}
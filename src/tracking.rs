@@ -0,0 +1,259 @@
+use crate::area::polygon_contains;
+use crate::bbox::extend;
+use crate::error::Result;
+use crate::{Contour, ContourBuilder, Float, Pt};
+use rustc_hash::FxHashMap;
+
+/// A link between a polygon of the previous frame and a polygon of the current frame
+/// (of the same threshold) overlapping it, in a [`TrackedFrame`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackLink {
+    /// The position of the threshold of the polygons in the slice of thresholds.
+    pub threshold_index: usize,
+    /// The id of the polygon of the previous frame.
+    pub from: u64,
+    /// The id of the polygon of the current frame.
+    pub to: u64,
+    /// The area of their overlap (measured on the samples of the grid, each of
+    /// them counting for the area of a cell).
+    pub area: Float,
+}
+
+/// An event in the tracking of the polygons of a threshold
+/// between the previous frame and the current frame, in a [`TrackedFrame`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackEvent {
+    /// A polygon, which doesn't overlap any polygon of the previous frame, appears.
+    Appear { threshold_index: usize, id: u64 },
+    /// A polygon of the previous frame, which doesn't overlap
+    /// any polygon of the current frame, vanishes.
+    Vanish { threshold_index: usize, id: u64 },
+    /// A polygon of the previous frame splits into several polygons
+    /// (in descending order of overlap, the first one keeping its id if it doesn't
+    /// overlap a larger part of another polygon of the previous frame).
+    Split {
+        threshold_index: usize,
+        id: u64,
+        into: Vec<u64>,
+    },
+    /// Several polygons of the previous frame (in descending order of overlap)
+    /// merge into a polygon of the current frame.
+    Merge {
+        threshold_index: usize,
+        from: Vec<u64>,
+        into: u64,
+    },
+}
+
+/// The contours of a frame, with the persistent ids of their polygons,
+/// as returned by [`ContourTracker::push`].
+#[derive(Debug, Clone)]
+pub struct TrackedFrame {
+    contours: Vec<Contour>,
+    ids: Vec<Vec<u64>>,
+    links: Vec<TrackLink>,
+    events: Vec<TrackEvent>,
+}
+
+impl TrackedFrame {
+    /// Borrow the contours of this frame (in the order of the thresholds).
+    pub fn contours(&self) -> &[Contour] {
+        &self.contours
+    }
+
+    /// Borrow the ids of the polygons of the contour at the given position
+    /// (in the order of the polygons of its geometry).
+    pub fn ids(&self, threshold_index: usize) -> &[u64] {
+        &self.ids[threshold_index]
+    }
+
+    /// Borrow the links between the polygons of the previous frame and the
+    /// polygons of this frame overlapping them.
+    pub fn links(&self) -> &[TrackLink] {
+        &self.links
+    }
+
+    /// Borrow the events (appearances, disappearances, splits and merges)
+    /// between the previous frame and this frame.
+    pub fn events(&self) -> &[TrackEvent] {
+        &self.events
+    }
+}
+
+// The polygons of a threshold in a frame: the polygon containing each sample
+// of the grid (if any), and the id of each polygon.
+#[derive(Debug, Clone)]
+struct Labels {
+    labels: Vec<Option<u32>>,
+    ids: Vec<u64>,
+}
+
+/// Tracks the contours of a sequence of grids (e.g. the frames of a
+/// time series), matching the polygons of each threshold between consecutive frames
+/// by their overlap, so that they keep a persistent id (e.g. to follow storm cells or plumes).
+///
+/// A polygon keeps the id of the polygon of the previous frame overlapping
+/// the largest part of it, if it is also its largest successor, and gets a new id otherwise.
+/// The overlaps are measured on the samples of the grid (each sample above the threshold
+/// belonging to the polygon containing it).
+///
+/// # Example
+///
+/// ```
+/// use contour::{ContourBuilder, ContourTracker, TrackEvent};
+///
+/// let mut tracker = ContourTracker::new(ContourBuilder::new(4, 1, false), &[0.5]);
+/// let first = tracker.push(&[1., 1., 0., 0.]).unwrap();
+/// let second = tracker.push(&[0., 1., 1., 0.]).unwrap();
+/// assert_eq!(first.ids(0), second.ids(0));
+/// let third = tracker.push(&[0., 0., 0., 0.]).unwrap();
+/// assert_eq!(third.events(), &[TrackEvent::Vanish { threshold_index: 0, id: 0 }]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ContourTracker {
+    builder: ContourBuilder,
+    thresholds: Vec<Float>,
+    next_id: u64,
+    previous: Option<Vec<Labels>>,
+}
+
+impl ContourTracker {
+    /// Constructs a new tracker of the contours computed by `builder` for the given thresholds.
+    pub fn new(builder: ContourBuilder, thresholds: &[Float]) -> Self {
+        ContourTracker {
+            builder,
+            thresholds: thresholds.to_vec(),
+            next_id: 0,
+            previous: None,
+        }
+    }
+
+    /// Computes the contours of the next frame and matches their polygons
+    /// with the polygons of the previous frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values of the frame.
+    pub fn push(&mut self, values: &[Float]) -> Result<TrackedFrame> {
+        let contours = self.builder.contours(values, &self.thresholds)?;
+        let grid = self.builder.grid(values)?;
+        let cell_area = (self.builder.x_step * self.builder.y_step).abs();
+        let (mut links, mut events) = (Vec::new(), Vec::new());
+        let mut labels = Vec::with_capacity(contours.len());
+
+        for (k, contour) in contours.iter().enumerate() {
+            let polygons = &contour.geometry.0;
+            let bboxes = polygons
+                .iter()
+                .map(|p| p.exterior().0.iter().fold(None, extend))
+                .collect::<Vec<_>>();
+            let current = (0..grid.len())
+                .map(|i| {
+                    // Only the samples above the threshold (and not NaN) are inside
+                    if grid.get(i) < contour.threshold || grid.get(i).is_nan() {
+                        return None;
+                    }
+                    let mut pt = Pt {
+                        x: (i % self.builder.dx) as Float + 0.5,
+                        y: (i / self.builder.dx) as Float + 0.5,
+                    };
+                    self.builder.transform_point(&mut pt);
+                    (0..polygons.len())
+                        .find(|&p| {
+                            bboxes[p].is_some_and(|b| {
+                                b.min().x <= pt.x
+                                    && pt.x <= b.max().x
+                                    && b.min().y <= pt.y
+                                    && pt.y <= b.max().y
+                            }) && polygon_contains(&polygons[p], &pt)
+                        })
+                        .map(|p| p as u32)
+                })
+                .collect::<Vec<_>>();
+
+            // The overlaps between the polygons of the previous frame and the current one
+            let previous = self.previous.as_ref().map(|previous| &previous[k]);
+            let mut overlaps = FxHashMap::<(u32, u32), usize>::default();
+            if let Some(previous) = previous {
+                for (a, b) in previous.labels.iter().zip(&current) {
+                    if let (Some(a), Some(b)) = (a, b) {
+                        *overlaps.entry((*a, *b)).or_default() += 1;
+                    }
+                }
+            }
+            let mut overlaps = overlaps.into_iter().collect::<Vec<_>>();
+            // In descending order of overlap (then in the order of the polygons)
+            overlaps.sort_by(|(a, x), (b, y)| y.cmp(x).then(a.cmp(b)));
+            let n_previous = previous.map_or(0, |p| p.ids.len());
+            let mut successors = vec![Vec::new(); n_previous];
+            let mut predecessors = vec![Vec::new(); polygons.len()];
+            for &((a, b), _) in &overlaps {
+                successors[a as usize].push(b as usize);
+                predecessors[b as usize].push(a as usize);
+            }
+
+            let ids = predecessors
+                .iter()
+                .enumerate()
+                .map(|(b, preds)| match (preds.first(), previous) {
+                    (Some(&a), Some(previous)) if successors[a][0] == b => previous.ids[a],
+                    _ => {
+                        self.next_id += 1;
+                        self.next_id - 1
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            if let Some(previous) = previous {
+                links.extend(overlaps.iter().map(|&((a, b), count)| TrackLink {
+                    threshold_index: k,
+                    from: previous.ids[a as usize],
+                    to: ids[b as usize],
+                    area: count as Float * cell_area,
+                }));
+                for (a, succs) in successors.iter().enumerate() {
+                    let id = previous.ids[a];
+                    match succs.len() {
+                        0 => events.push(TrackEvent::Vanish {
+                            threshold_index: k,
+                            id,
+                        }),
+                        1 => {}
+                        _ => events.push(TrackEvent::Split {
+                            threshold_index: k,
+                            id,
+                            into: succs.iter().map(|&b| ids[b]).collect(),
+                        }),
+                    }
+                }
+            }
+            for (b, preds) in predecessors.iter().enumerate() {
+                match preds.len() {
+                    0 => events.push(TrackEvent::Appear {
+                        threshold_index: k,
+                        id: ids[b],
+                    }),
+                    1 => {}
+                    _ => events.push(TrackEvent::Merge {
+                        threshold_index: k,
+                        from: preds.iter().map(|&a| previous.unwrap().ids[a]).collect(),
+                        into: ids[b],
+                    }),
+                }
+            }
+            labels.push(Labels {
+                labels: current,
+                ids,
+            });
+        }
+
+        let ids = labels.iter().map(|l| l.ids.clone()).collect();
+        self.previous = Some(labels);
+        Ok(TrackedFrame {
+            contours,
+            ids,
+            links,
+            events,
+        })
+    }
+}
@@ -0,0 +1,221 @@
+use crate::area::{area, contains};
+use crate::{Float, Pt};
+use geo_types::{LineString, MultiPolygon};
+use rustc_hash::FxHashMap;
+use std::fmt;
+
+/// An issue found in the geometry of a contour or band by
+/// [`Contour::validate`](crate::Contour::validate) or [`Band::validate`](crate::Band::validate).
+///
+/// The rings are designated by the position of their polygon in the geometry and
+/// by their own position in the polygon (`0` for the exterior ring, `1..` for the holes).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidityIssue {
+    /// The last vertex of the ring isn't equal to its first vertex.
+    UnclosedRing { polygon: usize, ring: usize },
+    /// The ring has less than 4 vertices (including the closing one).
+    TooFewPoints {
+        polygon: usize,
+        ring: usize,
+        /// The number of vertices of the ring
+        points: usize,
+    },
+    /// The ring is wound in the wrong direction: like its exterior ring for a hole,
+    /// or unlike the exterior ring of the first polygon for an exterior ring.
+    WrongWinding { polygon: usize, ring: usize },
+    /// A segment of the ring crosses another segment of the same polygon.
+    SelfIntersection {
+        polygon: usize,
+        ring: usize,
+        /// The ring of the polygon crossed by the segment (possibly the same ring)
+        other: usize,
+        /// The crossing point
+        point: Pt,
+    },
+    /// The hole isn't contained in the exterior ring of its polygon.
+    HoleOutside { polygon: usize, ring: usize },
+}
+
+impl fmt::Display for ValidityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidityIssue::UnclosedRing { polygon, ring } => {
+                write!(
+                    f,
+                    "The ring {} of the polygon {} isn't closed",
+                    ring, polygon
+                )
+            }
+            ValidityIssue::TooFewPoints {
+                polygon,
+                ring,
+                points,
+            } => write!(
+                f,
+                "The ring {} of the polygon {} only has {} vertices",
+                ring, polygon, points
+            ),
+            ValidityIssue::WrongWinding { polygon, ring } => write!(
+                f,
+                "The ring {} of the polygon {} is wound in the wrong direction",
+                ring, polygon
+            ),
+            ValidityIssue::SelfIntersection {
+                polygon,
+                ring,
+                other,
+                point,
+            } => write!(
+                f,
+                "The ring {} of the polygon {} crosses its ring {} at ({}, {})",
+                ring, polygon, other, point.x, point.y
+            ),
+            ValidityIssue::HoleOutside { polygon, ring } => write!(
+                f,
+                "The hole {} of the polygon {} isn't inside its exterior ring",
+                ring, polygon
+            ),
+        }
+    }
+}
+
+/// The issues found in the geometry of a contour or band
+/// (see [`Contour::validate`](crate::Contour::validate)).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ValidityReport {
+    /// The issues, grouped by polygon
+    pub issues: Vec<ValidityIssue>,
+}
+
+impl ValidityReport {
+    /// Returns true if no issue was found.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Checks the closure, the winding, the intersections and the nesting of the rings
+/// of the given polygons, without modifying them.
+pub(crate) fn validate(polygons: &MultiPolygon<Float>) -> ValidityReport {
+    let mut issues = Vec::new();
+    let mut reference = None;
+    for (p, polygon) in polygons.0.iter().enumerate() {
+        let rings = std::iter::once(polygon.exterior())
+            .chain(polygon.interiors())
+            .collect::<Vec<_>>();
+        let mut exterior = 0.;
+        for (r, ring) in rings.iter().enumerate() {
+            let points = ring.0.len();
+            if points < 4 {
+                issues.push(ValidityIssue::TooFewPoints {
+                    polygon: p,
+                    ring: r,
+                    points,
+                });
+                continue;
+            }
+            if ring.0[0] != ring.0[points - 1] {
+                issues.push(ValidityIssue::UnclosedRing {
+                    polygon: p,
+                    ring: r,
+                });
+            }
+            let winding = area(&ring.0).signum();
+            let expected = if r == 0 {
+                exterior = winding;
+                *reference.get_or_insert(winding)
+            } else {
+                -exterior
+            };
+            if expected != 0. && winding != expected {
+                issues.push(ValidityIssue::WrongWinding {
+                    polygon: p,
+                    ring: r,
+                });
+            }
+            if r > 0 && rings[0].0.len() >= 4 && contains(&rings[0].0, &ring.0) == -1 {
+                issues.push(ValidityIssue::HoleOutside {
+                    polygon: p,
+                    ring: r,
+                });
+            }
+        }
+        crossings(&rings, |ring, other, point| {
+            issues.push(ValidityIssue::SelfIntersection {
+                polygon: p,
+                ring,
+                other,
+                point,
+            })
+        });
+    }
+    ValidityReport { issues }
+}
+
+/// Calls `f` with the rings and the crossing point of each pair of crossing segments
+/// of the given rings (touching segments aren't considered as crossing).
+fn crossings(rings: &[&LineString<Float>], mut f: impl FnMut(usize, usize, Pt)) {
+    let segments = rings
+        .iter()
+        .enumerate()
+        .flat_map(|(r, ring)| {
+            ring.0
+                .windows(2)
+                .enumerate()
+                .map(move |(i, s)| (r, i, s[0], s[1]))
+        })
+        .collect::<Vec<_>>();
+    // Two crossing segments have their middles closer than the longest segment:
+    // they are bucketed by cells of this size and compared to the neighbouring cells
+    let size = segments
+        .iter()
+        .map(|(_, _, a, b)| (b.x - a.x).abs().max((b.y - a.y).abs()))
+        .fold(0., Float::max);
+    if size <= 0. || !size.is_finite() {
+        return;
+    }
+    let bucket = |a: &Pt, b: &Pt| {
+        (
+            ((a.x + b.x) / 2. / size).floor() as i64,
+            ((a.y + b.y) / 2. / size).floor() as i64,
+        )
+    };
+    let mut buckets: FxHashMap<(i64, i64), Vec<usize>> = FxHashMap::default();
+    for (k, (_, _, a, b)) in segments.iter().enumerate() {
+        buckets.entry(bucket(a, b)).or_default().push(k);
+    }
+    for (k, &(r, i, a, b)) in segments.iter().enumerate() {
+        let (bx, by) = bucket(&a, &b);
+        for (ox, oy) in (-1..=1).flat_map(|ox| (-1..=1).map(move |oy| (ox, oy))) {
+            let Some(others) = buckets.get(&(bx + ox, by + oy)) else {
+                continue;
+            };
+            for &l in others.iter().filter(|&&l| l > k) {
+                let (r2, j, c, d) = segments[l];
+                let n = rings[r].0.len() - 1;
+                let consecutive = r == r2 && (j == i + 1 || (i == 0 && j == n - 1));
+                if consecutive {
+                    continue;
+                }
+                if let Some(point) = crossing(a, b, c, d) {
+                    f(r, r2, point);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the crossing point of the segments [a, b] and [c, d], if they properly cross.
+fn crossing(a: Pt, b: Pt, c: Pt, d: Pt) -> Option<Pt> {
+    let orientation = |p: Pt, q: Pt, r: Pt| (q.x - p.x) * (r.y - p.y) - (q.y - p.y) * (r.x - p.x);
+    let (o1, o2) = (orientation(a, b, c), orientation(a, b, d));
+    let (o3, o4) = (orientation(c, d, a), orientation(c, d, b));
+    let crosses = o1 * o2 < 0. && o3 * o4 < 0.;
+    crosses.then(|| {
+        let t = o3 / (o3 - o4);
+        Pt {
+            x: a.x + t * (b.x - a.x),
+            y: a.y + t * (b.y - a.y),
+        }
+    })
+}
@@ -0,0 +1,174 @@
+use crate::cache::fingerprint;
+use crate::{Float, GridSource};
+use rustc_hash::FxHasher;
+use std::hash::Hasher;
+use std::sync::Arc;
+
+/// A function combining a value of the grid with its weight.
+pub(crate) type WeightOp = Arc<dyn Fn(Float, Float) -> Float + Send + Sync>;
+
+/// A grid of weights combined cell-by-cell with the values of the grid.
+#[derive(Clone)]
+pub(crate) struct Weights {
+    pub(crate) weights: Arc<[Float]>,
+    pub(crate) op: WeightOp,
+}
+
+impl Weights {
+    pub(crate) fn new(weights: Vec<Float>, op: WeightOp) -> Self {
+        Weights {
+            weights: weights.into(),
+            op,
+        }
+    }
+}
+
+impl std::fmt::Debug for Weights {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Weights")
+            .field("len", &self.weights.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for Weights {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.weights, &other.weights) && Arc::ptr_eq(&self.op, &other.op)
+    }
+}
+
+/// Where the values of a grid are read from.
+#[derive(Clone, Copy)]
+enum Values<'a> {
+    /// A flat slice of values
+    Slice(&'a [Float]),
+    /// A [`GridSource`], with its width
+    Source(&'a dyn GridSource, usize),
+}
+
+/// A view of the values of a grid, combined with its weights (if any)
+/// when they are read, so that no weighted copy of the grid is materialized.
+#[derive(Clone, Copy)]
+pub(crate) struct Grid<'a> {
+    values: Values<'a>,
+    len: usize,
+    /// The dimensions of the grid if the slices are stored column by column
+    column_major: Option<(usize, usize)>,
+    weights: Option<&'a Weights>,
+    infinite_outside: bool,
+    nodata: Option<Float>,
+}
+
+impl<'a> Grid<'a> {
+    pub(crate) fn new(
+        values: &'a [Float],
+        weights: Option<&'a Weights>,
+        infinite_outside: bool,
+        nodata: Option<Float>,
+    ) -> Self {
+        Grid {
+            values: Values::Slice(values),
+            len: values.len(),
+            column_major: None,
+            weights,
+            infinite_outside,
+            nodata,
+        }
+    }
+
+    /// Constructs the view of the values of the given `source`.
+    pub(crate) fn from_source(
+        source: &'a dyn GridSource,
+        weights: Option<&'a Weights>,
+        infinite_outside: bool,
+        nodata: Option<Float>,
+    ) -> Self {
+        Grid {
+            values: Values::Source(source, source.width()),
+            len: source.width() * source.height(),
+            column_major: None,
+            weights,
+            infinite_outside,
+            nodata,
+        }
+    }
+
+    /// Reads the slices of values (if any) and weights column by column.
+    pub(crate) fn column_major(mut self, dx: usize, dy: usize) -> Self {
+        self.column_major = Some((dx, dy));
+        self
+    }
+
+    /// Returns the position in the slices of values and weights
+    /// of the cell at the given index (in row-major order).
+    #[inline]
+    fn index(&self, i: usize) -> usize {
+        match self.column_major {
+            Some((dx, dy)) => (i % dx) * dy + i / dx,
+            None => i,
+        }
+    }
+
+    /// Copies the (unweighted) values of the grid, row by row.
+    pub(crate) fn to_vec(self) -> Vec<Float> {
+        (0..self.len).map(|i| self.raw(i)).collect()
+    }
+
+    /// Returns the (unweighted) value of the cell at the given index.
+    #[inline]
+    fn raw(&self, i: usize) -> Float {
+        match self.values {
+            Values::Slice(values) => values[self.index(i)],
+            Values::Source(source, width) => source.get(i % width, i / width),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the (weighted) value of the cell at the given index
+    /// (infinite values being read as `-inf` if they are outside of every contour,
+    /// and the cells without data as `NaN`).
+    #[inline]
+    pub(crate) fn get(&self, i: usize) -> Float {
+        let raw = self.raw(i);
+        if self.nodata == Some(raw) {
+            return Float::NAN;
+        }
+        let value = match self.weights {
+            Some(w) => (w.op)(raw, w.weights[self.index(i)]),
+            None => raw,
+        };
+        if self.infinite_outside && value.is_infinite() {
+            Float::NEG_INFINITY
+        } else {
+            value
+        }
+    }
+
+    /// Computes the fingerprint of the values, and weights, of the grid.
+    pub(crate) fn fingerprint(&self, dx: usize, dy: usize) -> u64 {
+        let values = match self.values {
+            Values::Slice(values) if self.column_major.is_none() => {
+                fingerprint(values.iter().copied(), dx, dy)
+            }
+            _ => fingerprint((0..self.len).map(|i| self.raw(i)), dx, dy),
+        };
+        if self.weights.is_none() && !self.infinite_outside && self.nodata.is_none() {
+            return values;
+        }
+        let mut hasher = FxHasher::default();
+        hasher.write_u64(values);
+        if let Some(w) = self.weights {
+            hasher.write_u64(fingerprint(w.weights.iter().copied(), dx, dy));
+            hasher.write_usize(Arc::as_ptr(&w.op) as *const () as usize);
+        }
+        hasher.write_u8(self.infinite_outside as u8);
+        if let Some(nodata) = self.nodata {
+            #[allow(clippy::unnecessary_cast)]
+            hasher.write_u64((nodata as f64).to_bits());
+        }
+        hasher.finish()
+    }
+}
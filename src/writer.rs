@@ -0,0 +1,222 @@
+use crate::error::{Error, Result};
+use crate::{Band, Contour, ContourLayer, GeoJsonKeys, LayerItem, Line, Pt};
+use geo_types::{LineString, Polygon};
+use std::io::Write;
+
+/// How the features are laid out by a [`GeoJsonWriter`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GeoJsonLayout {
+    /// The features are written in a FeatureCollection (the default).
+    #[default]
+    FeatureCollection,
+    /// The features are written as a GeoJSON Text Sequence (RFC 8142), each of them
+    /// preceded by a record separator (`0x1E`) and followed by a line feed.
+    TextSequence,
+    /// The features are written one per line (newline-delimited GeoJSON, as ingested
+    /// by tippecanoe and by many data pipelines).
+    NewlineDelimited,
+}
+
+/// Writes lines, contours and bands as the features of a GeoJSON FeatureCollection
+/// (or of a sequence of features, see [`GeoJsonLayout`]) directly to an
+/// [`io::Write`](std::io::Write), without building the intermediate
+/// `geojson::Feature` objects (and the `serde_json` values of their coordinates),
+/// so that large results can be serialized with a low memory footprint.
+///
+/// The output is equivalent to the serialization of the FeatureCollection built from
+/// the [`to_geojson_with_keys`](crate::Contour::to_geojson_with_keys) conversions.
+///
+/// # Example
+///
+/// ```
+/// use contour::{ContourBuilder, GeoJsonWriter};
+///
+/// let builder = ContourBuilder::new(3, 3, false);
+/// let bands = builder
+///     .isobands(&[0., 0., 0., 0., 2., 0., 0., 0., 0.], &[0., 1., 3.])
+///     .unwrap();
+/// let mut writer = GeoJsonWriter::new(Vec::new()).unwrap();
+/// for band in &bands {
+///     writer.write_band(band).unwrap();
+/// }
+/// let output = writer.finish().unwrap();
+/// assert!(output.starts_with(br#"{"type":"FeatureCollection","features":[{"type":"Feature""#));
+/// ```
+///
+/// The features can also be written as soon as each threshold is computed:
+///
+/// ```
+/// use contour::{ContourBuilder, GeoJsonKeys, GeoJsonLayout, GeoJsonWriter};
+///
+/// let builder = ContourBuilder::new(3, 3, false);
+/// let values = [0., 0., 0., 0., 2., 0., 0., 0., 0.];
+/// let layout = GeoJsonLayout::NewlineDelimited;
+/// let mut writer = GeoJsonWriter::with_layout(Vec::new(), GeoJsonKeys::default(), layout).unwrap();
+/// builder
+///     .for_each_contour(&values, &[0.5, 1.5], |contour| writer.write_contour(&contour))
+///     .unwrap();
+/// let output = writer.finish().unwrap();
+/// assert_eq!(output.iter().filter(|&&b| b == b'\n').count(), 2);
+/// ```
+#[derive(Debug)]
+pub struct GeoJsonWriter<W: Write> {
+    writer: W,
+    keys: GeoJsonKeys,
+    layout: GeoJsonLayout,
+    count: usize,
+}
+
+// Converts an IO error to an error of this crate.
+fn io_error(err: std::io::Error) -> Error {
+    serde_json::Error::io(err).into()
+}
+
+impl<W: Write> GeoJsonWriter<W> {
+    /// Starts writing a FeatureCollection to `writer`
+    /// (which should be buffered, e.g. with a [`BufWriter`](std::io::BufWriter)).
+    pub fn new(writer: W) -> Result<Self> {
+        Self::with_keys(writer, GeoJsonKeys::default())
+    }
+
+    /// Starts writing a FeatureCollection to `writer`,
+    /// using the given names for the properties of the features.
+    pub fn with_keys(writer: W, keys: GeoJsonKeys) -> Result<Self> {
+        Self::with_layout(writer, keys, GeoJsonLayout::FeatureCollection)
+    }
+
+    /// Starts writing features to `writer` with the given layout,
+    /// using the given names for their properties.
+    pub fn with_layout(mut writer: W, keys: GeoJsonKeys, layout: GeoJsonLayout) -> Result<Self> {
+        if layout == GeoJsonLayout::FeatureCollection {
+            writer
+                .write_all(br#"{"type":"FeatureCollection","features":["#)
+                .map_err(io_error)?;
+        }
+        Ok(GeoJsonWriter {
+            writer,
+            keys,
+            layout,
+            count: 0,
+        })
+    }
+
+    /// Returns the number of features written so far.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns true if no feature was written so far.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Writes a line as a MultiLineString feature.
+    pub fn write_line(&mut self, line: &Line) -> Result<()> {
+        self.start_feature("MultiLineString")?;
+        let lines = &line.geometry().0;
+        self.write_array(lines, |w, line| w.write_line_string(line))?;
+        self.end_feature(line.properties(&self.keys))
+    }
+
+    /// Writes a contour as a MultiPolygon feature.
+    pub fn write_contour(&mut self, contour: &Contour) -> Result<()> {
+        self.start_feature("MultiPolygon")?;
+        self.write_array(&contour.geometry().0, Self::write_polygon)?;
+        self.end_feature(contour.properties(&self.keys))
+    }
+
+    /// Writes a band as a MultiPolygon feature.
+    pub fn write_band(&mut self, band: &Band) -> Result<()> {
+        self.start_feature("MultiPolygon")?;
+        self.write_array(&band.geometry().0, Self::write_polygon)?;
+        self.end_feature(band.properties(&self.keys))
+    }
+
+    /// Writes an item of a [`ContourLayer`].
+    pub fn write_item(&mut self, item: LayerItem) -> Result<()> {
+        match item {
+            LayerItem::Band(band) => self.write_band(band),
+            LayerItem::Contour(contour) => self.write_contour(contour),
+            LayerItem::Line(line) => self.write_line(line),
+        }
+    }
+
+    /// Writes the items of a [`ContourLayer`], in paint order.
+    pub fn write_layer(&mut self, layer: &ContourLayer) -> Result<()> {
+        layer.iter().try_for_each(|item| self.write_item(item))
+    }
+
+    /// Ends the FeatureCollection (if any), flushes the writer and returns it.
+    pub fn finish(mut self) -> Result<W> {
+        if self.layout == GeoJsonLayout::FeatureCollection {
+            self.writer.write_all(b"]}").map_err(io_error)?;
+        }
+        self.writer.flush().map_err(io_error)?;
+        Ok(self.writer)
+    }
+
+    fn start_feature(&mut self, kind: &str) -> Result<()> {
+        let separator = match self.layout {
+            GeoJsonLayout::FeatureCollection if self.count > 0 => ",",
+            GeoJsonLayout::TextSequence => "\x1e",
+            _ => "",
+        };
+        self.count += 1;
+        write!(
+            self.writer,
+            r#"{}{{"type":"Feature","geometry":{{"type":"{}","coordinates":"#,
+            separator, kind
+        )
+        .map_err(io_error)
+    }
+
+    fn end_feature(&mut self, properties: geojson::JsonObject) -> Result<()> {
+        self.writer
+            .write_all(br#"},"properties":"#)
+            .map_err(io_error)?;
+        serde_json::to_writer(&mut self.writer, &properties)?;
+        let end: &[u8] = match self.layout {
+            GeoJsonLayout::FeatureCollection => b"}",
+            _ => b"}\n",
+        };
+        self.writer.write_all(end).map_err(io_error)
+    }
+
+    fn write_array<T>(
+        &mut self,
+        items: &[T],
+        mut f: impl FnMut(&mut Self, &T) -> Result<()>,
+    ) -> Result<()> {
+        self.writer.write_all(b"[").map_err(io_error)?;
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.writer.write_all(b",").map_err(io_error)?;
+            }
+            f(self, item)?;
+        }
+        self.writer.write_all(b"]").map_err(io_error)
+    }
+
+    fn write_polygon(&mut self, polygon: &Polygon<crate::Float>) -> Result<()> {
+        self.writer.write_all(b"[").map_err(io_error)?;
+        self.write_line_string(polygon.exterior())?;
+        for ring in polygon.interiors() {
+            self.writer.write_all(b",").map_err(io_error)?;
+            self.write_line_string(ring)?;
+        }
+        self.writer.write_all(b"]").map_err(io_error)
+    }
+
+    // The casts to f64 are unnecessary when not using the "f32" feature.
+    #[allow(clippy::unnecessary_cast)]
+    fn write_line_string(&mut self, line: &LineString<crate::Float>) -> Result<()> {
+        self.write_array(&line.0, |w, pt: &Pt| {
+            w.writer.write_all(b"[").map_err(io_error)?;
+            // The coordinates are written as in the `geojson` crate, in double precision
+            serde_json::to_writer(&mut w.writer, &(pt.x as f64))?;
+            w.writer.write_all(b",").map_err(io_error)?;
+            serde_json::to_writer(&mut w.writer, &(pt.y as f64))?;
+            w.writer.write_all(b"]").map_err(io_error)
+        })
+    }
+}